@@ -0,0 +1,91 @@
+//! Throughput of the decoders: data types, and play packets through the codec.
+//! The `_baseline` benchmarks run the former decoders over a `VecDeque`.
+use std::hint::black_box;
+
+use atmospheremc_void::bench::{self, baseline};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+/// Frames of play packets decoded per iteration.
+const FRAMES: usize = 4096;
+
+/// Compression threshold of the compressed frames, the vanilla default.
+const THRESHOLD: usize = 256;
+
+/// Decode VarInts of every length, and strings of a few sizes.
+fn data_types(c: &mut Criterion) {
+    let mut group = c.benchmark_group("data_types");
+
+    let values: Vec<i32> = (0..4096).map(|i| i * 524_309).collect();
+    let var_ints: Vec<u8> = bench::encode_var_ints(&values);
+    group.throughput(Throughput::Bytes(var_ints.len() as u64));
+    group.bench_function("var_int", |b| {
+        b.iter_batched(
+            || var_ints.clone(),
+            |bytes| black_box(bench::decode_var_ints(&bytes)),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("var_int_baseline", |b| {
+        b.iter_batched(
+            || var_ints.clone(),
+            |bytes| black_box(baseline::decode_var_ints(bytes)),
+            BatchSize::LargeInput,
+        );
+    });
+
+    let words: Vec<String> = (0..1024).map(|i| "minecraft:".repeat(i % 8 + 1)).collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let strings: Vec<u8> = bench::encode_strings(&words);
+    group.throughput(Throughput::Bytes(strings.len() as u64));
+    group.bench_function("string", |b| {
+        b.iter_batched(
+            || strings.clone(),
+            |bytes| black_box(bench::decode_strings(&bytes)),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("string_baseline", |b| {
+        b.iter_batched(
+            || strings.clone(),
+            |bytes| black_box(baseline::decode_strings(bytes)),
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.finish();
+}
+
+/// Decode the frames of a moving client and parse its packets, without and with
+/// compression.
+fn play_packets(c: &mut Criterion) {
+    let mut group = c.benchmark_group("play_packets");
+    group.throughput(Throughput::Elements(FRAMES as u64));
+
+    for (name, compression) in [("uncompressed", None), ("compressed", Some(THRESHOLD))] {
+        let frames: Vec<Vec<u8>> = bench::play_frames(FRAMES, compression);
+        assert_eq!(bench::parse_play_frames(&frames, compression), FRAMES);
+        assert_eq!(
+            baseline::parse_play_frames(frames.clone(), compression),
+            FRAMES
+        );
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || frames.clone(),
+                |frames| black_box(bench::parse_play_frames(&frames, compression)),
+                BatchSize::LargeInput,
+            );
+        });
+        group.bench_function(format!("{name}_baseline"), |b| {
+            b.iter_batched(
+                || frames.clone(),
+                |frames| black_box(baseline::parse_play_frames(frames, compression)),
+                BatchSize::LargeInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, data_types, play_packets);
+criterion_main!(benches);
@@ -0,0 +1,423 @@
+//! Build script: generates Rust tables from the vanilla data generator output.
+//!
+//! The output of `java -DbundlerMainClass=net.minecraft.data.Main -jar server.jar
+//! --reports --server` is read from:
+//!
+//! * `reports/blocks.json` and `reports/registries.json`, the reports.
+//! * `data/minecraft/<registry>/**.json`, the built-in datapack, for the registries
+//!   synchronized during configuration.
+//!
+//! With `ATMOSPHEREMC_SERVER_JAR` set to the path of a 1.21.2 server jar, the data
+//! generator is run at build time and its output must be complete. Otherwise the
+//! subset checked into `data/` (`data/reports` and `data/generated/data`) is used:
+//! the first blocks of the block registry and a few entries of each synchronized
+//! registry, trimmed by hand from the same output. The build warns about what is
+//! missing, as clients would be sent wrong registry IDs, and fails instead with
+//! `ATMOSPHEREMC_REQUIRE_COMPLETE_DATA` set.
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde_json::Value;
+
+/// Registries sent to the client in the configuration state, in vanilla order.
+const SYNCHRONIZED_REGISTRIES: [&str; 12] = [
+    "minecraft:worldgen/biome",
+    "minecraft:chat_type",
+    "minecraft:trim_pattern",
+    "minecraft:trim_material",
+    "minecraft:wolf_variant",
+    "minecraft:painting_variant",
+    "minecraft:dimension_type",
+    "minecraft:damage_type",
+    "minecraft:banner_pattern",
+    "minecraft:enchantment",
+    "minecraft:jukebox_song",
+    "minecraft:instrument",
+];
+
+/// Static registries any complete `registries.json` has, including those the
+/// server looks up.
+const REQUIRED_REGISTRIES: [&str; 7] = [
+    "minecraft:block",
+    "minecraft:item",
+    "minecraft:entity_type",
+    "minecraft:fluid",
+    "minecraft:command_argument_type",
+    "minecraft:menu",
+    "minecraft:particle_type",
+];
+
+/// Environment variable giving the path of a server jar to run the data generator
+/// of.
+const SERVER_JAR: &str = "ATMOSPHEREMC_SERVER_JAR";
+
+/// Environment variable making incomplete data in `data/` a build error.
+const REQUIRE_COMPLETE: &str = "ATMOSPHEREMC_REQUIRE_COMPLETE_DATA";
+
+fn main() {
+    println!("cargo:rerun-if-env-changed={SERVER_JAR}");
+    println!("cargo:rerun-if-env-changed={REQUIRE_COMPLETE}");
+
+    let out_dir: PathBuf =
+        PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR not set"));
+
+    // Where the reports and the datapack are, and whether they must be complete
+    let (reports, generated, strict): (PathBuf, PathBuf, bool) =
+        if let Some(jar) = env::var_os(SERVER_JAR) {
+            let jar: PathBuf = PathBuf::from(jar);
+            println!("cargo:rerun-if-changed={}", jar.display());
+            let output: PathBuf = run_generator(&jar, &out_dir.join("vanilla"));
+            (output.join("reports"), output.join("data"), true)
+        } else {
+            let data: PathBuf = PathBuf::from("data");
+            println!("cargo:rerun-if-changed={}", data.display());
+            (
+                data.join("reports"),
+                data.join("generated/data"),
+                env::var_os(REQUIRE_COMPLETE).is_some(),
+            )
+        };
+
+    let blocks: Value = read_json(&reports.join("blocks.json"));
+    let registries: Value = read_json(&reports.join("registries.json"));
+    let complete: bool = check_complete(&blocks, &registries, &generated, strict);
+
+    fs::write(out_dir.join("blocks.rs"), generate_blocks(&blocks))
+        .expect("Failed to write blocks.rs");
+    fs::write(
+        out_dir.join("registries.rs"),
+        generate_registries(&registries, &generated, complete),
+    )
+    .expect("Failed to write registries.rs");
+}
+
+/// Run the data generator of the server jar with `output` as working and output
+/// directory, returning `output`.
+fn run_generator(jar: &Path, output: &Path) -> PathBuf {
+    let jar: PathBuf = jar
+        .canonicalize()
+        .unwrap_or_else(|e| panic!("Failed to find {}: {e}", jar.display()));
+    fs::create_dir_all(output)
+        .unwrap_or_else(|e| panic!("Failed to create {}: {e}", output.display()));
+
+    // The bundler extracts the server and its libraries to the working directory
+    let status = Command::new(env::var_os("JAVA").unwrap_or_else(|| "java".into()))
+        .current_dir(output)
+        .arg("-DbundlerMainClass=net.minecraft.data.Main")
+        .arg("-jar")
+        .arg(&jar)
+        .args(["--reports", "--server", "--output", "."])
+        .status()
+        .unwrap_or_else(|e| {
+            panic!("Failed to run the data generator of {}: {e}", jar.display())
+        });
+    assert!(
+        status.success(),
+        "The data generator of {} failed: {status}",
+        jar.display()
+    );
+
+    output.to_path_buf()
+}
+
+/// Check the data is the complete output of the data generator, returning whether
+/// it is. Incomplete data fails the build if `strict`, and is a warning otherwise.
+fn check_complete(
+    blocks: &Value,
+    registries: &Value,
+    generated: &Path,
+    strict: bool,
+) -> bool {
+    let mut missing: Vec<String> = Vec::new();
+
+    for name in REQUIRED_REGISTRIES {
+        if registries.get(name).is_none() {
+            missing.push(format!("registry {name} in registries.json"));
+        }
+    }
+
+    // Every block of the registry has its states in blocks.json
+    let block_registry = registries["minecraft:block"]["entries"].as_object();
+    for name in block_registry
+        .into_iter()
+        .flat_map(|entries| entries.keys())
+    {
+        if blocks.get(name).is_none() {
+            missing.push(format!("block {name} in blocks.json"));
+        }
+    }
+
+    for name in SYNCHRONIZED_REGISTRIES {
+        let (namespace, path) = name
+            .split_once(':')
+            .expect("registry name must be namespaced");
+        let root: PathBuf = generated.join(namespace).join(path);
+        let mut names: Vec<String> = Vec::new();
+        collect_entries(&root, &root, namespace, &mut names);
+        if names.is_empty() {
+            missing.push(format!("entries of {name} in {}", root.display()));
+        }
+    }
+
+    if missing.is_empty() {
+        return true;
+    }
+    assert!(
+        !strict,
+        "Incomplete vanilla data, missing:\n  {}\n\
+         Set {SERVER_JAR} to the path of a 1.21.2 server jar to generate it.",
+        missing.join("\n  ")
+    );
+    println!(
+        "cargo:warning=Incomplete vanilla data in data/, missing {} items: \
+         set {SERVER_JAR} to the path of a 1.21.2 server jar to generate it",
+        missing.len()
+    );
+    false
+}
+
+/// Read and parse a JSON file, panicking with the path on failure.
+fn read_json(path: &Path) -> Value {
+    let contents: String = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
+    serde_json::from_str(&contents)
+        .unwrap_or_else(|e| panic!("Failed to parse {}: {e}", path.display()))
+}
+
+/// Generate the `BLOCKS` table and its name index from `blocks.json`.
+///
+/// Vanilla assigns state IDs contiguously per block, iterating over the cartesian
+/// product of the property values with the last property varying fastest. This is
+/// checked here so that the runtime can compute state IDs arithmetically.
+fn generate_blocks(report: &Value) -> String {
+    let report = report.as_object().expect("blocks.json must be an object");
+
+    let mut blocks: Vec<(&str, &Value)> =
+        report.iter().map(|(k, v)| (k.as_str(), v)).collect();
+    blocks.sort_by_key(|(_, block)| first_state_id(block));
+
+    let mut out: String =
+        String::from("// @generated by build.rs from the blocks.json report\n\n");
+    out.push_str("/// All blocks, sorted by state ID.\n");
+    writeln!(
+        out,
+        "pub(super) static BLOCKS: [Block; {}] = [",
+        blocks.len()
+    )
+    .unwrap();
+
+    let mut expected_id: u64 = 0;
+    for (name, block) in &blocks {
+        let properties: Vec<(&str, Vec<&str>)> = block
+            .get("properties")
+            .and_then(Value::as_object)
+            .map(|props| {
+                props
+                    .iter()
+                    .map(|(k, v)| {
+                        let values = v
+                            .as_array()
+                            .expect("property values must be an array")
+                            .iter()
+                            .map(|v| v.as_str().expect("property value must be a string"))
+                            .collect();
+                        (k.as_str(), values)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let states = block["states"].as_array().expect("states must be an array");
+        let mut default_state: Option<u64> = None;
+
+        for (index, state) in states.iter().enumerate() {
+            let id: u64 = state["id"].as_u64().expect("state id must be an integer");
+            assert_eq!(id, expected_id, "{name}: state IDs are not contiguous");
+            expected_id += 1;
+
+            if state.get("default").and_then(Value::as_bool) == Some(true) {
+                default_state = Some(id);
+            }
+
+            // Check the state matches the mixed radix layout
+            let mut remainder: usize = index;
+            for (property, values) in properties.iter().rev() {
+                let expected: &str = values[remainder % values.len()];
+                remainder /= values.len();
+                assert_eq!(
+                    state["properties"][property].as_str(),
+                    Some(expected),
+                    "{name}: state {id} does not follow the vanilla state layout"
+                );
+            }
+        }
+
+        let mut props: String = String::new();
+        for (property, values) in &properties {
+            write!(
+                props,
+                "Property {{ name: {property:?}, values: &{values:?} }}, "
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            out,
+            "    Block {{ name: {name:?}, first_state: {first}, default_state: {default}, properties: &[{props}] }},",
+            first = first_state_id(block),
+            default = default_state.unwrap_or_else(|| panic!("{name}: no default state")),
+        )
+        .unwrap();
+    }
+    out.push_str("];\n\n");
+
+    let mut by_name: Vec<usize> = (0..blocks.len()).collect();
+    by_name.sort_by_key(|&i| {
+        blocks[i]
+            .0
+            .strip_prefix("minecraft:")
+            .unwrap_or(blocks[i].0)
+    });
+    out.push_str("/// All blocks, sorted by name without the `minecraft:` namespace.\n");
+    writeln!(
+        out,
+        "pub(super) static BLOCKS_BY_NAME: [&Block; {}] = [",
+        blocks.len()
+    )
+    .unwrap();
+    for i in by_name {
+        writeln!(out, "    &BLOCKS[{i}],").unwrap();
+    }
+    out.push_str("];\n\n");
+
+    out.push_str("/// Total number of block states.\n");
+    writeln!(out, "pub(super) const STATE_COUNT: u32 = {expected_id};").unwrap();
+
+    out
+}
+
+/// Get the first state ID of a block from `blocks.json`.
+fn first_state_id(block: &Value) -> u64 {
+    block["states"][0]["id"]
+        .as_u64()
+        .expect("state id must be an integer")
+}
+
+/// Generate the `STATIC_REGISTRIES` and `SYNCHRONIZED_REGISTRIES` tables, and the
+/// `COMPLETE` flag.
+fn generate_registries(report: &Value, generated: &Path, complete: bool) -> String {
+    let report = report
+        .as_object()
+        .expect("registries.json must be an object");
+
+    let mut out: String = String::from(
+        "// @generated by build.rs from the registries.json report and the datapack\n\n",
+    );
+
+    // BTreeMap iteration keeps the table sorted by name for binary search
+    let report: BTreeMap<&String, &Value> = report.iter().collect();
+    out.push_str("/// Registries from `registries.json`, sorted by name.\n");
+    writeln!(
+        out,
+        "pub(super) static STATIC_REGISTRIES: [Registry; {}] = [",
+        report.len()
+    )
+    .unwrap();
+    for (name, registry) in report {
+        let mut entries: Vec<(&str, u64)> = registry["entries"]
+            .as_object()
+            .expect("entries must be an object")
+            .iter()
+            .map(|(k, v)| {
+                (
+                    k.as_str(),
+                    v["protocol_id"]
+                        .as_u64()
+                        .expect("protocol_id must be an integer"),
+                )
+            })
+            .collect();
+        entries.sort_by_key(|(_, id)| *id);
+        for (index, (entry, id)) in entries.iter().enumerate() {
+            assert_eq!(
+                *id, index as u64,
+                "{name}: {entry} has a non contiguous protocol ID"
+            );
+        }
+        let names: Vec<&str> = entries.into_iter().map(|(k, _)| k).collect();
+
+        write_registry(&mut out, name, &names);
+    }
+    out.push_str("];\n\n");
+
+    out.push_str(
+        "/// Registries synchronized in the configuration state, in vanilla order.\n",
+    );
+    writeln!(
+        out,
+        "pub(super) static SYNCHRONIZED_REGISTRIES: [Registry; {}] = [",
+        SYNCHRONIZED_REGISTRIES.len()
+    )
+    .unwrap();
+    for name in SYNCHRONIZED_REGISTRIES {
+        let (namespace, path) = name
+            .split_once(':')
+            .expect("registry name must be namespaced");
+        let root: PathBuf = generated.join(namespace).join(path);
+
+        let mut names: Vec<String> = Vec::new();
+        collect_entries(&root, &root, namespace, &mut names);
+        names.sort();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        write_registry(&mut out, name, &names);
+    }
+    out.push_str("];\n\n");
+
+    out.push_str(
+        "/// Whether the tables come from the complete data generator output.\n",
+    );
+    writeln!(out, "pub(super) const COMPLETE: bool = {complete};").unwrap();
+
+    out
+}
+
+/// Write a single `Registry` literal, with its entries sorted by protocol ID.
+fn write_registry(out: &mut String, name: &str, entries: &[&str]) {
+    let mut by_name: Vec<(&str, usize)> = entries.iter().copied().zip(0..).collect();
+    by_name.sort_unstable();
+
+    writeln!(
+        out,
+        "    Registry {{ name: {name:?}, entries: &{entries:?}, entries_by_name: &{by_name:?} }},",
+    )
+    .unwrap();
+}
+
+/// Recursively collect the entry names of a datapack registry directory.
+fn collect_entries(root: &Path, dir: &Path, namespace: &str, names: &mut Vec<String>) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in read_dir {
+        let path: PathBuf = entry.expect("Failed to read directory entry").path();
+        if path.is_dir() {
+            collect_entries(root, &path, namespace, names);
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            let relative: PathBuf = path.strip_prefix(root).unwrap().with_extension("");
+            let relative: String = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            names.push(format!("{namespace}:{relative}"));
+        }
+    }
+}
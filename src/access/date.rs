@@ -0,0 +1,137 @@
+//! Dates of the ban lists, in vanilla's `yyyy-MM-dd HH:mm:ss Z` format (e.g.
+//! `2024-05-01 12:34:56 +0000`).
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds in a day.
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Format a date in UTC.
+pub fn format(time: SystemTime) -> String {
+    let seconds: i64 = to_seconds(time);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let time_of_day: i64 = seconds.rem_euclid(SECONDS_PER_DAY);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} +0000",
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60
+    )
+}
+
+/// Parse a date, with any UTC offset.
+pub fn parse(date: &str) -> Option<SystemTime> {
+    let mut fields = date.split_whitespace();
+    let (Some(day), Some(time), Some(offset), None) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        return None;
+    };
+
+    let day: Vec<i64> = numbers(day, '-')?;
+    let time: Vec<i64> = numbers(time, ':')?;
+    let (&[year, month, day], &[hour, minute, second]) =
+        (day.as_slice(), time.as_slice())
+    else {
+        return None;
+    };
+    if !(0..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    let (sign, offset) = match offset.split_at_checked(1)? {
+        ("+", offset) => (1, offset),
+        ("-", offset) => (-1, offset),
+        _ => return None,
+    };
+    if offset.len() != 4 || !offset.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let offset: i64 = offset.parse().ok()?;
+    if offset % 100 > 59 {
+        return None;
+    }
+    let offset: i64 = sign * (offset / 100 * 3600 + offset % 100 * 60);
+
+    let seconds: i64 = days_from_civil(year, month, day)
+        .checked_mul(SECONDS_PER_DAY)?
+        .checked_add(hour * 3600 + minute * 60 + second)?
+        .checked_sub(offset)?;
+    from_seconds(seconds)
+}
+
+/// Parse numbers separated by `separator`.
+fn numbers(s: &str, separator: char) -> Option<Vec<i64>> {
+    s.split(separator)
+        .map(|n| {
+            if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+                None
+            } else {
+                n.parse().ok()
+            }
+        })
+        .collect()
+}
+
+/// Seconds since the Unix epoch, negative before it.
+fn to_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => i64::try_from(since.as_secs()).unwrap_or(i64::MAX),
+        Err(e) => -i64::try_from(e.duration().as_secs()).unwrap_or(i64::MAX),
+    }
+}
+
+/// Time from seconds since the Unix epoch, `None` if the platform cannot
+/// represent it.
+fn from_seconds(seconds: i64) -> Option<SystemTime> {
+    let duration: Duration = Duration::from_secs(seconds.unsigned_abs());
+    if seconds >= 0 {
+        UNIX_EPOCH.checked_add(duration)
+    } else {
+        UNIX_EPOCH.checked_sub(duration)
+    }
+}
+
+/// Days since the Unix epoch of a date in the proleptic Gregorian calendar, for
+/// years between 0 and 9999.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year: i64 = if month <= 2 { year - 1 } else { year };
+    let era: i64 = year.div_euclid(400);
+    let year_of_era: i64 = year - era * 400;
+    let day_of_year: i64 =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era: i64 =
+        year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Date in the proleptic Gregorian calendar of days since the Unix epoch.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+const fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days: i64 = days + 719_468;
+    let era: i64 = days.div_euclid(146_097);
+    let day_of_era: i64 = days - era * 146_097;
+    let year_of_era: i64 = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year: i64 =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month: i64 = (5 * day_of_year + 2) / 153;
+    let day: i64 = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month: i64 = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year: i64 = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
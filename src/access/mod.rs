@@ -0,0 +1,705 @@
+//! Access control with vanilla's JSON lists: `whitelist.json`,
+//! `banned-players.json`, `banned-ips.json` and `ops.json`, reloaded when they change
+//! on disk.
+use std::{
+    fs, io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        PoisonError, RwLock,
+    },
+    time::SystemTime,
+};
+
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{profile::PlayerProfile, text::Text, types::uuid::Uuid};
+
+mod date;
+mod test;
+
+/// Reason of a ban when none is given.
+pub const DEFAULT_BAN_REASON: &str = "Banned by an operator.";
+
+/// Source of a ban when none is given.
+const UNKNOWN_BAN_SOURCE: &str = "(Unknown)";
+
+/// Value of `expires` for permanent bans.
+const FOREVER: &str = "forever";
+
+/// Permission level of operators when `ops.json` does not give one.
+const DEFAULT_OP_LEVEL: u8 = 4;
+
+/// Error when reading or writing an access list.
+#[derive(Error, Debug)]
+pub enum AccessListError {
+    /// The file could not be read or written.
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+
+    /// The file is not valid JSON.
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+
+    /// The file is not a JSON array.
+    #[error("Expected a JSON array")]
+    NotAnArray,
+
+    /// An entry is missing a field or has an invalid one.
+    #[error("Invalid entry: {0}")]
+    InvalidEntry(Value),
+
+    /// Lists could not be read again, each error being logged.
+    #[error("{0} list(s) could not be read")]
+    ReloadFailed(usize),
+}
+
+/// An entry of an access list, stored as a JSON object.
+pub trait ListEntry: Clone {
+    /// Read the entry from its JSON object.
+    fn from_json(value: &Value) -> Result<Self, AccessListError>;
+
+    /// Write the entry as a JSON object.
+    fn to_json(&self) -> Value;
+
+    /// Whether the entry no longer applies, as for expired bans.
+    fn is_expired(&self, _now: SystemTime) -> bool {
+        false
+    }
+}
+
+/// A whitelisted player.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserEntry {
+    /// UUID of the player.
+    pub uuid: Uuid,
+
+    /// Username of the player, for reference.
+    pub name: String,
+}
+
+impl UserEntry {
+    /// Entry for a player profile.
+    pub fn new(profile: &PlayerProfile) -> Self {
+        Self {
+            uuid: profile.uuid,
+            name: profile.name.clone(),
+        }
+    }
+}
+
+impl ListEntry for UserEntry {
+    fn from_json(value: &Value) -> Result<Self, AccessListError> {
+        let (uuid, name) = read_user(value)?;
+        Ok(Self { uuid, name })
+    }
+
+    fn to_json(&self) -> Value {
+        json!({ "uuid": self.uuid.to_string(), "name": self.name })
+    }
+}
+
+/// An operator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpEntry {
+    /// UUID of the player.
+    pub uuid: Uuid,
+
+    /// Username of the player, for reference.
+    pub name: String,
+
+    /// Permission level, from 1 to 4.
+    pub level: u8,
+
+    /// Whether the operator may join when the server is full.
+    pub bypasses_player_limit: bool,
+}
+
+impl ListEntry for OpEntry {
+    fn from_json(value: &Value) -> Result<Self, AccessListError> {
+        let (uuid, name) = read_user(value)?;
+        let level: u8 = match value.get("level") {
+            Some(level) => level
+                .as_u64()
+                .and_then(|level| u8::try_from(level).ok())
+                .ok_or_else(|| AccessListError::InvalidEntry(value.clone()))?,
+            None => DEFAULT_OP_LEVEL,
+        };
+
+        Ok(Self {
+            uuid,
+            name,
+            level,
+            bypasses_player_limit: value
+                .get("bypassesPlayerLimit")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "uuid": self.uuid.to_string(),
+            "name": self.name,
+            "level": self.level,
+            "bypassesPlayerLimit": self.bypasses_player_limit,
+        })
+    }
+}
+
+/// Details shared by player and IP bans.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BanDetails {
+    /// When the ban was created.
+    pub created: SystemTime,
+
+    /// Who created the ban.
+    pub source: String,
+
+    /// When the ban ends, `None` for a permanent ban.
+    pub expires: Option<SystemTime>,
+
+    /// Reason shown to the banned player.
+    pub reason: String,
+}
+
+impl BanDetails {
+    /// Create a ban now, with the default reason if none is given.
+    pub fn new(
+        reason: Option<String>,
+        source: impl Into<String>,
+        expires: Option<SystemTime>,
+    ) -> Self {
+        Self {
+            created: SystemTime::now(),
+            source: source.into(),
+            expires,
+            reason: reason.unwrap_or_else(|| DEFAULT_BAN_REASON.to_string()),
+        }
+    }
+
+    /// Read the details from a ban entry. Like vanilla, an invalid creation date
+    /// means now and an invalid expiration date means forever.
+    fn from_json(value: &Value) -> Self {
+        let field = |key: &str| value.get(key).and_then(Value::as_str);
+        Self {
+            created: field("created")
+                .and_then(date::parse)
+                .unwrap_or_else(SystemTime::now),
+            source: field("source").unwrap_or(UNKNOWN_BAN_SOURCE).to_string(),
+            expires: field("expires").and_then(date::parse),
+            reason: field("reason").unwrap_or(DEFAULT_BAN_REASON).to_string(),
+        }
+    }
+
+    /// Add the details to a ban entry.
+    fn write_json(&self, value: &mut Value) {
+        if let Value::Object(object) = value {
+            object.insert("created".to_string(), json!(date::format(self.created)));
+            object.insert("source".to_string(), json!(self.source));
+            object.insert(
+                "expires".to_string(),
+                json!(self
+                    .expires
+                    .map_or_else(|| FOREVER.to_string(), date::format)),
+            );
+            object.insert("reason".to_string(), json!(self.reason));
+        }
+    }
+
+    /// Whether the ban ended.
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// Disconnect message of a login refused by the ban, using the translation keys
+    /// under `key` (`banned` or `banned_ip`).
+    fn login_message(&self, key: &str) -> Text {
+        let message: Text = Text::translate(
+            format!("multiplayer.disconnect.{key}.reason"),
+            vec![Text::plain(self.reason.clone())],
+        );
+        match self.expires {
+            Some(expires) => message.append(Text::translate(
+                format!("multiplayer.disconnect.{key}.expiration"),
+                vec![Text::plain(date::format(expires))],
+            )),
+            None => message,
+        }
+    }
+}
+
+/// A banned player.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlayerBan {
+    /// UUID of the player.
+    pub uuid: Uuid,
+
+    /// Username of the player, for reference.
+    pub name: String,
+
+    /// Details of the ban.
+    pub details: BanDetails,
+}
+
+impl ListEntry for PlayerBan {
+    fn from_json(value: &Value) -> Result<Self, AccessListError> {
+        let (uuid, name) = read_user(value)?;
+        Ok(Self {
+            uuid,
+            name,
+            details: BanDetails::from_json(value),
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        let mut value: Value =
+            json!({ "uuid": self.uuid.to_string(), "name": self.name });
+        self.details.write_json(&mut value);
+        value
+    }
+
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.details.is_expired(now)
+    }
+}
+
+/// A banned IP address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpBan {
+    /// The banned address.
+    pub ip: IpAddr,
+
+    /// Details of the ban.
+    pub details: BanDetails,
+}
+
+impl ListEntry for IpBan {
+    fn from_json(value: &Value) -> Result<Self, AccessListError> {
+        let ip: IpAddr = value
+            .get("ip")
+            .and_then(Value::as_str)
+            .and_then(|ip| ip.parse().ok())
+            .ok_or_else(|| AccessListError::InvalidEntry(value.clone()))?;
+
+        Ok(Self {
+            ip: ip.to_canonical(),
+            details: BanDetails::from_json(value),
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        let mut value: Value = json!({ "ip": self.ip.to_string() });
+        self.details.write_json(&mut value);
+        value
+    }
+
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.details.is_expired(now)
+    }
+}
+
+/// Read the UUID and the username of a player entry. The name is optional.
+fn read_user(value: &Value) -> Result<(Uuid, String), AccessListError> {
+    let uuid: Uuid = value
+        .get("uuid")
+        .and_then(Value::as_str)
+        .and_then(|uuid| uuid.parse().ok())
+        .ok_or_else(|| AccessListError::InvalidEntry(value.clone()))?;
+    let name: &str = value
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    Ok((uuid, name.to_string()))
+}
+
+/// Entries of a [`ListFile`] and the modification time of the file they were read
+/// from.
+struct ListState<E> {
+    /// The entries, possibly with expired ones.
+    entries: Vec<E>,
+
+    /// Modification time of the file when it was last read or written.
+    modified: Option<SystemTime>,
+}
+
+/// An access list, stored in a JSON file.
+pub struct ListFile<E> {
+    /// Path of the file.
+    path: PathBuf,
+
+    /// The entries.
+    state: RwLock<ListState<E>>,
+}
+
+impl<E: ListEntry> ListFile<E> {
+    /// Open a list, creating an empty file if it does not exist. The list is empty if
+    /// the file cannot be read.
+    fn open(path: PathBuf) -> Self {
+        let list: Self = Self {
+            path,
+            state: RwLock::new(ListState {
+                entries: Vec::new(),
+                modified: None,
+            }),
+        };
+
+        let result: Result<(), AccessListError> = if list.path.exists() {
+            list.reload()
+        } else {
+            list.update(|_| ())
+        };
+        if let Err(e) = result {
+            log::error!(target: "Access lists", "Failed to load {}: {e}", list.path.display());
+        }
+
+        list
+    }
+
+    /// Read the file again. The entries are kept if it cannot be read.
+    fn reload(&self) -> Result<(), AccessListError> {
+        let modified: Option<SystemTime> = modified(&self.path);
+        let entries: Result<Vec<E>, AccessListError> = self.read();
+
+        let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+        // Even if it cannot be read, not to read it again until it changes
+        state.modified = modified;
+        state.entries = entries?;
+        drop(state);
+        Ok(())
+    }
+
+    /// Read the entries of the file.
+    fn read(&self) -> Result<Vec<E>, AccessListError> {
+        let Value::Array(values) =
+            serde_json::from_str(&fs::read_to_string(&self.path)?)?
+        else {
+            return Err(AccessListError::NotAnArray);
+        };
+        values.iter().map(E::from_json).collect()
+    }
+
+    /// Whether the file changed since it was last read or written.
+    fn changed(&self) -> bool {
+        modified(&self.path)
+            != self
+                .state
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .modified
+    }
+
+    /// The entries in effect.
+    pub fn entries(&self) -> Vec<E> {
+        let now: SystemTime = SystemTime::now();
+        self.state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    /// The first entry in effect matching `predicate`.
+    fn find(&self, predicate: impl Fn(&E) -> bool) -> Option<E> {
+        let now: SystemTime = SystemTime::now();
+        self.state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .iter()
+            .find(|entry| !entry.is_expired(now) && predicate(entry))
+            .cloned()
+    }
+
+    /// Change the entries and write the file, dropping expired entries. The entries
+    /// are left unchanged if the file cannot be written.
+    fn update<R>(
+        &self,
+        change: impl FnOnce(&mut Vec<E>) -> R,
+    ) -> Result<R, AccessListError> {
+        let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+        let mut entries: Vec<E> = state.entries.clone();
+        let result: R = change(&mut entries);
+
+        let now: SystemTime = SystemTime::now();
+        entries.retain(|entry| !entry.is_expired(now));
+        let json: String = serde_json::to_string_pretty(&Value::Array(
+            entries.iter().map(ListEntry::to_json).collect(),
+        ))?;
+        fs::write(&self.path, json)?;
+        state.entries = entries;
+        state.modified = modified(&self.path);
+        drop(state);
+
+        Ok(result)
+    }
+
+    /// Remove the entries matching `predicate`, returning whether there were any.
+    fn remove(&self, predicate: impl Fn(&E) -> bool) -> Result<bool, AccessListError> {
+        self.update(|entries| {
+            let length: usize = entries.len();
+            entries.retain(|entry| !predicate(entry));
+            entries.len() != length
+        })
+    }
+}
+
+/// Modification time of a file, `None` if it does not exist.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The whitelist, ban lists and operators of the server.
+pub struct AccessLists {
+    /// Whether only whitelisted players and operators may join (`white-list`).
+    whitelist_enabled: AtomicBool,
+
+    /// Players allowed to join when the whitelist is enabled.
+    whitelist: ListFile<UserEntry>,
+
+    /// Banned players.
+    banned_players: ListFile<PlayerBan>,
+
+    /// Banned IP addresses.
+    banned_ips: ListFile<IpBan>,
+
+    /// Operators.
+    ops: ListFile<OpEntry>,
+}
+
+impl AccessLists {
+    /// Load the lists from `directory`, creating the missing files.
+    pub fn load(directory: &Path, whitelist_enabled: bool) -> Self {
+        Self {
+            whitelist_enabled: AtomicBool::new(whitelist_enabled),
+            whitelist: ListFile::open(directory.join("whitelist.json")),
+            banned_players: ListFile::open(directory.join("banned-players.json")),
+            banned_ips: ListFile::open(directory.join("banned-ips.json")),
+            ops: ListFile::open(directory.join("ops.json")),
+        }
+    }
+
+    /// Read every list again, keeping those that cannot be read.
+    pub fn reload(&self) -> Result<(), AccessListError> {
+        /// Reload a list, returning whether it failed.
+        fn reload<E: ListEntry>(list: &ListFile<E>) -> bool {
+            list.reload()
+                .inspect_err(|e| {
+                    log::error!(target: "Access lists", "Failed to reload {}: {e}", list.path.display());
+                })
+                .is_err()
+        }
+
+        let failed: usize = [
+            reload(&self.whitelist),
+            reload(&self.banned_players),
+            reload(&self.banned_ips),
+            reload(&self.ops),
+        ]
+        .into_iter()
+        .filter(|&failed| failed)
+        .count();
+        if failed > 0 {
+            return Err(AccessListError::ReloadFailed(failed));
+        }
+        Ok(())
+    }
+
+    /// Read the lists whose file changed, returning whether any was reloaded.
+    pub fn reload_changed(&self) -> bool {
+        /// Reload a list if its file changed.
+        fn reload<E: ListEntry>(list: &ListFile<E>) -> bool {
+            if !list.changed() {
+                return false;
+            }
+            match list.reload() {
+                Ok(()) => {
+                    log::info!(target: "Access lists", "Reloaded {}", list.path.display());
+                    true
+                }
+                Err(e) => {
+                    log::error!(target: "Access lists", "Failed to reload {}: {e}", list.path.display());
+                    false
+                }
+            }
+        }
+
+        // Not short-circuiting, to reload every changed list
+        reload(&self.whitelist)
+            | reload(&self.banned_players)
+            | reload(&self.banned_ips)
+            | reload(&self.ops)
+    }
+
+    /// Disconnect message refusing the login of a player from `ip`, if the player or
+    /// the address is banned or the player is not whitelisted.
+    pub fn login_refusal(&self, uuid: Uuid, ip: IpAddr) -> Option<Text> {
+        if let Some(ban) = self.player_ban(uuid) {
+            return Some(ban.details.login_message("banned"));
+        }
+        if self.is_whitelist_enabled() && !self.is_whitelisted(uuid) {
+            return Some(Text::translate(
+                "multiplayer.disconnect.not_whitelisted",
+                vec![],
+            ));
+        }
+        self.ip_ban(ip)
+            .map(|ban| ban.details.login_message("banned_ip"))
+    }
+
+    /// Disconnect message kicking a player in game from `ip`, if the player or the
+    /// address was banned, or if the player is no longer whitelisted and
+    /// `enforce_whitelist` is set.
+    pub fn kick_reason(
+        &self,
+        uuid: Uuid,
+        ip: IpAddr,
+        enforce_whitelist: bool,
+    ) -> Option<Text> {
+        if self.player_ban(uuid).is_some() {
+            Some(Text::translate("multiplayer.disconnect.banned", vec![]))
+        } else if self.ip_ban(ip).is_some() {
+            Some(Text::translate("multiplayer.disconnect.ip_banned", vec![]))
+        } else if enforce_whitelist
+            && self.is_whitelist_enabled()
+            && !self.is_whitelisted(uuid)
+        {
+            Some(Text::translate(
+                "multiplayer.disconnect.not_whitelisted",
+                vec![],
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Whether only whitelisted players and operators may join.
+    pub fn is_whitelist_enabled(&self) -> bool {
+        self.whitelist_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable the whitelist.
+    pub fn set_whitelist_enabled(&self, enabled: bool) {
+        self.whitelist_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// The whitelisted players.
+    pub fn whitelist(&self) -> Vec<UserEntry> {
+        self.whitelist.entries()
+    }
+
+    /// Whether a player may join when the whitelist is enabled: whitelisted players
+    /// and operators.
+    pub fn is_whitelisted(&self, uuid: Uuid) -> bool {
+        self.whitelist.find(|entry| entry.uuid == uuid).is_some()
+            || self.op(uuid).is_some()
+    }
+
+    /// Whitelist a player, returning `false` if already whitelisted.
+    pub fn add_to_whitelist(
+        &self,
+        profile: &PlayerProfile,
+    ) -> Result<bool, AccessListError> {
+        self.whitelist.update(|entries| {
+            if entries.iter().any(|entry| entry.uuid == profile.uuid) {
+                return false;
+            }
+            entries.push(UserEntry::new(profile));
+            true
+        })
+    }
+
+    /// Remove a player from the whitelist, returning `false` if not whitelisted.
+    pub fn remove_from_whitelist(&self, uuid: Uuid) -> Result<bool, AccessListError> {
+        self.whitelist.remove(|entry| entry.uuid == uuid)
+    }
+
+    /// The bans in effect.
+    pub fn banned_players(&self) -> Vec<PlayerBan> {
+        self.banned_players.entries()
+    }
+
+    /// The ban of a player, if in effect.
+    pub fn player_ban(&self, uuid: Uuid) -> Option<PlayerBan> {
+        self.banned_players.find(|ban| ban.uuid == uuid)
+    }
+
+    /// Ban a player, replacing a previous ban.
+    pub fn ban_player(&self, ban: PlayerBan) -> Result<(), AccessListError> {
+        self.banned_players.update(|bans| {
+            bans.retain(|previous| previous.uuid != ban.uuid);
+            bans.push(ban);
+        })
+    }
+
+    /// Lift the ban of a player, returning `false` if not banned.
+    pub fn pardon_player(&self, uuid: Uuid) -> Result<bool, AccessListError> {
+        self.banned_players.remove(|ban| ban.uuid == uuid)
+    }
+
+    /// The IP bans in effect.
+    #[allow(dead_code)] // No ban-ip command yet
+    pub fn banned_ips(&self) -> Vec<IpBan> {
+        self.banned_ips.entries()
+    }
+
+    /// The ban of an IP address, if in effect.
+    pub fn ip_ban(&self, ip: IpAddr) -> Option<IpBan> {
+        let ip: IpAddr = ip.to_canonical();
+        self.banned_ips.find(|ban| ban.ip == ip)
+    }
+
+    /// Ban an IP address, replacing a previous ban.
+    #[allow(dead_code)] // No ban-ip command yet
+    pub fn ban_ip(&self, mut ban: IpBan) -> Result<(), AccessListError> {
+        ban.ip = ban.ip.to_canonical();
+        self.banned_ips.update(|bans| {
+            bans.retain(|previous| previous.ip != ban.ip);
+            bans.push(ban);
+        })
+    }
+
+    /// Lift the ban of an IP address, returning `false` if not banned.
+    #[allow(dead_code)] // No ban-ip command yet
+    pub fn pardon_ip(&self, ip: IpAddr) -> Result<bool, AccessListError> {
+        let ip: IpAddr = ip.to_canonical();
+        self.banned_ips.remove(|ban| ban.ip == ip)
+    }
+
+    /// The operators.
+    #[allow(dead_code)] // No op command yet
+    pub fn ops(&self) -> Vec<OpEntry> {
+        self.ops.entries()
+    }
+
+    /// The operator entry of a player, if an operator.
+    pub fn op(&self, uuid: Uuid) -> Option<OpEntry> {
+        self.ops.find(|op| op.uuid == uuid)
+    }
+
+    /// Operator level of a player, 0 if not an operator.
+    pub fn permission_level(&self, uuid: Uuid) -> u8 {
+        self.op(uuid).map_or(0, |op| op.level)
+    }
+
+    /// Make a player an operator, replacing a previous entry.
+    #[allow(dead_code)] // No op command yet
+    pub fn add_op(&self, op: OpEntry) -> Result<(), AccessListError> {
+        self.ops.update(|ops| {
+            ops.retain(|previous| previous.uuid != op.uuid);
+            ops.push(op);
+        })
+    }
+
+    /// Remove an operator, returning `false` if not an operator.
+    #[allow(dead_code)] // No op command yet
+    pub fn remove_op(&self, uuid: Uuid) -> Result<bool, AccessListError> {
+        self.ops.remove(|op| op.uuid == uuid)
+    }
+}
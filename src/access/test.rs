@@ -0,0 +1,214 @@
+//! Test [`crate::access`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{self, File},
+        net::{IpAddr, Ipv4Addr},
+        path::PathBuf,
+        process,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use crate::{
+        access::{date, AccessLists, BanDetails, IpBan, PlayerBan, DEFAULT_BAN_REASON},
+        profile::PlayerProfile,
+        text::Text,
+        types::uuid::Uuid,
+    };
+
+    /// UUID of a banned player.
+    const BANNED: Uuid = Uuid(0x0692_0508_d5d8_4eed_93d2_92f1_93af_e7d7);
+
+    /// UUID of an operator.
+    const OP: Uuid = Uuid(0x853c_80ef_3c37_49fd_aa49_9382_2c6b_a8d9);
+
+    /// A client address.
+    const ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+
+    #[test]
+    fn test_date() {
+        let time: SystemTime = UNIX_EPOCH + Duration::from_secs(1_714_566_896);
+        assert_eq!(date::format(time), "2024-05-01 12:34:56 +0000");
+        assert_eq!(date::parse("2024-05-01 12:34:56 +0000"), Some(time));
+        assert_eq!(date::parse("2024-05-01 14:34:56 +0200"), Some(time));
+        assert_eq!(date::parse("2024-05-01 07:04:56 -0530"), Some(time));
+
+        let leap_day: SystemTime = date::parse("2024-02-29 00:00:00 +0000").unwrap();
+        assert_eq!(date::format(leap_day), "2024-02-29 00:00:00 +0000");
+        let before_epoch: SystemTime = date::parse("1969-12-31 23:59:59 +0000").unwrap();
+        assert_eq!(before_epoch, UNIX_EPOCH - Duration::from_secs(1));
+        assert_eq!(date::format(before_epoch), "1969-12-31 23:59:59 +0000");
+
+        assert_eq!(date::parse("forever"), None);
+        assert_eq!(date::parse("2024-13-01 00:00:00 +0000"), None);
+        assert_eq!(date::parse("2024-05-01 12:34:56 UTC"), None);
+        assert_eq!(date::parse("2024-05-01 12:34:56"), None);
+    }
+
+    #[test]
+    fn test_date_range() {
+        let first: SystemTime = date::parse("0000-01-01 00:00:00 +0000").unwrap();
+        assert_eq!(date::format(first), "0000-01-01 00:00:00 +0000");
+        let last: SystemTime = date::parse("9999-12-31 23:59:59 -2359").unwrap();
+        assert_eq!(date::format(last), "10000-01-01 23:58:59 +0000");
+
+        // Out of range, without overflowing
+        assert_eq!(date::parse("10000-01-01 00:00:00 +0000"), None);
+        assert_eq!(
+            date::parse("9223372036854775807-12-31 23:59:59 +0000"),
+            None
+        );
+        assert_eq!(
+            date::parse("99999999999999999999-01-01 00:00:00 +0000"),
+            None
+        );
+        assert_eq!(date::parse("2024-05-01 12:34:56 +0060"), None);
+    }
+
+    #[test]
+    fn test_access_lists() {
+        let directory: PathBuf =
+            std::env::temp_dir().join(format!("atmospheremc-access-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("banned-players.json"),
+            r#"[
+                {"uuid": "06920508-d5d8-4eed-93d2-92f193afe7d7", "name": "Mallory", "created": "2024-05-01 12:34:56 +0000", "source": "Server", "expires": "forever", "reason": "Griefing"},
+                {"uuid": "0f0f0f0f-0000-0000-0000-000000000000", "name": "Eve", "created": "2024-05-01 12:34:56 +0000", "source": "Server", "expires": "2024-05-02 12:34:56 +0000", "reason": "Spam"}
+            ]"#,
+        )
+        .unwrap();
+        fs::write(
+            directory.join("ops.json"),
+            r#"[{"uuid": "853c80ef-3c37-49fd-aa49-93822c6ba8d9", "name": "Alice", "level": 4, "bypassesPlayerLimit": true}]"#,
+        )
+        .unwrap();
+        fs::remove_file(directory.join("whitelist.json")).ok();
+        fs::remove_file(directory.join("banned-ips.json")).ok();
+
+        let lists: AccessLists = AccessLists::load(&directory, false);
+        assert!(directory.join("whitelist.json").exists());
+        assert!(lists.whitelist().is_empty());
+        assert!(lists.op(OP).unwrap().bypasses_player_limit);
+
+        assert_eq!(
+            lists.login_refusal(BANNED, ADDRESS),
+            Some(Text::translate(
+                "multiplayer.disconnect.banned.reason",
+                vec![Text::plain("Griefing")]
+            ))
+        );
+        // Expired bans are ignored
+        assert_eq!(lists.banned_players().len(), 1);
+        let bob: PlayerProfile = PlayerProfile::offline("Bob".to_string());
+        assert_eq!(lists.login_refusal(bob.uuid, ADDRESS), None);
+
+        // Operators bypass the whitelist
+        lists.set_whitelist_enabled(true);
+        assert!(lists.login_refusal(bob.uuid, ADDRESS).is_some());
+        assert_eq!(lists.login_refusal(OP, ADDRESS), None);
+        assert!(lists.add_to_whitelist(&bob).unwrap());
+        assert!(!lists.add_to_whitelist(&bob).unwrap());
+        assert_eq!(lists.login_refusal(bob.uuid, ADDRESS), None);
+        assert!(lists.kick_reason(bob.uuid, ADDRESS, true).is_none());
+        assert!(lists.remove_from_whitelist(bob.uuid).unwrap());
+        assert!(lists.kick_reason(bob.uuid, ADDRESS, false).is_none());
+        assert!(lists.kick_reason(bob.uuid, ADDRESS, true).is_some());
+        lists.set_whitelist_enabled(false);
+
+        let expires: SystemTime = date::parse("2100-01-01 00:00:00 +0000").unwrap();
+        lists
+            .ban_ip(IpBan {
+                ip: "::ffff:203.0.113.7".parse().unwrap(),
+                details: BanDetails::new(None, "Console", Some(expires)),
+            })
+            .unwrap();
+        assert_eq!(
+            lists.login_refusal(bob.uuid, ADDRESS),
+            Some(
+                Text::translate(
+                    "multiplayer.disconnect.banned_ip.reason",
+                    vec![Text::plain(DEFAULT_BAN_REASON)]
+                )
+                .append(Text::translate(
+                    "multiplayer.disconnect.banned_ip.expiration",
+                    vec![Text::plain("2100-01-01 00:00:00 +0000")]
+                ))
+            )
+        );
+        assert!(lists.pardon_ip(ADDRESS).unwrap());
+        assert!(!lists.pardon_ip(ADDRESS).unwrap());
+
+        lists
+            .ban_player(PlayerBan {
+                uuid: bob.uuid,
+                name: bob.name.clone(),
+                details: BanDetails::new(Some("Cheating".to_string()), "Alice", None),
+            })
+            .unwrap();
+        assert!(lists.pardon_player(BANNED).unwrap());
+
+        // Written in vanilla's format, without the expired ban
+        let written: AccessLists = AccessLists::load(&directory, false);
+        let bans: Vec<PlayerBan> = written.banned_players();
+        assert_eq!(bans.len(), 1);
+        assert_eq!(bans.first().unwrap().details.reason, "Cheating");
+        assert!(fs::read_to_string(directory.join("banned-players.json"))
+            .unwrap()
+            .contains("\"expires\": \"forever\""));
+
+        // Changes on disk are picked up
+        assert!(!lists.reload_changed());
+        let path: PathBuf = directory.join("banned-players.json");
+        fs::write(&path, "[]").unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert!(lists.reload_changed());
+        assert!(lists.player_ban(bob.uuid).is_none());
+
+        // Invalid files keep the previous entries
+        fs::write(directory.join("ops.json"), "{").unwrap();
+        assert!(lists.reload().is_err());
+        assert!(lists.op(OP).is_some());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_access_lists_failures() {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-access-failures-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("ops.json"),
+            r#"[{"uuid": "853c80ef-3c37-49fd-aa49-93822c6ba8d9", "name": "Alice", "level": 4, "bypassesPlayerLimit": true}]"#,
+        )
+        .unwrap();
+        let lists: AccessLists = AccessLists::load(&directory, false);
+        let bob: PlayerProfile = PlayerProfile::offline("Bob".to_string());
+
+        // An invalid file does not stop the other lists from being reloaded
+        fs::write(directory.join("ops.json"), "{").unwrap();
+        assert!(AccessLists::load(&directory, false)
+            .add_to_whitelist(&bob)
+            .unwrap());
+        assert!(lists.reload().is_err());
+        assert!(lists.op(OP).is_some());
+        assert!(lists.is_whitelisted(bob.uuid));
+        // Nor is it read again until it changes
+        assert!(!lists.reload_changed());
+
+        // Entries are kept if the file cannot be written
+        let whitelist: PathBuf = directory.join("whitelist.json");
+        fs::remove_file(&whitelist).unwrap();
+        fs::create_dir(&whitelist).unwrap();
+        assert!(lists.remove_from_whitelist(bob.uuid).is_err());
+        assert!(lists.is_whitelisted(bob.uuid));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,190 @@
+//! The former decoders, as a baseline for the benchmarks.
+//!
+//! Frames are copied into a [`VecDeque`] and decoded one [`Read`] call per
+//! byte, with strings read in temporary chunks.
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+};
+
+use flate2::read::ZlibDecoder;
+
+use crate::types::var::VarString;
+
+/// Chunk size to read in [`read_bytes`].
+const CHUNK_SIZE: usize = 256;
+
+/// Segment bits of a `VarInt`.
+const SEGMENT_BITS: i32 = 0x7F;
+
+/// Continue bit of a `VarInt`.
+const CONTINUE_BIT: i32 = 0x80;
+
+/// Maximum uncompressed length of a packet, as in the codec.
+const MAX_DATA_LENGTH: usize = 1 << 23;
+
+/// Decode `VarInt`s until the end of `bytes`, returning their sum.
+#[must_use]
+pub fn decode_var_ints(bytes: Vec<u8>) -> i64 {
+    let mut bytes: VecDeque<u8> = VecDeque::from(bytes);
+    let mut sum: i64 = 0;
+    while let Ok(value) = decode_var_int(&mut bytes) {
+        sum += i64::from(value);
+    }
+    sum
+}
+
+/// Decode strings until the end of `bytes`, returning their total length.
+#[must_use]
+pub fn decode_strings(bytes: Vec<u8>) -> usize {
+    let mut bytes: VecDeque<u8> = VecDeque::from(bytes);
+    let mut length: usize = 0;
+    while let Ok(string) = decode_string(&mut bytes, VarString::MAX_LENGTH) {
+        length += string.len();
+    }
+    length
+}
+
+/// Decode `frames` and parse them as play packets, returning the number of
+/// packets parsed.
+#[must_use]
+pub fn parse_play_frames(frames: Vec<Vec<u8>>, compression: Option<usize>) -> usize {
+    frames
+        .into_iter()
+        .filter_map(|frame| decode_frame(frame, compression).ok())
+        .filter_map(|packet| parse_play_packet(packet).ok())
+        .count()
+}
+
+/// Read a single byte.
+fn read_byte(from: &mut impl Read) -> io::Result<u8> {
+    let mut buf: [u8; 1] = [0; 1];
+    from.read_exact(&mut buf)?;
+    let [byte] = buf;
+    Ok(byte)
+}
+
+/// Read `length` bytes, in chunks of [`CHUNK_SIZE`].
+fn read_bytes(from: &mut impl Read, length: usize) -> io::Result<Vec<u8>> {
+    let mut buf: Vec<u8> = Vec::with_capacity(length);
+    let mut bytes_read: usize = 0;
+    while bytes_read < length {
+        let bytes_to_read: usize = (length - bytes_read).min(CHUNK_SIZE);
+
+        let mut tmp_buf: Vec<u8> = vec![0; bytes_to_read];
+        from.read_exact(&mut tmp_buf)?;
+        buf.append(&mut tmp_buf);
+
+        bytes_read += bytes_to_read;
+    }
+    Ok(buf)
+}
+
+/// Read `N` bytes.
+fn read_array<const N: usize>(from: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf: [u8; N] = [0; N];
+    from.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Decode a `VarInt`, one byte at a time.
+fn decode_var_int(from: &mut impl Read) -> io::Result<i32> {
+    let mut value: i32 = 0;
+    let mut position: u8 = 0;
+
+    loop {
+        let current_byte: u8 = read_byte(from)?;
+        value |= (i32::from(current_byte) & SEGMENT_BITS) << position;
+
+        if (i32::from(current_byte) & CONTINUE_BIT) == 0 {
+            break Ok(value);
+        }
+
+        position += 7;
+
+        if position >= 32 {
+            break Err(io::ErrorKind::InvalidData.into());
+        }
+    }
+}
+
+/// Decode a string of at most `max_length` UTF-16 code units, counting them
+/// every time.
+fn decode_string(from: &mut impl Read, max_length: usize) -> io::Result<String> {
+    let byte_size: usize =
+        usize::try_from(decode_var_int(from)?).map_err(|_| io::ErrorKind::InvalidData)?;
+    // A code unit takes up to 3 bytes in UTF-8
+    if byte_size > max_length * 3 {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+
+    let value: String = String::from_utf8(read_bytes(from, byte_size)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if value.encode_utf16().count() > max_length {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+
+    Ok(value)
+}
+
+/// Decode the content of a frame into a packet, copying it into a [`VecDeque`].
+fn decode_frame(frame: Vec<u8>, compression: Option<usize>) -> io::Result<VecDeque<u8>> {
+    let mut frame: VecDeque<u8> = VecDeque::from(frame);
+    if compression.is_none() {
+        return Ok(frame);
+    }
+
+    let data_length: usize = usize::try_from(decode_var_int(&mut frame)?)
+        .map_err(|_| io::ErrorKind::InvalidData)?;
+    if data_length == 0 {
+        return Ok(frame);
+    }
+    if data_length > MAX_DATA_LENGTH {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+
+    let mut packet: Vec<u8> = Vec::with_capacity(data_length);
+    ZlibDecoder::new(&mut frame)
+        .take(data_length as u64 + 1)
+        .read_to_end(&mut packet)?;
+    if packet.len() != data_length {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+
+    Ok(VecDeque::from(packet))
+}
+
+/// Parse the play packets of [`super::play_frames`], field by field.
+fn parse_play_packet(mut packet: VecDeque<u8>) -> io::Result<()> {
+    match decode_var_int(&mut packet)? {
+        // Keep Alive
+        0x1A => {
+            let _id: i64 = i64::from_be_bytes(read_array(&mut packet)?);
+        }
+        // Chat Command
+        0x05 => {
+            let _command: String = decode_string(&mut packet, VarString::MAX_LENGTH)?;
+        }
+        // Set Player Rotation
+        0x1E => {
+            let _rotation: (f32, f32, u8) = (
+                f32::from_be_bytes(read_array(&mut packet)?),
+                f32::from_be_bytes(read_array(&mut packet)?),
+                read_byte(&mut packet)?,
+            );
+        }
+        // Set Player Position and Rotation
+        0x1D => {
+            let _position: (f64, f64, f64, f32, f32, u8) = (
+                f64::from_be_bytes(read_array(&mut packet)?),
+                f64::from_be_bytes(read_array(&mut packet)?),
+                f64::from_be_bytes(read_array(&mut packet)?),
+                f32::from_be_bytes(read_array(&mut packet)?),
+                f32::from_be_bytes(read_array(&mut packet)?),
+                read_byte(&mut packet)?,
+            );
+        }
+        _ => return Err(io::ErrorKind::InvalidData.into()),
+    }
+    Ok(())
+}
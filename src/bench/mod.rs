@@ -0,0 +1,142 @@
+//! Entry points of the benchmarks in `benches/`, decoding buffers the way a
+//! connection does. The inputs are built with the encoders of the server.
+use crate::{
+    protocol::{Codec, PlayServerBoundPacket},
+    types::{
+        var::{VarInt, VarString},
+        DataType,
+    },
+};
+
+pub mod baseline;
+
+/// Length of the command of the large Chat Command packets, above the default
+/// compression threshold.
+const LARGE_COMMAND: usize = 1024;
+
+/// Encode `values` one after the other.
+///
+/// # Panics
+///
+/// Panics if a value does not encode.
+#[must_use]
+pub fn encode_var_ints(values: &[i32]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for value in values {
+        VarInt(*value).encode(&mut bytes).expect("VarInt encodes");
+    }
+    bytes
+}
+
+/// Decode [`VarInt`]s until the end of `bytes`, returning their sum.
+#[must_use]
+pub fn decode_var_ints(mut bytes: &[u8]) -> i64 {
+    let mut sum: i64 = 0;
+    while let Ok(VarInt(value)) = VarInt::decode(&mut bytes) {
+        sum += i64::from(value);
+    }
+    sum
+}
+
+/// Encode `values` as strings, one after the other.
+///
+/// # Panics
+///
+/// Panics if a value does not encode.
+#[must_use]
+pub fn encode_strings(values: &[&str]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for value in values {
+        VarString::new((*value).to_string())
+            .encode(&mut bytes)
+            .expect("string encodes");
+    }
+    bytes
+}
+
+/// Decode strings until the end of `bytes`, returning their total length.
+#[must_use]
+pub fn decode_strings(mut bytes: &[u8]) -> usize {
+    let mut length: usize = 0;
+    while let Ok(string) = VarString::decode_str(&mut bytes, VarString::MAX_LENGTH) {
+        length += string.len();
+    }
+    length
+}
+
+/// `count` frames of play packets, without their length prefix, as sent by a
+/// moving client: mostly movements, some keep alives and chat commands, a few
+/// of them large.
+///
+/// # Panics
+///
+/// Panics if a packet does not encode.
+#[must_use]
+pub fn play_frames(count: usize, compression: Option<usize>) -> Vec<Vec<u8>> {
+    let mut codec: Codec = Codec::new();
+    codec.set_compression(compression);
+    (0..count)
+        .map(|i| {
+            let packet: Vec<u8> = play_packet(i).expect("packet encodes");
+            let frame: Vec<u8> = codec.encode(&packet).expect("frame encodes");
+            let mut body: &[u8] = &frame;
+            VarInt::decode(&mut body).expect("length prefix decodes");
+            body.to_vec()
+        })
+        .collect()
+}
+
+/// The `i`-th packet of [`play_frames`].
+fn play_packet(i: usize) -> Result<Vec<u8>, crate::types::DataTypeEncodeError> {
+    let mut packet: Vec<u8> = Vec::new();
+    #[allow(clippy::cast_precision_loss)]
+    let x: f64 = i as f64 / 16.0;
+    match i % 16 {
+        0 => {
+            // Keep Alive
+            VarInt(0x1A).encode(&mut packet)?;
+            i64::try_from(i)?.encode(&mut packet)?;
+        }
+        1 => {
+            // Chat Command
+            VarInt(0x05).encode(&mut packet)?;
+            VarString::new(format!("tell Player{i} hello there")).encode(&mut packet)?;
+        }
+        2 => {
+            // A large Chat Command
+            VarInt(0x05).encode(&mut packet)?;
+            VarString::new("say ".repeat(LARGE_COMMAND / 4)).encode(&mut packet)?;
+        }
+        3 | 7 | 11 | 15 => {
+            // Set Player Rotation
+            VarInt(0x1E).encode(&mut packet)?;
+            90.0_f32.encode(&mut packet)?;
+            0.0_f32.encode(&mut packet)?;
+            0_u8.encode(&mut packet)?;
+        }
+        _ => {
+            // Set Player Position and Rotation
+            VarInt(0x1D).encode(&mut packet)?;
+            x.encode(&mut packet)?;
+            100.0_f64.encode(&mut packet)?;
+            (-x).encode(&mut packet)?;
+            90.0_f32.encode(&mut packet)?;
+            0.0_f32.encode(&mut packet)?;
+            0_u8.encode(&mut packet)?;
+        }
+    }
+    Ok(packet)
+}
+
+/// Decode `frames` and parse them as play packets, as a connection does,
+/// returning the number of packets parsed.
+#[must_use]
+pub fn parse_play_frames(frames: &[Vec<u8>], compression: Option<usize>) -> usize {
+    let mut codec: Codec = Codec::new();
+    codec.set_compression(compression);
+    frames
+        .iter()
+        .filter_map(|frame| codec.decode(frame).ok())
+        .filter(|packet| PlayServerBoundPacket::try_from(&**packet).is_ok())
+        .count()
+}
@@ -0,0 +1,240 @@
+//! Packet capture: the frames of a connection in both directions, with their
+//! timing and state, written to a file for offline inspection and replay.
+//!
+//! A capture file starts with [`MAGIC`], the format version as a byte and the
+//! address of the client as a string. Each record then holds, big-endian:
+//!
+//! * the time since the connection opened, in microseconds (`u64`);
+//! * the direction, `0` for serverbound and `1` for clientbound (`u8`);
+//! * the connection state, as in [`state_id`] (`u8`);
+//! * whether compression was enabled for the frame (`u8`);
+//! * the length of the frame (`u32`) and the frame, without its length prefix.
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Read, Write},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
+
+use crate::{protocol::Direction, state::ServerState};
+
+mod test;
+pub mod tool;
+
+/// First bytes of a capture file.
+const MAGIC: &[u8; 6] = b"AMCCAP";
+
+/// Version of the capture format.
+const VERSION: u8 = 1;
+
+/// Maximum length of a recorded frame, a compressed frame being at most the
+/// maximum packet size.
+const MAX_FRAME_LENGTH: u32 = 1 << 23;
+
+/// Error when reading a capture file.
+#[derive(Error, Debug)]
+pub enum CaptureError {
+    /// The file could not be read or written.
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+
+    /// The file is not a capture, or of another version.
+    #[error("Not a capture file (version {VERSION})")]
+    InvalidHeader,
+
+    /// A record is invalid.
+    #[error("Invalid record: {0}")]
+    InvalidRecord(String),
+}
+
+/// A frame of a capture.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Time since the connection opened.
+    pub elapsed: Duration,
+
+    /// Who sent the frame.
+    pub direction: Direction,
+
+    /// State of the connection when the frame was sent.
+    pub state: ServerState,
+
+    /// Whether compression was enabled for the frame.
+    pub compressed: bool,
+
+    /// The frame, without its length prefix.
+    pub frame: Vec<u8>,
+}
+
+impl Record {
+    /// Write the record.
+    pub fn write(&self, to: &mut impl Write) -> Result<(), io::Error> {
+        let micros: u64 = u64::try_from(self.elapsed.as_micros()).unwrap_or(u64::MAX);
+        let length: u32 = u32::try_from(self.frame.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        to.write_all(&micros.to_be_bytes())?;
+        to.write_all(&[
+            u8::from(self.direction == Direction::Clientbound),
+            state_id(self.state),
+            u8::from(self.compressed),
+        ])?;
+        to.write_all(&length.to_be_bytes())?;
+        to.write_all(&self.frame)
+    }
+
+    /// Read the next record, `None` at the end of the capture.
+    pub fn read(from: &mut impl Read) -> Result<Option<Self>, CaptureError> {
+        let mut micros: [u8; 8] = [0; 8];
+        match from.read_exact(&mut micros) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let mut flags: [u8; 3] = [0; 3];
+        from.read_exact(&mut flags)?;
+        let [direction, state, compressed] = flags;
+        let mut length: [u8; 4] = [0; 4];
+        from.read_exact(&mut length)?;
+        let length: u32 = u32::from_be_bytes(length);
+        if length > MAX_FRAME_LENGTH {
+            return Err(CaptureError::InvalidRecord(format!(
+                "frame of {length} bytes"
+            )));
+        }
+        let mut frame: Vec<u8> = vec![0; length as usize];
+        from.read_exact(&mut frame)?;
+
+        Ok(Some(Self {
+            elapsed: Duration::from_micros(u64::from_be_bytes(micros)),
+            direction: match direction {
+                0 => Direction::Serverbound,
+                1 => Direction::Clientbound,
+                other => {
+                    return Err(CaptureError::InvalidRecord(format!("direction {other}")))
+                }
+            },
+            state: state_from_id(state)
+                .ok_or_else(|| CaptureError::InvalidRecord(format!("state {state}")))?,
+            compressed: compressed != 0,
+            frame,
+        }))
+    }
+}
+
+/// ID of a state in capture files.
+pub const fn state_id(state: ServerState) -> u8 {
+    match state {
+        ServerState::Handshake => 0,
+        ServerState::Status => 1,
+        ServerState::Login => 2,
+        ServerState::Configuration => 3,
+        ServerState::Play => 4,
+        ServerState::Closed => 5,
+    }
+}
+
+/// State with an ID in capture files.
+pub const fn state_from_id(id: u8) -> Option<ServerState> {
+    Some(match id {
+        0 => ServerState::Handshake,
+        1 => ServerState::Status,
+        2 => ServerState::Login,
+        3 => ServerState::Configuration,
+        4 => ServerState::Play,
+        5 => ServerState::Closed,
+        _ => return None,
+    })
+}
+
+/// Write the header of a capture of a connection from `peer`.
+pub fn write_header(to: &mut impl Write, peer: SocketAddr) -> Result<(), io::Error> {
+    let peer: String = peer.to_string();
+    to.write_all(MAGIC)?;
+    to.write_all(&[VERSION])?;
+    // Addresses are far shorter than 255 bytes
+    to.write_all(&[u8::try_from(peer.len()).unwrap_or(u8::MAX)])?;
+    to.write_all(peer.as_bytes())
+}
+
+/// Read the header of a capture, returning the address of the client.
+pub fn read_header(from: &mut impl Read) -> Result<String, CaptureError> {
+    let mut magic: [u8; 8] = [0; 8];
+    from.read_exact(&mut magic)
+        .map_err(|_| CaptureError::InvalidHeader)?;
+    let (signature, [version, length]) = magic.split_at(MAGIC.len()) else {
+        return Err(CaptureError::InvalidHeader);
+    };
+    if signature != MAGIC || *version != VERSION {
+        return Err(CaptureError::InvalidHeader);
+    }
+    let mut peer: Vec<u8> = vec![0; usize::from(*length)];
+    from.read_exact(&mut peer)?;
+    String::from_utf8(peer).map_err(|_| CaptureError::InvalidHeader)
+}
+
+/// Capture of a connection being written, shared by its reader and writer threads.
+pub struct Capture {
+    /// When the connection opened.
+    opened_at: Instant,
+
+    /// The capture file.
+    file: Mutex<BufWriter<File>>,
+}
+
+impl Capture {
+    /// Create the capture file of a connection in `directory`, named after the
+    /// current time and the connection ID.
+    pub fn create(
+        directory: &Path,
+        connection_id: u64,
+        peer: SocketAddr,
+    ) -> Result<Self, io::Error> {
+        fs::create_dir_all(directory)?;
+        let now: u128 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path: PathBuf = directory.join(format!("{now}-{connection_id}.cap"));
+
+        let mut file: BufWriter<File> = BufWriter::new(File::create(path)?);
+        write_header(&mut file, peer)?;
+        Ok(Self {
+            opened_at: Instant::now(),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Record a frame, without its length prefix.
+    pub fn record(
+        &self,
+        direction: Direction,
+        state: ServerState,
+        compressed: bool,
+        frame: &[u8],
+    ) {
+        let record: Record = Record {
+            elapsed: self.opened_at.elapsed(),
+            direction,
+            state,
+            compressed,
+            frame: frame.to_vec(),
+        };
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Err(e) = record.write(&mut *file) {
+            log::warn!(target: "Capture", "Failed to record frame: {e}");
+        }
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        let file = self.file.get_mut().unwrap_or_else(PoisonError::into_inner);
+        if let Err(e) = file.flush() {
+            log::warn!(target: "Capture", "Failed to flush capture: {e}");
+        }
+    }
+}
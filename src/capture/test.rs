@@ -0,0 +1,181 @@
+//! Test [`crate::capture`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{self, File},
+        io::BufReader,
+        net::SocketAddr,
+        path::PathBuf,
+        process,
+        time::Duration,
+    };
+
+    use crate::{
+        capture::{
+            read_header, state_from_id, state_id,
+            tool::{corpus, describe, hex},
+            write_header, Capture, CaptureError, Record,
+        },
+        protocol::{Codec, Direction},
+        state::ServerState,
+    };
+
+    /// Handshake of a 1.21.2 client to `localhost:25565`, for the status.
+    const HANDSHAKE: [u8; 16] = [
+        0x00, 0x80, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+        0x63, 0xdd, 0x01,
+    ];
+
+    #[test]
+    fn test_records() {
+        let peer: SocketAddr = "192.0.2.1:4000".parse().unwrap();
+        let records: [Record; 2] = [
+            Record {
+                elapsed: Duration::from_micros(1500),
+                direction: Direction::Serverbound,
+                state: ServerState::Handshake,
+                compressed: false,
+                frame: HANDSHAKE.to_vec(),
+            },
+            Record {
+                elapsed: Duration::from_secs(2),
+                direction: Direction::Clientbound,
+                state: ServerState::Configuration,
+                compressed: true,
+                frame: vec![0x00, 0x03],
+            },
+        ];
+
+        let mut bytes: Vec<u8> = Vec::new();
+        write_header(&mut bytes, peer).unwrap();
+        for record in &records {
+            record.write(&mut bytes).unwrap();
+        }
+        // Header, then the timing, direction, state and compression of a record
+        assert!(bytes.starts_with(b"AMCCAP\x01\x0e192.0.2.1:4000"));
+        assert!(bytes
+            .get(22..)
+            .unwrap()
+            .starts_with(&[0, 0, 0, 0, 0, 0, 0x05, 0xdc, 0, 0, 0]));
+
+        let mut reader: &[u8] = &bytes;
+        assert_eq!(read_header(&mut reader).unwrap(), "192.0.2.1:4000");
+        for record in &records {
+            assert_eq!(Record::read(&mut reader).unwrap().as_ref(), Some(record));
+        }
+        assert_eq!(Record::read(&mut reader).unwrap(), None);
+
+        assert!(matches!(
+            read_header(&mut b"AMCCAQ\x01\x00".as_slice()),
+            Err(CaptureError::InvalidHeader)
+        ));
+        let mut invalid: Vec<u8> = vec![0; 8];
+        invalid.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0]);
+        assert!(matches!(
+            Record::read(&mut invalid.as_slice()),
+            Err(CaptureError::InvalidRecord(_))
+        ));
+
+        for id in 0..=5 {
+            assert_eq!(state_from_id(id).map(state_id), Some(id));
+        }
+        assert_eq!(state_from_id(6), None);
+    }
+
+    #[test]
+    fn test_describe() {
+        let record: Record = Record {
+            elapsed: Duration::from_micros(1500),
+            direction: Direction::Serverbound,
+            state: ServerState::Handshake,
+            compressed: false,
+            frame: HANDSHAKE.to_vec(),
+        };
+        let line: String = describe(&record);
+        assert!(line.starts_with(
+            "     1.500ms C->S Handshake     0x00 (16 bytes) Handshake(SHandshake {"
+        ));
+        assert!(line.contains("server_address: \"localhost\""));
+
+        // Compressed frames are inflated first
+        let mut codec: Codec = Codec::new();
+        codec.set_compression(Some(0));
+        let frame: Vec<u8> = codec.encode(&[0x00, 0x03]).unwrap();
+        let record: Record = Record {
+            elapsed: Duration::from_millis(2),
+            direction: Direction::Clientbound,
+            state: ServerState::Configuration,
+            compressed: true,
+            frame: frame.get(1..).unwrap().to_vec(),
+        };
+        assert_eq!(
+            describe(&record),
+            "     2.000ms S->C Configuration 0x00 (2 bytes) 03"
+        );
+
+        // Unknown serverbound packets fall back to hexadecimal
+        let record: Record = Record {
+            elapsed: Duration::ZERO,
+            direction: Direction::Serverbound,
+            state: ServerState::Status,
+            compressed: false,
+            frame: vec![0x7f, 0xca, 0xfe],
+        };
+        assert_eq!(
+            describe(&record),
+            "     0.000ms C->S Status        0x7F (3 bytes) ca fe"
+        );
+        assert!(hex(&[0xab; 66]).ends_with("ab ab ... (+2 bytes)"));
+    }
+
+    #[test]
+    fn test_capture() {
+        let directory: PathBuf =
+            std::env::temp_dir().join(format!("atmospheremc-capture-{}", process::id()));
+        let peer: SocketAddr = "192.0.2.1:4000".parse().unwrap();
+
+        let capture: Capture = Capture::create(&directory, 7, peer).unwrap();
+        capture.record(
+            Direction::Serverbound,
+            ServerState::Handshake,
+            false,
+            &HANDSHAKE,
+        );
+        capture.record(Direction::Clientbound, ServerState::Status, false, &[0x01]);
+        drop(capture);
+
+        let path: PathBuf = fs::read_dir(&directory)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        assert!(path.to_string_lossy().ends_with("-7.cap"));
+        let mut reader: BufReader<File> = BufReader::new(File::open(&path).unwrap());
+        assert_eq!(read_header(&mut reader).unwrap(), "192.0.2.1:4000");
+        let first: Record = Record::read(&mut reader).unwrap().unwrap();
+        assert_eq!(first.frame, HANDSHAKE);
+        let second: Record = Record::read(&mut reader).unwrap().unwrap();
+        assert_eq!(
+            (second.direction, second.state),
+            (Direction::Clientbound, ServerState::Status)
+        );
+        assert!(second.elapsed >= first.elapsed);
+        assert_eq!(Record::read(&mut reader).unwrap(), None);
+
+        // Both frames seed the codec targets, only the handshake a packet target
+        let seeds: PathBuf = directory.join("corpus");
+        assert_eq!(corpus(&path.to_string_lossy(), &seeds).unwrap(), 5);
+        let handshake: PathBuf = fs::read_dir(seeds.join("handshake_packet"))
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        assert_eq!(fs::read(handshake).unwrap(), HANDSHAKE);
+        assert!(!seeds.join("status_packet").exists());
+        assert_eq!(corpus(&path.to_string_lossy(), &seeds).unwrap(), 0);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
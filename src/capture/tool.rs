@@ -0,0 +1,264 @@
+//! `capture` subcommand: list the packets of a capture file, replay its
+//! serverbound frames against a server, or turn it into a fuzzing corpus.
+use std::{
+    borrow::Cow,
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, BufReader, Write},
+    net::{Shutdown, TcpStream},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    capture::{read_header, CaptureError, Record},
+    protocol::{
+        Codec, ConfigurationServerBoundPacket, Direction, HandshakeServerBoundPacket,
+        LoginServerBoundPacket, PacketParseError, PlayServerBoundPacket,
+        StatusServerBoundPacket,
+    },
+    state::ServerState,
+    types::{var::VarInt, DataType},
+};
+
+/// Bytes of a packet shown in hexadecimal, the rest is only counted.
+const HEX_LIMIT: usize = 64;
+
+/// Usage of the subcommand.
+const USAGE: &str = "Usage:
+    atmospheremc-void capture decode <file>
+    atmospheremc-void capture replay <file> <address> [--fast]
+    atmospheremc-void capture corpus <file> <directory>";
+
+/// Selector byte of the frame codec in the round trip fuzz target, after the
+/// data types.
+const ROUND_TRIP_FRAME: u8 = 0xFF;
+
+/// Run the subcommand with its arguments, returning the exit code.
+pub fn main(args: &[String]) -> i32 {
+    let result: Result<(), CaptureError> = match args {
+        [command, file] if command == "decode" => decode(file, &mut io::stdout().lock()),
+        [command, file, address] if command == "replay" => replay(file, address, false),
+        [command, file, address, fast] if command == "replay" && fast == "--fast" => {
+            replay(file, address, true)
+        }
+        [command, file, directory] if command == "corpus" => {
+            corpus(file, Path::new(directory)).map(|seeds| {
+                println!("Wrote {seeds} seeds to {directory}");
+            })
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+/// Read the records of a capture file, after its header.
+fn open(file: &str) -> Result<(String, BufReader<File>), CaptureError> {
+    let mut reader: BufReader<File> = BufReader::new(File::open(file)?);
+    let peer: String = read_header(&mut reader)?;
+    Ok((peer, reader))
+}
+
+/// Print the packets of a capture, parsing the serverbound ones.
+fn decode(file: &str, output: &mut impl Write) -> Result<(), CaptureError> {
+    let (peer, mut reader) = open(file)?;
+    writeln!(output, "Capture of {peer}")?;
+    while let Some(record) = Record::read(&mut reader)? {
+        writeln!(output, "{}", describe(&record))?;
+    }
+    Ok(())
+}
+
+/// One line describing a record: timing, direction, state and packet. Packets
+/// without a definition are shown in hexadecimal.
+pub fn describe(record: &Record) -> String {
+    let mut line: String = format!(
+        "{:>10.3}ms {} {:<13}",
+        record.elapsed.as_secs_f64() * 1000.0,
+        match record.direction {
+            Direction::Serverbound => "C->S",
+            Direction::Clientbound => "S->C",
+        },
+        format!("{:?}", record.state),
+    );
+
+    let mut codec: Codec = Codec::new();
+    // The threshold only matters when encoding
+    codec.set_compression(record.compressed.then_some(0));
+    let packet: Cow<[u8]> = match codec.decode(&record.frame) {
+        Ok(packet) => packet,
+        Err(e) => {
+            let _ = write!(line, " undecodable frame: {e}");
+            return line;
+        }
+    };
+
+    let mut data: &[u8] = &packet;
+    let Ok(VarInt(id)) = VarInt::decode(&mut data) else {
+        line.push_str(" empty packet");
+        return line;
+    };
+    let _ = write!(line, " 0x{id:02X} ({} bytes)", packet.len());
+    let parsed: Result<String, PacketParseError> = match record.direction {
+        Direction::Serverbound => parse(record.state, &packet),
+        // Clientbound packets are only encoded by the server
+        Direction::Clientbound => Err(PacketParseError::UnknownPacket(id, record.state)),
+    };
+    match parsed {
+        Ok(parsed) => {
+            let _ = write!(line, " {parsed}");
+        }
+        Err(PacketParseError::UnknownPacket(..)) if !data.is_empty() => {
+            let _ = write!(line, " {}", hex(data));
+        }
+        Err(PacketParseError::UnknownPacket(..)) => {}
+        Err(e) => {
+            let _ = write!(line, " {e}: {}", hex(data));
+        }
+    }
+    line
+}
+
+/// Bytes in hexadecimal, up to [`HEX_LIMIT`] of them.
+pub fn hex(bytes: &[u8]) -> String {
+    let mut hex: String = String::with_capacity(HEX_LIMIT * 3);
+    for (i, byte) in bytes.iter().take(HEX_LIMIT).enumerate() {
+        if i > 0 {
+            hex.push(' ');
+        }
+        let _ = write!(hex, "{byte:02x}");
+    }
+    if bytes.len() > HEX_LIMIT {
+        let _ = write!(hex, " ... (+{} bytes)", bytes.len() - HEX_LIMIT);
+    }
+    hex
+}
+
+/// Parse a serverbound packet with the packet definitions of `state`.
+fn parse(state: ServerState, packet: &[u8]) -> Result<String, PacketParseError> {
+    match state {
+        ServerState::Handshake => {
+            HandshakeServerBoundPacket::try_from(packet).map(|p| format!("{p:?}"))
+        }
+        ServerState::Status => {
+            StatusServerBoundPacket::try_from(packet).map(|p| format!("{p:?}"))
+        }
+        ServerState::Login => {
+            LoginServerBoundPacket::try_from(packet).map(|p| format!("{p:?}"))
+        }
+        ServerState::Configuration => {
+            ConfigurationServerBoundPacket::try_from(packet).map(|p| format!("{p:?}"))
+        }
+        ServerState::Play => {
+            PlayServerBoundPacket::try_from(packet).map(|p| format!("{p:?}"))
+        }
+        ServerState::Closed => Ok(String::new()),
+    }
+}
+
+/// Send the serverbound frames of a capture to the server at `address`, with
+/// their original timing unless `fast`. The responses are discarded.
+fn replay(file: &str, address: &str, fast: bool) -> Result<(), CaptureError> {
+    let (peer, mut reader) = open(file)?;
+    let mut stream: TcpStream = TcpStream::connect(address)?;
+    println!("Replaying the capture of {peer} to {address}");
+
+    let mut drain: TcpStream = stream.try_clone()?;
+    let drained = thread::spawn(move || io::copy(&mut drain, &mut io::sink()));
+
+    let start: Instant = Instant::now();
+    let mut sent: usize = 0;
+    while let Some(record) = Record::read(&mut reader)? {
+        if record.direction != Direction::Serverbound {
+            continue;
+        }
+        if !fast {
+            if let Some(wait) = record.elapsed.checked_sub(start.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+
+        let mut frame: Vec<u8> = Vec::with_capacity(record.frame.len() + 5);
+        let length: i32 = i32::try_from(record.frame.len())
+            .map_err(|e| CaptureError::InvalidRecord(e.to_string()))?;
+        VarInt(length)
+            .encode(&mut frame)
+            .map_err(|e| CaptureError::InvalidRecord(e.to_string()))?;
+        frame.extend_from_slice(&record.frame);
+        stream.write_all(&frame)?;
+        sent += 1;
+    }
+
+    // Give the server time to answer the last packets
+    thread::sleep(Duration::from_millis(500));
+    stream.shutdown(Shutdown::Both)?;
+    let received: u64 = drained
+        .join()
+        .map_err(|_| CaptureError::InvalidRecord("reader thread panicked".to_string()))?
+        .unwrap_or_default();
+    println!("Sent {sent} packets, received {received} bytes");
+    Ok(())
+}
+
+/// Write the frames of a capture as seeds of the fuzz targets, in a directory per
+/// target under `directory`: every frame for `codec` and `round_trip`, and the
+/// serverbound packets for the packet target of their state. Returns the number
+/// of seeds written, identical seeds being written once.
+pub fn corpus(file: &str, directory: &Path) -> Result<usize, CaptureError> {
+    let (_, mut reader) = open(file)?;
+    let mut seeds: usize = 0;
+    while let Some(record) = Record::read(&mut reader)? {
+        // The codec targets take whether the frame is compressed first
+        let mut frame: Vec<u8> = vec![u8::from(record.compressed)];
+        frame.extend_from_slice(&record.frame);
+        seeds += write_seed(&directory.join("codec"), &frame)?;
+        frame.insert(0, ROUND_TRIP_FRAME);
+        seeds += write_seed(&directory.join("round_trip"), &frame)?;
+
+        let target: &str = match record.state {
+            ServerState::Handshake => "handshake_packet",
+            ServerState::Status => "status_packet",
+            ServerState::Login => "login_packet",
+            ServerState::Configuration => "configuration_packet",
+            ServerState::Play => "play_packet",
+            ServerState::Closed => continue,
+        };
+        if record.direction != Direction::Serverbound {
+            continue;
+        }
+        let mut codec: Codec = Codec::new();
+        codec.set_compression(record.compressed.then_some(0));
+        if let Ok(packet) = codec.decode(&record.frame) {
+            seeds += write_seed(&directory.join(target), &packet)?;
+        }
+    }
+    Ok(seeds)
+}
+
+/// Write a seed named after its hash, returning 1 if it is new.
+fn write_seed(directory: &Path, seed: &[u8]) -> Result<usize, CaptureError> {
+    let mut name: String = String::with_capacity(64);
+    for byte in Sha256::digest(seed) {
+        let _ = write!(name, "{byte:02x}");
+    }
+    let path: PathBuf = directory.join(name);
+    if path.exists() {
+        return Ok(0);
+    }
+    fs::create_dir_all(directory)?;
+    fs::write(path, seed)?;
+    Ok(1)
+}
@@ -0,0 +1,288 @@
+//! Headless client: goes through the handshake, status, login, configuration and
+//! join like a vanilla client, answering keep alives, and hands every received
+//! packet to the caller to assert on.
+use std::{
+    borrow::Cow,
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+use thiserror::Error;
+
+use crate::{
+    protocol::Codec,
+    server::PROTOCOL_VERSION,
+    state::ServerState,
+    types::{
+        uuid::Uuid,
+        var::{VarInt, VarString},
+        DataType, DataTypeDecodeError, DataTypeEncodeError,
+    },
+};
+
+mod test;
+
+/// Reads block for at most this long, not to hang on a silent server.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// View distance sent in Client Information.
+const VIEW_DISTANCE: u8 = 2;
+
+/// Login Disconnect, Configuration Disconnect and Play Disconnect.
+const DISCONNECT: [(ServerState, i32); 3] = [
+    (ServerState::Login, 0x00),
+    (ServerState::Configuration, 0x02),
+    (ServerState::Play, 0x1D),
+];
+
+/// Clientbound Keep Alive, in the play state.
+const KEEP_ALIVE: i32 = 0x27;
+
+/// Error of a [`Client`].
+#[derive(Error, Debug)]
+pub enum ClientError {
+    /// The connection failed.
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+
+    /// A received packet could not be decoded.
+    #[error(transparent)]
+    DataTypeDecodeError(#[from] DataTypeDecodeError),
+
+    /// A packet could not be encoded.
+    #[error(transparent)]
+    DataTypeEncodeError(#[from] DataTypeEncodeError),
+
+    /// The status response is not valid JSON.
+    #[error("Invalid status response: {0}")]
+    InvalidStatus(#[from] serde_json::Error),
+
+    /// The server sent another packet than the expected one.
+    #[error("Unexpected packet 0x{id:02X} in {state:?}, expected 0x{expected:02X}")]
+    UnexpectedPacket {
+        /// State of the client.
+        state: ServerState,
+
+        /// ID of the received packet.
+        id: i32,
+
+        /// ID of the expected packet.
+        expected: i32,
+    },
+
+    /// The server disconnected the client.
+    #[error("Disconnected in {0:?}")]
+    Disconnected(ServerState),
+}
+
+/// A packet received from the server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Packet {
+    /// ID of the packet.
+    pub id: i32,
+
+    /// Data of the packet, after the ID.
+    pub data: Vec<u8>,
+}
+
+/// Profile given by Login Success.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoginProfile {
+    /// UUID of the player.
+    pub uuid: Uuid,
+
+    /// Username of the player.
+    pub name: String,
+}
+
+/// A connection to a server, speaking the protocol of the server.
+pub struct Client {
+    /// The connection.
+    stream: TcpStream,
+
+    /// Framing of both directions, sharing the compression threshold.
+    codec: Codec,
+
+    /// State of the connection.
+    state: ServerState,
+}
+
+impl Client {
+    /// Connect to the server at `address`.
+    pub fn connect(address: SocketAddr) -> Result<Self, ClientError> {
+        let stream: TcpStream = TcpStream::connect(address)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            codec: Codec::new(),
+            state: ServerState::Handshake,
+        })
+    }
+
+    /// Send a packet, its ID followed by `data`.
+    pub fn send(&mut self, id: i32, data: &[u8]) -> Result<(), ClientError> {
+        let mut packet: Vec<u8> = Vec::with_capacity(data.len() + 1);
+        VarInt(id).encode(&mut packet)?;
+        packet.extend_from_slice(data);
+        let frame: Vec<u8> = self.codec.encode(&packet)?;
+        self.stream.write_all(&frame)?;
+        Ok(())
+    }
+
+    /// Receive the next packet. Keep alives are answered before being returned,
+    /// and disconnections are errors.
+    pub fn receive(&mut self) -> Result<Packet, ClientError> {
+        let length: usize = usize::try_from(VarInt::read(&mut self.stream)?.0)
+            .map_err(DataTypeDecodeError::from)?;
+        let mut frame: Vec<u8> = vec![0; length];
+        self.stream.read_exact(&mut frame)?;
+
+        let packet: Cow<[u8]> = self.codec.decode(&frame)?;
+        let mut data: &[u8] = &packet;
+        let VarInt(id) = VarInt::decode(&mut data)?;
+        if DISCONNECT.contains(&(self.state, id)) {
+            return Err(ClientError::Disconnected(self.state));
+        }
+        if self.state == ServerState::Play && id == KEEP_ALIVE {
+            self.send(0x1A, data)?;
+        }
+
+        Ok(Packet {
+            id,
+            data: data.to_vec(),
+        })
+    }
+
+    /// Receive the next packet, failing if it is not an `id` packet.
+    pub fn expect(&mut self, id: i32) -> Result<Packet, ClientError> {
+        let packet: Packet = self.receive()?;
+        if packet.id != id {
+            return Err(ClientError::UnexpectedPacket {
+                state: self.state,
+                id: packet.id,
+                expected: id,
+            });
+        }
+        Ok(packet)
+    }
+
+    /// Receive packets until an `id` packet, returning them all.
+    pub fn receive_until(&mut self, id: i32) -> Result<Vec<Packet>, ClientError> {
+        let mut packets: Vec<Packet> = Vec::new();
+        loop {
+            let packet: Packet = self.receive()?;
+            let last: bool = packet.id == id;
+            packets.push(packet);
+            if last {
+                return Ok(packets);
+            }
+        }
+    }
+
+    /// Send the handshake, switching to the status state if `next_state` is
+    /// [`ServerState::Status`], else to the login state.
+    pub fn handshake(&mut self, next_state: ServerState) -> Result<(), ClientError> {
+        let address: SocketAddr = self.stream.peer_addr()?;
+        let mut data: Vec<u8> = Vec::new();
+        VarInt(PROTOCOL_VERSION).encode(&mut data)?;
+        VarString::new(address.ip().to_string()).encode(&mut data)?;
+        address.port().encode(&mut data)?;
+        let status: bool = next_state == ServerState::Status;
+        VarInt(if status { 1 } else { 2 }).encode(&mut data)?;
+        self.send(0x00, &data)?;
+
+        self.state = if status {
+            ServerState::Status
+        } else {
+            ServerState::Login
+        };
+        Ok(())
+    }
+
+    /// Log in as `name`, in the login state, enabling compression if the server
+    /// asks for it. Switches to the configuration state.
+    pub fn login(&mut self, name: &str) -> Result<LoginProfile, ClientError> {
+        let mut data: Vec<u8> = Vec::new();
+        VarString::new(name.to_string()).encode(&mut data)?;
+        Uuid::offline(name).encode(&mut data)?;
+        self.send(0x00, &data)?;
+
+        let mut success: Packet = self.receive()?;
+        if success.id == 0x03 {
+            // Set Compression, a negative threshold disabling it
+            let VarInt(threshold) = VarInt::decode(&mut success.data.as_slice())?;
+            self.codec.set_compression(usize::try_from(threshold).ok());
+            success = self.expect(0x02)?;
+        } else if success.id != 0x02 {
+            return Err(ClientError::UnexpectedPacket {
+                state: self.state,
+                id: success.id,
+                expected: 0x02,
+            });
+        }
+        let mut data: &[u8] = &success.data;
+        let profile: LoginProfile = LoginProfile {
+            uuid: Uuid::decode(&mut data)?,
+            name: VarString::decode(&mut data)?.get(),
+        };
+
+        // Login Acknowledged
+        self.send(0x03, &[])?;
+        self.state = ServerState::Configuration;
+        Ok(profile)
+    }
+
+    /// Go through the configuration: send Client Information, agree on the
+    /// vanilla data pack and acknowledge the end of the configuration. Returns
+    /// the packets received until Finish Configuration, included, and switches
+    /// to the play state.
+    pub fn configure(&mut self) -> Result<Vec<Packet>, ClientError> {
+        let mut information: Vec<u8> = Vec::new();
+        VarString::new("en_us".to_string()).encode(&mut information)?;
+        VIEW_DISTANCE.encode(&mut information)?;
+        self.send(0x00, &information)?;
+
+        let mut packets: Vec<Packet> = vec![self.expect(0x0E)?];
+        let mut known_packs: Vec<u8> = Vec::new();
+        VarInt(1).encode(&mut known_packs)?;
+        for field in ["minecraft", "core", "1.21.2"] {
+            VarString::new(field.to_string()).encode(&mut known_packs)?;
+        }
+        self.send(0x07, &known_packs)?;
+
+        packets.append(&mut self.receive_until(0x03)?);
+        // Acknowledge Finish Configuration
+        self.send(0x03, &[])?;
+        self.state = ServerState::Play;
+        Ok(packets)
+    }
+
+    /// Join the world: receive Login (play) and the packets following it until
+    /// Synchronize Player Position, included, and confirm the teleport.
+    pub fn join(&mut self) -> Result<Vec<Packet>, ClientError> {
+        let mut packets: Vec<Packet> = vec![self.expect(0x2C)?];
+        packets.append(&mut self.receive_until(0x42)?);
+
+        let mut teleport: &[u8] = packets
+            .last()
+            .map(|packet| packet.data.as_slice())
+            .unwrap_or_default();
+        let mut confirm: Vec<u8> = Vec::new();
+        VarInt::decode(&mut teleport)?.encode(&mut confirm)?;
+        self.send(0x00, &confirm)?;
+        Ok(packets)
+    }
+
+    /// Move to a position, in the play state.
+    pub fn move_to(&mut self, x: f64, y: f64, z: f64) -> Result<(), ClientError> {
+        let mut data: Vec<u8> = Vec::new();
+        x.encode(&mut data)?;
+        y.encode(&mut data)?;
+        z.encode(&mut data)?;
+        // Not on the ground, there is none in the void
+        0_u8.encode(&mut data)?;
+        self.send(0x1C, &data)
+    }
+}
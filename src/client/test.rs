@@ -0,0 +1,321 @@
+//! Test [`crate::client`] against a server on an ephemeral port. Requests only
+//! the tests make are implemented here, not to be dead code in the binary.
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        net::{SocketAddr, TcpListener},
+        path::PathBuf,
+        process,
+        sync::Arc,
+        thread::JoinHandle,
+        time::{Duration, Instant},
+    };
+
+    use serde_json::Value;
+
+    use crate::{
+        access::AccessLists,
+        client::{Client, ClientError, LoginProfile, Packet},
+        config::Config,
+        forwarding::ForwardingMode,
+        listener::ListenerThread,
+        player::Player,
+        protocol::play::CKeepAlive,
+        server::{Server, PROTOCOL_VERSION},
+        state::ServerState,
+        text::Text,
+        tick,
+        types::{
+            uuid::Uuid,
+            var::{VarInt, VarString},
+            DataType,
+        },
+    };
+
+    impl Client {
+        /// State of the connection.
+        const fn state(&self) -> ServerState {
+            self.state
+        }
+
+        /// Request the status, in the status state.
+        fn status(&mut self) -> Result<Value, ClientError> {
+            self.send(0x00, &[])?;
+            let response: Packet = self.expect(0x00)?;
+            let json: &str = VarString::decode_str(
+                &mut response.data.as_slice(),
+                VarString::MAX_LENGTH,
+            )?;
+            Ok(serde_json::from_str(json)?)
+        }
+
+        /// Send a handshake with the transfer intent, switching to the login
+        /// state.
+        fn handshake_transfer(&mut self) -> Result<(), ClientError> {
+            let mut data: Vec<u8> = Vec::new();
+            VarInt(PROTOCOL_VERSION).encode(&mut data)?;
+            VarString::new("localhost".to_string()).encode(&mut data)?;
+            25565_u16.encode(&mut data)?;
+            VarInt(3).encode(&mut data)?;
+            self.send(0x00, &data)?;
+            self.state = ServerState::Login;
+            Ok(())
+        }
+
+        /// Send Login Start for `name`, in the login state, without waiting for
+        /// the answer.
+        fn login_start(&mut self, name: &str) -> Result<(), ClientError> {
+            let mut data: Vec<u8> = Vec::new();
+            VarString::new(name.to_string()).encode(&mut data)?;
+            Uuid::offline(name).encode(&mut data)?;
+            self.send(0x00, &data)
+        }
+
+        /// Send a ping with `payload`, in the status state, returning the payload
+        /// of the pong.
+        fn ping(&mut self, payload: i64) -> Result<i64, ClientError> {
+            let mut data: Vec<u8> = Vec::new();
+            payload.encode(&mut data)?;
+            self.send(0x01, &data)?;
+            let pong: Packet = self.expect(0x01)?;
+            Ok(i64::decode(&mut pong.data.as_slice())?)
+        }
+    }
+
+    /// A server running the accept and tick threads of `main`, closing the
+    /// connections that do not log in in time.
+    struct TestServer {
+        /// The server.
+        server: Arc<Server>,
+
+        /// Address of the game listener.
+        address: SocketAddr,
+
+        /// The accept thread.
+        listener: ListenerThread,
+
+        /// The tick thread.
+        tick: JoinHandle<()>,
+
+        /// Directory of the access lists.
+        directory: PathBuf,
+    }
+
+    impl TestServer {
+        /// Start a server listening on an ephemeral port of the loopback address.
+        fn start(name: &str) -> Self {
+            Self::start_with(name, Config::default())
+        }
+
+        /// Start a server with `config`, listening on an ephemeral port of the
+        /// loopback address.
+        fn start_with(name: &str, config: Config) -> Self {
+            let directory: PathBuf = std::env::temp_dir()
+                .join(format!("atmospheremc-client-{name}-{}", process::id()));
+            fs::create_dir_all(&directory).unwrap();
+            let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let config: Config = Config {
+                address: listener.local_addr().unwrap(),
+                view_distance: 2,
+                ..config
+            };
+            let server: Arc<Server> =
+                Arc::new(Server::new(config, AccessLists::load(&directory, false)));
+
+            server
+                .scheduler()
+                .schedule_repeating(1, 1, Server::close_stale_logins);
+
+            let accepting: Arc<Server> = Arc::clone(&server);
+            let listener: ListenerThread =
+                ListenerThread::spawn("Listener", listener, move |listener| {
+                    crate::accept(listener, &accepting);
+                })
+                .unwrap();
+            Self {
+                address: server.config().address,
+                listener,
+                tick: tick::spawn(Arc::clone(&server)),
+                server,
+                directory,
+            }
+        }
+
+        /// Disconnect the clients and stop the accept and tick threads.
+        fn stop(self) {
+            self.server
+                .shutdown(&Text::plain("Test over"), Duration::from_secs(1));
+            self.tick.join().unwrap();
+            self.listener.join();
+            fs::remove_dir_all(&self.directory).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_status() {
+        let server: TestServer = TestServer::start("status");
+        let mut client: Client = Client::connect(server.address).unwrap();
+        client.handshake(ServerState::Status).unwrap();
+        assert_eq!(client.state(), ServerState::Status);
+
+        let status: Value = client.status().unwrap();
+        assert_eq!(
+            status.pointer("/version/protocol"),
+            Some(&Value::from(PROTOCOL_VERSION))
+        );
+        assert_eq!(status.pointer("/players/online"), Some(&Value::from(0)));
+        assert_eq!(client.ping(0x1234_5678).unwrap(), 0x1234_5678);
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_stop_closes_listener() {
+        let server: TestServer = TestServer::start("stop");
+        let address: SocketAddr = server.address;
+        server.stop();
+
+        // The accept thread ended, closing the listener
+        assert!(Client::connect(address).is_err());
+        TcpListener::bind(address).unwrap();
+    }
+
+    #[test]
+    fn test_join() {
+        let server: TestServer = TestServer::start("join");
+        let mut client: Client = Client::connect(server.address).unwrap();
+        client.handshake(ServerState::Login).unwrap();
+
+        let profile: LoginProfile = client.login("Tester").unwrap();
+        assert_eq!(profile.name, "Tester");
+        assert_eq!(profile.uuid, Uuid::offline("Tester"));
+        assert_eq!(client.state(), ServerState::Configuration);
+
+        // Known Packs, the registries, then Finish Configuration
+        let configuration: Vec<Packet> = client.configure().unwrap();
+        assert_eq!(configuration.first().map(|p| p.id), Some(0x0E));
+        assert!(configuration.iter().any(|p| p.id == 0x07));
+        assert_eq!(configuration.last().map(|p| p.id), Some(0x03));
+        assert_eq!(client.state(), ServerState::Play);
+
+        // Login, the chunks around the spawn, then Synchronize Player Position
+        let join: Vec<Packet> = client.join().unwrap();
+        assert_eq!(join.first().map(|p| p.id), Some(0x2C));
+        assert_eq!(join.iter().filter(|p| p.id == 0x28).count(), 25);
+        assert_eq!(join.last().map(|p| p.id), Some(0x42));
+        // Declare Commands, Update Time, then the join message
+        let welcome: Vec<Packet> = client.receive_until(0x73).unwrap();
+        assert_eq!(welcome.first().map(|p| p.id), Some(0x11));
+        assert!(welcome.iter().any(|p| p.id == 0x6B));
+
+        let player: Arc<Player> = server.server.players().get(profile.uuid).unwrap();
+        assert_eq!(server.server.players().in_game(), 1);
+        player.connection().send(&CKeepAlive::new(42)).unwrap();
+        // Update Time is sent every second, the keep alive may come after it
+        let keep_alive: Vec<Packet> = client.receive_until(0x27).unwrap();
+        assert_eq!(keep_alive.last().unwrap().data, 42_i64.to_be_bytes());
+
+        let server_handle: Arc<Server> = Arc::clone(&server.server);
+        server.stop();
+        assert!(matches!(
+            client.receive(),
+            Err(ClientError::Disconnected(ServerState::Play))
+        ));
+        assert_eq!(server_handle.players().len(), 0);
+    }
+
+    #[test]
+    fn test_login_timeout() {
+        let config: Config = Config {
+            handshake_timeout: Duration::from_millis(200),
+            ..Config::default()
+        };
+        let server: TestServer = TestServer::start_with("login-timeout", config);
+        let mut client: Client = Client::connect(server.address).unwrap();
+        let start: Instant = Instant::now();
+        client.handshake(ServerState::Login).unwrap();
+
+        // Idle in the login state, the client is disconnected once the deadline passes
+        assert!(matches!(
+            client.receive(),
+            Err(ClientError::Disconnected(ServerState::Login))
+        ));
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert_eq!(server.server.limiter().counters().handshake_timeouts(), 1);
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_repeated_login_start() {
+        let config: Config = Config {
+            forwarding_mode: ForwardingMode::Modern,
+            forwarding_secret: "secret".to_string(),
+            ..Config::default()
+        };
+        let server: TestServer = TestServer::start_with("repeated-login", config);
+        let mut client: Client = Client::connect(server.address).unwrap();
+        client.handshake(ServerState::Login).unwrap();
+
+        // The forwarding query is sent once, a second Login Start is refused
+        client.login_start("Tester").unwrap();
+        client.expect(0x04).unwrap();
+        client.login_start("Tester").unwrap();
+        assert!(matches!(
+            client.receive(),
+            Err(ClientError::Disconnected(ServerState::Login))
+        ));
+        assert_eq!(server.server.players().len(), 0);
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_transfer_refused() {
+        let server: TestServer = TestServer::start("transfer-refused");
+        let mut client: Client = Client::connect(server.address).unwrap();
+        client.handshake_transfer().unwrap();
+
+        // `accepts-transfers` is off by default
+        assert!(matches!(
+            client.login("Tester"),
+            Err(ClientError::Disconnected(ServerState::Login))
+        ));
+        assert_eq!(server.server.players().len(), 0);
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_transfer() {
+        let config: Config = Config {
+            accepts_transfers: true,
+            ..Config::default()
+        };
+        let server: TestServer = TestServer::start_with("transfer", config);
+        let mut client: Client = Client::connect(server.address).unwrap();
+        client.handshake_transfer().unwrap();
+        let profile: LoginProfile = client.login("Tester").unwrap();
+        client.configure().unwrap();
+        client.join().unwrap();
+
+        // Store Cookie, then Transfer to the given host and port
+        let player: Arc<Player> = server.server.players().get(profile.uuid).unwrap();
+        let cookie: (String, Vec<u8>) = ("atmospheremc:lobby".to_string(), vec![1, 2]);
+        player
+            .transfer("game.example.com", 25566, vec![cookie])
+            .unwrap();
+        let packets: Vec<Packet> = client.receive_until(0x7A).unwrap();
+        assert!(packets.iter().any(|p| p.id == 0x72));
+        let mut data: &[u8] = &packets.last().unwrap().data;
+        assert_eq!(
+            VarString::decode(&mut data).unwrap().get(),
+            "game.example.com"
+        );
+        assert_eq!(VarInt::decode(&mut data).unwrap(), VarInt(25566));
+        assert!(data.is_empty());
+
+        server.stop();
+    }
+}
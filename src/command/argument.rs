@@ -0,0 +1,373 @@
+//! Parsing of command arguments, as done by the vanilla parsers of each
+//! [`ArgumentType`].
+use crate::{
+    command::tree::{ArgumentType, StringKind},
+    player::Position,
+    text::Text,
+};
+
+/// Maximum length of a player name.
+const MAX_NAME_LENGTH: usize = 16;
+
+/// A cursor over a command line.
+pub struct Reader<'a> {
+    /// The command line, without the leading slash.
+    input: &'a str,
+
+    /// Byte offset of the next character.
+    cursor: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Read `input` from the start.
+    pub const fn new(input: &'a str) -> Self {
+        Self { input, cursor: 0 }
+    }
+
+    /// Byte offset of the next character.
+    pub const fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Move the cursor back to `cursor`, to try another node.
+    pub const fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor;
+    }
+
+    /// The input not read yet.
+    pub fn remaining(&self) -> &'a str {
+        self.input.get(self.cursor..).unwrap_or_default()
+    }
+
+    /// Whether there is input left.
+    pub fn can_read(&self) -> bool {
+        !self.remaining().is_empty()
+    }
+
+    /// The next character.
+    pub fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    /// Skip the separators between arguments.
+    pub fn skip_whitespace(&mut self) {
+        let remaining: &str = self.remaining();
+        self.cursor += remaining.len() - remaining.trim_start().len();
+    }
+
+    /// Read until the next separator.
+    pub fn read_word(&mut self) -> &'a str {
+        let remaining: &'a str = self.remaining();
+        let word: &'a str = remaining
+            .split(char::is_whitespace)
+            .next()
+            .unwrap_or_default();
+        self.cursor += word.len();
+        word
+    }
+
+    /// Read the rest of the input.
+    pub fn read_rest(&mut self) -> &'a str {
+        let rest: &'a str = self.remaining();
+        self.cursor = self.input.len();
+        rest
+    }
+
+    /// Read a string between quotes, with backslash escapes.
+    fn read_quoted(&mut self, quote: char) -> Result<String, Text> {
+        let mut result: String = String::new();
+        let mut escaped: bool = false;
+        let mut chars = self.remaining().char_indices().skip(1);
+        for (offset, c) in &mut chars {
+            if escaped {
+                if c != quote && c != '\\' {
+                    return Err(Text::translate(
+                        "parsing.quote.escape",
+                        vec![Text::plain(c.to_string())],
+                    ));
+                }
+                result.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                self.cursor += offset + c.len_utf8();
+                return Ok(result);
+            } else {
+                result.push(c);
+            }
+        }
+        Err(Text::translate("parsing.quote.expected.end", vec![]))
+    }
+}
+
+/// Value of a parsed argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgumentValue {
+    /// Value of an [`ArgumentType::Bool`].
+    Bool(bool),
+
+    /// Value of an [`ArgumentType::Integer`].
+    Integer(i32),
+
+    /// Value of an [`ArgumentType::String`].
+    String(String),
+
+    /// Value of an [`ArgumentType::Entity`].
+    Entity(EntitySelector),
+
+    /// Value of an [`ArgumentType::GameProfile`].
+    GameProfile(EntitySelector),
+
+    /// Value of an [`ArgumentType::BlockPos`].
+    BlockPos(BlockPos),
+
+    /// Value of an [`ArgumentType::Message`].
+    Message(String),
+}
+
+/// Players selected by an entity argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntitySelector {
+    /// The player with this name.
+    Name(String),
+
+    /// `@a` and `@e`: all players.
+    AllPlayers,
+
+    /// `@p`: the player nearest to the source.
+    NearestPlayer,
+
+    /// `@r`: a random player.
+    RandomPlayer,
+
+    /// `@s`: the player running the command.
+    Source,
+}
+
+impl EntitySelector {
+    /// Whether the selector may select more than one player.
+    const fn is_multiple(&self) -> bool {
+        matches!(self, Self::AllPlayers)
+    }
+}
+
+/// A coordinate of a [`BlockPos`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Coordinate {
+    /// Whether the value is relative to the source, with `~`.
+    pub relative: bool,
+
+    /// The value, or the offset if relative.
+    pub value: i32,
+}
+
+impl Coordinate {
+    /// The absolute block coordinate, relative to `origin` if needed.
+    #[allow(clippy::cast_possible_truncation)] // Positions are far within `i32`
+    fn resolve(self, origin: f64) -> i32 {
+        if self.relative {
+            (origin.floor() as i32).saturating_add(self.value)
+        } else {
+            self.value
+        }
+    }
+}
+
+/// Block coordinates, possibly relative to the source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockPos {
+    /// X coordinate.
+    pub x: Coordinate,
+
+    /// Y coordinate.
+    pub y: Coordinate,
+
+    /// Z coordinate.
+    pub z: Coordinate,
+}
+
+impl BlockPos {
+    /// The absolute block coordinates, relative to `origin` if needed.
+    pub fn resolve(self, origin: Position) -> (i32, i32, i32) {
+        (
+            self.x.resolve(origin.x),
+            self.y.resolve(origin.y),
+            self.z.resolve(origin.z),
+        )
+    }
+}
+
+/// Parse the value of an argument at the cursor of `reader`.
+pub fn parse(parser: &ArgumentType, reader: &mut Reader) -> Result<ArgumentValue, Text> {
+    Ok(match parser {
+        ArgumentType::Bool => ArgumentValue::Bool(parse_bool(reader)?),
+        ArgumentType::Integer { min, max } => {
+            let value: i32 = parse_integer(reader)?;
+            if let Some(min) = min.filter(|min| value < *min) {
+                return Err(Text::translate(
+                    "argument.integer.low",
+                    vec![Text::plain(min.to_string()), Text::plain(value.to_string())],
+                ));
+            }
+            if let Some(max) = max.filter(|max| value > *max) {
+                return Err(Text::translate(
+                    "argument.integer.big",
+                    vec![Text::plain(max.to_string()), Text::plain(value.to_string())],
+                ));
+            }
+            ArgumentValue::Integer(value)
+        }
+        ArgumentType::String(StringKind::SingleWord) => {
+            ArgumentValue::String(reader.read_word().to_string())
+        }
+        ArgumentType::String(StringKind::QuotablePhrase) => {
+            ArgumentValue::String(match reader.peek() {
+                Some(quote @ ('"' | '\'')) => reader.read_quoted(quote)?,
+                _ => reader.read_word().to_string(),
+            })
+        }
+        ArgumentType::String(StringKind::GreedyPhrase) => {
+            ArgumentValue::String(reader.read_rest().to_string())
+        }
+        ArgumentType::Entity {
+            single,
+            players_only,
+        } => ArgumentValue::Entity(parse_entity(reader, *single, *players_only)?),
+        ArgumentType::GameProfile => {
+            ArgumentValue::GameProfile(parse_entity(reader, false, true)?)
+        }
+        ArgumentType::BlockPos => ArgumentValue::BlockPos(parse_block_pos(reader)?),
+        ArgumentType::Message => {
+            // Spaces are collapsed, as in chat messages
+            let words: Vec<&str> = reader.read_rest().split_whitespace().collect();
+            ArgumentValue::Message(words.join(" "))
+        }
+    })
+}
+
+/// Parse `true` or `false`.
+fn parse_bool(reader: &mut Reader) -> Result<bool, Text> {
+    match reader.read_word() {
+        "" => Err(Text::translate("parsing.bool.expected", vec![])),
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => Err(Text::translate(
+            "parsing.bool.invalid",
+            vec![Text::plain(other)],
+        )),
+    }
+}
+
+/// Parse a 32-bit integer.
+fn parse_integer(reader: &mut Reader) -> Result<i32, Text> {
+    let remaining: &str = reader.remaining();
+    let number: &str = remaining
+        .split(|c: char| !(c.is_ascii_digit() || c == '-' || c == '.'))
+        .next()
+        .unwrap_or_default();
+    if number.is_empty() {
+        return Err(Text::translate("parsing.int.expected", vec![]));
+    }
+    let value: i32 = number
+        .parse()
+        .map_err(|_| Text::translate("parsing.int.invalid", vec![Text::plain(number)]))?;
+    reader.cursor += number.len();
+    Ok(value)
+}
+
+/// Parse the selector of an entity argument, checking its constraints.
+fn parse_entity(
+    reader: &mut Reader,
+    single: bool,
+    players_only: bool,
+) -> Result<EntitySelector, Text> {
+    let start: usize = reader.cursor();
+    let selector: EntitySelector = parse_selector(reader)?;
+    let everything: bool = reader
+        .input
+        .get(start..reader.cursor())
+        .is_some_and(|token| token == "@e");
+    if players_only && everything {
+        reader.set_cursor(start);
+        return Err(Text::translate("argument.player.entities", vec![]));
+    }
+    if single && selector.is_multiple() {
+        reader.set_cursor(start);
+        let key: &str = if players_only {
+            "argument.player.toomany"
+        } else {
+            "argument.entity.toomany"
+        };
+        return Err(Text::translate(key, vec![]));
+    }
+    Ok(selector)
+}
+
+/// Parse a player name or a selector without arguments.
+fn parse_selector(reader: &mut Reader) -> Result<EntitySelector, Text> {
+    let start: usize = reader.cursor();
+    let token: &str = reader.read_word();
+    let selector: Option<EntitySelector> = token.strip_prefix('@').map_or_else(
+        || {
+            (!token.is_empty() && token.chars().count() <= MAX_NAME_LENGTH)
+                .then(|| EntitySelector::Name(token.to_string()))
+        },
+        |kind| match kind {
+            "a" | "e" => Some(EntitySelector::AllPlayers),
+            "p" => Some(EntitySelector::NearestPlayer),
+            "r" => Some(EntitySelector::RandomPlayer),
+            "s" => Some(EntitySelector::Source),
+            _ => None,
+        },
+    );
+
+    selector.ok_or_else(|| {
+        reader.set_cursor(start);
+        if token.starts_with('@') {
+            Text::translate("argument.entity.selector.unknown", vec![Text::plain(token)])
+        } else {
+            Text::translate("argument.entity.invalid", vec![])
+        }
+    })
+}
+
+/// Parse three block coordinates separated by spaces.
+fn parse_block_pos(reader: &mut Reader) -> Result<BlockPos, Text> {
+    let x: Coordinate = parse_coordinate(reader)?;
+    let y: Coordinate = next_coordinate(reader)?;
+    let z: Coordinate = next_coordinate(reader)?;
+    Ok(BlockPos { x, y, z })
+}
+
+/// Parse a coordinate after the one just read.
+fn next_coordinate(reader: &mut Reader) -> Result<Coordinate, Text> {
+    if reader.peek() != Some(' ') {
+        return Err(Text::translate("argument.pos.incomplete", vec![]));
+    }
+    reader.cursor += 1;
+    parse_coordinate(reader)
+}
+
+/// Parse an integer coordinate, relative if it starts with `~`.
+fn parse_coordinate(reader: &mut Reader) -> Result<Coordinate, Text> {
+    if reader.peek() == Some('~') {
+        reader.cursor += 1;
+        let offset_given: bool = reader.peek().is_some_and(|c| !c.is_whitespace());
+        return Ok(Coordinate {
+            relative: true,
+            value: if offset_given {
+                parse_integer(reader)?
+            } else {
+                0
+            },
+        });
+    }
+    if !reader.can_read() {
+        return Err(Text::translate("argument.pos.missing.int", vec![]));
+    }
+    Ok(Coordinate {
+        relative: false,
+        value: parse_integer(reader)?,
+    })
+}
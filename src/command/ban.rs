@@ -0,0 +1,59 @@
+//! `ban` command: ban players.
+use crate::{
+    access::{BanDetails, PlayerBan},
+    command::{
+        tree::{argument, literal, ArgumentType, CommandNode},
+        CommandContext, CommandError,
+    },
+    profile::PlayerProfile,
+    text::Text,
+};
+
+/// The command.
+pub fn command() -> CommandNode {
+    literal("ban").requires(3).then(
+        argument("targets", ArgumentType::GameProfile)
+            .executes(execute)
+            .then(argument("reason", ArgumentType::Message).executes(execute)),
+    )
+}
+
+/// Ban the players, by offline UUID if not in game, and kick them.
+fn execute(context: &CommandContext) -> Result<(), CommandError> {
+    let profiles: Vec<PlayerProfile> = context.profiles("targets")?;
+    let reason: Option<String> = context.string("reason").map(ToString::to_string);
+    let access = context.server.access();
+
+    let mut banned: usize = 0;
+    for profile in profiles {
+        if access.player_ban(profile.uuid).is_some() {
+            continue;
+        }
+
+        let details: BanDetails =
+            BanDetails::new(reason.clone(), context.source.name(), None);
+        let reason: String = details.reason.clone();
+        access.ban_player(PlayerBan {
+            uuid: profile.uuid,
+            name: profile.name.clone(),
+            details,
+        })?;
+        banned += 1;
+
+        if let Some(player) = context.server.players().get(profile.uuid) {
+            player.disconnect(&Text::translate("multiplayer.disconnect.banned", vec![]));
+        }
+        context.feedback(&Text::translate(
+            "commands.ban.success",
+            vec![Text::plain(profile.name), Text::plain(reason)],
+        ));
+    }
+
+    if banned == 0 {
+        return Err(CommandError::Failed(Text::translate(
+            "commands.ban.failed",
+            vec![],
+        )));
+    }
+    Ok(())
+}
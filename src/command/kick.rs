@@ -0,0 +1,40 @@
+//! `kick` command: disconnect players.
+use crate::{
+    command::{
+        tree::{argument, literal, ArgumentType, CommandNode},
+        CommandContext, CommandError,
+    },
+    text::Text,
+};
+
+/// The command.
+pub fn command() -> CommandNode {
+    literal("kick").requires(3).then(
+        argument(
+            "targets",
+            ArgumentType::Entity {
+                single: false,
+                players_only: true,
+            },
+        )
+        .executes(execute)
+        .then(argument("reason", ArgumentType::Message).executes(execute)),
+    )
+}
+
+/// Disconnect the players, with the reason if any.
+fn execute(context: &CommandContext) -> Result<(), CommandError> {
+    let reason: Text = context.string("reason").map_or_else(
+        || Text::translate("multiplayer.disconnect.kicked", vec![]),
+        Text::plain,
+    );
+
+    for player in context.players("targets")? {
+        player.disconnect(&reason);
+        context.feedback(&Text::translate(
+            "commands.kick.success",
+            vec![Text::plain(player.name()), reason.clone()],
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! `list` command: list the players in game.
+use crate::{
+    command::{
+        tree::{literal, CommandNode},
+        CommandContext, CommandError,
+    },
+    text::Text,
+};
+
+/// The command.
+pub fn command() -> CommandNode {
+    literal("list").executes(execute)
+}
+
+/// Send the number and the names of the players.
+#[allow(clippy::unnecessary_wraps)] // Signature of `Executor`
+fn execute(context: &CommandContext) -> Result<(), CommandError> {
+    let names: Vec<String> = context
+        .server
+        .players()
+        .all()
+        .iter()
+        .map(|player| player.name().to_string())
+        .collect();
+
+    context.feedback(&Text::translate(
+        "commands.list.players",
+        vec![
+            Text::plain(names.len().to_string()),
+            Text::plain(context.server.config().max_players.to_string()),
+            Text::plain(names.join(", ")),
+        ],
+    ));
+    Ok(())
+}
@@ -0,0 +1,702 @@
+//! Commands, run from the console or by players in game through the same
+//! [`CommandDispatcher`], and declared to clients as a Brigadier command tree.
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt::Write as _,
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use thiserror::Error;
+
+use crate::{
+    access::AccessListError,
+    player::{Player, Position},
+    profile::PlayerProfile,
+    protocol::{play::SPAWN, CSystemChatMessage},
+    server::Server,
+    text::Text,
+};
+
+pub use argument::ArgumentValue;
+use argument::{EntitySelector, Reader};
+use tree::{ArgumentType, CommandNode, Executor, NodeKind};
+
+/// Parsing of command arguments.
+mod argument;
+/// `ban` command.
+mod ban;
+/// `kick` command.
+mod kick;
+/// `list` command.
+mod list;
+/// `pardon` command.
+mod pardon;
+/// `reload` command.
+mod reload;
+/// `say` command.
+mod say;
+/// `stop` command.
+mod stop;
+mod test;
+/// `tps` command.
+mod tps;
+/// Command tree builder.
+pub mod tree;
+/// `whitelist` command.
+mod whitelist;
+
+/// Permission level of the console, above every operator.
+const CONSOLE_PERMISSION_LEVEL: u8 = 4;
+
+/// Index of the root node in [`CommandDispatcher::nodes`].
+const ROOT: usize = 0;
+
+/// Who runs a command.
+#[derive(Clone)]
+pub enum CommandSource {
+    /// The server console.
+    Console,
+
+    /// A player in game.
+    Player(Arc<Player>),
+
+    /// An RCON client, whose output is collected to be sent back.
+    Rcon(Arc<Mutex<String>>),
+}
+
+impl CommandSource {
+    /// Name of the source, as shown in broadcasts and ban lists.
+    pub fn name(&self) -> String {
+        match self {
+            Self::Console => "Server".to_string(),
+            Self::Player(player) => player.name().to_string(),
+            Self::Rcon(_) => "Rcon".to_string(),
+        }
+    }
+
+    /// Permission level of the source: 4 for the console and RCON, the operator
+    /// level for players.
+    pub fn permission_level(&self, server: &Server) -> u8 {
+        match self {
+            Self::Console | Self::Rcon(_) => CONSOLE_PERMISSION_LEVEL,
+            Self::Player(player) => server.access().permission_level(player.uuid()),
+        }
+    }
+
+    /// Where the source is, the spawn for the console and RCON.
+    pub fn position(&self) -> Position {
+        match self {
+            Self::Console | Self::Rcon(_) => SPAWN,
+            Self::Player(player) => player.position(),
+        }
+    }
+
+    /// Send the result of a command to the source.
+    pub fn send_feedback(&self, message: &Text) {
+        match self {
+            Self::Console => log::info!(target: "Console", "{message}"),
+            Self::Player(player) => {
+                if let Err(e) = player
+                    .connection()
+                    .send(&CSystemChatMessage::new(message.clone(), false))
+                {
+                    player
+                        .connection()
+                        .in_context(|| log::error!("Failed to send feedback: {e}"));
+                }
+            }
+            Self::Rcon(output) => {
+                let mut output = output.lock().unwrap_or_else(PoisonError::into_inner);
+                output.push_str(&message.to_string());
+                output.push('\n');
+            }
+        }
+    }
+}
+
+/// Error when running a command, sent to its source.
+#[derive(Error, Debug)]
+pub enum CommandError {
+    /// No command has this name, or the source may not run it.
+    #[error("Unknown command: {0}")]
+    UnknownCommand(String),
+
+    /// The arguments do not match the usage of the command.
+    #[error("Usage: {0}")]
+    InvalidUsage(String),
+
+    /// An argument could not be parsed, as explained by the message.
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(Text),
+
+    /// No player in game has this name.
+    #[error("Player not found: {0}")]
+    PlayerNotFound(String),
+
+    /// An access list could not be saved.
+    #[error(transparent)]
+    AccessListError(#[from] AccessListError),
+
+    /// The command did not change anything, as explained by the message.
+    #[error("{0}")]
+    Failed(Text),
+}
+
+impl CommandError {
+    /// Message sent to the source.
+    pub fn to_text(&self) -> Text {
+        match self {
+            Self::UnknownCommand(_) => Text::translate("command.unknown.command", vec![]),
+            Self::InvalidUsage(usage) => Text::plain(format!("Usage: {usage}")),
+            Self::PlayerNotFound(_) => {
+                Text::translate("argument.entity.notfound.player", vec![])
+            }
+            Self::AccessListError(e) => Text::plain(format!("Failed to save: {e}")),
+            Self::InvalidArgument(message) | Self::Failed(message) => message.clone(),
+        }
+        .color("red")
+    }
+}
+
+/// Arguments of a command and where it runs.
+pub struct CommandContext<'a> {
+    /// The server.
+    pub server: &'a Server,
+
+    /// Who runs the command.
+    pub source: &'a CommandSource,
+
+    /// Parsed arguments, by name.
+    pub arguments: &'a HashMap<&'static str, ArgumentValue>,
+}
+
+impl CommandContext<'_> {
+    /// Send the result of the command to the source.
+    pub fn feedback(&self, message: &Text) {
+        self.source.send_feedback(message);
+    }
+
+    /// Value of an argument, if given.
+    #[allow(dead_code)] // Built-in commands use the typed getters
+    pub fn argument(&self, name: &str) -> Option<&ArgumentValue> {
+        self.arguments.get(name)
+    }
+
+    /// Value of a string or message argument, if given.
+    pub fn string(&self, name: &str) -> Option<&str> {
+        match self.arguments.get(name)? {
+            ArgumentValue::String(value) | ArgumentValue::Message(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Block coordinates of a block position argument, relative to the source.
+    #[allow(dead_code)] // No built-in command takes a position yet
+    pub fn block_pos(&self, name: &str) -> Option<(i32, i32, i32)> {
+        match self.arguments.get(name)? {
+            ArgumentValue::BlockPos(position) => {
+                Some(position.resolve(self.source.position()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The players in game selected by an entity or game profile argument, at
+    /// least one.
+    pub fn players(&self, name: &str) -> Result<Vec<Arc<Player>>, CommandError> {
+        let selector: &EntitySelector = match self.arguments.get(name) {
+            Some(
+                ArgumentValue::Entity(selector) | ArgumentValue::GameProfile(selector),
+            ) => selector,
+            _ => return Err(CommandError::PlayerNotFound(name.to_string())),
+        };
+
+        let all: Vec<Arc<Player>> = self.server.players().all();
+        let players: Vec<Arc<Player>> = match selector {
+            EntitySelector::Name(name) => {
+                self.server.players().by_name(name).into_iter().collect()
+            }
+            EntitySelector::AllPlayers => all,
+            EntitySelector::NearestPlayer => {
+                let origin: Position = self.source.position();
+                let distance = |player: &Arc<Player>| {
+                    let position: Position = player.position();
+                    let (x, y, z) = (
+                        position.x - origin.x,
+                        position.y - origin.y,
+                        position.z - origin.z,
+                    );
+                    x.mul_add(x, y.mul_add(y, z * z))
+                };
+                all.into_iter()
+                    .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+                    .into_iter()
+                    .collect()
+            }
+            EntitySelector::RandomPlayer => {
+                let random: u64 = RandomState::new().build_hasher().finish();
+                usize::try_from(random)
+                    .ok()
+                    .and_then(|random| all.get(random % all.len().max(1)))
+                    .cloned()
+                    .into_iter()
+                    .collect()
+            }
+            EntitySelector::Source => match self.source {
+                CommandSource::Console | CommandSource::Rcon(_) => Vec::new(),
+                CommandSource::Player(player) => vec![Arc::clone(player)],
+            },
+        };
+
+        if players.is_empty() {
+            let name: String = match selector {
+                EntitySelector::Name(name) => name.clone(),
+                _ => name.to_string(),
+            };
+            return Err(CommandError::PlayerNotFound(name));
+        }
+        Ok(players)
+    }
+
+    /// The profiles selected by a game profile argument, at least one. Names of
+    /// players who are not in game give their offline profile.
+    pub fn profiles(&self, name: &str) -> Result<Vec<PlayerProfile>, CommandError> {
+        if let Some(ArgumentValue::GameProfile(EntitySelector::Name(name))) =
+            self.arguments.get(name)
+        {
+            return Ok(vec![self.server.players().by_name(name).map_or_else(
+                || PlayerProfile::offline(name.clone()),
+                |player| player.profile().clone(),
+            )]);
+        }
+
+        Ok(self
+            .players(name)?
+            .iter()
+            .map(|player| player.profile().clone())
+            .collect())
+    }
+}
+
+/// A registered node of the command graph.
+pub struct Node {
+    /// Kind of the node.
+    pub kind: NodeKind,
+
+    /// Permission level needed to use the node, from 0 to 4.
+    pub permission: u8,
+
+    /// Runs the command if the input ends at this node.
+    pub executor: Option<Executor>,
+
+    /// Node whose children follow this one, instead of its own.
+    pub redirect: Option<usize>,
+
+    /// Indices of the children.
+    pub children: Vec<usize>,
+}
+
+/// A node as declared to a client, with the indices of the nodes it may use.
+pub struct DeclaredNode {
+    /// Kind of the node.
+    pub kind: NodeKind,
+
+    /// Whether the input may end at this node.
+    pub executable: bool,
+
+    /// Node whose children follow this one, instead of its own.
+    pub redirect: Option<usize>,
+
+    /// Indices of the children.
+    pub children: Vec<usize>,
+}
+
+/// The command graph of the server.
+pub struct CommandDispatcher {
+    /// The nodes, the root first.
+    nodes: Vec<Node>,
+}
+
+impl CommandDispatcher {
+    /// Create the dispatcher with the built-in commands.
+    pub fn new() -> Self {
+        let mut dispatcher: Self = Self {
+            nodes: vec![Node {
+                kind: NodeKind::Root,
+                permission: 0,
+                executor: None,
+                redirect: None,
+                children: Vec::new(),
+            }],
+        };
+
+        for command in [
+            ban::command(),
+            kick::command(),
+            list::command(),
+            pardon::command(),
+            reload::command(),
+            say::command(),
+            stop::command(),
+            tps::command(),
+            whitelist::command(),
+        ] {
+            dispatcher.register(command);
+        }
+
+        dispatcher
+    }
+
+    /// Register a command, given as its literal node.
+    pub fn register(&mut self, command: CommandNode) {
+        let index: usize = self.add(command);
+        if let Some(root) = self.nodes.get_mut(ROOT) {
+            root.children.push(index);
+        }
+    }
+
+    /// Add a node and its children, returning its index.
+    fn add(&mut self, node: CommandNode) -> usize {
+        let redirect: Option<usize> = node.redirect.and_then(|path| {
+            let target: Option<usize> = self.find(path);
+            if target.is_none() {
+                log::error!(target: "Commands", "Unknown redirect target: {}", path.join(" "));
+            }
+            target
+        });
+
+        let index: usize = self.nodes.len();
+        self.nodes.push(Node {
+            kind: node.kind,
+            permission: node.permission,
+            executor: node.executor,
+            redirect,
+            children: Vec::new(),
+        });
+
+        for child in node.children {
+            let child: usize = self.add(child);
+            if let Some(node) = self.nodes.get_mut(index) {
+                node.children.push(child);
+            }
+        }
+
+        index
+    }
+
+    /// Find the node at the end of a path of literal names from the root.
+    fn find(&self, path: &[&str]) -> Option<usize> {
+        path.iter().try_fold(ROOT, |index, name| {
+            self.nodes.get(index)?.children.iter().copied().find(|child| {
+                self.nodes
+                    .get(*child)
+                    .is_some_and(|child| matches!(child.kind, NodeKind::Literal(literal) if literal == *name))
+            })
+        })
+    }
+
+    /// The nodes following `index`: its children, or those of its redirect target.
+    fn children(&self, index: usize) -> &[usize] {
+        self.nodes
+            .get(index)
+            .map(|node| node.redirect.unwrap_or(index))
+            .and_then(|index| self.nodes.get(index))
+            .map_or(&[], |node| node.children.as_slice())
+    }
+
+    /// The nodes a source with `level` may use, reachable from the root, which is
+    /// the first one.
+    pub fn declared_nodes(&self, level: u8) -> Vec<DeclaredNode> {
+        let mut order: Vec<usize> = vec![ROOT];
+        let mut indices: HashMap<usize, usize> = HashMap::from([(ROOT, 0)]);
+        let mut next: usize = 0;
+        while let Some(index) = order.get(next).copied() {
+            next += 1;
+            let children = self.nodes.get(index).map_or(&[][..], |n| &n.children);
+            for child in children {
+                let allowed: bool = self
+                    .nodes
+                    .get(*child)
+                    .is_some_and(|c| c.permission <= level);
+                if allowed && !indices.contains_key(child) {
+                    indices.insert(*child, order.len());
+                    order.push(*child);
+                }
+            }
+        }
+
+        order
+            .iter()
+            .filter_map(|index| self.nodes.get(*index))
+            .map(|node| DeclaredNode {
+                kind: node.kind.clone(),
+                executable: node.executor.is_some(),
+                redirect: node
+                    .redirect
+                    .and_then(|target| indices.get(&target).copied()),
+                children: node
+                    .children
+                    .iter()
+                    .filter_map(|child| indices.get(child).copied())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Run a command line, with or without the leading slash, sending errors to the
+    /// source.
+    pub fn dispatch(
+        &self,
+        server: &Server,
+        source: &CommandSource,
+        line: &str,
+    ) -> Result<(), CommandError> {
+        let line: &str = line.trim().trim_start_matches('/');
+        let result: Result<(), CommandError> = self.run(server, source, line);
+        if let Err(e) = &result {
+            source.send_feedback(&e.to_text());
+        }
+        result
+    }
+
+    /// Run a command line.
+    fn run(
+        &self,
+        server: &Server,
+        source: &CommandSource,
+        line: &str,
+    ) -> Result<(), CommandError> {
+        let level: u8 = source.permission_level(server);
+        let mut reader: Reader = Reader::new(line);
+        let name: &str = reader.read_word();
+
+        let command: usize = self
+            .children(ROOT)
+            .iter()
+            .copied()
+            .find(|index| {
+                self.nodes.get(*index).is_some_and(|node| {
+                    node.kind.name().eq_ignore_ascii_case(name)
+                        && node.permission <= level
+                })
+            })
+            .ok_or_else(|| CommandError::UnknownCommand(name.to_string()))?;
+
+        if let CommandSource::Player(player) = source {
+            log::info!("{} issued server command: /{line}", player.name());
+        }
+
+        let mut arguments: HashMap<&'static str, ArgumentValue> = HashMap::new();
+        let executor: Option<Executor> =
+            match self.walk(command, &mut reader, level, &mut arguments) {
+                Ok(end) => self.nodes.get(end).and_then(|node| node.executor),
+                Err(Some(message)) => return Err(CommandError::InvalidArgument(message)),
+                Err(None) => None,
+            };
+        let executor: Executor = executor.ok_or_else(|| {
+            CommandError::InvalidUsage(format!("/{}", self.usage(command, level)))
+        })?;
+
+        executor(&CommandContext {
+            server,
+            source,
+            arguments: &arguments,
+        })
+    }
+
+    /// Parse the input following node `index`, whose own input was just read.
+    ///
+    /// Returns the node the input ends at, or the error of the deepest argument
+    /// that could not be parsed, if any.
+    fn walk(
+        &self,
+        index: usize,
+        reader: &mut Reader,
+        level: u8,
+        arguments: &mut HashMap<&'static str, ArgumentValue>,
+    ) -> Result<usize, Option<Text>> {
+        if !reader.can_read() {
+            return Ok(index);
+        }
+        if reader.peek().is_some_and(|c| !c.is_whitespace()) {
+            return Err(Some(Text::translate("command.expected.separator", vec![])));
+        }
+        reader.skip_whitespace();
+        if !reader.can_read() {
+            return Ok(index);
+        }
+
+        let start: usize = reader.cursor();
+        let mut error: Option<Text> = None;
+        // Literals first: once one matches, the arguments are not tried
+        let mut children: Vec<(usize, &Node)> = self
+            .children(index)
+            .iter()
+            .filter_map(|child| self.nodes.get(*child).map(|node| (*child, node)))
+            .filter(|(_, node)| node.permission <= level)
+            .collect();
+        children.sort_by_key(|(_, node)| matches!(node.kind, NodeKind::Argument { .. }));
+
+        for (child, node) in children {
+            reader.set_cursor(start);
+            match &node.kind {
+                NodeKind::Root => {}
+                NodeKind::Literal(name) => {
+                    if reader.read_word().eq_ignore_ascii_case(name) {
+                        return self.walk(child, reader, level, arguments);
+                    }
+                }
+                NodeKind::Argument { name, parser } => {
+                    match argument::parse(parser, reader) {
+                        Ok(value) => {
+                            arguments.insert(name, value);
+                            match self.walk(child, reader, level, arguments) {
+                                Ok(end) => return Ok(end),
+                                Err(e) => {
+                                    arguments.remove(name);
+                                    error = error.or(e);
+                                }
+                            }
+                        }
+                        Err(e) => error = error.or(Some(e)),
+                    }
+                }
+            }
+        }
+
+        Err(error)
+    }
+
+    /// Usage of node `index` and its children, as in `ban <targets> [<reason>]`.
+    fn usage(&self, index: usize, level: u8) -> String {
+        let Some(node) = self.nodes.get(index) else {
+            return String::new();
+        };
+        let mut usage: String = match &node.kind {
+            NodeKind::Root => String::new(),
+            NodeKind::Literal(name) => (*name).to_string(),
+            NodeKind::Argument { name, .. } => format!("<{name}>"),
+        };
+
+        if let Some(target) = node.redirect.and_then(|target| self.nodes.get(target)) {
+            usage.push_str(" -> ");
+            usage.push_str(target.kind.name());
+            return usage;
+        }
+
+        let children: Vec<String> = node
+            .children
+            .iter()
+            .filter(|child| {
+                self.nodes
+                    .get(**child)
+                    .is_some_and(|child| child.permission <= level)
+            })
+            .map(|child| self.usage(*child, level))
+            .collect();
+        let (open, close) = if node.executor.is_some() {
+            ("[", "]")
+        } else {
+            ("(", ")")
+        };
+        match children.as_slice() {
+            [] => {}
+            [child] if node.executor.is_none() => {
+                usage.push(' ');
+                usage.push_str(child);
+            }
+            children => {
+                let _ = write!(usage, " {open}{}{close}", children.join("|"));
+            }
+        }
+
+        usage
+    }
+
+    /// Suggestions for the last word of a command line without the leading slash:
+    /// the byte offset of the word and the values it may be completed with.
+    pub fn suggestions(
+        &self,
+        server: &Server,
+        source: &CommandSource,
+        line: &str,
+    ) -> (usize, Vec<String>) {
+        let level: u8 = source.permission_level(server);
+        let start: usize = line
+            .rfind(char::is_whitespace)
+            .map_or(0, |separator| separator + 1);
+        let prefix: &str = line.get(..start).unwrap_or_default();
+        let word: &str = line.get(start..).unwrap_or_default();
+
+        let node: Option<usize> = if start == 0 {
+            Some(ROOT)
+        } else {
+            let mut reader: Reader = Reader::new(prefix);
+            let name: &str = reader.read_word();
+            self.children(ROOT)
+                .iter()
+                .copied()
+                .find(|index| {
+                    self.nodes.get(*index).is_some_and(|node| {
+                        node.kind.name().eq_ignore_ascii_case(name)
+                            && node.permission <= level
+                    })
+                })
+                .and_then(|command| {
+                    self.walk(command, &mut reader, level, &mut HashMap::new())
+                        .ok()
+                })
+        };
+
+        let mut suggestions: Vec<String> = Vec::new();
+        let children = node.map_or(&[][..], |node| self.children(node));
+        for node in children.iter().filter_map(|child| self.nodes.get(*child)) {
+            if node.permission > level {
+                continue;
+            }
+            match &node.kind {
+                NodeKind::Root => {}
+                NodeKind::Literal(name) => suggestions.push((*name).to_string()),
+                NodeKind::Argument { parser, .. } => {
+                    suggestions.extend(values(server, parser));
+                }
+            }
+        }
+
+        let lowercase: String = word.to_lowercase();
+        suggestions.retain(|s| s.to_lowercase().starts_with(&lowercase));
+        suggestions.sort();
+        suggestions.dedup();
+        (start, suggestions)
+    }
+}
+
+/// Values an argument may take, as suggested by the server.
+fn values(server: &Server, parser: &ArgumentType) -> Vec<String> {
+    let selectors: &[&str] = match parser {
+        ArgumentType::Bool => return vec!["true".to_string(), "false".to_string()],
+        ArgumentType::Entity {
+            single: true,
+            players_only: _,
+        } => &["@p", "@r", "@s"],
+        ArgumentType::Entity {
+            single: false,
+            players_only: true,
+        }
+        | ArgumentType::GameProfile => &["@a", "@p", "@r", "@s"],
+        ArgumentType::Entity {
+            single: false,
+            players_only: false,
+        } => &["@a", "@e", "@p", "@r", "@s"],
+        _ => return Vec::new(),
+    };
+
+    server
+        .players()
+        .all()
+        .iter()
+        .map(|player| player.name().to_string())
+        .chain(selectors.iter().map(ToString::to_string))
+        .collect()
+}
@@ -0,0 +1,47 @@
+//! `pardon` command: lift the ban of players.
+use crate::{
+    access::PlayerBan,
+    command::{
+        tree::{argument, literal, ArgumentType, CommandNode},
+        CommandContext, CommandError,
+    },
+    text::Text,
+};
+
+/// The command.
+pub fn command() -> CommandNode {
+    literal("pardon")
+        .requires(3)
+        .then(argument("targets", ArgumentType::GameProfile).executes(execute))
+}
+
+/// Lift the bans with the names of the players.
+fn execute(context: &CommandContext) -> Result<(), CommandError> {
+    let access = context.server.access();
+    let bans: Vec<PlayerBan> = access.banned_players();
+
+    let mut pardoned: usize = 0;
+    for profile in context.profiles("targets")? {
+        let Some(ban) = bans
+            .iter()
+            .find(|ban| ban.name.eq_ignore_ascii_case(&profile.name))
+        else {
+            continue;
+        };
+        access.pardon_player(ban.uuid)?;
+        pardoned += 1;
+
+        context.feedback(&Text::translate(
+            "commands.pardon.success",
+            vec![Text::plain(ban.name.clone())],
+        ));
+    }
+
+    if pardoned == 0 {
+        return Err(CommandError::Failed(Text::translate(
+            "commands.pardon.failed",
+            vec![],
+        )));
+    }
+    Ok(())
+}
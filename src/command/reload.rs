@@ -0,0 +1,28 @@
+//! `reload` command: read the access lists again.
+use crate::{
+    command::{
+        tree::{literal, CommandNode},
+        CommandContext, CommandError,
+    },
+    text::Text,
+};
+
+/// The command.
+pub fn command() -> CommandNode {
+    literal("reload").requires(2).executes(execute)
+}
+
+/// Reload the access lists, kicking the players they no longer allow.
+fn execute(context: &CommandContext) -> Result<(), CommandError> {
+    if let Err(e) = context.server.access().reload() {
+        log::error!(target: "Access lists", "Failed to reload: {e}");
+        return Err(CommandError::Failed(Text::translate(
+            "commands.reload.failure",
+            vec![],
+        )));
+    }
+
+    context.server.enforce_access_lists();
+    context.feedback(&Text::translate("commands.reload.success", vec![]));
+    Ok(())
+}
@@ -0,0 +1,26 @@
+//! `say` command: send a message to every player.
+use crate::{
+    command::{
+        tree::{argument, literal, ArgumentType, CommandNode},
+        CommandContext, CommandError,
+    },
+    text::Text,
+};
+
+/// The command.
+pub fn command() -> CommandNode {
+    literal("say")
+        .requires(2)
+        .then(argument("message", ArgumentType::Message).executes(execute))
+}
+
+/// Broadcast the message as an announcement from the source.
+#[allow(clippy::unnecessary_wraps)] // Signature of `Executor`
+fn execute(context: &CommandContext) -> Result<(), CommandError> {
+    let message: &str = context.string("message").unwrap_or_default();
+    context.server.broadcast_message(&Text::translate(
+        "chat.type.announcement",
+        vec![Text::plain(context.source.name()), Text::plain(message)],
+    ));
+    Ok(())
+}
@@ -0,0 +1,21 @@
+//! `stop` command: stop the server.
+use crate::{
+    command::{
+        tree::{literal, CommandNode},
+        CommandContext, CommandError,
+    },
+    text::Text,
+};
+
+/// The command.
+pub fn command() -> CommandNode {
+    literal("stop").requires(4).executes(execute)
+}
+
+/// Ask the main thread to stop the server.
+#[allow(clippy::unnecessary_wraps)] // Signature of `Executor`
+fn execute(context: &CommandContext) -> Result<(), CommandError> {
+    context.feedback(&Text::translate("commands.stop.stopping", vec![]));
+    context.server.request_stop();
+    Ok(())
+}
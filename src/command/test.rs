@@ -0,0 +1,347 @@
+//! Test [`crate::command`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::PathBuf,
+        process,
+        sync::atomic::{AtomicI32, Ordering},
+    };
+
+    use crate::{
+        access::AccessLists,
+        command::{
+            argument::{
+                self, ArgumentValue, BlockPos, Coordinate, EntitySelector, Reader,
+            },
+            tree::{argument, literal, ArgumentType, StringKind},
+            CommandContext, CommandDispatcher, CommandError, CommandSource,
+        },
+        config::Config,
+        player::Position,
+        profile::PlayerProfile,
+        protocol::{play::CDeclareCommands, ClientboundPacket},
+        server::Server,
+        types::{var::VarInt, DataType},
+    };
+
+    /// Value of the last `add` test command.
+    static ADDED: AtomicI32 = AtomicI32::new(0);
+
+    /// Create a server with its access lists in a temporary directory.
+    fn server(name: &str) -> (Server, PathBuf) {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-command-{name}-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let server: Server =
+            Server::new(Config::default(), AccessLists::load(&directory, false));
+        (server, directory)
+    }
+
+    /// Store the sum of the arguments of the `add` test command.
+    #[allow(clippy::unnecessary_wraps)] // Signature of `Executor`
+    fn add(context: &CommandContext) -> Result<(), CommandError> {
+        let value = |name: &str| match context.argument(name) {
+            Some(ArgumentValue::Integer(value)) => *value,
+            _ => 0,
+        };
+        ADDED.store(value("a") + value("b"), Ordering::Relaxed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_arguments() {
+        let parse = |parser: &ArgumentType, input: &str| {
+            let mut reader: Reader = Reader::new(input);
+            argument::parse(parser, &mut reader).map(|value| (value, reader.cursor()))
+        };
+        let bounded: ArgumentType = ArgumentType::Integer {
+            min: Some(0),
+            max: Some(10),
+        };
+
+        assert_eq!(
+            parse(&bounded, "7 rest").unwrap(),
+            (ArgumentValue::Integer(7), 1)
+        );
+        assert!(parse(&bounded, "11").is_err());
+        assert!(parse(&bounded, "-1").is_err());
+        assert!(parse(&bounded, "x").is_err());
+        assert_eq!(
+            parse(&ArgumentType::Bool, "true").unwrap().0,
+            ArgumentValue::Bool(true)
+        );
+        assert!(parse(&ArgumentType::Bool, "yes").is_err());
+
+        let quotable: ArgumentType = ArgumentType::String(StringKind::QuotablePhrase);
+        assert_eq!(
+            parse(&quotable, r#""a \"b\" c" d"#).unwrap(),
+            (ArgumentValue::String(r#"a "b" c"#.to_string()), 11)
+        );
+        assert!(parse(&quotable, "\"open").is_err());
+        assert_eq!(
+            parse(&ArgumentType::String(StringKind::GreedyPhrase), "a  b").unwrap(),
+            (ArgumentValue::String("a  b".to_string()), 4)
+        );
+        assert_eq!(
+            parse(&ArgumentType::Message, "a  b").unwrap().0,
+            ArgumentValue::Message("a b".to_string())
+        );
+
+        let single: ArgumentType = ArgumentType::Entity {
+            single: true,
+            players_only: true,
+        };
+        assert_eq!(
+            parse(&single, "Bob").unwrap().0,
+            ArgumentValue::Entity(EntitySelector::Name("Bob".to_string()))
+        );
+        assert_eq!(
+            parse(&single, "@s").unwrap().0,
+            ArgumentValue::Entity(EntitySelector::Source)
+        );
+        assert!(parse(&single, "@a").is_err());
+        assert!(parse(&single, "@x").is_err());
+        assert!(parse(&ArgumentType::GameProfile, "@e").is_err());
+        assert!(parse(&ArgumentType::GameProfile, "SeventeenLetters_").is_err());
+
+        let position: ArgumentValue =
+            parse(&ArgumentType::BlockPos, "~ 64 ~-2").unwrap().0;
+        let ArgumentValue::BlockPos(position) = position else {
+            panic!("Not a block position: {position:?}");
+        };
+        assert_eq!(
+            position,
+            BlockPos {
+                x: Coordinate {
+                    relative: true,
+                    value: 0
+                },
+                y: Coordinate {
+                    relative: false,
+                    value: 64
+                },
+                z: Coordinate {
+                    relative: true,
+                    value: -2
+                },
+            }
+        );
+        let origin: Position = Position {
+            x: -0.5,
+            y: 100.0,
+            z: 10.5,
+            ..Position::default()
+        };
+        assert_eq!(position.resolve(origin), (-1, 64, 8));
+        assert!(parse(&ArgumentType::BlockPos, "1 2").is_err());
+    }
+
+    #[test]
+    fn test_tree() {
+        let (server, directory) = server("tree");
+        let console: CommandSource = CommandSource::Console;
+        let mut dispatcher: CommandDispatcher = CommandDispatcher::new();
+        dispatcher.register(
+            literal("add").requires(2).then(
+                argument(
+                    "a",
+                    ArgumentType::Integer {
+                        min: None,
+                        max: Some(100),
+                    },
+                )
+                .executes(add)
+                .then(
+                    argument(
+                        "b",
+                        ArgumentType::Integer {
+                            min: None,
+                            max: None,
+                        },
+                    )
+                    .executes(add),
+                ),
+            ),
+        );
+        dispatcher.register(literal("plus").requires(2).redirect(&["add"]));
+
+        assert!(dispatcher.dispatch(&server, &console, "add 1 2").is_ok());
+        assert_eq!(ADDED.load(Ordering::Relaxed), 3);
+        assert!(dispatcher.dispatch(&server, &console, "/plus 4").is_ok());
+        assert_eq!(ADDED.load(Ordering::Relaxed), 4);
+        assert!(matches!(
+            dispatcher.dispatch(&server, &console, "add 101"),
+            Err(CommandError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            dispatcher.dispatch(&server, &console, "add 1x"),
+            Err(CommandError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            dispatcher.dispatch(&server, &console, "add"),
+            Err(CommandError::InvalidUsage(usage)) if usage == "/add <a> [<b>]"
+        ));
+        assert!(matches!(
+            dispatcher.dispatch(&server, &console, "whitelist"),
+            Err(CommandError::InvalidUsage(usage))
+                if usage == "/whitelist (on|off|list|reload|add <targets>|remove <targets>)"
+        ));
+
+        // Players without a permission level only see `list`
+        let nodes = dispatcher.declared_nodes(0);
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes.first().unwrap().children, [1]);
+        assert!(nodes.get(1).unwrap().executable);
+        let bytes: Vec<u8> = CDeclareCommands::new(nodes).to_bytes().unwrap();
+        assert_eq!(
+            bytes,
+            [
+                0x11, // Packet ID
+                0x02, // Nodes
+                0x00, 0x01, 0x01, // Root, child 1
+                0x05, 0x00, 0x04, b'l', b'i', b's',
+                b't', // Executable literal `list`
+                0x00, // Root index
+            ]
+        );
+
+        let nodes = dispatcher.declared_nodes(4);
+        let plus = nodes
+            .iter()
+            .find(|node| node.kind.name() == "plus")
+            .unwrap();
+        let target = nodes.get(plus.redirect.unwrap()).unwrap();
+        assert_eq!(target.kind.name(), "add");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_parser_ids() {
+        let parsers: [(ArgumentType, i32); 7] = [
+            (ArgumentType::Bool, 0),
+            (
+                ArgumentType::Integer {
+                    min: None,
+                    max: None,
+                },
+                3,
+            ),
+            (ArgumentType::String(StringKind::SingleWord), 5),
+            (
+                ArgumentType::Entity {
+                    single: true,
+                    players_only: true,
+                },
+                6,
+            ),
+            (ArgumentType::GameProfile, 7),
+            (ArgumentType::BlockPos, 8),
+            (ArgumentType::Message, 19),
+        ];
+        for (parser, id) in parsers {
+            let mut bytes: Vec<u8> = Vec::new();
+            parser.encode(&mut bytes).unwrap();
+            assert_eq!(
+                VarInt::decode(&mut bytes.as_slice()).unwrap(),
+                VarInt(id),
+                "{}",
+                parser.parser()
+            );
+        }
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let (server, directory) = server("suggestions");
+        let console: CommandSource = CommandSource::Console;
+        let commands: &CommandDispatcher = server.commands();
+
+        assert_eq!(
+            commands.suggestions(&server, &console, "wh"),
+            (0, vec!["whitelist".to_string()])
+        );
+        assert_eq!(
+            commands.suggestions(&server, &console, "whitelist re"),
+            (10, vec!["reload".to_string(), "remove".to_string()])
+        );
+        assert_eq!(
+            commands.suggestions(&server, &console, "kick @"),
+            (
+                5,
+                vec![
+                    "@a".to_string(),
+                    "@p".to_string(),
+                    "@r".to_string(),
+                    "@s".to_string()
+                ]
+            )
+        );
+        assert!(commands
+            .suggestions(&server, &console, "say hi")
+            .1
+            .is_empty());
+        assert!(commands.suggestions(&server, &console, "fly ").1.is_empty());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let (server, directory) = server("dispatch");
+        let console: CommandSource = CommandSource::Console;
+
+        assert!(server.run_command(&console, "list").is_ok());
+        assert!(server.run_command(&console, "/TPS").is_ok());
+        assert!(matches!(
+            server.run_command(&console, "fly"),
+            Err(CommandError::UnknownCommand(_))
+        ));
+        assert!(matches!(
+            server.run_command(&console, "kick"),
+            Err(CommandError::InvalidUsage(_))
+        ));
+        assert!(matches!(
+            server.run_command(&console, "kick Bob"),
+            Err(CommandError::PlayerNotFound(_))
+        ));
+
+        // Players who are not in game are banned by their offline UUID
+        let bob: PlayerProfile = PlayerProfile::offline("Bob".to_string());
+        assert!(server
+            .run_command(&console, "ban Bob Griefing  again")
+            .is_ok());
+        let ban = server.access().player_ban(bob.uuid).unwrap();
+        assert_eq!(ban.details.reason, "Griefing again");
+        assert_eq!(ban.details.source, "Server");
+        assert!(matches!(
+            server.run_command(&console, "ban Bob"),
+            Err(CommandError::Failed(_))
+        ));
+        assert!(server.run_command(&console, "pardon BOB").is_ok());
+        assert!(server.access().player_ban(bob.uuid).is_none());
+
+        assert!(server.run_command(&console, "whitelist on").is_ok());
+        assert!(server.access().is_whitelist_enabled());
+        assert!(server.run_command(&console, "whitelist on").is_err());
+        assert!(server.run_command(&console, "whitelist add Bob").is_ok());
+        assert!(server.access().is_whitelisted(bob.uuid));
+        assert!(server.run_command(&console, "whitelist remove bob").is_ok());
+        assert!(!server.access().is_whitelisted(bob.uuid));
+        assert!(matches!(
+            server.run_command(&console, "whitelist remove Bob"),
+            Err(CommandError::Failed(_))
+        ));
+        assert!(matches!(
+            server.run_command(&console, "whitelist add"),
+            Err(CommandError::InvalidUsage(_))
+        ));
+
+        assert!(!server.is_stop_requested());
+        assert!(server.run_command(&console, "stop").is_ok());
+        assert!(server.is_stop_requested());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,25 @@
+//! `tps` command: show the tick rate of the server.
+use crate::{
+    command::{
+        tree::{literal, CommandNode},
+        CommandContext, CommandError,
+    },
+    text::Text,
+};
+
+/// The command.
+pub fn command() -> CommandNode {
+    literal("tps").requires(2).executes(execute)
+}
+
+/// Send the ticks per second and the milliseconds per tick.
+#[allow(clippy::unnecessary_wraps)] // Signature of `Executor`
+fn execute(context: &CommandContext) -> Result<(), CommandError> {
+    let ticks = context.server.ticks();
+    context.feedback(&Text::plain(format!(
+        "TPS: {:.1}, MSPT: {:.2}",
+        ticks.tps(),
+        ticks.mspt()
+    )));
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//! Command tree builder, in the shape of Brigadier's: literal and argument nodes,
+//! permission requirements, executors and redirects.
+use std::io::Write;
+
+use crate::{
+    command::{CommandContext, CommandError},
+    data::registries::Registry,
+    types::{var::VarInt, DataType, DataTypeEncodeError},
+};
+
+/// Runs a command once its arguments are parsed.
+pub type Executor = fn(&CommandContext) -> Result<(), CommandError>;
+
+/// Create a literal node, matching its name.
+pub const fn literal(name: &'static str) -> CommandNode {
+    CommandNode::new(NodeKind::Literal(name))
+}
+
+/// Create an argument node, parsing its value with `parser`.
+pub const fn argument(name: &'static str, parser: ArgumentType) -> CommandNode {
+    CommandNode::new(NodeKind::Argument { name, parser })
+}
+
+/// Kind of a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The root of the tree, parent of the commands.
+    Root,
+
+    /// A keyword.
+    Literal(&'static str),
+
+    /// A value, parsed by the client and the server.
+    Argument {
+        /// Name of the argument, to get its value in the executor.
+        name: &'static str,
+
+        /// How the value is parsed.
+        parser: ArgumentType,
+    },
+}
+
+impl NodeKind {
+    /// Name of the node, empty for the root.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Root => "",
+            Self::Literal(name) | Self::Argument { name, .. } => name,
+        }
+    }
+}
+
+/// How a string argument is read.
+#[allow(dead_code)] // No built-in command takes a string yet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringKind {
+    /// A single word.
+    SingleWord,
+
+    /// A single word, or a quoted phrase.
+    QuotablePhrase,
+
+    /// The rest of the command.
+    GreedyPhrase,
+}
+
+/// Parser of an argument, as known by the client.
+#[allow(dead_code)] // Not all parsers are used by the built-in commands
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgumentType {
+    /// `brigadier:bool`: `true` or `false`.
+    Bool,
+
+    /// `brigadier:integer`: a 32-bit integer within bounds.
+    Integer {
+        /// Smallest allowed value.
+        min: Option<i32>,
+
+        /// Largest allowed value.
+        max: Option<i32>,
+    },
+
+    /// `brigadier:string`.
+    String(StringKind),
+
+    /// `minecraft:entity`: a player name or a selector. Only players exist in the
+    /// void.
+    Entity {
+        /// Whether at most one entity may be selected.
+        single: bool,
+
+        /// Whether only players may be selected.
+        players_only: bool,
+    },
+
+    /// `minecraft:game_profile`: a player name or a selector, the player may be
+    /// offline.
+    GameProfile,
+
+    /// `minecraft:block_pos`: three integer coordinates, relative with `~`.
+    BlockPos,
+
+    /// `minecraft:message`: the rest of the command, signed by the client.
+    Message,
+}
+
+impl ArgumentType {
+    /// Namespaced name of the parser, in the `command_argument_type` registry.
+    pub const fn parser(&self) -> &'static str {
+        match self {
+            Self::Bool => "brigadier:bool",
+            Self::Integer { .. } => "brigadier:integer",
+            Self::String(_) => "brigadier:string",
+            Self::Entity { .. } => "minecraft:entity",
+            Self::GameProfile => "minecraft:game_profile",
+            Self::BlockPos => "minecraft:block_pos",
+            Self::Message => "minecraft:message",
+        }
+    }
+
+    /// Whether the server suggests the values instead of the client.
+    pub const fn asks_server(&self) -> bool {
+        // The client only knows the players in the tab list, which is not sent
+        matches!(self, Self::Entity { .. } | Self::GameProfile)
+    }
+
+    /// Encode the parser ID and its properties, as in the Declare Commands packet.
+    pub fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        /// Registry of the parsers.
+        const REGISTRY: &str = "minecraft:command_argument_type";
+        let id: i32 = Registry::by_name(REGISTRY)
+            .and_then(|r| r.id_of(self.parser()))
+            .ok_or_else(|| DataTypeEncodeError::UnknownRegistryEntry {
+                registry: REGISTRY,
+                entry: self.parser(),
+            })?;
+        VarInt(id).encode(to)?;
+
+        match self {
+            Self::Integer { min, max } => {
+                let flags: u8 = u8::from(min.is_some()) | u8::from(max.is_some()) << 1;
+                flags.encode(to)?;
+                if let Some(min) = min {
+                    min.encode(to)?;
+                }
+                if let Some(max) = max {
+                    max.encode(to)?;
+                }
+            }
+            Self::String(kind) => VarInt(*kind as i32).encode(to)?,
+            Self::Entity {
+                single,
+                players_only,
+            } => (u8::from(*single) | u8::from(*players_only) << 1).encode(to)?,
+            Self::Bool | Self::GameProfile | Self::BlockPos | Self::Message => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// A node of the command tree, with its children.
+pub struct CommandNode {
+    /// Kind of the node.
+    pub kind: NodeKind,
+
+    /// Permission level needed to use the node, from 0 to 4.
+    pub permission: u8,
+
+    /// Runs the command if the input ends at this node.
+    pub executor: Option<Executor>,
+
+    /// Path of literal names from the root to the node parsing continues with,
+    /// after this one.
+    pub redirect: Option<&'static [&'static str]>,
+
+    /// Children of the node.
+    pub children: Vec<Self>,
+}
+
+impl CommandNode {
+    /// Create a node without children.
+    const fn new(kind: NodeKind) -> Self {
+        Self {
+            kind,
+            permission: 0,
+            executor: None,
+            redirect: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Require a permission level to use the node and its children.
+    #[must_use]
+    pub const fn requires(mut self, permission: u8) -> Self {
+        self.permission = permission;
+        self
+    }
+
+    /// Run `executor` if the input ends at this node.
+    #[must_use]
+    pub fn executes(mut self, executor: Executor) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    /// Continue parsing with the children of the node at `path` from the root,
+    /// which must be registered before this one.
+    #[must_use]
+    #[allow(dead_code)] // No built-in command is an alias yet
+    pub const fn redirect(mut self, path: &'static [&'static str]) -> Self {
+        self.redirect = Some(path);
+        self
+    }
+
+    /// Add a child.
+    #[must_use]
+    pub fn then(mut self, child: Self) -> Self {
+        self.children.push(child);
+        self
+    }
+}
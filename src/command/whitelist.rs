@@ -0,0 +1,135 @@
+//! `whitelist` command: manage the whitelist.
+use crate::{
+    access::UserEntry,
+    command::{
+        tree::{argument, literal, ArgumentType, CommandNode},
+        CommandContext, CommandError,
+    },
+    text::Text,
+};
+
+/// The command.
+pub fn command() -> CommandNode {
+    literal("whitelist")
+        .requires(3)
+        .then(literal("on").executes(on))
+        .then(literal("off").executes(off))
+        .then(literal("list").executes(list))
+        .then(literal("reload").executes(reload))
+        .then(
+            literal("add")
+                .then(argument("targets", ArgumentType::GameProfile).executes(add)),
+        )
+        .then(
+            literal("remove")
+                .then(argument("targets", ArgumentType::GameProfile).executes(remove)),
+        )
+}
+
+/// Turn the whitelist on, kicking the players it does not allow.
+fn on(context: &CommandContext) -> Result<(), CommandError> {
+    let access = context.server.access();
+    if access.is_whitelist_enabled() {
+        return Err(failed("commands.whitelist.alreadyOn"));
+    }
+    access.set_whitelist_enabled(true);
+    context.server.enforce_access_lists();
+    context.feedback(&Text::translate("commands.whitelist.enabled", vec![]));
+    Ok(())
+}
+
+/// Turn the whitelist off.
+fn off(context: &CommandContext) -> Result<(), CommandError> {
+    let access = context.server.access();
+    if !access.is_whitelist_enabled() {
+        return Err(failed("commands.whitelist.alreadyOff"));
+    }
+    access.set_whitelist_enabled(false);
+    context.feedback(&Text::translate("commands.whitelist.disabled", vec![]));
+    Ok(())
+}
+
+/// Send the names of the whitelisted players.
+#[allow(clippy::unnecessary_wraps)] // Signature of `Executor`
+fn list(context: &CommandContext) -> Result<(), CommandError> {
+    let names: Vec<String> = context
+        .server
+        .access()
+        .whitelist()
+        .into_iter()
+        .map(|entry| entry.name)
+        .collect();
+    context.feedback(&if names.is_empty() {
+        Text::translate("commands.whitelist.none", vec![])
+    } else {
+        Text::translate(
+            "commands.whitelist.list",
+            vec![
+                Text::plain(names.len().to_string()),
+                Text::plain(names.join(", ")),
+            ],
+        )
+    });
+    Ok(())
+}
+
+/// Read the whitelist again.
+fn reload(context: &CommandContext) -> Result<(), CommandError> {
+    context.server.access().reload()?;
+    context.server.enforce_access_lists();
+    context.feedback(&Text::translate("commands.whitelist.reloaded", vec![]));
+    Ok(())
+}
+
+/// Add the players to the whitelist.
+fn add(context: &CommandContext) -> Result<(), CommandError> {
+    let access = context.server.access();
+    let mut added: usize = 0;
+    for profile in context.profiles("targets")? {
+        if access.add_to_whitelist(&profile)? {
+            added += 1;
+            context.feedback(&Text::translate(
+                "commands.whitelist.add.success",
+                vec![Text::plain(profile.name)],
+            ));
+        }
+    }
+
+    if added == 0 {
+        return Err(failed("commands.whitelist.add.failed"));
+    }
+    Ok(())
+}
+
+/// Remove the players from the whitelist, kicking them if it is enforced.
+fn remove(context: &CommandContext) -> Result<(), CommandError> {
+    let access = context.server.access();
+    let whitelist: Vec<UserEntry> = access.whitelist();
+    let mut removed: usize = 0;
+    for profile in context.profiles("targets")? {
+        let name: &str = &profile.name;
+        let Some(entry) = whitelist
+            .iter()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+        else {
+            continue;
+        };
+        access.remove_from_whitelist(entry.uuid)?;
+        removed += 1;
+        context.feedback(&Text::translate(
+            "commands.whitelist.remove.success",
+            vec![Text::plain(entry.name.clone())],
+        ));
+    }
+
+    if removed == 0 {
+        return Err(failed("commands.whitelist.remove.failed"));
+    }
+    context.server.enforce_access_lists();
+    Ok(())
+}
+
+/// Error with a translated message.
+fn failed(key: &str) -> CommandError {
+    CommandError::Failed(Text::translate(key, vec![]))
+}
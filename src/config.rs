@@ -0,0 +1,516 @@
+//! Server configuration, read from a vanilla style `server.properties` file.
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs, io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use thiserror::Error;
+
+use crate::{
+    data::blocks::BlockState,
+    forwarding::ForwardingMode,
+    limiter::{ConnectionLimits, PacketRateLimits},
+    proxy_protocol::IpNetwork,
+};
+
+/// Error when loading the configuration.
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    /// The file could not be read or written.
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+
+    /// A property has an invalid value.
+    #[error("Invalid value `{value}` for `{key}`")]
+    InvalidValue {
+        /// Name of the property.
+        key: String,
+
+        /// Value in the file.
+        value: String,
+    },
+}
+
+/// Game mode of players joining the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameMode {
+    /// Survival mode.
+    Survival,
+
+    /// Creative mode.
+    Creative,
+
+    /// Adventure mode.
+    Adventure,
+
+    /// Spectator mode.
+    Spectator,
+}
+
+impl GameMode {
+    /// ID of the game mode in the protocol.
+    pub const fn id(self) -> u8 {
+        match self {
+            Self::Survival => 0,
+            Self::Creative => 1,
+            Self::Adventure => 2,
+            Self::Spectator => 3,
+        }
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "survival" | "0" => Self::Survival,
+            "creative" | "1" => Self::Creative,
+            "adventure" | "2" => Self::Adventure,
+            "spectator" | "3" => Self::Spectator,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Survival => "survival",
+            Self::Creative => "creative",
+            Self::Adventure => "adventure",
+            Self::Spectator => "spectator",
+        })
+    }
+}
+
+/// The server configuration.
+///
+/// Keys follow vanilla's `server.properties` where an equivalent exists.
+#[derive(Clone, Debug)]
+#[allow(clippy::struct_excessive_bools)] // Flags of server.properties
+pub struct Config {
+    /// Address to listen on (`server-ip` and `server-port`).
+    pub address: SocketAddr,
+
+    /// Message of the day, shown in the server list (`motd`).
+    pub motd: String,
+
+    /// Maximum number of players (`max-players`).
+    pub max_players: u32,
+
+    /// Packets of at least this size are compressed, negative to disable
+    /// (`network-compression-threshold`).
+    pub compression_threshold: Option<usize>,
+
+    /// View distance in chunks (`view-distance`).
+    pub view_distance: u8,
+
+    /// Simulation distance in chunks (`simulation-distance`).
+    pub simulation_distance: u8,
+
+    /// Game mode of joining players (`gamemode`).
+    pub game_mode: GameMode,
+
+    /// Block placed under the spawn for players to stand on, e.g.
+    /// `minecraft:stone`, none if empty (`spawn-platform`).
+    pub spawn_platform: Option<BlockState>,
+
+    /// Disconnect message sent to clients when the server stops
+    /// (`shutdown-message`).
+    pub shutdown_message: String,
+
+    /// Limits on open and new connections (`max-connections`,
+    /// `max-connections-per-ip`, `connection-throttle-rate` and
+    /// `connection-throttle-burst`).
+    pub connection_limits: ConnectionLimits,
+
+    /// Connections must complete their handshake, status or login and reach the
+    /// play state within this delay (`handshake-timeout`, in seconds).
+    pub handshake_timeout: Duration,
+
+    /// Maximum number of packets per second from a client, by state, `0` disabling a
+    /// limit (`packet-rate-limit-status`, `packet-rate-limit-login`,
+    /// `packet-rate-limit-configuration` and `packet-rate-limit-play`).
+    pub packet_rate_limits: PacketRateLimits,
+
+    /// Whether connections from trusted sources start with a PROXY protocol header
+    /// giving the real address of the client (`proxy-protocol`).
+    pub proxy_protocol: bool,
+
+    /// Load balancers allowed to send PROXY protocol headers, as comma separated
+    /// addresses or networks (`proxy-protocol-trusted`). Other sources connect
+    /// directly.
+    pub proxy_protocol_trusted: Vec<IpNetwork>,
+
+    /// How the proxy in front of the server forwards player information
+    /// (`forwarding-mode`: `none`, `legacy` for `BungeeCord` or `modern` for
+    /// Velocity). All players then share the address of the proxy for the
+    /// connection limits.
+    pub forwarding_mode: ForwardingMode,
+
+    /// Secret shared with Velocity to verify forwarded information
+    /// (`forwarding-secret`), required by modern forwarding.
+    pub forwarding_secret: String,
+
+    /// Whether clients transferred from another server may log in
+    /// (`accepts-transfers`).
+    pub accepts_transfers: bool,
+
+    /// Whether only whitelisted players and operators may join (`white-list`).
+    pub white_list: bool,
+
+    /// Whether players in game are kicked when they are no longer whitelisted
+    /// (`enforce-whitelist`).
+    pub enforce_whitelist: bool,
+
+    /// Whether to accept RCON clients (`enable-rcon`).
+    pub enable_rcon: bool,
+
+    /// Address the RCON listener binds to (`rcon.ip` and `rcon.port`), separate
+    /// from the game address so that it can stay on a private interface.
+    pub rcon_address: SocketAddr,
+
+    /// Password of RCON clients (`rcon.password`), required to enable RCON.
+    pub rcon_password: String,
+
+    /// Whether to answer query clients (`enable-query`).
+    pub enable_query: bool,
+
+    /// UDP port of the query listener (`query.port`), on the `server-ip` address.
+    pub query_port: u16,
+
+    /// Whether to serve Prometheus metrics over HTTP (`enable-metrics`).
+    pub enable_metrics: bool,
+
+    /// Address of the metrics endpoint (`metrics.ip` and `metrics.port`).
+    pub metrics_address: SocketAddr,
+
+    /// Directory where the frames of each connection are recorded, disabled if
+    /// empty (`capture-directory`).
+    pub capture_directory: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 2565),
+            motd: "AtmosphereMC - Void".to_string(),
+            max_players: 100,
+            compression_threshold: Some(256),
+            view_distance: 10,
+            simulation_distance: 10,
+            // Nothing to stand on in the void without a spawn platform
+            game_mode: GameMode::Spectator,
+            spawn_platform: None,
+            shutdown_message: "Server closed".to_string(),
+            connection_limits: ConnectionLimits {
+                max_connections: 1024,
+                max_connections_per_ip: 8,
+                rate_per_ip: 2.0,
+                burst_per_ip: 10,
+            },
+            handshake_timeout: Duration::from_secs(5),
+            packet_rate_limits: PacketRateLimits {
+                status: 10,
+                login: 20,
+                configuration: 100,
+                play: 500,
+            },
+            proxy_protocol: false,
+            proxy_protocol_trusted: ["127.0.0.1", "::1"]
+                .iter()
+                .filter_map(|network| network.parse().ok())
+                .collect(),
+            forwarding_mode: ForwardingMode::None,
+            forwarding_secret: String::new(),
+            accepts_transfers: false,
+            white_list: false,
+            enforce_whitelist: false,
+            enable_rcon: false,
+            rcon_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 25575),
+            rcon_password: String::new(),
+            enable_query: false,
+            query_port: 25565,
+            enable_metrics: false,
+            metrics_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9225),
+            capture_directory: None,
+        }
+    }
+}
+
+impl Config {
+    /// Load the configuration from a properties file, creating it with the default
+    /// values if it does not exist.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        if !path.exists() {
+            let config: Self = Self::default();
+            fs::write(path, config.to_properties())?;
+            return Ok(config);
+        }
+
+        Self::from_properties(&fs::read_to_string(path)?)
+    }
+
+    /// Parse the configuration from the contents of a properties file. Missing keys
+    /// keep their default value, unknown keys are ignored.
+    pub fn from_properties(contents: &str) -> Result<Self, ConfigError> {
+        let properties: HashMap<&str, &str> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(k, v)| (k.trim(), v.trim()))
+            .collect();
+
+        let mut config: Self = Self::default();
+
+        if let Some(ip) = properties.get("server-ip").filter(|ip| !ip.is_empty()) {
+            config.address.set_ip(parse("server-ip", ip)?);
+        }
+        if let Some(port) = properties.get("server-port") {
+            config.address.set_port(parse("server-port", port)?);
+        }
+        if let Some(motd) = properties.get("motd") {
+            config.motd = (*motd).to_string();
+        }
+        if let Some(max_players) = properties.get("max-players") {
+            config.max_players = parse("max-players", max_players)?;
+        }
+        if let Some(threshold) = properties.get("network-compression-threshold") {
+            let threshold: i32 = parse("network-compression-threshold", threshold)?;
+            config.compression_threshold = usize::try_from(threshold).ok();
+        }
+        if let Some(distance) = properties.get("view-distance") {
+            config.view_distance = parse("view-distance", distance)?;
+        }
+        if let Some(distance) = properties.get("simulation-distance") {
+            config.simulation_distance = parse("simulation-distance", distance)?;
+        }
+        if let Some(game_mode) = properties.get("gamemode") {
+            config.game_mode = parse("gamemode", game_mode)?;
+        }
+        if let Some(block) = properties.get("spawn-platform") {
+            config.spawn_platform = Some(*block)
+                .filter(|block| !block.is_empty())
+                .map(|block| parse("spawn-platform", block))
+                .transpose()?;
+        }
+        if let Some(message) = properties.get("shutdown-message") {
+            config.shutdown_message = (*message).to_string();
+        }
+        config.read_limits(&properties)?;
+        if let Some(enabled) = properties.get("proxy-protocol") {
+            config.proxy_protocol = parse("proxy-protocol", enabled)?;
+        }
+        if let Some(trusted) = properties.get("proxy-protocol-trusted") {
+            config.proxy_protocol_trusted = trusted
+                .split(',')
+                .filter(|network| !network.trim().is_empty())
+                .map(|network| parse("proxy-protocol-trusted", network))
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(accepts) = properties.get("accepts-transfers") {
+            config.accepts_transfers = parse("accepts-transfers", accepts)?;
+        }
+        if let Some(enabled) = properties.get("white-list") {
+            config.white_list = parse("white-list", enabled)?;
+        }
+        if let Some(enforce) = properties.get("enforce-whitelist") {
+            config.enforce_whitelist = parse("enforce-whitelist", enforce)?;
+        }
+        config.read_rcon(&properties)?;
+        if let Some(enabled) = properties.get("enable-query") {
+            config.enable_query = parse("enable-query", enabled)?;
+        }
+        if let Some(port) = properties.get("query.port") {
+            config.query_port = parse("query.port", port)?;
+        }
+        if let Some(directory) = properties.get("capture-directory") {
+            config.capture_directory =
+                Some(PathBuf::from(directory)).filter(|d| !d.as_os_str().is_empty());
+        }
+        if let Some(enabled) = properties.get("enable-metrics") {
+            config.enable_metrics = parse("enable-metrics", enabled)?;
+        }
+        if let Some(ip) = properties.get("metrics.ip").filter(|ip| !ip.is_empty()) {
+            config.metrics_address.set_ip(parse("metrics.ip", ip)?);
+        }
+        if let Some(port) = properties.get("metrics.port") {
+            config
+                .metrics_address
+                .set_port(parse("metrics.port", port)?);
+        }
+        if let Some(mode) = properties.get("forwarding-mode") {
+            config.forwarding_mode = parse("forwarding-mode", mode)?;
+        }
+        if let Some(secret) = properties.get("forwarding-secret") {
+            config.forwarding_secret = (*secret).to_string();
+        }
+        if config.forwarding_mode == ForwardingMode::Modern
+            && config.forwarding_secret.is_empty()
+        {
+            return Err(ConfigError::InvalidValue {
+                key: "forwarding-secret".to_string(),
+                value: String::new(),
+            });
+        }
+
+        Ok(config)
+    }
+
+    /// Read the connection, handshake and packet rate limits.
+    fn read_limits(
+        &mut self,
+        properties: &HashMap<&str, &str>,
+    ) -> Result<(), ConfigError> {
+        if let Some(max) = properties.get("max-connections") {
+            self.connection_limits.max_connections = parse("max-connections", max)?;
+        }
+        if let Some(max) = properties.get("max-connections-per-ip") {
+            self.connection_limits.max_connections_per_ip =
+                parse("max-connections-per-ip", max)?;
+        }
+        if let Some(rate) = properties.get("connection-throttle-rate") {
+            self.connection_limits.rate_per_ip = parse("connection-throttle-rate", rate)?;
+        }
+        if let Some(burst) = properties.get("connection-throttle-burst") {
+            self.connection_limits.burst_per_ip =
+                parse("connection-throttle-burst", burst)?;
+        }
+        if let Some(timeout) = properties.get("handshake-timeout") {
+            self.handshake_timeout =
+                Duration::from_secs(parse("handshake-timeout", timeout)?);
+        }
+        if let Some(limit) = properties.get("packet-rate-limit-status") {
+            self.packet_rate_limits.status = parse("packet-rate-limit-status", limit)?;
+        }
+        if let Some(limit) = properties.get("packet-rate-limit-login") {
+            self.packet_rate_limits.login = parse("packet-rate-limit-login", limit)?;
+        }
+        if let Some(limit) = properties.get("packet-rate-limit-configuration") {
+            self.packet_rate_limits.configuration =
+                parse("packet-rate-limit-configuration", limit)?;
+        }
+        if let Some(limit) = properties.get("packet-rate-limit-play") {
+            self.packet_rate_limits.play = parse("packet-rate-limit-play", limit)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the RCON listener settings.
+    fn read_rcon(&mut self, properties: &HashMap<&str, &str>) -> Result<(), ConfigError> {
+        if let Some(enabled) = properties.get("enable-rcon") {
+            self.enable_rcon = parse("enable-rcon", enabled)?;
+        }
+        if let Some(ip) = properties.get("rcon.ip").filter(|ip| !ip.is_empty()) {
+            self.rcon_address.set_ip(parse("rcon.ip", ip)?);
+        }
+        if let Some(port) = properties.get("rcon.port") {
+            self.rcon_address.set_port(parse("rcon.port", port)?);
+        }
+        if let Some(password) = properties.get("rcon.password") {
+            self.rcon_password = (*password).to_string();
+        }
+
+        Ok(())
+    }
+
+    /// Serialize the configuration as a properties file.
+    pub fn to_properties(&self) -> String {
+        let threshold: i64 = self
+            .compression_threshold
+            .and_then(|t| i64::try_from(t).ok())
+            .unwrap_or(-1);
+
+        [
+            "#Minecraft server properties".to_string(),
+            format!("accepts-transfers={}", self.accepts_transfers),
+            format!(
+                "capture-directory={}",
+                self.capture_directory
+                    .as_deref()
+                    .map(Path::display)
+                    .map(|d| d.to_string())
+                    .unwrap_or_default()
+            ),
+            format!(
+                "connection-throttle-burst={}",
+                self.connection_limits.burst_per_ip
+            ),
+            format!(
+                "connection-throttle-rate={}",
+                self.connection_limits.rate_per_ip
+            ),
+            format!("enable-metrics={}", self.enable_metrics),
+            format!("enable-query={}", self.enable_query),
+            format!("enable-rcon={}", self.enable_rcon),
+            format!("enforce-whitelist={}", self.enforce_whitelist),
+            format!("forwarding-mode={}", self.forwarding_mode),
+            format!("forwarding-secret={}", self.forwarding_secret),
+            format!("gamemode={}", self.game_mode),
+            format!("handshake-timeout={}", self.handshake_timeout.as_secs()),
+            format!("max-connections={}", self.connection_limits.max_connections),
+            format!(
+                "max-connections-per-ip={}",
+                self.connection_limits.max_connections_per_ip
+            ),
+            format!("max-players={}", self.max_players),
+            format!("metrics.ip={}", self.metrics_address.ip()),
+            format!("metrics.port={}", self.metrics_address.port()),
+            format!("motd={}", self.motd),
+            format!("network-compression-threshold={threshold}"),
+            format!(
+                "packet-rate-limit-configuration={}",
+                self.packet_rate_limits.configuration
+            ),
+            format!("packet-rate-limit-login={}", self.packet_rate_limits.login),
+            format!("packet-rate-limit-play={}", self.packet_rate_limits.play),
+            format!(
+                "packet-rate-limit-status={}",
+                self.packet_rate_limits.status
+            ),
+            format!("proxy-protocol={}", self.proxy_protocol),
+            format!(
+                "proxy-protocol-trusted={}",
+                self.proxy_protocol_trusted
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            format!("query.port={}", self.query_port),
+            format!("rcon.ip={}", self.rcon_address.ip()),
+            format!("rcon.password={}", self.rcon_password),
+            format!("rcon.port={}", self.rcon_address.port()),
+            format!("server-ip={}", self.address.ip()),
+            format!("server-port={}", self.address.port()),
+            format!("shutdown-message={}", self.shutdown_message),
+            format!("simulation-distance={}", self.simulation_distance),
+            format!(
+                "spawn-platform={}",
+                self.spawn_platform
+                    .map(|block| block.to_string())
+                    .unwrap_or_default()
+            ),
+            format!("view-distance={}", self.view_distance),
+            format!("white-list={}", self.white_list),
+            String::new(),
+        ]
+        .join("\n")
+    }
+}
+
+/// Parse the value of a property.
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+    value.parse().map_err(|_| ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    })
+}
@@ -0,0 +1,986 @@
+//! Client connections.
+use std::{
+    borrow::Cow,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, Shutdown, SocketAddr, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, PoisonError, Weak,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    capture::Capture,
+    forwarding::ForwardedPlayer,
+    limiter::{ConnectionPermit, PacketRate},
+    logging::{self, LogContext},
+    metrics::Metrics,
+    pending::{PendingQueries, QueryKey, Response},
+    player::Player,
+    profile::PlayerProfile,
+    protocol::{
+        CConfigurationCookieRequest, CConfigurationDisconnect, CConfigurationStoreCookie,
+        CConfigurationTransfer, CLoginCookieRequest, CLoginDisconnect,
+        CLoginPluginRequest, CPlayCookieRequest, CPlayDisconnect, CPlayStoreCookie,
+        CPlayTransfer, Cipher, CipherReader, ClientboundPacket, Codec,
+        ConfigurationServerBoundPacket, Direction, HandshakeServerBoundPacket,
+        LoginServerBoundPacket, PacketParseError, PlayServerBoundPacket, StateEnum,
+        StatusServerBoundPacket, SHARED_SECRET_SIZE,
+    },
+    server::Server,
+    state::ServerState,
+    text::Text,
+    types::{var::VarInt, DataType, DataTypeDecodeError, DataTypeEncodeError},
+};
+
+mod test;
+
+/// The legacy ping payload for clients before 1.7.
+const LEGACY_PING: [u8; 25] = [
+    0xFA, 0x00, 0x0B, 0x00, 0x4D, 0x00, 0x43, 0x00, 0x7C, 0x00, 0x50, 0x00, 0x69, 0x00,
+    0x6E, 0x00, 0x67, 0x00, 0x48, 0x00, 0x6F, 0x00, 0x73, 0x00, 0x00,
+]; // Last should be 0x74
+
+/// Maximum size of a serverbound packet, as in vanilla.
+const MAX_PACKET_SIZE: usize = 2_097_151;
+
+/// Connections not sending anything for this long are closed.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Connections not reading what is sent to them for this long are closed.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Called on the connection thread with the answer to a query, returning the next
+/// state of the connection.
+pub type QueryCallback = Box<
+    dyn FnOnce(&mut Connection, Response) -> Result<ServerState, DataTypeEncodeError>
+        + Send,
+>;
+
+/// A message for the writer thread of a [`Connection`].
+enum Outbound {
+    /// A packet (ID and data) queued in a state, framed with the codec state when
+    /// it is written.
+    Packet(Vec<u8>, ServerState),
+
+    /// Raw bytes, written as is.
+    Raw(Vec<u8>),
+
+    /// Change the compression threshold for the following packets.
+    Compression(Option<usize>),
+
+    /// Encrypt the following packets with the shared secret.
+    Encryption([u8; SHARED_SECRET_SIZE]),
+
+    /// Stop writing and close the socket.
+    Close,
+}
+
+/// The part of a [`Connection`] shared with other threads, to send packets to the
+/// client or disconnect it.
+pub struct ConnectionHandle {
+    /// Unique ID of the connection.
+    id: u64,
+
+    /// Address of the client, as given by the PROXY protocol header or the
+    /// forwarding proxy if any.
+    address: Mutex<SocketAddr>,
+
+    /// Current state of the connection.
+    state: Mutex<ServerState>,
+
+    /// Name of the player, once logged in.
+    player: Mutex<Option<String>>,
+
+    /// Outbound queue, consumed by the writer thread.
+    outbound: Sender<Outbound>,
+
+    /// When the connection was accepted.
+    opened_at: Instant,
+
+    /// Whether [`ConnectionHandle::close`] was called.
+    closing: AtomicBool,
+
+    /// Counters of the server, recording the packets sent.
+    metrics: Arc<Metrics>,
+}
+
+impl ConnectionHandle {
+    /// Unique ID of the connection.
+    pub const fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Address of the client, as given by the PROXY protocol header or the
+    /// forwarding proxy if any.
+    pub fn address(&self) -> SocketAddr {
+        *self.address.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Set the IP address of the client forwarded by a proxy, keeping the port.
+    fn set_forwarded_ip(&self, ip: IpAddr) {
+        self.address
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_ip(ip);
+    }
+
+    /// Context of the connection, for records logged about it.
+    pub fn log_context(&self) -> LogContext {
+        LogContext {
+            connection_id: Some(self.id),
+            peer: Some(self.address()),
+            state: Some(self.state()),
+            player: self
+                .player
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+            packet_id: None,
+        }
+    }
+
+    /// Run `f` with the context of the connection, so that what it logs from
+    /// another thread is attributed to the client.
+    pub fn in_context<R>(&self, f: impl FnOnce() -> R) -> R {
+        if logging::connection_id() == Some(self.id) {
+            // Already on a thread of the connection
+            f()
+        } else {
+            logging::with_context(self.log_context(), f)
+        }
+    }
+
+    /// When the connection was accepted.
+    pub const fn opened_at(&self) -> Instant {
+        self.opened_at
+    }
+
+    /// Whether the connection is closing or closed.
+    pub fn is_closing(&self) -> bool {
+        self.closing.load(Ordering::Relaxed)
+    }
+
+    /// Current state of the connection.
+    pub fn state(&self) -> ServerState {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Change the state of the connection, from the connection thread.
+    fn set_state(&self, state: ServerState) {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = state;
+        logging::update_context(|context| context.state = Some(state));
+    }
+
+    /// Encode a packet and queue it for sending.
+    pub fn send<P: ClientboundPacket>(
+        &self,
+        packet: &P,
+    ) -> Result<(), DataTypeEncodeError> {
+        let bytes: Vec<u8> = packet.to_bytes()?;
+
+        self.in_context(|| {
+            log::trace!("Queueing packet {0} ({1:X?})", P::PACKET_ID, bytes);
+        });
+
+        self.send_bytes(bytes);
+        Ok(())
+    }
+
+    /// Queue an already encoded packet (ID and data) for sending.
+    pub fn send_bytes(&self, packet: Vec<u8>) {
+        let state: ServerState = self.state();
+        // Packets sent by the server all have a known ID
+        if let Ok(id) = VarInt::decode(&mut packet.as_slice()) {
+            self.metrics
+                .record_packet(Direction::Clientbound, state, Some(id.0));
+        }
+        self.queue(Outbound::Packet(packet, state));
+    }
+
+    /// Send a disconnect packet suitable for the current state, then close the
+    /// connection.
+    pub fn disconnect(&self, reason: &Text) {
+        self.in_context(|| log::info!("Disconnecting: {reason}"));
+
+        let result: Result<(), DataTypeEncodeError> = match self.state() {
+            ServerState::Login => self.send(&CLoginDisconnect::new(reason.clone())),
+            ServerState::Configuration => {
+                self.send(&CConfigurationDisconnect::new(reason.clone()))
+            }
+            ServerState::Play => self.send(&CPlayDisconnect::new(reason.clone())),
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            self.in_context(|| log::error!("Failed to encode disconnect packet: {e}"));
+        }
+
+        self.close();
+    }
+
+    /// Store a cookie on the client, kept across transfers. Only possible in the
+    /// configuration and play states.
+    pub fn store_cookie(
+        &self,
+        key: &str,
+        payload: Vec<u8>,
+    ) -> Result<(), DataTypeEncodeError> {
+        match self.state() {
+            ServerState::Configuration => {
+                self.send(&CConfigurationStoreCookie::new(key, payload))
+            }
+            ServerState::Play => self.send(&CPlayStoreCookie::new(key, payload)),
+            state => {
+                self.in_context(|| log::error!("Cannot store cookie {key} in {state:?}"));
+                Ok(())
+            }
+        }
+    }
+
+    /// Send the client to another server, storing `cookies` (key and payload) first
+    /// so the other server can read them. Only possible in the configuration and
+    /// play states.
+    pub fn transfer(
+        &self,
+        host: &str,
+        port: u16,
+        cookies: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), DataTypeEncodeError> {
+        let state: ServerState = self.state();
+        if !matches!(state, ServerState::Configuration | ServerState::Play) {
+            self.in_context(|| log::error!("Cannot transfer in {state:?}"));
+            return Ok(());
+        }
+
+        self.in_context(|| log::info!("Transferring to {host}:{port}"));
+        for (key, payload) in cookies {
+            self.store_cookie(&key, payload)?;
+        }
+        if state == ServerState::Configuration {
+            self.send(&CConfigurationTransfer::new(host, port))
+        } else {
+            self.send(&CPlayTransfer::new(host, port))
+        }
+    }
+
+    /// Flush the outbound queue and close the socket, ending the connection thread.
+    pub fn close(&self) {
+        self.closing.store(true, Ordering::Relaxed);
+        // The writer thread is already gone if the connection is closed
+        let _ = self.outbound.send(Outbound::Close);
+    }
+
+    /// Push a message to the writer thread.
+    fn queue(&self, message: Outbound) {
+        if self.outbound.send(message).is_err() {
+            self.in_context(|| {
+                log::warn!("Writer thread stopped, dropping outbound data");
+            });
+        }
+    }
+}
+
+/// A client connection, owned by its thread.
+///
+/// Reading and handling packets happens on the connection thread. Packets sent with
+/// [`Connection::send`] are queued and written by a dedicated writer thread, so a
+/// handler can send any number of packets without blocking.
+pub struct Connection {
+    /// The socket, buffered for reading and decrypted once encryption is enabled.
+    reader: BufReader<CipherReader<TcpStream>>,
+
+    /// The part of the connection shared with other threads.
+    handle: Arc<ConnectionHandle>,
+
+    /// The server.
+    server: Arc<Server>,
+
+    /// Protocol version sent by the client in the handshake.
+    protocol_version: Option<VarInt>,
+
+    /// Codec state for inbound packets: compression only, they are decrypted by the
+    /// reader.
+    codec: Codec,
+
+    /// Profile of the player, once logged in.
+    profile: Option<PlayerProfile>,
+
+    /// The player, once registered on the server.
+    player: Option<Arc<Player>>,
+
+    /// The writer thread.
+    writer: Option<JoinHandle<()>>,
+
+    /// Slot in the connection limits, released when the connection is dropped.
+    _permit: ConnectionPermit,
+
+    /// Inbound packet rate.
+    packet_rate: PacketRate,
+
+    /// Player information forwarded by the proxy in the handshake.
+    forwarded: Option<ForwardedPlayer>,
+
+    /// Queries awaiting an answer from the client.
+    queries: PendingQueries<QueryCallback>,
+
+    /// Next message ID for login plugin queries.
+    next_message_id: i32,
+
+    /// Whether the client was transferred from another server.
+    transferred: bool,
+
+    /// Whether the client sent Login Start.
+    login_started: bool,
+
+    /// Capture of the frames, if enabled.
+    capture: Option<Arc<Capture>>,
+}
+
+impl Connection {
+    /// Set up a connection for an accepted socket, spawning its writer thread.
+    pub fn new(
+        stream: TcpStream,
+        address: SocketAddr,
+        server: Arc<Server>,
+        permit: ConnectionPermit,
+    ) -> Result<Self, io::Error> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+        let id: u64 = server.allocate_connection_id();
+        let capture: Option<Arc<Capture>> = server
+            .config()
+            .capture_directory
+            .as_deref()
+            .and_then(|directory| {
+                Capture::create(directory, id, address)
+                    .inspect_err(|e| {
+                        log::error!("Failed to create capture for {address}: {e}");
+                    })
+                    .ok()
+                    .map(Arc::new)
+            });
+
+        let (outbound, queue) = mpsc::channel();
+        let handle: Arc<ConnectionHandle> = Arc::new(ConnectionHandle {
+            id,
+            address: Mutex::new(address),
+            state: Mutex::new(ServerState::Handshake),
+            player: Mutex::new(None),
+            outbound,
+            opened_at: Instant::now(),
+            closing: AtomicBool::new(false),
+            metrics: Arc::clone(server.metrics()),
+        });
+
+        let writer_stream: TcpStream = stream.try_clone()?;
+        let writer_handle: Weak<ConnectionHandle> = Arc::downgrade(&handle);
+        let writer_metrics: Arc<Metrics> = Arc::clone(server.metrics());
+        let writer_capture: Option<Arc<Capture>> = capture.clone();
+        let writer: JoinHandle<()> = thread::spawn(move || {
+            write_loop(
+                writer_stream,
+                &writer_handle,
+                &queue,
+                &writer_metrics,
+                writer_capture.as_deref(),
+            );
+        });
+        if !server.register_connection(&handle) {
+            handle.close();
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "Server is stopping",
+            ));
+        }
+
+        Ok(Self {
+            reader: BufReader::new(CipherReader::new(stream)),
+            handle,
+            server,
+            protocol_version: None,
+            codec: Codec::new(),
+            profile: None,
+            player: None,
+            writer: Some(writer),
+            _permit: permit,
+            packet_rate: PacketRate::new(),
+            forwarded: None,
+            queries: PendingQueries::new(),
+            next_message_id: 0,
+            transferred: false,
+            login_started: false,
+            capture,
+        })
+    }
+
+    /// Current state of the connection.
+    pub fn state(&self) -> ServerState {
+        self.handle.state()
+    }
+
+    /// The part of the connection shared with other threads.
+    pub const fn handle(&self) -> &Arc<ConnectionHandle> {
+        &self.handle
+    }
+
+    /// The server.
+    pub const fn server(&self) -> &Arc<Server> {
+        &self.server
+    }
+
+    /// Protocol version sent by the client in the handshake.
+    #[allow(dead_code)]
+    pub const fn protocol_version(&self) -> Option<VarInt> {
+        self.protocol_version
+    }
+
+    /// Set the protocol version, when receiving the handshake.
+    pub const fn set_protocol_version(&mut self, version: VarInt) {
+        self.protocol_version = Some(version);
+    }
+
+    /// Profile of the player, once logged in.
+    pub const fn profile(&self) -> Option<&PlayerProfile> {
+        self.profile.as_ref()
+    }
+
+    /// Set the profile of the player.
+    pub fn set_profile(&mut self, profile: PlayerProfile) {
+        logging::update_context(|context| context.player = Some(profile.name.clone()));
+        *self
+            .handle
+            .player
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(profile.name.clone());
+        self.profile = Some(profile);
+    }
+
+    /// The player, once registered on the server.
+    pub const fn player(&self) -> Option<&Arc<Player>> {
+        self.player.as_ref()
+    }
+
+    /// Set the player registered on the server for this connection.
+    pub fn set_player(&mut self, player: Arc<Player>) {
+        self.player = Some(player);
+    }
+
+    /// Keep the player information forwarded by a proxy in the handshake for the
+    /// login, updating the address of the client.
+    pub fn set_forwarded(&mut self, forwarded: ForwardedPlayer) {
+        self.set_forwarded_ip(forwarded.address);
+        self.forwarded = Some(forwarded);
+    }
+
+    /// Set the IP address of the client forwarded by a proxy.
+    pub fn set_forwarded_ip(&self, ip: IpAddr) {
+        log::info!("Forwarded address: {ip}");
+        self.handle.set_forwarded_ip(ip);
+        logging::update_context(|context| context.peer = Some(self.handle.address()));
+    }
+
+    /// Take the player information forwarded by a proxy in the handshake.
+    pub const fn take_forwarded(&mut self) -> Option<ForwardedPlayer> {
+        self.forwarded.take()
+    }
+
+    /// Whether the client was transferred from another server.
+    pub const fn is_transferred(&self) -> bool {
+        self.transferred
+    }
+
+    /// Mark the client as transferred from another server.
+    pub const fn set_transferred(&mut self) {
+        self.transferred = true;
+    }
+
+    /// Record that the client sent Login Start, returning `false` if it already
+    /// did.
+    pub const fn start_login(&mut self) -> bool {
+        !std::mem::replace(&mut self.login_started, true)
+    }
+
+    /// Send a Login Plugin Request on a channel. `callback` runs with the answer, or
+    /// with [`Response::TimedOut`] after `timeout`. Returns the message ID.
+    pub fn send_login_query(
+        &mut self,
+        channel: &str,
+        data: Vec<u8>,
+        timeout: Duration,
+        callback: impl FnOnce(&mut Self, Response) -> Result<ServerState, DataTypeEncodeError>
+            + Send
+            + 'static,
+    ) -> Result<i32, DataTypeEncodeError> {
+        let message_id: i32 = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+
+        self.send(&CLoginPluginRequest::new(message_id, channel, data))?;
+        self.queries.insert(
+            QueryKey::LoginPlugin(message_id),
+            timeout,
+            Box::new(callback),
+        );
+
+        Ok(message_id)
+    }
+
+    /// Request a cookie from the client. `callback` runs with the payload, or with
+    /// [`Response::TimedOut`] after `timeout`. Only possible in the login,
+    /// configuration and play states.
+    #[allow(dead_code)]
+    pub fn request_cookie(
+        &mut self,
+        key: &str,
+        timeout: Duration,
+        callback: impl FnOnce(&mut Self, Response) -> Result<ServerState, DataTypeEncodeError>
+            + Send
+            + 'static,
+    ) -> Result<(), DataTypeEncodeError> {
+        match self.state() {
+            ServerState::Login => self.send(&CLoginCookieRequest::new(key))?,
+            ServerState::Configuration => {
+                self.send(&CConfigurationCookieRequest::new(key))?;
+            }
+            ServerState::Play => self.send(&CPlayCookieRequest::new(key))?,
+            state => {
+                log::error!("Cannot request cookie {key} in {state:?}");
+                return Ok(());
+            }
+        }
+        self.queries.insert(
+            QueryKey::Cookie(key.to_string()),
+            timeout,
+            Box::new(callback),
+        );
+
+        Ok(())
+    }
+
+    /// Store a cookie on the client, kept across transfers. Only possible in the
+    /// configuration and play states.
+    #[allow(dead_code)]
+    pub fn store_cookie(
+        &self,
+        key: &str,
+        payload: Vec<u8>,
+    ) -> Result<(), DataTypeEncodeError> {
+        self.handle.store_cookie(key, payload)
+    }
+
+    /// Run the callback of the query answered by `key`, returning the next state.
+    pub fn answer_query(
+        &mut self,
+        key: &QueryKey,
+        response: Response,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if let Some(callback) = self.queries.take(key) {
+            callback(self, response)
+        } else {
+            log::debug!("Ignoring unexpected answer to {key:?}");
+            Ok(self.state())
+        }
+    }
+
+    /// Run the callbacks of the queries that timed out, returning the next state.
+    fn expire_queries(&mut self) -> Result<ServerState, DataTypeEncodeError> {
+        for callback in self.queries.take_expired() {
+            let state: ServerState = callback(self, Response::TimedOut)?;
+            if state == ServerState::Closed {
+                return Ok(state);
+            }
+            self.handle.set_state(state);
+        }
+
+        Ok(self.state())
+    }
+
+    /// Enable or disable compression, for inbound packets and for outbound packets
+    /// queued after this call.
+    pub fn set_compression(&mut self, threshold: Option<usize>) {
+        self.codec.set_compression(threshold);
+        self.handle.queue(Outbound::Compression(threshold));
+    }
+
+    /// Enable encryption with the shared secret of the Encryption Response, for
+    /// inbound bytes following it and for outbound packets queued after this call.
+    #[allow(dead_code)] // No online mode yet
+    pub fn set_encryption(&mut self, shared_secret: &[u8; SHARED_SECRET_SIZE]) {
+        // Read ahead by the buffer, but sent by the client after the response
+        let read_ahead: Vec<u8> = self.reader.buffer().to_vec();
+        self.reader.consume(read_ahead.len());
+        self.reader
+            .get_mut()
+            .set_encryption(Cipher::new(shared_secret), read_ahead);
+        self.handle.queue(Outbound::Encryption(*shared_secret));
+    }
+
+    /// Encode a packet and queue it for sending.
+    pub fn send<P: ClientboundPacket>(
+        &self,
+        packet: &P,
+    ) -> Result<(), DataTypeEncodeError> {
+        self.handle.send(packet)
+    }
+
+    /// Send a disconnect packet suitable for the current state, then close the
+    /// connection.
+    pub fn disconnect(&self, reason: &Text) {
+        self.handle.disconnect(reason);
+    }
+
+    /// Handle the connection until it is closed.
+    pub fn run(mut self) {
+        logging::update_context(|context| {
+            *context = LogContext {
+                connection_id: Some(self.handle.id),
+                peer: Some(self.handle.address()),
+                state: Some(self.state()),
+                ..LogContext::default()
+            };
+        });
+        log::info!("Opening connection");
+
+        loop {
+            logging::update_context(|context| context.packet_id = None);
+            match self.wait_for_packet() {
+                Ok(true) => {}
+                Ok(false) => {
+                    let state: ServerState = self.expire_queries().unwrap_or_else(|e| {
+                        log::error!("Failed to encode packet: {e}");
+                        self.state()
+                    });
+                    if self.apply(Ok(state)) {
+                        continue;
+                    }
+                    break;
+                }
+                Err(e) => {
+                    log::error!("Failed to wait for packet: {e}");
+                    break;
+                }
+            }
+
+            // Read packet length
+            match VarInt::read(&mut self.reader) {
+                Ok(length) => {
+                    if length == VarInt(0) {
+                        break;
+                    } else if length == VarInt(254)
+                        && self.state() == ServerState::Handshake
+                    {
+                        match self.handle_legacy_ping() {
+                            Ok(true) => break,
+                            Ok(false) => {}
+                            Err(e) => {
+                                log::error!("Failed to handle legacy ping: {e}");
+                                break;
+                            }
+                        }
+                    }
+                    log::trace!("Reading packet of length {length}");
+                    let result: Result<ServerState, PacketParseError> =
+                        self.handle_packet(length);
+                    if !self.apply(result) {
+                        break;
+                    }
+                }
+                Err(DataTypeDecodeError::IOError(e))
+                    if e.kind() == io::ErrorKind::UnexpectedEof =>
+                {
+                    log::debug!("Client closed the connection");
+                    break;
+                }
+                Err(e) => {
+                    log::error!("Failed to read packet length: {e}");
+                    break;
+                }
+            }
+        }
+
+        self.close();
+    }
+
+    /// Apply the next state returned by a handler. Returns whether the connection
+    /// stays open.
+    fn apply(&self, result: Result<ServerState, PacketParseError>) -> bool {
+        match result {
+            Ok(ServerState::Closed) => {
+                log::info!("Gracefully closing connection");
+                return false;
+            }
+            Ok(s) => self.handle.set_state(s),
+            Err(e @ PacketParseError::UnknownPacket(..)) => {
+                self.server.metrics().record_error(&e);
+                log::debug!("Ignoring packet: {e}");
+            }
+            Err(e) => {
+                self.server.metrics().record_error(&e);
+                if let Some(reason) = e.kick_reason() {
+                    log::warn!("Kicking client: {e}");
+                    self.disconnect(&reason);
+                    return false;
+                }
+                log::error!("Failed to handle packet: {e}");
+            }
+        }
+
+        true
+    }
+
+    /// Wait for the next packet until the earliest query deadline.
+    ///
+    /// Returns `false` if the deadline passed first.
+    fn wait_for_packet(&mut self) -> Result<bool, io::Error> {
+        let Some(deadline) = self.queries.next_deadline() else {
+            return Ok(true);
+        };
+        let wait: Duration = deadline.saturating_duration_since(Instant::now());
+        if wait.is_zero() {
+            return Ok(false);
+        }
+
+        self.reader
+            .get_ref()
+            .get_ref()
+            .set_read_timeout(Some(wait.min(READ_TIMEOUT)))?;
+        let result: Result<(), io::Error> = self.reader.fill_buf().map(|_| ());
+        self.reader
+            .get_ref()
+            .get_ref()
+            .set_read_timeout(Some(READ_TIMEOUT))?;
+
+        match result {
+            // Including the end of the stream, seen when reading the packet
+            Ok(()) => Ok(true),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Answer a pre-1.7 server list ping, if that is what the client sent.
+    ///
+    /// Returns whether a legacy ping was handled, in which case the connection must
+    /// be closed.
+    fn handle_legacy_ping(&mut self) -> Result<bool, io::Error> {
+        // As with peeking the socket, only the bytes received so far are compared
+        if !self.reader.fill_buf()?.starts_with(&LEGACY_PING) {
+            return Ok(false);
+        }
+
+        log::info!("Handling legacy ping");
+        self.server.metrics().record_legacy_ping();
+
+        // Skip 25 bytes (peeked)
+        self.reader.consume(LEGACY_PING.len());
+
+        // Read length of rest of data
+        let mut length_buf: [u8; 2] = [0; 2];
+        self.reader.read_exact(&mut length_buf)?;
+        let length: u16 = u16::from_be_bytes(length_buf);
+
+        // We don't give a fuck
+        // Discard remaining data
+        io::copy(
+            &mut Read::by_ref(&mut self.reader).take(length.into()),
+            &mut io::sink(),
+        )?;
+
+        // Send answer
+        let mut answer: Vec<u8> = Vec::new();
+        answer.write_all(&[0xff])?; // Kick packet
+        answer.write_all(&[0x00, 0x23])?; // Length of data after in characters
+        answer.write_all(&[0x00, 0xa7, 0x00, 0x31, 0x00, 0x00])?; // String
+        answer.write_all(&[0x0, 0x31, 0x0, 0x32, 0x0, 0x37, 0x00, 0x00])?; // Protocol version
+        answer.write_all(&[
+            0x0, 0x31, 0x0, 0x2E, 0x0, 0x32, 0x0, 0x31, 0x0, 0x2E, 0x0, 0x31, 0x00, 0x00,
+        ])?; // 1.21.2
+        answer.write_all(&[
+            0x0, 0x41, 0x0, 0x74, 0x0, 0x6D, 0x0, 0x6F, 0x0, 0x73, 0x0, 0x70, 0x0, 0x68,
+            0x0, 0x65, 0x0, 0x72, 0x0, 0x65, 0x0, 0x4D, 0x0, 0x43, 0x0, 0x20, 0x0, 0x56,
+            0x0, 0x6F, 0x0, 0x69, 0x0, 0x64, 0x00, 0x00,
+        ])?; // AtmosphereMC - Void
+        answer.write_all(&[0x0, 0x30, 0x00, 0x00])?; // 0 players
+        answer.write_all(&[0x0, 0x30])?; // Out of 0
+        self.handle.queue(Outbound::Raw(answer));
+
+        // Skip further processing: close the connection
+        Ok(true)
+    }
+
+    /// Read and handle a packet of `length` bytes.
+    fn handle_packet(&mut self, length: VarInt) -> Result<ServerState, PacketParseError> {
+        let limit: u32 = self
+            .server
+            .config()
+            .packet_rate_limits
+            .for_state(self.state());
+        if !self.packet_rate.record(limit) {
+            return Err(PacketParseError::RateLimitExceeded(self.state()));
+        }
+
+        let length: usize =
+            usize::try_from(length.0).map_err(DataTypeDecodeError::from)?;
+        if length > MAX_PACKET_SIZE {
+            return Err(DataTypeDecodeError::PacketTooLarge(length).into());
+        }
+
+        let mut request: Vec<u8> = vec![0; length];
+        self.reader
+            .read_exact(&mut request)
+            .map_err(|_| DataTypeDecodeError::PrematureEnd)?;
+        if let Some(capture) = &self.capture {
+            let compressed: bool = self.codec.is_compressed();
+            capture.record(Direction::Serverbound, self.state(), compressed, &request);
+        }
+        // The length prefix takes a byte per 7 bits
+        let prefix: u32 = (usize::BITS - length.leading_zeros()).div_ceil(7).max(1);
+        self.server
+            .metrics()
+            .record_bytes_in(length + prefix as usize);
+
+        log::trace!("Request: {request:X?}");
+
+        let packet: Cow<[u8]> = self.codec.decode(&request)?;
+        if let Ok(id) = VarInt::decode(&mut &*packet) {
+            logging::update_context(|context| context.packet_id = Some(id.0));
+        }
+
+        let result: Result<ServerState, DataTypeEncodeError> = match self.state() {
+            ServerState::Handshake => self
+                .parse::<HandshakeServerBoundPacket>(&packet)?
+                .handle(self),
+            ServerState::Status => {
+                self.parse::<StatusServerBoundPacket>(&packet)?.handle(self)
+            }
+            ServerState::Login => {
+                self.parse::<LoginServerBoundPacket>(&packet)?.handle(self)
+            }
+            ServerState::Configuration => self
+                .parse::<ConfigurationServerBoundPacket>(&packet)?
+                .handle(self),
+            ServerState::Play => {
+                self.parse::<PlayServerBoundPacket>(&packet)?.handle(self)
+            }
+            ServerState::Closed => {
+                log::error!("Unexpected data while in closed state");
+                Ok(ServerState::Closed)
+            }
+        };
+
+        Ok(result.unwrap_or_else(|e| {
+            log::error!("Failed to encode packet: {e}");
+            self.state()
+        }))
+    }
+
+    /// Parse a packet of the current state, recording it in the metrics under its
+    /// ID if it is a valid packet of the state, as an other packet otherwise.
+    fn parse<'a, P: StateEnum<'a>>(
+        &self,
+        packet: &'a [u8],
+    ) -> Result<P, PacketParseError> {
+        let parsed: Result<P, PacketParseError> = P::try_from(packet);
+        let id: Option<i32> = parsed
+            .as_ref()
+            .ok()
+            .and_then(|_| VarInt::decode(&mut &*packet).ok())
+            .map(|id| id.0);
+        self.server
+            .metrics()
+            .record_packet(Direction::Serverbound, self.state(), id);
+        parsed
+    }
+
+    /// Flush the outbound queue and close the socket.
+    fn close(mut self) {
+        if let Some(player) = self.player.take() {
+            self.server.leave(&player);
+        }
+
+        self.handle.close();
+        if let Some(writer) = self.writer.take() {
+            if writer.join().is_err() {
+                log::error!("Writer thread panicked");
+            }
+        }
+        let _ = self.reader.get_ref().get_ref().shutdown(Shutdown::Both);
+        self.server.unregister_connection(self.handle.id());
+
+        log::info!("Closing connection");
+    }
+}
+
+/// Write queued messages to the socket until the connection is closed.
+fn write_loop(
+    mut stream: TcpStream,
+    handle: &Weak<ConnectionHandle>,
+    queue: &Receiver<Outbound>,
+    metrics: &Metrics,
+    capture: Option<&Capture>,
+) {
+    let mut codec: Codec = Codec::new();
+    // Logged with the context of the connection as long as it is open
+    let log = |record: &dyn Fn()| match handle.upgrade() {
+        Some(handle) => handle.in_context(record),
+        None => record(),
+    };
+
+    for message in queue {
+        let bytes: Vec<u8> = match message {
+            Outbound::Packet(packet, state) => match codec.encode(&packet) {
+                Ok(mut frame) => {
+                    if let Some(capture) = capture {
+                        // Recorded without the length prefix, as inbound frames
+                        let mut body: &[u8] = &frame;
+                        if VarInt::decode(&mut body).is_ok() {
+                            let compressed: bool = codec.is_compressed();
+                            capture.record(
+                                Direction::Clientbound,
+                                state,
+                                compressed,
+                                body,
+                            );
+                        }
+                    }
+                    codec.encrypt(&mut frame);
+                    frame
+                }
+                Err(e) => {
+                    log(&|| log::error!("Failed to encode packet frame: {e}"));
+                    continue;
+                }
+            },
+            Outbound::Raw(bytes) => bytes,
+            Outbound::Compression(threshold) => {
+                codec.set_compression(threshold);
+                continue;
+            }
+            Outbound::Encryption(shared_secret) => {
+                codec.set_encryption(&shared_secret);
+                continue;
+            }
+            Outbound::Close => {
+                let _ = stream.shutdown(Shutdown::Both);
+                break;
+            }
+        };
+
+        if let Err(e) = stream.write_all(&bytes) {
+            log(&|| log::warn!("Failed to send {0} bytes: {e}", bytes.len()));
+            break;
+        }
+        metrics.record_bytes_out(bytes.len());
+        log(&|| log::trace!("Sent {0} bytes", bytes.len()));
+    }
+}
@@ -0,0 +1,240 @@
+//! Test [`crate::connection`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{Read, Write},
+        net::{SocketAddr, TcpListener, TcpStream},
+        path::PathBuf,
+        process,
+        sync::{mpsc, Arc, Weak},
+        thread,
+    };
+
+    use crate::{
+        access::AccessLists,
+        config::Config,
+        connection::{write_loop, Connection, Outbound, LEGACY_PING},
+        limiter::ConnectionPermit,
+        logging::{self, LogContext},
+        metrics::Metrics,
+        profile::PlayerProfile,
+        protocol::{Cipher, Codec},
+        server::Server,
+        state::ServerState,
+    };
+
+    /// A connected pair of sockets: the client side and the server side.
+    fn socket_pair() -> (TcpStream, TcpStream, SocketAddr) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client: TcpStream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, address): (TcpStream, SocketAddr) = listener.accept().unwrap();
+        (client, stream, address)
+    }
+
+    /// Frame of a packet encoded with `threshold`.
+    fn frame(packet: &[u8], threshold: Option<usize>) -> Vec<u8> {
+        let mut codec: Codec = Codec::new();
+        codec.set_compression(threshold);
+        codec.encode(packet).unwrap()
+    }
+
+    #[test]
+    fn test_write_loop() {
+        let (mut client, stream, _) = socket_pair();
+        let (outbound, queue) = mpsc::channel();
+        let metrics: Arc<Metrics> = Arc::new(Metrics::default());
+
+        let large: Vec<u8> = vec![0x01; 64];
+        let messages: [Outbound; 7] = [
+            Outbound::Packet(vec![0x00, 0x2A], ServerState::Login),
+            Outbound::Raw(vec![0xFE, 0x01]),
+            Outbound::Compression(Some(16)),
+            Outbound::Packet(vec![0x00, 0x2A], ServerState::Login),
+            Outbound::Packet(large.clone(), ServerState::Login),
+            Outbound::Close,
+            // Never written, the socket is closed
+            Outbound::Raw(vec![0xFF]),
+        ];
+        for message in messages {
+            outbound.send(message).unwrap();
+        }
+        let writer_metrics: Arc<Metrics> = Arc::clone(&metrics);
+        let writer = thread::spawn(move || {
+            write_loop(stream, &Weak::new(), &queue, &writer_metrics, None);
+        });
+
+        // Written in order, compression applying to the packets queued after it
+        let mut expected: Vec<u8> = frame(&[0x00, 0x2A], None);
+        expected.extend_from_slice(&[0xFE, 0x01]);
+        expected.extend(frame(&[0x00, 0x2A], Some(16)));
+        expected.extend(frame(&large, Some(16)));
+        let mut received: Vec<u8> = Vec::new();
+        client.read_to_end(&mut received).unwrap();
+        assert_eq!(received, expected);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_write_loop_encryption() {
+        let (mut client, stream, _) = socket_pair();
+        let (outbound, queue) = mpsc::channel();
+        let secret: [u8; 16] = *b"0123456789abcdef";
+
+        for message in [
+            Outbound::Packet(vec![0x01, 0x2A], ServerState::Login),
+            Outbound::Encryption(secret),
+            Outbound::Compression(Some(16)),
+            Outbound::Packet(vec![0x02, 0x2A], ServerState::Login),
+            Outbound::Packet(vec![0x03; 64], ServerState::Login),
+            Outbound::Close,
+        ] {
+            outbound.send(message).unwrap();
+        }
+        let writer = thread::spawn(move || {
+            write_loop(stream, &Weak::new(), &queue, &Metrics::default(), None);
+        });
+
+        // Encryption applies to the whole frames queued after it
+        let mut received: Vec<u8> = Vec::new();
+        client.read_to_end(&mut received).unwrap();
+        let clear: Vec<u8> = frame(&[0x01, 0x2A], None);
+        let mut encrypted: Vec<u8> = received.split_off(clear.len());
+        assert_eq!(received, clear);
+        Cipher::new(&secret).decrypt(&mut encrypted);
+        let mut expected: Vec<u8> = frame(&[0x02, 0x2A], Some(16));
+        expected.extend(frame(&[0x03; 64], Some(16)));
+        assert_eq!(encrypted, expected);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_write_loop_closed_socket() {
+        let (client, stream, _) = socket_pair();
+        let (outbound, queue) = mpsc::channel();
+        drop(client);
+
+        // The writer stops at the first failed write instead of draining the queue
+        let writer = thread::spawn(move || {
+            write_loop(stream, &Weak::new(), &queue, &Metrics::default(), None);
+        });
+        for _ in 0..64 {
+            if outbound.send(Outbound::Raw(vec![0x00; 1 << 16])).is_err() {
+                break;
+            }
+        }
+        writer.join().unwrap();
+        assert!(outbound.send(Outbound::Close).is_err());
+    }
+
+    #[test]
+    fn test_outbound_queue() {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-connection-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let server: Arc<Server> = Arc::new(Server::new(
+            Config::default(),
+            AccessLists::load(&directory, false),
+        ));
+        let (mut client, stream, address) = socket_pair();
+        let permit: ConnectionPermit =
+            server.limiter().try_acquire(address.ip()).unwrap();
+        let mut connection: Connection =
+            Connection::new(stream, address, Arc::clone(&server), permit).unwrap();
+
+        // Queued from another thread through the handle, and from the connection
+        let handle = Arc::clone(connection.handle());
+        thread::spawn(move || handle.send_bytes(vec![0x01, 0x02]))
+            .join()
+            .unwrap();
+        connection.set_compression(Some(1));
+        connection.handle().send_bytes(vec![0x03, 0x04]);
+        assert!(!connection.handle().is_closing());
+        connection.handle().close();
+        assert!(connection.handle().is_closing());
+
+        let mut expected: Vec<u8> = frame(&[0x01, 0x02], None);
+        expected.extend(frame(&[0x03, 0x04], Some(1)));
+        let mut received: Vec<u8> = Vec::new();
+        client.read_to_end(&mut received).unwrap();
+        assert_eq!(received, expected);
+    }
+
+    #[test]
+    fn test_log_context() {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-connection-log-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let server: Arc<Server> = Arc::new(Server::new(
+            Config::default(),
+            AccessLists::load(&directory, false),
+        ));
+        let (_client, stream, address) = socket_pair();
+        let permit: ConnectionPermit =
+            server.limiter().try_acquire(address.ip()).unwrap();
+        let mut connection: Connection =
+            Connection::new(stream, address, Arc::clone(&server), permit).unwrap();
+        connection.set_profile(PlayerProfile::offline("Tester".to_string()));
+
+        // Records logged about the client from another thread carry its fields
+        let handle = Arc::clone(connection.handle());
+        let (inside, outside) = thread::spawn(move || {
+            let inside: LogContext = handle.in_context(|| {
+                let mut context: LogContext = LogContext::default();
+                logging::update_context(|current| context = current.clone());
+                context
+            });
+            (inside, logging::connection_id())
+        })
+        .join()
+        .unwrap();
+        assert_eq!(
+            inside,
+            LogContext {
+                connection_id: Some(connection.handle().id()),
+                peer: Some(address),
+                state: Some(ServerState::Handshake),
+                player: Some("Tester".to_string()),
+                packet_id: None,
+            }
+        );
+        assert_eq!(outside, None);
+
+        connection.handle().close();
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_ping() {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-connection-legacy-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let server: Arc<Server> = Arc::new(Server::new(
+            Config::default(),
+            AccessLists::load(&directory, false),
+        ));
+        let (mut client, stream, address) = socket_pair();
+        let permit: ConnectionPermit =
+            server.limiter().try_acquire(address.ip()).unwrap();
+        let connection: Connection =
+            Connection::new(stream, address, Arc::clone(&server), permit).unwrap();
+        let running = thread::spawn(move || connection.run());
+
+        let mut ping: Vec<u8> = vec![0xFE, 0x01];
+        ping.extend_from_slice(&LEGACY_PING);
+        ping.extend_from_slice(&[0x00, 0x03, 0x4A, 0x00, 0x00]);
+        client.write_all(&ping).unwrap();
+        let mut received: Vec<u8> = Vec::new();
+        client.read_to_end(&mut received).unwrap();
+        running.join().unwrap();
+
+        assert_eq!(received.first(), Some(&0xFF));
+        let output: String = server.metrics().render(&server);
+        assert!(output
+            .lines()
+            .any(|line| line == "atmospheremc_legacy_pings_total 1"));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
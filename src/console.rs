@@ -0,0 +1,134 @@
+//! Server console: reads commands from the terminal with line editing and history,
+//! and prints logs above the prompt instead of over it.
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+use rustyline::{error::ReadlineError, DefaultEditor, ExternalPrinter};
+
+use crate::{command::CommandSource, server::Server};
+
+/// Prompt of the console.
+const PROMPT: &str = "> ";
+
+/// Prints above the prompt while the console reads a line.
+type Printer = Box<dyn ExternalPrinter + Send>;
+
+/// Where logs are printed: above the console prompt once it is shown, on stderr
+/// before or if the terminal does not support it.
+#[derive(Clone, Default)]
+pub struct LogOutput {
+    /// Printer of the console, once it started.
+    printer: Arc<Mutex<Option<Printer>>>,
+}
+
+impl LogOutput {
+    /// A writer for the logger.
+    pub fn writer(&self) -> LogWriter {
+        LogWriter {
+            output: self.clone(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Print a log record.
+    fn print(&self, record: &str) {
+        let mut printer = self.printer.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(console) = printer.as_mut() {
+            if console.print(record.to_string()).is_ok() {
+                return;
+            }
+            // The terminal went away, stop using it
+            *printer = None;
+        }
+        drop(printer);
+
+        let _ = io::stderr().write_all(record.as_bytes());
+    }
+}
+
+/// Writer given to the logger, printing each record through a [`LogOutput`].
+pub struct LogWriter {
+    /// Where records are printed.
+    output: LogOutput,
+
+    /// The record being written, printed on flush.
+    buffer: Vec<u8>,
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.output.print(&String::from_utf8_lossy(&self.buffer));
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+/// Read commands on a dedicated thread, printing logs above the prompt through
+/// `output`. The thread ends with stdin, the server keeps running.
+pub fn spawn(server: Arc<Server>, output: &LogOutput) {
+    let mut editor: DefaultEditor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            log::error!(target: "Console", "Failed to open the console: {e}");
+            return;
+        }
+    };
+
+    // Without a terminal, lines are read without editing and logs go to stderr
+    if let Ok(printer) = editor.create_external_printer() {
+        *output
+            .printer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Box::new(printer));
+    }
+
+    let output: LogOutput = output.clone();
+    let spawned = thread::Builder::new()
+        .name("Console".to_string())
+        .spawn(move || {
+            run(&server, &mut editor);
+            // Logs can no longer go through the editor
+            output
+                .printer
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+        });
+    if let Err(e) = spawned {
+        log::error!(target: "Console", "Failed to spawn the console thread: {e}");
+    }
+}
+
+/// Run the commands read from the console until stdin closes or the server stops.
+fn run(server: &Server, editor: &mut DefaultEditor) {
+    while !server.is_stop_requested() {
+        match editor.readline(PROMPT) {
+            Ok(line) => {
+                let line: &str = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let _ = editor.add_history_entry(line);
+                // Errors are sent back to the console by the dispatcher
+                let _ = server.run_command(&CommandSource::Console, line);
+            }
+            // Ctrl-C does not raise a signal while the terminal is in raw mode
+            Err(ReadlineError::Interrupted) => server.request_stop(),
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                log::error!(target: "Console", "Failed to read the console: {e}");
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Blocks and block states.
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::types::var::VarInt;
+
+include!(concat!(env!("OUT_DIR"), "/blocks.rs"));
+
+/// Error when building a [`BlockState`] from property values.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BlockStateError {
+    /// No block has this name.
+    #[error("Unknown block `{0}`")]
+    UnknownBlock(String),
+
+    /// The properties are not written as `[name=value,...]`.
+    #[error("Invalid block state `{0}`")]
+    InvalidSyntax(String),
+
+    /// The block has no such property.
+    #[error("{block} has no property `{property}`")]
+    UnknownProperty {
+        /// The name of the block.
+        block: &'static str,
+
+        /// The property requested.
+        property: String,
+    },
+
+    /// The property exists but does not accept this value.
+    #[error("Invalid value `{value}` for property `{property}` of {block}")]
+    InvalidValue {
+        /// The name of the block.
+        block: &'static str,
+
+        /// The property requested.
+        property: &'static str,
+
+        /// The value requested.
+        value: String,
+    },
+}
+
+/// A block property, e.g. `facing` or `waterlogged`.
+#[derive(Debug)]
+pub struct Property {
+    /// Name of the property.
+    pub name: &'static str,
+
+    /// Accepted values, in state ID order.
+    pub values: &'static [&'static str],
+}
+
+/// A block type, owning a contiguous range of [`BlockState`]s.
+#[derive(Debug)]
+pub struct Block {
+    /// Namespaced name, e.g. `minecraft:grass_block`.
+    pub name: &'static str,
+
+    /// First state ID of the block.
+    first_state: u32,
+
+    /// Default state ID of the block.
+    default_state: u32,
+
+    /// Properties of the block, first property varying slowest.
+    pub properties: &'static [Property],
+}
+
+impl Block {
+    /// Find a block by its name. The `minecraft:` namespace may be omitted.
+    pub fn by_name(name: &str) -> Option<&'static Self> {
+        let name: &str = name.strip_prefix("minecraft:").unwrap_or(name);
+
+        BLOCKS_BY_NAME
+            .binary_search_by(|block| {
+                block
+                    .name
+                    .strip_prefix("minecraft:")
+                    .unwrap_or(block.name)
+                    .cmp(name)
+            })
+            .ok()
+            .and_then(|i| BLOCKS_BY_NAME.get(i).copied())
+    }
+
+    /// The default state of this block, used when no property is specified.
+    pub const fn default_state(&self) -> BlockState {
+        BlockState(self.default_state)
+    }
+
+    /// Get the state of this block with the given properties. Properties not listed
+    /// keep their value from the [default state](Self::default_state).
+    pub fn state(
+        &self,
+        properties: &[(&str, &str)],
+    ) -> Result<BlockState, BlockStateError> {
+        properties
+            .iter()
+            .try_fold(self.default_state(), |state, (property, value)| {
+                state.with(property, value)
+            })
+    }
+
+    /// Stride of a property in the state index: the number of states between two
+    /// consecutive values of that property.
+    fn stride(&self, property: usize) -> u32 {
+        self.properties
+            .iter()
+            .skip(property + 1)
+            .map(|p| u32::try_from(p.values.len()).unwrap_or(u32::MAX))
+            .product()
+    }
+
+    /// Find a property and its index by name.
+    fn property(&self, name: &str) -> Option<(usize, &'static Property)> {
+        self.properties
+            .iter()
+            .enumerate()
+            .find(|(_, p)| p.name == name)
+    }
+}
+
+impl Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A block state, identified by its global state ID as sent over the network.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BlockState(u32);
+
+impl BlockState {
+    /// `minecraft:air`, the state of empty space in the void.
+    pub const AIR: Self = Self(0);
+
+    /// Number of block states, all state IDs being below it.
+    pub const COUNT: u32 = STATE_COUNT;
+
+    /// Get the global state ID.
+    pub const fn id(self) -> u32 {
+        self.0
+    }
+
+    /// Get the block of this state.
+    pub fn block(self) -> &'static Block {
+        BLOCKS
+            .partition_point(|b| b.first_state <= self.0)
+            .checked_sub(1)
+            .and_then(|i| BLOCKS.get(i))
+            .expect("state IDs start at 0")
+    }
+
+    /// Iterate over the `(property, value)` pairs of this state.
+    pub fn properties(self) -> impl Iterator<Item = (&'static str, &'static str)> {
+        let block: &'static Block = self.block();
+        block
+            .properties
+            .iter()
+            .enumerate()
+            .map(move |(i, p)| (p.name, self.value(block, i, p)))
+    }
+
+    /// Index of the value of the `index`-th property of `block` in this state.
+    fn value_index(self, block: &Block, index: usize, property: &Property) -> u32 {
+        let values: u32 = u32::try_from(property.values.len()).unwrap_or(1);
+        (self.0 - block.first_state) / block.stride(index) % values
+    }
+
+    /// Value of the `index`-th property of `block` in this state.
+    fn value(
+        self,
+        block: &Block,
+        index: usize,
+        property: &'static Property,
+    ) -> &'static str {
+        usize::try_from(self.value_index(block, index, property))
+            .ok()
+            .and_then(|i| property.values.get(i))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Get the same block with a property changed.
+    pub fn with(self, property: &str, value: &str) -> Result<Self, BlockStateError> {
+        let block: &'static Block = self.block();
+        let (index, definition) =
+            block
+                .property(property)
+                .ok_or_else(|| BlockStateError::UnknownProperty {
+                    block: block.name,
+                    property: property.to_string(),
+                })?;
+        let new_value: usize = definition
+            .values
+            .iter()
+            .position(|v| *v == value)
+            .ok_or_else(|| BlockStateError::InvalidValue {
+                block: block.name,
+                property: definition.name,
+                value: value.to_string(),
+            })?;
+
+        let stride: u32 = block.stride(index);
+        let old_value: u32 = self.value_index(block, index, definition);
+        let new_value: u32 = u32::try_from(new_value).unwrap_or(0);
+        Ok(Self(self.0 - old_value * stride + new_value * stride))
+    }
+}
+
+impl Display for BlockState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let block: &Block = self.block();
+        write!(f, "{block}")?;
+        if !block.properties.is_empty() {
+            let properties: Vec<String> =
+                self.properties().map(|(k, v)| format!("{k}={v}")).collect();
+            write!(f, "[{}]", properties.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BlockState {
+    type Err = BlockStateError;
+
+    /// Parse a block state as written by [`Display`], e.g.
+    /// `minecraft:grass_block[snowy=true]`. Properties not listed keep their default
+    /// value.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, properties) = match s.split_once('[') {
+            Some((name, rest)) => (
+                name,
+                rest.strip_suffix(']')
+                    .ok_or_else(|| BlockStateError::InvalidSyntax(s.to_string()))?,
+            ),
+            None => (s, ""),
+        };
+        let block: &Block = Block::by_name(name)
+            .ok_or_else(|| BlockStateError::UnknownBlock(name.to_string()))?;
+
+        let properties: Vec<(&str, &str)> = properties
+            .split(',')
+            .filter(|property| !property.is_empty())
+            .map(|property| {
+                property
+                    .split_once('=')
+                    .ok_or_else(|| BlockStateError::InvalidSyntax(s.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        block.state(&properties)
+    }
+}
+
+impl From<BlockState> for VarInt {
+    fn from(value: BlockState) -> Self {
+        // State IDs are far below i32::MAX
+        Self(i32::try_from(value.0).unwrap_or(i32::MAX))
+    }
+}
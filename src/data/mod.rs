@@ -0,0 +1,9 @@
+//! Vanilla game data, generated at build time from the data generator output (see
+//! `build.rs`).
+pub mod blocks;
+pub mod registries;
+mod test;
+
+/// Whether the data is the complete output of the data generator, rather than the
+/// subset checked into `data/`.
+pub const COMPLETE: bool = registries::COMPLETE;
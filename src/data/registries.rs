@@ -0,0 +1,47 @@
+//! Registries: static registries from the `registries.json` report, and the
+//! datapack registries synchronized to the client during configuration.
+
+include!(concat!(env!("OUT_DIR"), "/registries.rs"));
+
+/// A registry, mapping namespaced entry names to network IDs.
+#[derive(Debug)]
+pub struct Registry {
+    /// Namespaced name of the registry, e.g. `minecraft:block`.
+    pub name: &'static str,
+
+    /// Entry names, indexed by network ID.
+    entries: &'static [&'static str],
+
+    /// `(name, network ID)` pairs sorted by name, for lookups.
+    entries_by_name: &'static [(&'static str, u16)],
+}
+
+impl Registry {
+    /// Find a static or synchronized registry by name.
+    pub fn by_name(name: &str) -> Option<&'static Self> {
+        STATIC_REGISTRIES
+            .binary_search_by(|r| r.name.cmp(name))
+            .ok()
+            .and_then(|i| STATIC_REGISTRIES.get(i))
+            .or_else(|| SYNCHRONIZED_REGISTRIES.iter().find(|r| r.name == name))
+    }
+
+    /// Registries to send in the configuration state, in vanilla order.
+    pub fn synchronized() -> &'static [Self] {
+        &SYNCHRONIZED_REGISTRIES
+    }
+
+    /// Entry names, indexed by network ID.
+    pub const fn entries(&self) -> &'static [&'static str] {
+        self.entries
+    }
+
+    /// Get the network ID of an entry.
+    pub fn id_of(&self, entry: &str) -> Option<i32> {
+        self.entries_by_name
+            .binary_search_by(|(name, _)| (*name).cmp(entry))
+            .ok()
+            .and_then(|i| self.entries_by_name.get(i))
+            .map(|(_, id)| i32::from(*id))
+    }
+}
@@ -0,0 +1,111 @@
+//! Test [`crate::data`].
+#[cfg(test)]
+mod tests {
+    use crate::data::{
+        blocks::{Block, BlockState, BlockStateError},
+        registries::Registry,
+    };
+
+    #[test]
+    fn test_block_lookup() {
+        let air: &Block = Block::by_name("minecraft:air").unwrap();
+        assert_eq!(air.default_state(), BlockState::AIR);
+        assert_eq!(BlockState::AIR.block().name, "minecraft:air");
+
+        let grass: &Block = Block::by_name("grass_block").unwrap();
+        assert_eq!(grass.default_state().id(), 9);
+        assert_eq!(
+            grass.default_state().properties().collect::<Vec<_>>(),
+            [("snowy", "false")]
+        );
+        assert_eq!(grass.state(&[("snowy", "true")]).unwrap().id(), 8);
+
+        assert!(Block::by_name("minecraft:not_a_block").is_none());
+    }
+
+    #[test]
+    fn test_block_state_properties() {
+        let propagule: &Block = Block::by_name("minecraft:mangrove_propagule").unwrap();
+
+        let state: BlockState = propagule
+            .state(&[("age", "3"), ("hanging", "true"), ("waterlogged", "true")])
+            .unwrap();
+        assert_eq!(state.block().name, propagule.name);
+        assert_eq!(
+            state.properties().collect::<Vec<_>>(),
+            [
+                ("age", "3"),
+                ("hanging", "true"),
+                ("stage", "0"),
+                ("waterlogged", "true")
+            ]
+        );
+        assert_eq!(
+            state.to_string(),
+            "minecraft:mangrove_propagule[age=3,hanging=true,stage=0,waterlogged=true]"
+        );
+        assert_eq!(state.to_string().parse(), Ok(state));
+        assert!(state.id() < BlockState::COUNT);
+
+        assert_eq!(
+            propagule.state(&[("age", "7")]),
+            Err(BlockStateError::InvalidValue {
+                block: propagule.name,
+                property: "age",
+                value: "7".to_string()
+            })
+        );
+        assert!(matches!(
+            propagule.state(&[("facing", "north")]),
+            Err(BlockStateError::UnknownProperty { .. })
+        ));
+    }
+
+    #[test]
+    fn test_block_state_parse() {
+        assert_eq!("minecraft:air".parse(), Ok(BlockState::AIR));
+        assert_eq!(
+            "grass_block[snowy=true]"
+                .parse::<BlockState>()
+                .unwrap()
+                .id(),
+            8
+        );
+        assert_eq!("grass_block[]".parse::<BlockState>().unwrap().id(), 9);
+
+        assert_eq!(
+            "minecraft:not_a_block".parse::<BlockState>(),
+            Err(BlockStateError::UnknownBlock(
+                "minecraft:not_a_block".to_string()
+            ))
+        );
+        for invalid in ["grass_block[snowy=true", "grass_block[snowy]"] {
+            assert_eq!(
+                invalid.parse::<BlockState>(),
+                Err(BlockStateError::InvalidSyntax(invalid.to_string()))
+            );
+        }
+        assert!(matches!(
+            "grass_block[snowy=maybe]".parse::<BlockState>(),
+            Err(BlockStateError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_registries() {
+        let blocks: &Registry = Registry::by_name("minecraft:block").unwrap();
+        assert_eq!(blocks.id_of("minecraft:air"), Some(0));
+        assert_eq!(blocks.entries().first(), Some(&"minecraft:air"));
+        assert!(blocks
+            .entries()
+            .iter()
+            .all(|name| name.parse::<BlockState>().is_ok()));
+
+        let biomes: &Registry = Registry::by_name("minecraft:worldgen/biome").unwrap();
+        assert!(biomes.id_of("minecraft:the_void").is_some());
+        assert_eq!(
+            Registry::synchronized().first().map(|r| r.name),
+            Some(biomes.name)
+        );
+    }
+}
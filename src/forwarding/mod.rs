@@ -0,0 +1,250 @@
+//! Player information forwarded by a proxy: `BungeeCord` legacy forwarding in the
+//! handshake address, and Velocity modern forwarding in a signed login plugin
+//! response.
+use std::{fmt::Display, net::IpAddr, str::FromStr};
+
+use hmac::{Hmac, KeyInit, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::{
+    profile::{PlayerProfile, ProfileProperty},
+    types::{
+        uuid::{Uuid, UuidParseError},
+        var::{VarInt, VarString},
+        DataType, DataTypeDecodeError,
+    },
+};
+
+mod test;
+
+/// Channel of the Velocity login plugin query.
+pub const VELOCITY_CHANNEL: &str = "velocity:player_info";
+
+/// Velocity forwarding version requested by the server, without the player chat
+/// key which 1.19.3 and later clients no longer forward.
+const MODERN_FORWARDING_VERSION: u8 = 1;
+
+/// Length of the HMAC-SHA256 signature prefixing the Velocity response.
+const SIGNATURE_LENGTH: usize = 32;
+
+/// Maximum length of a username.
+const MAX_NAME_LENGTH: usize = 16;
+
+/// How player information is forwarded by the proxy in front of the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForwardingMode {
+    /// Clients connect directly.
+    None,
+
+    /// `BungeeCord` legacy forwarding, in the handshake address.
+    Legacy,
+
+    /// Velocity modern forwarding, in a login plugin response signed with a shared
+    /// secret.
+    Modern,
+}
+
+impl FromStr for ForwardingMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "none" => Self::None,
+            "legacy" | "bungeecord" => Self::Legacy,
+            "modern" | "velocity" => Self::Modern,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl Display for ForwardingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Legacy => "legacy",
+            Self::Modern => "modern",
+        })
+    }
+}
+
+/// Error when reading forwarded player information.
+#[derive(Error, Debug)]
+pub enum ForwardingError {
+    /// The proxy did not forward the player information.
+    #[error("Missing forwarded player information")]
+    Missing,
+
+    /// The signature does not match the forwarding secret.
+    #[error("Invalid forwarding signature")]
+    InvalidSignature,
+
+    /// The proxy used a forwarding version the server did not request.
+    #[error("Unsupported forwarding version {0}")]
+    UnsupportedVersion(i32),
+
+    /// The forwarded address is not an IP address.
+    #[error("Invalid forwarded address: {0}")]
+    InvalidAddress(String),
+
+    /// The forwarded UUID is invalid.
+    #[error(transparent)]
+    InvalidUuid(#[from] UuidParseError),
+
+    /// The forwarded profile properties are not valid JSON.
+    #[error("Invalid forwarded properties")]
+    InvalidProperties,
+
+    /// A field could not be decoded.
+    #[error(transparent)]
+    DataTypeDecodeError(#[from] DataTypeDecodeError),
+}
+
+/// Player information forwarded by a proxy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForwardedPlayer {
+    /// Address of the client.
+    pub address: IpAddr,
+
+    /// UUID of the player.
+    pub uuid: Uuid,
+
+    /// Username of the player, not forwarded by legacy forwarding.
+    pub name: Option<String>,
+
+    /// Properties of the profile (skin, cape...).
+    pub properties: Vec<ProfileProperty>,
+}
+
+impl ForwardedPlayer {
+    /// Profile of the player, using `name` if the proxy did not forward one.
+    pub fn into_profile(self, name: String) -> PlayerProfile {
+        PlayerProfile {
+            uuid: self.uuid,
+            name: self.name.unwrap_or(name),
+            properties: self.properties,
+        }
+    }
+}
+
+/// Split the handshake address into the host and the data appended after a `\0`,
+/// such as legacy forwarding.
+pub fn split_address(address: &str) -> (&str, Option<&str>) {
+    address
+        .split_once('\0')
+        .map_or((address, None), |(host, rest)| (host, Some(rest)))
+}
+
+/// Parse legacy forwarding data: `address\0uuid\0properties`, the properties being
+/// optional.
+pub fn parse_legacy(data: &str) -> Result<ForwardedPlayer, ForwardingError> {
+    let mut fields = data.split('\0');
+    let (Some(address), Some(uuid)) = (fields.next(), fields.next()) else {
+        return Err(ForwardingError::Missing);
+    };
+
+    let properties: Vec<ProfileProperty> = match fields.next() {
+        Some(json) => parse_properties(json)?,
+        None => Vec::new(),
+    };
+
+    Ok(ForwardedPlayer {
+        address: parse_address(address)?,
+        uuid: uuid.parse()?,
+        name: None,
+        properties,
+    })
+}
+
+/// Data of the Velocity login plugin query, requesting the forwarding version.
+pub fn modern_request() -> Vec<u8> {
+    vec![MODERN_FORWARDING_VERSION]
+}
+
+/// Verify and parse the Velocity login plugin response, signed with `secret`.
+pub fn parse_modern(
+    data: &[u8],
+    secret: &[u8],
+) -> Result<ForwardedPlayer, ForwardingError> {
+    let (Some(signature), Some(mut payload)) =
+        (data.get(..SIGNATURE_LENGTH), data.get(SIGNATURE_LENGTH..))
+    else {
+        return Err(ForwardingError::Missing);
+    };
+
+    let mut mac: Hmac<Sha256> =
+        Hmac::new_from_slice(secret).map_err(|_| ForwardingError::InvalidSignature)?;
+    mac.update(payload);
+    mac.verify_slice(signature)
+        .map_err(|_| ForwardingError::InvalidSignature)?;
+
+    let version: i32 = VarInt::decode(&mut payload)?.0;
+    if version != i32::from(MODERN_FORWARDING_VERSION) {
+        return Err(ForwardingError::UnsupportedVersion(version));
+    }
+
+    let address: IpAddr = parse_address(VarString::decode(&mut payload)?.get_ref())?;
+    let uuid: Uuid = Uuid::decode(&mut payload)?;
+    let name: String = VarString::decode_bounded(&mut payload, MAX_NAME_LENGTH)?.get();
+
+    let count: usize = usize::try_from(VarInt::decode(&mut payload)?.0)
+        .map_err(DataTypeDecodeError::from)?;
+    let mut properties: Vec<ProfileProperty> = Vec::new();
+    for _ in 0..count {
+        let name: String = VarString::decode(&mut payload)?.get();
+        let value: String = VarString::decode(&mut payload)?.get();
+        let signature: Option<String> = if bool::decode(&mut payload)? {
+            Some(VarString::decode(&mut payload)?.get())
+        } else {
+            None
+        };
+        properties.push(ProfileProperty {
+            name,
+            value,
+            signature,
+        });
+    }
+
+    Ok(ForwardedPlayer {
+        address,
+        uuid,
+        name: Some(name),
+        properties,
+    })
+}
+
+/// Parse a forwarded IP address, possibly in brackets for IPv6.
+fn parse_address(address: &str) -> Result<IpAddr, ForwardingError> {
+    address
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .map_err(|_| ForwardingError::InvalidAddress(address.to_string()))
+}
+
+/// Parse profile properties from the JSON array of legacy forwarding.
+fn parse_properties(json: &str) -> Result<Vec<ProfileProperty>, ForwardingError> {
+    let Ok(Value::Array(properties)) = serde_json::from_str(json) else {
+        return Err(ForwardingError::InvalidProperties);
+    };
+
+    properties
+        .iter()
+        .map(|property| {
+            let name: Option<&str> = property.get("name").and_then(Value::as_str);
+            let value: Option<&str> = property.get("value").and_then(Value::as_str);
+            match (name, value) {
+                (Some(name), Some(value)) => Ok(ProfileProperty {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                    signature: property
+                        .get("signature")
+                        .and_then(Value::as_str)
+                        .map(ToString::to_string),
+                }),
+                _ => Err(ForwardingError::InvalidProperties),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,123 @@
+//! Test [`crate::forwarding`].
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use hmac::{Hmac, KeyInit, Mac};
+    use sha2::Sha256;
+
+    use crate::{
+        forwarding::{
+            parse_legacy, parse_modern, split_address, ForwardedPlayer, ForwardingError,
+        },
+        profile::ProfileProperty,
+        types::{
+            uuid::Uuid,
+            var::{VarInt, VarString},
+            DataType,
+        },
+    };
+
+    /// Address of the client.
+    const ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+
+    /// UUID of the player.
+    const UUID: Uuid = Uuid(0x0692_0508_d5d8_4eed_93d2_92f1_93af_e7d7);
+
+    #[test]
+    fn test_legacy() {
+        let address: &str = "lobby.example.com\x00203.0.113.7\x0006920508d5d84eed93d292f193afe7d7\x00[{\"name\":\"textures\",\"value\":\"e30=\",\"signature\":\"c2ln\"}]";
+        let (host, forwarded) = split_address(address);
+        assert_eq!(host, "lobby.example.com");
+
+        let player: ForwardedPlayer = parse_legacy(forwarded.unwrap()).unwrap();
+        assert_eq!(
+            player,
+            ForwardedPlayer {
+                address: ADDRESS,
+                uuid: UUID,
+                name: None,
+                properties: vec![ProfileProperty {
+                    name: "textures".to_string(),
+                    value: "e30=".to_string(),
+                    signature: Some("c2ln".to_string()),
+                }],
+            }
+        );
+        assert_eq!(player.into_profile("Alice".to_string()).name, "Alice");
+
+        // Properties are optional
+        assert!(
+            parse_legacy("203.0.113.7\x0006920508-d5d8-4eed-93d2-92f193afe7d7")
+                .unwrap()
+                .properties
+                .is_empty()
+        );
+
+        assert_eq!(split_address("localhost"), ("localhost", None));
+        assert!(matches!(
+            parse_legacy("203.0.113.7"),
+            Err(ForwardingError::Missing)
+        ));
+        assert!(matches!(
+            parse_legacy("203.0.113.7\x00not-a-uuid"),
+            Err(ForwardingError::InvalidUuid(_))
+        ));
+    }
+
+    #[test]
+    fn test_modern() {
+        let mut payload: Vec<u8> = Vec::new();
+        VarInt(1).encode(&mut payload).unwrap();
+        VarString::new("203.0.113.7".to_string())
+            .encode(&mut payload)
+            .unwrap();
+        UUID.encode(&mut payload).unwrap();
+        VarString::new("Alice".to_string())
+            .encode(&mut payload)
+            .unwrap();
+        VarInt(1).encode(&mut payload).unwrap();
+        VarString::new("textures".to_string())
+            .encode(&mut payload)
+            .unwrap();
+        VarString::new("e30=".to_string())
+            .encode(&mut payload)
+            .unwrap();
+        false.encode(&mut payload).unwrap();
+
+        let mut mac: Hmac<Sha256> = Hmac::new_from_slice(b"secret").unwrap();
+        mac.update(&payload);
+        let mut data: Vec<u8> = mac.finalize().into_bytes().to_vec();
+        data.extend(&payload);
+
+        let player: ForwardedPlayer = parse_modern(&data, b"secret").unwrap();
+        assert_eq!(
+            player.into_profile("ignored".to_string()),
+            crate::profile::PlayerProfile {
+                uuid: UUID,
+                name: "Alice".to_string(),
+                properties: vec![ProfileProperty {
+                    name: "textures".to_string(),
+                    value: "e30=".to_string(),
+                    signature: None,
+                }],
+            }
+        );
+
+        assert!(matches!(
+            parse_modern(&data, b"wrong secret"),
+            Err(ForwardingError::InvalidSignature)
+        ));
+        if let Some(byte) = data.last_mut() {
+            *byte ^= 1;
+        }
+        assert!(matches!(
+            parse_modern(&data, b"secret"),
+            Err(ForwardingError::InvalidSignature)
+        ));
+        assert!(matches!(
+            parse_modern(&[0; 8], b"secret"),
+            Err(ForwardingError::Missing)
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+//! Entry points of the fuzz targets in `fuzz/`, feeding untrusted bytes to the
+//! decoders.
+//!
+//! Decoding may fail but must not panic. The round trip target also checks that
+//! what decodes encodes to bytes which decode and encode to the same bytes again.
+//!
+//! Targets are named after their entry point and run with `cargo fuzz run <target>`.
+//! Their corpus in `fuzz/corpus/<target>` is seeded from captures with
+//! `atmospheremc-void capture corpus <file> fuzz/corpus`.
+use std::borrow::Cow;
+
+use crate::{
+    protocol::{
+        Codec, ConfigurationServerBoundPacket, HandshakeServerBoundPacket,
+        LoginServerBoundPacket, PlayServerBoundPacket, StatusServerBoundPacket,
+    },
+    types::{
+        uuid::Uuid,
+        var::{VarInt, VarLong, VarString},
+        DataType,
+    },
+};
+
+mod test;
+
+/// Define an entry point decoding a data type.
+macro_rules! decoders {
+    ($($name:ident: $inner:ty => $data_type:ty),* $(,)?) => {
+        $(
+            #[doc = concat!("Decode a `", stringify!($data_type), "`.")]
+            pub fn $name(data: &[u8]) {
+                let _ = <$data_type as DataType<$inner>>::decode(&mut &*data);
+            }
+        )*
+
+        /// Decoders of the data types, in the order of the selector byte of
+        /// [`round_trip`].
+        const ROUND_TRIPS: &[fn(&[u8]) -> Option<()>] = &[
+            $(|data| round_trip_data_type::<$inner, $data_type>(data),)*
+        ];
+    };
+}
+
+decoders! {
+    decode_bool: bool => bool,
+    decode_i8: i8 => i8,
+    decode_u8: u8 => u8,
+    decode_i16: i16 => i16,
+    decode_u16: u16 => u16,
+    decode_i32: i32 => i32,
+    decode_i64: i64 => i64,
+    decode_u128: u128 => u128,
+    decode_f32: f32 => f32,
+    decode_f64: f64 => f64,
+    decode_var_int: i32 => VarInt,
+    decode_var_long: i64 => VarLong,
+    decode_var_string: String => VarString,
+    decode_uuid: u128 => Uuid,
+}
+
+/// Parse a serverbound packet of the handshake state, ID first.
+pub fn handshake_packet(data: &[u8]) {
+    let _ = HandshakeServerBoundPacket::try_from(data);
+}
+
+/// Parse a serverbound packet of the status state, ID first.
+pub fn status_packet(data: &[u8]) {
+    let _ = StatusServerBoundPacket::try_from(data);
+}
+
+/// Parse a serverbound packet of the login state, ID first.
+pub fn login_packet(data: &[u8]) {
+    let _ = LoginServerBoundPacket::try_from(data);
+}
+
+/// Parse a serverbound packet of the configuration state, ID first.
+pub fn configuration_packet(data: &[u8]) {
+    let _ = ConfigurationServerBoundPacket::try_from(data);
+}
+
+/// Parse a serverbound packet of the play state, ID first.
+pub fn play_packet(data: &[u8]) {
+    let _ = PlayServerBoundPacket::try_from(data);
+}
+
+/// Decode a frame without its length prefix, compressed if the first byte is odd.
+pub fn codec(data: &[u8]) {
+    let Some((flags, frame)) = data.split_first() else {
+        return;
+    };
+    let _ = frame_codec(*flags).decode(frame);
+}
+
+/// Decode, encode and decode again the value selected by the first byte: a data
+/// type, or a frame as in [`codec`].
+///
+/// # Panics
+///
+/// Panics if the second encoding differs from the first one, or if an encoding
+/// does not decode.
+pub fn round_trip(data: &[u8]) {
+    let Some((selector, data)) = data.split_first() else {
+        return;
+    };
+    ROUND_TRIPS
+        .get(usize::from(*selector))
+        .map_or_else(|| round_trip_frame(data), |round_trip| round_trip(data));
+}
+
+/// The round trip of a data type, `None` if the input does not decode or the
+/// value does not encode.
+fn round_trip_data_type<I, T: DataType<I>>(mut data: &[u8]) -> Option<()> {
+    let value: T = T::decode(&mut data).ok()?;
+    let mut encoded: Vec<u8> = Vec::new();
+    value.encode(&mut encoded).ok()?;
+
+    // Compared encoded: NaN floats are not equal to themselves
+    let decoded: T =
+        T::decode(&mut encoded.as_slice()).expect("encoding does not decode");
+    let mut reencoded: Vec<u8> = Vec::new();
+    decoded
+        .encode(&mut reencoded)
+        .expect("decoded value does not encode");
+    assert_eq!(encoded, reencoded, "round trip changed the encoding");
+    Some(())
+}
+
+/// The round trip of a frame through the codec, `None` if it does not decode
+/// or is too large to encode.
+fn round_trip_frame(data: &[u8]) -> Option<()> {
+    let (flags, frame) = data.split_first()?;
+    let codec: Codec = frame_codec(*flags);
+    let packet: Vec<u8> = codec.decode(frame).ok()?.into_owned();
+    let encoded: Vec<u8> = codec.encode(&packet).ok()?;
+
+    let mut prefixed: &[u8] = &encoded;
+    VarInt::decode(&mut prefixed).expect("length prefix does not decode");
+    let decoded: Cow<[u8]> = codec
+        .decode(prefixed)
+        .expect("encoded frame does not decode");
+    assert_eq!(packet, *decoded, "round trip changed the packet");
+    Some(())
+}
+
+/// Codec selected by `flags`: compressed with a threshold of 256 bytes if odd.
+fn frame_codec(flags: u8) -> Codec {
+    let mut codec: Codec = Codec::new();
+    codec.set_compression((flags & 1 == 1).then_some(256));
+    codec
+}
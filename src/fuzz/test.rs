@@ -0,0 +1,83 @@
+//! Test [`crate::fuzz`] on the seed corpus and on malformed inputs.
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use crate::fuzz::{
+        codec, configuration_packet, decode_bool, decode_f32, decode_f64, decode_i16,
+        decode_i32, decode_i64, decode_i8, decode_u128, decode_u16, decode_u8,
+        decode_uuid, decode_var_int, decode_var_long, decode_var_string,
+        handshake_packet, login_packet, play_packet, round_trip, status_packet,
+    };
+
+    /// Entry point of a fuzz target.
+    type Target = fn(&[u8]);
+
+    /// Every target, by name of its corpus directory.
+    const TARGETS: &[(&str, Target)] = &[
+        ("decode_bool", decode_bool),
+        ("decode_i8", decode_i8),
+        ("decode_u8", decode_u8),
+        ("decode_i16", decode_i16),
+        ("decode_u16", decode_u16),
+        ("decode_i32", decode_i32),
+        ("decode_i64", decode_i64),
+        ("decode_u128", decode_u128),
+        ("decode_f32", decode_f32),
+        ("decode_f64", decode_f64),
+        ("decode_var_int", decode_var_int),
+        ("decode_var_long", decode_var_long),
+        ("decode_var_string", decode_var_string),
+        ("decode_uuid", decode_uuid),
+        ("handshake_packet", handshake_packet),
+        ("status_packet", status_packet),
+        ("login_packet", login_packet),
+        ("configuration_packet", configuration_packet),
+        ("play_packet", play_packet),
+        ("codec", codec),
+        ("round_trip", round_trip),
+    ];
+
+    #[test]
+    fn test_malformed() {
+        let inputs: &[&[u8]] = &[
+            &[],
+            &[0x00],
+            &[0xFF; 16],
+            // Overlong VarInt
+            &[0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+            // String longer than its data
+            &[0x00, 0x7F, b'a'],
+            // Invalid UTF-8
+            &[0x00, 0x02, 0xC3, 0x28],
+            // Compressed frame announcing more data than it holds
+            &[0x01, 0xFF, 0xFF, 0xFF, 0x03, 0x78, 0x9C],
+        ];
+        for (_, target) in TARGETS {
+            for input in inputs {
+                target(input);
+                // Every data type of `round_trip`, the frame codec after them
+                for selector in 0..=16 {
+                    let mut data: Vec<u8> = vec![selector];
+                    data.extend_from_slice(input);
+                    target(&data);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_corpus() {
+        let corpus: PathBuf =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus");
+        for (name, target) in TARGETS {
+            let Ok(entries) = fs::read_dir(corpus.join(name)) else {
+                continue;
+            };
+            for entry in entries {
+                let data: Vec<u8> = fs::read(entry.unwrap().path()).unwrap();
+                target(&data);
+            }
+        }
+    }
+}
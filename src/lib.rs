@@ -0,0 +1,261 @@
+//! # `AtmosphereMC` - Void
+//!
+//! The `AtmosphereMC` server.
+#![warn(
+    missing_docs,
+    clippy::missing_docs_in_private_items,
+    clippy::cargo_common_metadata,
+    clippy::all,
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::cargo,
+    clippy::indexing_slicing
+)]
+use std::{
+    env,
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::Path,
+    process,
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{
+    access::AccessLists, config::Config, connection::Connection, console::LogOutput,
+    limiter::ConnectionPermit, listener::ListenerThread,
+    proxy_protocol::ProxyProtocolError, server::Server, text::Text,
+};
+
+mod access;
+#[cfg(feature = "bench")]
+pub mod bench;
+mod capture;
+mod client;
+mod command;
+mod config;
+mod connection;
+mod console;
+mod data;
+mod forwarding;
+#[cfg(any(fuzzing, test))]
+pub mod fuzz;
+mod limiter;
+mod listener;
+mod logging;
+mod metrics;
+mod mitm;
+mod pending;
+mod player;
+mod profile;
+mod protocol;
+mod proxy_protocol;
+mod query;
+mod rcon;
+mod server;
+mod state;
+mod swarm;
+mod text;
+mod tick;
+mod types;
+
+/// Time given to connections to flush their outbound queue when the server stops.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Run the server, or the tool named by the first command line argument.
+///
+/// # Panics
+///
+/// Panics if the server address cannot be bound.
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some((command, args)) = args.split_first() {
+        match command.as_str() {
+            "capture" => process::exit(capture::tool::main(args)),
+            "mitm" => process::exit(mitm::main(args)),
+            "swarm" => process::exit(swarm::main(args)),
+            _ => {}
+        }
+    }
+
+    let log_output: LogOutput = LogOutput::default();
+    logging::init(&log_output);
+
+    log::info!(target: "Main thread", "Starting server...");
+    if !data::COMPLETE {
+        log::warn!(
+            target: "Main thread",
+            "Built with incomplete vanilla data, clients may be sent wrong registry IDs"
+        );
+    }
+
+    let config: Config = match Config::load(Path::new("server.properties")) {
+        Ok(config) => config,
+        Err(e) => {
+            log::error!(target: "Main thread", "Failed to load server.properties: {e}");
+            return;
+        }
+    };
+    let access: AccessLists = AccessLists::load(Path::new("."), config.white_list);
+    let server: Arc<Server> = Arc::new(Server::new(config, access));
+
+    let signal_handler: Arc<Server> = Arc::clone(&server);
+    if let Err(e) = ctrlc::set_handler(move || {
+        if signal_handler.signal_stop() {
+            log::warn!(target: "Main thread", "Stop requested twice, exiting now");
+            process::exit(1);
+        }
+    }) {
+        log::error!(target: "Main thread", "Failed to set up the signal handler: {e}");
+    }
+
+    let tick: JoinHandle<()> = tick::spawn(Arc::clone(&server));
+    // Every tick, for the deadline to be close to the configured timeout
+    server
+        .scheduler()
+        .schedule_repeating(1, 1, Server::close_stale_logins);
+    let maintenance: u64 = server.scheduler().schedule_repeating(
+        u64::from(tick::TPS),
+        u64::from(tick::TPS),
+        |server| {
+            server.limiter().prune();
+            if server.access().reload_changed() {
+                server.enforce_access_lists();
+            }
+        },
+    );
+
+    let listener: TcpListener = TcpListener::bind(server.config().address).unwrap();
+
+    log::info!(target: "Main thread", "Server ready!");
+
+    let accepting: Arc<Server> = Arc::clone(&server);
+    let mut listeners: Vec<ListenerThread> = Vec::new();
+    match ListenerThread::spawn("Listener", listener, move |listener| {
+        accept(listener, &accepting);
+    }) {
+        Ok(listener) => listeners.push(listener),
+        Err(e) => {
+            log::error!(target: "Main thread", "Failed to spawn the listener thread: {e}");
+            return;
+        }
+    }
+
+    listeners.extend(rcon::spawn(&server));
+    query::spawn(&server);
+    listeners.extend(metrics::spawn(&server));
+    console::spawn(Arc::clone(&server), &log_output);
+
+    server.wait_for_stop_request();
+
+    log::info!(target: "Main thread", "Stopping server...");
+
+    // Players are disconnected with the shutdown message, not by the access lists
+    server.scheduler().cancel(maintenance);
+
+    let reason: Text = Text::plain(server.config().shutdown_message.clone());
+    let remaining: usize = server.shutdown(&reason, SHUTDOWN_TIMEOUT);
+    if remaining > 0 {
+        log::warn!(
+            target: "Main thread",
+            "{remaining} connections did not close within {SHUTDOWN_TIMEOUT:?}"
+        );
+    }
+    if tick.join().is_err() {
+        log::error!(target: "Main thread", "Tick thread panicked");
+    }
+    // The server no longer runs, the listeners end on their next client
+    for listener in listeners {
+        listener.join();
+    }
+
+    log::info!(target: "Main thread", "Server stopped");
+}
+
+/// Accept connections until the server stops.
+fn accept(listener: &TcpListener, server: &Arc<Server>) {
+    for stream in listener.incoming() {
+        if !server.is_running() {
+            break;
+        }
+
+        let stream: TcpStream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::error!(target: "Main thread", "{e}");
+                continue;
+            }
+        };
+
+        let peer: SocketAddr = match stream.peer_addr() {
+            Ok(address) => address,
+            Err(e) => {
+                log::error!(target: "Main thread", "Failed to get peer address: {e}");
+                continue;
+            }
+        };
+
+        let config: &Config = server.config();
+        let proxied: bool = config.proxy_protocol
+            && config
+                .proxy_protocol_trusted
+                .iter()
+                .any(|network| network.contains(peer.ip()));
+
+        if proxied {
+            // Read the header on the connection thread, not to stall the listener
+            let server: Arc<Server> = Arc::clone(server);
+            thread::spawn(move || match read_proxy_header(&stream, peer, &server) {
+                Ok(address) => {
+                    log::debug!(target: "Main thread", "Connection from {address} through {peer}");
+                    if let Some(connection) = open(stream, address, &server) {
+                        connection.run();
+                    }
+                }
+                Err(e) => {
+                    log::info!(target: "Main thread", "Rejected connection from {peer}: {e}");
+                }
+            });
+        } else if let Some(connection) = open(stream, peer, server) {
+            thread::spawn(|| connection.run());
+        }
+    }
+}
+
+/// Read the PROXY protocol header sent by a trusted load balancer at `peer`,
+/// returning the address of the client.
+fn read_proxy_header(
+    mut stream: &TcpStream,
+    peer: SocketAddr,
+    server: &Server,
+) -> Result<SocketAddr, ProxyProtocolError> {
+    let timeout: Duration = server.config().handshake_timeout;
+    stream.set_read_timeout(Some(timeout).filter(|t| !t.is_zero()))?;
+
+    Ok(proxy_protocol::read_header(&mut stream)?.unwrap_or(peer))
+}
+
+/// Set up a connection from a client at `address`, if the connection limits allow
+/// it.
+fn open(
+    stream: TcpStream,
+    address: SocketAddr,
+    server: &Arc<Server>,
+) -> Option<Connection> {
+    let permit: ConnectionPermit = match server.limiter().try_acquire(address.ip()) {
+        Ok(permit) => permit,
+        Err(reason) => {
+            let rejected: u64 = server.limiter().counters().rejected(reason);
+            log::info!(target: "Main thread", "Rejected connection from {address}: {reason} ({rejected} so far)");
+            return None;
+        }
+    };
+
+    match Connection::new(stream, address, Arc::clone(server), permit) {
+        Ok(connection) => Some(connection),
+        Err(e) => {
+            log::error!(target: "Main thread", "Failed to set up connection: {e}");
+            None
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Connection limits: concurrent connections per IP address and in total, a token
+//! bucket throttling new connections per IP address, and inbound packet rates.
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::state::ServerState;
+
+mod test;
+
+/// Reason a connection was rejected by the [`ConnectionLimiter`].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// The server has too many open connections.
+    #[error("Too many open connections")]
+    TooManyConnections,
+
+    /// The address has too many open connections.
+    #[error("Too many open connections from this address")]
+    TooManyConnectionsFromAddress,
+
+    /// The address opens connections too fast.
+    #[error("Connecting too fast")]
+    Throttled,
+}
+
+impl Rejection {
+    /// Every reason, in the order they are checked.
+    pub const ALL: [Self; 3] = [
+        Self::TooManyConnections,
+        Self::TooManyConnectionsFromAddress,
+        Self::Throttled,
+    ];
+}
+
+/// Limits enforced by the [`ConnectionLimiter`], `0` disabling a limit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConnectionLimits {
+    /// Maximum number of open connections.
+    pub max_connections: u32,
+
+    /// Maximum number of open connections per IP address.
+    pub max_connections_per_ip: u32,
+
+    /// New connections allowed per second and IP address, on average.
+    pub rate_per_ip: f64,
+
+    /// New connections allowed in a burst per IP address.
+    pub burst_per_ip: u32,
+}
+
+/// Number of rejected connections, by reason.
+#[derive(Default)]
+pub struct RejectionCounters {
+    /// Rejected with [`Rejection::TooManyConnections`].
+    too_many_connections: AtomicU64,
+
+    /// Rejected with [`Rejection::TooManyConnectionsFromAddress`].
+    too_many_from_address: AtomicU64,
+
+    /// Rejected with [`Rejection::Throttled`].
+    throttled: AtomicU64,
+
+    /// Closed for not completing the handshake in time.
+    handshake_timeouts: AtomicU64,
+}
+
+impl RejectionCounters {
+    /// Number of connections rejected for a reason.
+    pub fn rejected(&self, reason: Rejection) -> u64 {
+        match reason {
+            Rejection::TooManyConnections => &self.too_many_connections,
+            Rejection::TooManyConnectionsFromAddress => &self.too_many_from_address,
+            Rejection::Throttled => &self.throttled,
+        }
+        .load(Ordering::Relaxed)
+    }
+
+    /// Number of connections closed for not completing the handshake in time.
+    pub fn handshake_timeouts(&self) -> u64 {
+        self.handshake_timeouts.load(Ordering::Relaxed)
+    }
+
+    /// Count a rejected connection.
+    fn record(&self, reason: Rejection) {
+        match reason {
+            Rejection::TooManyConnections => &self.too_many_connections,
+            Rejection::TooManyConnectionsFromAddress => &self.too_many_from_address,
+            Rejection::Throttled => &self.throttled,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Connections of an IP address.
+struct Address {
+    /// Number of open connections.
+    open: u32,
+
+    /// Tokens in the bucket, one being taken by each new connection.
+    tokens: f64,
+
+    /// When the bucket was last refilled.
+    refilled_at: Instant,
+}
+
+/// Open connections, in total and by IP address.
+#[derive(Default)]
+struct Connections {
+    /// Number of open connections.
+    open: u32,
+
+    /// Connections by IP address.
+    addresses: HashMap<IpAddr, Address>,
+}
+
+/// Decides whether to accept new connections.
+pub struct ConnectionLimiter {
+    /// The limits.
+    limits: ConnectionLimits,
+
+    /// Open connections.
+    connections: Mutex<Connections>,
+
+    /// Rejected connections.
+    counters: RejectionCounters,
+}
+
+impl ConnectionLimiter {
+    /// Create a limiter without any open connection.
+    pub fn new(limits: ConnectionLimits) -> Self {
+        Self {
+            limits,
+            connections: Mutex::default(),
+            counters: RejectionCounters::default(),
+        }
+    }
+
+    /// Rejected connections.
+    pub const fn counters(&self) -> &RejectionCounters {
+        &self.counters
+    }
+
+    /// Accept or reject a new connection from `ip`. The connection counts as open
+    /// until the returned permit is dropped.
+    pub fn try_acquire(
+        self: &Arc<Self>,
+        ip: IpAddr,
+    ) -> Result<ConnectionPermit, Rejection> {
+        self.try_acquire_at(ip, Instant::now())
+    }
+
+    /// Count a connection closed for not completing the handshake in time.
+    pub fn record_handshake_timeout(&self) {
+        self.counters
+            .handshake_timeouts
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Forget addresses without open connections and with a full bucket.
+    pub fn prune(&self) {
+        self.prune_at(Instant::now());
+    }
+
+    /// [`ConnectionLimiter::try_acquire`] at a given time.
+    fn try_acquire_at(
+        self: &Arc<Self>,
+        ip: IpAddr,
+        now: Instant,
+    ) -> Result<ConnectionPermit, Rejection> {
+        let result: Result<(), Rejection> = self.lock().acquire(&self.limits, ip, now);
+        if let Err(reason) = result {
+            self.counters.record(reason);
+            return Err(reason);
+        }
+
+        Ok(ConnectionPermit {
+            limiter: Arc::clone(self),
+            ip,
+        })
+    }
+
+    /// [`ConnectionLimiter::prune`] at a given time.
+    fn prune_at(&self, now: Instant) {
+        let limits: ConnectionLimits = self.limits;
+        self.lock().addresses.retain(|_, address| {
+            address.refill(&limits, now);
+            address.open > 0 || address.tokens < f64::from(limits.burst_per_ip)
+        });
+    }
+
+    /// Release a connection.
+    fn release(&self, ip: IpAddr) {
+        let mut connections = self.lock();
+        connections.open = connections.open.saturating_sub(1);
+        if let Some(address) = connections.addresses.get_mut(&ip) {
+            address.open = address.open.saturating_sub(1);
+        }
+    }
+
+    /// Lock the open connections.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connections> {
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Connections {
+    /// Count a new connection from `ip` if the limits allow it.
+    fn acquire(
+        &mut self,
+        limits: &ConnectionLimits,
+        ip: IpAddr,
+        now: Instant,
+    ) -> Result<(), Rejection> {
+        if limits.max_connections > 0 && self.open >= limits.max_connections {
+            return Err(Rejection::TooManyConnections);
+        }
+
+        let address: &mut Address = self.addresses.entry(ip).or_insert_with(|| Address {
+            open: 0,
+            tokens: f64::from(limits.burst_per_ip),
+            refilled_at: now,
+        });
+        if limits.max_connections_per_ip > 0
+            && address.open >= limits.max_connections_per_ip
+        {
+            return Err(Rejection::TooManyConnectionsFromAddress);
+        }
+        if limits.burst_per_ip > 0 {
+            address.refill(limits, now);
+            if address.tokens < 1.0 {
+                return Err(Rejection::Throttled);
+            }
+            address.tokens -= 1.0;
+        }
+
+        address.open += 1;
+        self.open += 1;
+        Ok(())
+    }
+}
+
+impl Address {
+    /// Add the tokens earned since the last refill.
+    fn refill(&mut self, limits: &ConnectionLimits, now: Instant) {
+        let elapsed: f64 = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = elapsed
+            .mul_add(limits.rate_per_ip, self.tokens)
+            .min(f64::from(limits.burst_per_ip));
+        self.refilled_at = now;
+    }
+}
+
+/// An accepted connection, released when dropped.
+pub struct ConnectionPermit {
+    /// The limiter that accepted the connection.
+    limiter: Arc<ConnectionLimiter>,
+
+    /// Address of the client.
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.limiter.release(self.ip);
+    }
+}
+
+/// Maximum number of packets per second a client may send, by state, `0` disabling
+/// a limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PacketRateLimits {
+    /// In the status state.
+    pub status: u32,
+
+    /// In the login state.
+    pub login: u32,
+
+    /// In the configuration state.
+    pub configuration: u32,
+
+    /// In the play state.
+    pub play: u32,
+}
+
+impl PacketRateLimits {
+    /// Limit for a state. The handshake state only receives a single packet.
+    pub const fn for_state(&self, state: ServerState) -> u32 {
+        match state {
+            ServerState::Status => self.status,
+            ServerState::Login => self.login,
+            ServerState::Configuration => self.configuration,
+            ServerState::Play => self.play,
+            ServerState::Handshake | ServerState::Closed => 0,
+        }
+    }
+}
+
+/// Counts the packets received by a connection over one second windows.
+pub struct PacketRate {
+    /// Start of the current window.
+    window_start: Instant,
+
+    /// Packets received in the current window.
+    count: u32,
+}
+
+impl PacketRate {
+    /// Length of a window.
+    const WINDOW: Duration = Duration::from_secs(1);
+
+    /// Start counting.
+    pub fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            count: 0,
+        }
+    }
+
+    /// Count a packet, returning whether it stays within `limit` packets per second.
+    pub fn record(&mut self, limit: u32) -> bool {
+        self.record_at(limit, Instant::now())
+    }
+
+    /// [`PacketRate::record`] at a given time.
+    fn record_at(&mut self, limit: u32, now: Instant) -> bool {
+        if now.saturating_duration_since(self.window_start) >= Self::WINDOW {
+            self.window_start = now;
+            self.count = 0;
+        }
+        self.count = self.count.saturating_add(1);
+
+        limit == 0 || self.count <= limit
+    }
+}
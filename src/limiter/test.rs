@@ -0,0 +1,103 @@
+//! Test [`crate::limiter`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use crate::limiter::{
+        ConnectionLimiter, ConnectionLimits, ConnectionPermit, PacketRate, Rejection,
+    };
+
+    /// A client address.
+    const ALICE: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    /// Another client address.
+    const BOB: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+
+    #[test]
+    fn test_concurrent_limits() {
+        let limiter: Arc<ConnectionLimiter> =
+            Arc::new(ConnectionLimiter::new(ConnectionLimits {
+                max_connections: 3,
+                max_connections_per_ip: 2,
+                rate_per_ip: 0.0,
+                burst_per_ip: 0,
+            }));
+
+        let first: ConnectionPermit = limiter.try_acquire(ALICE).unwrap();
+        let _second: ConnectionPermit = limiter.try_acquire(ALICE).unwrap();
+        assert_eq!(
+            limiter.try_acquire(ALICE).err(),
+            Some(Rejection::TooManyConnectionsFromAddress)
+        );
+
+        let _third: ConnectionPermit = limiter.try_acquire(BOB).unwrap();
+        assert_eq!(
+            limiter.try_acquire(BOB).err(),
+            Some(Rejection::TooManyConnections)
+        );
+
+        drop(first);
+        assert!(limiter.try_acquire(ALICE).is_ok());
+
+        let counters = limiter.counters();
+        assert_eq!(
+            counters.rejected(Rejection::TooManyConnectionsFromAddress),
+            1
+        );
+        assert_eq!(counters.rejected(Rejection::TooManyConnections), 1);
+        assert_eq!(counters.rejected(Rejection::Throttled), 0);
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter: Arc<ConnectionLimiter> =
+            Arc::new(ConnectionLimiter::new(ConnectionLimits {
+                max_connections: 0,
+                max_connections_per_ip: 0,
+                rate_per_ip: 2.0,
+                burst_per_ip: 3,
+            }));
+        let start: Instant = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire_at(ALICE, start).is_ok());
+        }
+        assert_eq!(
+            limiter.try_acquire_at(ALICE, start).err(),
+            Some(Rejection::Throttled)
+        );
+        // Other addresses have their own bucket
+        assert!(limiter.try_acquire_at(BOB, start).is_ok());
+
+        // One token every 500ms
+        let later: Instant = start + Duration::from_millis(500);
+        assert!(limiter.try_acquire_at(ALICE, later).is_ok());
+        assert!(limiter.try_acquire_at(ALICE, later).is_err());
+
+        // Addresses are forgotten once their bucket is full again
+        limiter.prune_at(start + Duration::from_secs(10));
+        assert!(limiter.lock().addresses.is_empty());
+    }
+
+    #[test]
+    fn test_packet_rate() {
+        let mut rate: PacketRate = PacketRate::new();
+        let start: Instant = rate.window_start;
+
+        for _ in 0..3 {
+            assert!(rate.record_at(3, start));
+        }
+        assert!(!rate.record_at(3, start + Duration::from_millis(999)));
+
+        // A new window starts every second
+        assert!(rate.record_at(3, start + Duration::from_secs(1)));
+
+        // No limit
+        for _ in 0..100 {
+            assert!(rate.record_at(0, start + Duration::from_secs(1)));
+        }
+    }
+}
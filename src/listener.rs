@@ -0,0 +1,65 @@
+//! Threads accepting TCP clients, joined when the server stops.
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Time to connect to a listener to wake it.
+const WAKE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A thread accepting clients on a listener until the server stops.
+///
+/// The thread is blocked in `accept`: [`ListenerThread::join`] connects to the
+/// listener so that it sees the server stopped, then waits for it.
+pub struct ListenerThread {
+    /// Name of the thread, also used as the log target.
+    name: String,
+
+    /// Address of the listener.
+    address: SocketAddr,
+
+    /// The thread.
+    thread: JoinHandle<()>,
+}
+
+impl ListenerThread {
+    /// Spawn a thread named `name`, running `accept` on `listener`. `accept` must
+    /// return once a client is accepted after the server stopped.
+    pub fn spawn(
+        name: &str,
+        listener: TcpListener,
+        accept: impl FnOnce(&TcpListener) + Send + 'static,
+    ) -> Result<Self, io::Error> {
+        let address: SocketAddr = listener.local_addr()?;
+        let thread: JoinHandle<()> = thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || accept(&listener))?;
+        Ok(Self {
+            name: name.to_string(),
+            address,
+            thread,
+        })
+    }
+
+    /// Wake the thread once the server stopped, and wait for it to end, closing
+    /// the listener.
+    pub fn join(self) {
+        let ip: IpAddr = match self.address.ip() {
+            IpAddr::V4(ip) if ip.is_unspecified() => Ipv4Addr::LOCALHOST.into(),
+            IpAddr::V6(ip) if ip.is_unspecified() => Ipv6Addr::LOCALHOST.into(),
+            ip => ip,
+        };
+        let address: SocketAddr = SocketAddr::new(ip, self.address.port());
+        if let Err(e) = TcpStream::connect_timeout(&address, WAKE_TIMEOUT) {
+            // Not joined, the thread ends with the process
+            log::error!(target: &self.name, "Failed to wake the listener on {address}: {e}");
+            return;
+        }
+
+        if self.thread.join().is_err() {
+            log::error!(target: &self.name, "Listener thread panicked");
+        }
+    }
+}
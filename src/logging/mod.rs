@@ -0,0 +1,168 @@
+//! Logger setup: colored lines for the terminal, or JSON objects for log
+//! aggregators, with the context of the connection logging on the current thread.
+use std::{cell::RefCell, convert::identity, env, io::Write, net::SocketAddr};
+
+use env_logger::{fmt::style::Style, Env};
+use log::Record;
+use serde_json::{json, Value};
+
+use crate::{console::LogOutput, state::ServerState};
+
+mod test;
+
+/// Environment variable selecting the log format, `pretty` (default) or `json`.
+const FORMAT_VARIABLE: &str = "LOG_FORMAT";
+
+thread_local! {
+    /// Context of the connection handled by the current thread.
+    static CONTEXT: RefCell<LogContext> = RefCell::new(LogContext::default());
+}
+
+/// How log records are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Colored lines, for the terminal.
+    Pretty,
+
+    /// One JSON object per line, with the connection context as fields.
+    Json,
+}
+
+/// Context of the connection handled by a thread, added to its JSON records.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogContext {
+    /// Unique ID of the connection.
+    pub connection_id: Option<u64>,
+
+    /// Address of the client.
+    pub peer: Option<SocketAddr>,
+
+    /// Current state of the connection.
+    pub state: Option<ServerState>,
+
+    /// Name of the player, once logged in.
+    pub player: Option<String>,
+
+    /// ID of the packet being handled.
+    pub packet_id: Option<i32>,
+}
+
+impl LogContext {
+    /// The client of the connection for terminal lines: ` (address player)`, or
+    /// nothing outside of a connection.
+    fn client(&self) -> String {
+        match (self.peer, &self.player) {
+            (Some(peer), Some(player)) => format!(" ({peer} {player})"),
+            (Some(peer), None) => format!(" ({peer})"),
+            (None, _) => String::new(),
+        }
+    }
+}
+
+/// Set up the logger, writing to `output` in the format selected by
+/// [`FORMAT_VARIABLE`]. Everything is logged unless `RUST_LOG` says otherwise.
+pub fn init(output: &LogOutput) {
+    let format: LogFormat = match env::var(FORMAT_VARIABLE).as_deref() {
+        Ok("json") => LogFormat::Json,
+        _ => LogFormat::Pretty,
+    };
+
+    let mut builder = env_logger::Builder::new();
+    builder
+        .filter(None, log::LevelFilter::Trace)
+        .filter(Some("rustyline"), log::LevelFilter::Info)
+        // Directives from `RUST_LOG` override the defaults above
+        .parse_env(Env::default())
+        .target(env_logger::Target::Pipe(Box::new(output.writer())));
+
+    match format {
+        LogFormat::Pretty => {
+            builder
+                .format(|buf, record| {
+                    let style = buf.default_level_style(record.level());
+
+                    let bold = Style::new().bold();
+                    let underline = Style::new().underline();
+                    let dimmed = Style::new().dimmed();
+                    let client: String = CONTEXT.with_borrow(LogContext::client);
+
+                    writeln!(
+                        buf,
+                        "{bold}{underline}[{3}]{bold:#}{underline:#}{5} {style}{bold}<{0: <5}>{bold:#}{style:#} {style}{1}{style:#} {dimmed}{2} - {4}{dimmed:#}",
+                        record.level(),
+                        record.args(),
+                        record.module_path().map_or("-", identity),
+                        record.target(),
+                        buf.timestamp_seconds(),
+                        client,
+                    )
+                })
+                .write_style(env_logger::WriteStyle::Always);
+        }
+        LogFormat::Json => {
+            builder
+                .format(|buf, record| {
+                    let timestamp: String = buf.timestamp_millis().to_string();
+                    let line: Value = CONTEXT
+                        .with_borrow(|context| to_json(record, context, &timestamp));
+                    writeln!(buf, "{line}")
+                })
+                .write_style(env_logger::WriteStyle::Never);
+        }
+    }
+
+    builder.init();
+}
+
+/// A record as a JSON object, with the fields of the context that are known.
+pub fn to_json(record: &Record, context: &LogContext, timestamp: &str) -> Value {
+    let mut line: Value = json!({
+        "timestamp": timestamp,
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "module": record.module_path(),
+        "message": record.args().to_string(),
+    });
+
+    if let Value::Object(fields) = &mut line {
+        let context_fields: [(&str, Option<Value>); 5] = [
+            ("connection_id", context.connection_id.map(Value::from)),
+            (
+                "peer",
+                context.peer.map(|peer| Value::from(peer.to_string())),
+            ),
+            (
+                "state",
+                context.state.map(|s| Value::from(format!("{s:?}"))),
+            ),
+            ("player", context.player.clone().map(Value::from)),
+            ("packet_id", context.packet_id.map(Value::from)),
+        ];
+        for (key, value) in context_fields {
+            if let Some(value) = value {
+                fields.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    line
+}
+
+/// ID of the connection handled by the current thread, if any.
+pub fn connection_id() -> Option<u64> {
+    CONTEXT.with_borrow(|context| context.connection_id)
+}
+
+/// Run `f` with `context` as the context of the current thread, restoring the
+/// previous one after.
+pub fn with_context<R>(context: LogContext, f: impl FnOnce() -> R) -> R {
+    let previous: LogContext = CONTEXT.replace(context);
+    let result: R = f();
+    CONTEXT.set(previous);
+    result
+}
+
+/// Update the context of the current thread.
+pub fn update_context(update: impl FnOnce(&mut LogContext)) {
+    CONTEXT.with_borrow_mut(update);
+}
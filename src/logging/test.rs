@@ -0,0 +1,75 @@
+//! Test [`crate::logging`].
+#[cfg(test)]
+mod tests {
+    use log::{Level, Record};
+    use serde_json::json;
+
+    use crate::{
+        logging::{
+            connection_id, to_json, update_context, with_context, LogContext, CONTEXT,
+        },
+        state::ServerState,
+    };
+
+    #[test]
+    fn test_json() {
+        let record = |message: &str| {
+            to_json(
+                &Record::builder()
+                    .level(Level::Info)
+                    .target("atmospheremc_void::connection")
+                    .module_path(Some("atmospheremc_void::connection"))
+                    .args(format_args!("{message}"))
+                    .build(),
+                &CONTEXT.with_borrow(Clone::clone),
+                "2024-10-22T12:00:00.000Z",
+            )
+        };
+
+        assert_eq!(
+            record("Starting"),
+            json!({
+                "timestamp": "2024-10-22T12:00:00.000Z",
+                "level": "INFO",
+                "target": "atmospheremc_void::connection",
+                "module": "atmospheremc_void::connection",
+                "message": "Starting",
+            })
+        );
+
+        update_context(|context| {
+            *context = LogContext {
+                connection_id: Some(3),
+                peer: "192.0.2.1:4000".parse().ok(),
+                state: Some(ServerState::Play),
+                player: Some("Notch".to_string()),
+                packet_id: Some(0x1a),
+            };
+        });
+        assert_eq!(
+            record("Moved \"quickly\""),
+            json!({
+                "timestamp": "2024-10-22T12:00:00.000Z",
+                "level": "INFO",
+                "target": "atmospheremc_void::connection",
+                "module": "atmospheremc_void::connection",
+                "message": "Moved \"quickly\"",
+                "connection_id": 3,
+                "peer": "192.0.2.1:4000",
+                "state": "Play",
+                "player": "Notch",
+                "packet_id": 26,
+            })
+        );
+    }
+    #[test]
+    fn test_with_context() {
+        update_context(|context| context.connection_id = Some(1));
+        let context: LogContext = LogContext {
+            connection_id: Some(2),
+            ..LogContext::default()
+        };
+        assert_eq!(with_context(context, connection_id), Some(2));
+        assert_eq!(connection_id(), Some(1));
+    }
+}
@@ -0,0 +1,465 @@
+//! Server metrics, exposed over HTTP in the Prometheus text format.
+//!
+//! See <https://prometheus.io/docs/instrumenting/exposition_formats/>.
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write as _},
+    fs,
+    io::{self, BufRead, BufReader, Read, Take, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    limiter::{Rejection, RejectionCounters},
+    listener::ListenerThread,
+    protocol::{Direction, PacketParseError},
+    server::{Server, PROTOCOL_VERSION},
+    state::ServerState,
+    types::DataTypeDecodeError,
+};
+
+mod test;
+
+/// Prefix of the metric names.
+const PREFIX: &str = "atmospheremc";
+
+/// Upper bounds of the tick duration histogram buckets, in seconds.
+const TICK_BUCKETS: [f64; 8] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25];
+
+/// Scrapers must send their request within this delay.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum length of a request, headers included.
+const MAX_REQUEST_LENGTH: u64 = 8192;
+
+/// Counters recorded under a lock, by label.
+#[derive(Default)]
+struct Labeled {
+    /// Packets by direction, state and ID, `None` for the packets that are not
+    /// known in their state.
+    packets: HashMap<(Direction, ServerState, Option<i32>), u64>,
+
+    /// Packet errors by error type and variant.
+    errors: HashMap<(&'static str, &'static str), u64>,
+
+    /// Handshakes by protocol version, `None` for the unsupported ones.
+    protocol_versions: HashMap<Option<i32>, u64>,
+
+    /// Number of ticks in each bucket of [`TICK_BUCKETS`], not cumulated.
+    tick_buckets: [u64; TICK_BUCKETS.len()],
+
+    /// Number of ticks longer than the last bucket.
+    tick_overflow: u64,
+
+    /// Total time spent in ticks, in seconds.
+    tick_sum: f64,
+}
+
+/// Metrics of the server, recorded by the connections and the tick loop.
+#[derive(Default)]
+pub struct Metrics {
+    /// Bytes read from clients.
+    bytes_in: AtomicU64,
+
+    /// Bytes written to clients.
+    bytes_out: AtomicU64,
+
+    /// Pre-1.7 server list pings answered.
+    legacy_pings: AtomicU64,
+
+    /// Labeled counters.
+    labeled: Mutex<Labeled>,
+}
+
+impl Metrics {
+    /// Lock the labeled counters.
+    fn labeled(&self) -> std::sync::MutexGuard<'_, Labeled> {
+        self.labeled.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record a packet with `id` in `state`, `None` if the packet is not known in
+    /// this state. Only known IDs get their own label, not to let clients create
+    /// any number of them.
+    pub fn record_packet(
+        &self,
+        direction: Direction,
+        state: ServerState,
+        id: Option<i32>,
+    ) {
+        *self
+            .labeled()
+            .packets
+            .entry((direction, state, id))
+            .or_default() += 1;
+    }
+
+    /// Record bytes read from a client.
+    pub fn record_bytes_in(&self, bytes: usize) {
+        self.bytes_in
+            .fetch_add(u64::try_from(bytes).unwrap_or(u64::MAX), Ordering::Relaxed);
+    }
+
+    /// Record bytes written to a client.
+    pub fn record_bytes_out(&self, bytes: usize) {
+        self.bytes_out
+            .fetch_add(u64::try_from(bytes).unwrap_or(u64::MAX), Ordering::Relaxed);
+    }
+
+    /// Record an error while reading a packet.
+    pub fn record_error(&self, error: &PacketParseError) {
+        let key: (&str, &str) = match error {
+            PacketParseError::DataTypeDecodeError(e) => {
+                ("DataTypeDecodeError", decode_error_variant(e))
+            }
+            PacketParseError::UnknownPacket(..) => ("PacketParseError", "UnknownPacket"),
+            PacketParseError::RateLimitExceeded(_) => {
+                ("PacketParseError", "RateLimitExceeded")
+            }
+        };
+        *self.labeled().errors.entry(key).or_default() += 1;
+    }
+
+    /// Record the protocol version of a handshake, counting the unsupported ones
+    /// together.
+    pub fn record_protocol_version(&self, version: i32) {
+        let version: Option<i32> = (version == PROTOCOL_VERSION).then_some(version);
+        *self.labeled().protocol_versions.entry(version).or_default() += 1;
+    }
+
+    /// Record a legacy server list ping.
+    pub fn record_legacy_ping(&self) {
+        self.legacy_pings.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the time spent in a tick.
+    pub fn record_tick(&self, duration: Duration) {
+        let seconds: f64 = duration.as_secs_f64();
+        let mut labeled = self.labeled();
+        labeled.tick_sum += seconds;
+        match TICK_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            Some(bucket) => {
+                if let Some(count) = labeled.tick_buckets.get_mut(bucket) {
+                    *count += 1;
+                }
+            }
+            None => labeled.tick_overflow += 1,
+        }
+    }
+
+    /// Render the metrics of `server` in the Prometheus text format.
+    pub fn render(&self, server: &Server) -> String {
+        let mut output: String = String::new();
+
+        let mut connections: HashMap<ServerState, u64> = HashMap::new();
+        for state in server.connection_states() {
+            *connections.entry(state).or_default() += 1;
+        }
+        header(
+            &mut output,
+            "connections",
+            "gauge",
+            "Open connections by state.",
+        );
+        for state in [
+            ServerState::Handshake,
+            ServerState::Status,
+            ServerState::Login,
+            ServerState::Configuration,
+            ServerState::Play,
+        ] {
+            let count: u64 = connections.get(&state).copied().unwrap_or_default();
+            let labels: String = format!("state=\"{state:?}\"");
+            sample(&mut output, "connections", &labels, count);
+        }
+
+        header(&mut output, "players_online", "gauge", "Players in game.");
+        sample(
+            &mut output,
+            "players_online",
+            "",
+            server.players().in_game(),
+        );
+
+        header(&mut output, "bytes_total", "counter", "Bytes by direction.");
+        for (direction, bytes) in [("in", &self.bytes_in), ("out", &self.bytes_out)] {
+            let labels: String = format!("direction=\"{direction}\"");
+            sample(
+                &mut output,
+                "bytes_total",
+                &labels,
+                bytes.load(Ordering::Relaxed),
+            );
+        }
+
+        header(
+            &mut output,
+            "legacy_pings_total",
+            "counter",
+            "Pre-1.7 server list pings.",
+        );
+        let legacy_pings: u64 = self.legacy_pings.load(Ordering::Relaxed);
+        sample(&mut output, "legacy_pings_total", "", legacy_pings);
+
+        let counters: &RejectionCounters = server.limiter().counters();
+        header(
+            &mut output,
+            "connections_rejected_total",
+            "counter",
+            "Connections rejected by the connection limits, by reason.",
+        );
+        for reason in Rejection::ALL {
+            let labels: String = format!("reason=\"{reason:?}\"");
+            let rejected: u64 = counters.rejected(reason);
+            sample(&mut output, "connections_rejected_total", &labels, rejected);
+        }
+
+        header(
+            &mut output,
+            "handshake_timeouts_total",
+            "counter",
+            "Connections closed for not reaching the play state in time.",
+        );
+        let timeouts: u64 = counters.handshake_timeouts();
+        sample(&mut output, "handshake_timeouts_total", "", timeouts);
+
+        header(
+            &mut output,
+            "ticks_total",
+            "counter",
+            "Ticks since the server started.",
+        );
+        sample(&mut output, "ticks_total", "", server.ticks().tick());
+
+        if let Some(memory) = resident_memory() {
+            header(
+                &mut output,
+                "resident_memory_bytes",
+                "gauge",
+                "Resident memory of the process.",
+            );
+            sample(&mut output, "resident_memory_bytes", "", memory);
+        }
+
+        self.labeled().render(&mut output);
+        output
+    }
+}
+
+impl Labeled {
+    /// Render the labeled counters and the tick duration histogram.
+    fn render(&self, output: &mut String) {
+        header(
+            output,
+            "packets_total",
+            "counter",
+            "Packets by direction, state and ID.",
+        );
+        let packets = self.packets.iter().map(|((direction, state, id), count)| {
+            let direction: &str = match direction {
+                Direction::Serverbound => "in",
+                Direction::Clientbound => "out",
+            };
+            let id: String =
+                id.map_or_else(|| "other".to_string(), |id| format!("0x{id:02X}"));
+            let labels: String =
+                format!("direction=\"{direction}\",state=\"{state:?}\",id=\"{id}\"");
+            (labels, *count)
+        });
+        sorted_samples(output, "packets_total", packets);
+
+        header(
+            output,
+            "packet_errors_total",
+            "counter",
+            "Packet errors by type and variant.",
+        );
+        let errors = self.errors.iter().map(|((kind, variant), count)| {
+            (format!("type=\"{kind}\",variant=\"{variant}\""), *count)
+        });
+        sorted_samples(output, "packet_errors_total", errors);
+
+        header(
+            output,
+            "handshakes_total",
+            "counter",
+            "Handshakes by protocol version.",
+        );
+        let mut versions: Vec<(&Option<i32>, &u64)> =
+            self.protocol_versions.iter().collect();
+        // Sorted by number, not as strings, the unsupported ones first
+        versions.sort_unstable();
+        for (version, count) in versions {
+            let version: String = version
+                .map_or_else(|| "other".to_string(), |version| version.to_string());
+            let labels: String = format!("protocol=\"{version}\"");
+            sample(output, "handshakes_total", &labels, count);
+        }
+
+        header(
+            output,
+            "tick_duration_seconds",
+            "histogram",
+            "Time spent in ticks.",
+        );
+        let mut cumulated: u64 = 0;
+        for (bound, count) in TICK_BUCKETS.iter().zip(self.tick_buckets) {
+            cumulated += count;
+            let labels: String = format!("le=\"{bound}\"");
+            sample(output, "tick_duration_seconds_bucket", &labels, cumulated);
+        }
+        cumulated += self.tick_overflow;
+        sample(
+            output,
+            "tick_duration_seconds_bucket",
+            "le=\"+Inf\"",
+            cumulated,
+        );
+        sample(output, "tick_duration_seconds_sum", "", self.tick_sum);
+        sample(output, "tick_duration_seconds_count", "", cumulated);
+    }
+}
+
+/// Write the samples of a labeled counter, sorted by labels.
+fn sorted_samples(
+    output: &mut String,
+    name: &str,
+    samples: impl Iterator<Item = (String, u64)>,
+) {
+    let mut samples: Vec<(String, u64)> = samples.collect();
+    samples.sort();
+    for (labels, count) in samples {
+        sample(output, name, &labels, count);
+    }
+}
+
+/// Name of the variant of a decode error.
+const fn decode_error_variant(error: &DataTypeDecodeError) -> &'static str {
+    match error {
+        DataTypeDecodeError::PrematureEndOfVarNumber => "PrematureEndOfVarNumber",
+        DataTypeDecodeError::VarNumberTooBig => "VarNumberTooBig",
+        DataTypeDecodeError::TryFromIntError(_) => "TryFromIntError",
+        DataTypeDecodeError::Utf8Error(_) => "Utf8Error",
+        DataTypeDecodeError::PrematureEnd => "PrematureEnd",
+        DataTypeDecodeError::InvalidVarIntEnumVariant { .. } => {
+            "InvalidVarIntEnumVariant"
+        }
+        DataTypeDecodeError::StringTooLong { .. } => "StringTooLong",
+        DataTypeDecodeError::PacketTooLarge(_) => "PacketTooLarge",
+        DataTypeDecodeError::ByteArrayTooLong { .. } => "ByteArrayTooLong",
+        DataTypeDecodeError::InvalidDataLength(_) => "InvalidDataLength",
+        DataTypeDecodeError::IOError(_) => "IOError",
+    }
+}
+
+/// Resident memory of the process, only known on Linux.
+fn resident_memory() -> Option<u64> {
+    let status: String = fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes: &str = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?;
+    Some(kilobytes.trim().parse::<u64>().ok()? * 1024)
+}
+
+/// Write the `HELP` and `TYPE` lines of a metric.
+fn header(output: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(output, "# HELP {PREFIX}_{name} {help}");
+    let _ = writeln!(output, "# TYPE {PREFIX}_{name} {kind}");
+}
+
+/// Write a sample of a metric.
+fn sample(output: &mut String, name: &str, labels: &str, value: impl Display) {
+    let _ = if labels.is_empty() {
+        writeln!(output, "{PREFIX}_{name} {value}")
+    } else {
+        writeln!(output, "{PREFIX}_{name}{{{labels}}} {value}")
+    };
+}
+
+/// Start the metrics endpoint if enabled, on a dedicated thread.
+pub fn spawn(server: &Arc<Server>) -> Option<ListenerThread> {
+    let config = server.config();
+    if !config.enable_metrics {
+        return None;
+    }
+
+    let listener: TcpListener = match TcpListener::bind(config.metrics_address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!(target: "Metrics", "Failed to bind {}: {e}", config.metrics_address);
+            return None;
+        }
+    };
+    log::info!(target: "Metrics", "Metrics on http://{}/metrics", config.metrics_address);
+
+    let server: Arc<Server> = Arc::clone(server);
+    ListenerThread::spawn("Metrics Listener", listener, move |listener| {
+        accept(listener, &server);
+    })
+    .inspect_err(
+        |e| log::error!(target: "Metrics", "Failed to spawn the listener thread: {e}"),
+    )
+    .ok()
+}
+
+/// Answer scrapers one at a time until the server stops.
+fn accept(listener: &TcpListener, server: &Server) {
+    for stream in listener.incoming() {
+        if !server.is_running() {
+            break;
+        }
+
+        let result: Result<(), io::Error> = stream.and_then(|s| respond(&s, server));
+        if let Err(e) = result {
+            log::debug!(target: "Metrics", "Failed to answer scraper: {e}");
+        }
+    }
+}
+
+/// Answer an HTTP request: the metrics on `GET /metrics`, an error otherwise.
+///
+/// Scrapers are answered one at a time, so the request is read within
+/// [`REQUEST_TIMEOUT`] in total and up to [`MAX_REQUEST_LENGTH`] bytes.
+fn respond(stream: &TcpStream, server: &Server) -> Result<(), io::Error> {
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let deadline: Instant = Instant::now() + REQUEST_TIMEOUT;
+    let mut reader: BufReader<Take<&TcpStream>> =
+        BufReader::new(stream.take(MAX_REQUEST_LENGTH));
+    let mut read_line = |line: &mut String| {
+        let remaining: Duration = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::from(io::ErrorKind::TimedOut));
+        }
+        stream.set_read_timeout(Some(remaining))?;
+        reader.read_line(line)
+    };
+
+    let mut request_line: String = String::new();
+    read_line(&mut request_line)?;
+    // Skip the headers, until the empty line or the end of the request
+    let mut line: String = String::new();
+    while read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, body): (&str, String) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", server.metrics().render(server)),
+        (Some("GET"), Some(_)) => ("404 Not Found", "Not Found\n".to_string()),
+        _ => ("405 Method Not Allowed", "Method Not Allowed\n".to_string()),
+    };
+
+    let mut writer: &TcpStream = stream;
+    write!(
+        writer,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {0}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    writer.flush()
+}
@@ -0,0 +1,144 @@
+//! Test [`crate::metrics`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{Read, Write},
+        net::{IpAddr, Ipv4Addr, TcpListener, TcpStream},
+        path::PathBuf,
+        process, thread,
+        time::Duration,
+    };
+
+    use crate::{
+        access::AccessLists,
+        config::Config,
+        limiter::ConnectionPermit,
+        metrics::{respond, Metrics, MAX_REQUEST_LENGTH},
+        protocol::{Direction, PacketParseError},
+        server::Server,
+        state::ServerState,
+        types::DataTypeDecodeError,
+    };
+
+    #[test]
+    fn test_render() {
+        let directory: PathBuf =
+            std::env::temp_dir().join(format!("atmospheremc-metrics-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let server: Server =
+            Server::new(Config::default(), AccessLists::load(&directory, false));
+
+        let metrics: &Metrics = server.metrics();
+        metrics.record_packet(Direction::Serverbound, ServerState::Handshake, Some(0));
+        metrics.record_packet(Direction::Serverbound, ServerState::Play, Some(0x1a));
+        metrics.record_packet(Direction::Serverbound, ServerState::Play, Some(0x1a));
+        metrics.record_packet(Direction::Serverbound, ServerState::Play, None);
+        metrics.record_packet(Direction::Serverbound, ServerState::Play, None);
+        metrics.record_packet(Direction::Clientbound, ServerState::Status, Some(0));
+        metrics.record_bytes_in(20);
+        metrics.record_bytes_in(5);
+        metrics.record_bytes_out(42);
+        metrics.record_error(&PacketParseError::UnknownPacket(0x7f, ServerState::Play));
+        metrics.record_error(
+            &DataTypeDecodeError::StringTooLong {
+                length: 20,
+                max_length: 16,
+            }
+            .into(),
+        );
+        metrics.record_protocol_version(768);
+        metrics.record_protocol_version(767);
+        metrics.record_protocol_version(768);
+        metrics.record_protocol_version(i32::MIN);
+        metrics.record_legacy_ping();
+        let ip: IpAddr = Ipv4Addr::LOCALHOST.into();
+        let permits: Vec<ConnectionPermit> = (0..8)
+            .map(|_| server.limiter().try_acquire(ip).unwrap())
+            .collect();
+        assert!(server.limiter().try_acquire(ip).is_err());
+        drop(permits);
+        server.limiter().record_handshake_timeout();
+        metrics.record_tick(Duration::from_micros(500));
+        metrics.record_tick(Duration::from_millis(20));
+        metrics.record_tick(Duration::from_secs(1));
+
+        let output: String = metrics.render(&server);
+        let lines: Vec<&str> = output.lines().collect();
+        for expected in [
+            "# TYPE atmospheremc_connections gauge",
+            "atmospheremc_connections{state=\"Play\"} 0",
+            "atmospheremc_players_online 0",
+            "# TYPE atmospheremc_packets_total counter",
+            "atmospheremc_packets_total{direction=\"in\",state=\"Handshake\",id=\"0x00\"} 1",
+            "atmospheremc_packets_total{direction=\"in\",state=\"Play\",id=\"0x1A\"} 2",
+            "atmospheremc_packets_total{direction=\"in\",state=\"Play\",id=\"other\"} 2",
+            "atmospheremc_packets_total{direction=\"out\",state=\"Status\",id=\"0x00\"} 1",
+            "atmospheremc_bytes_total{direction=\"in\"} 25",
+            "atmospheremc_bytes_total{direction=\"out\"} 42",
+            "atmospheremc_packet_errors_total{type=\"DataTypeDecodeError\",variant=\"StringTooLong\"} 1",
+            "atmospheremc_packet_errors_total{type=\"PacketParseError\",variant=\"UnknownPacket\"} 1",
+            "atmospheremc_handshakes_total{protocol=\"other\"} 2",
+            "atmospheremc_handshakes_total{protocol=\"768\"} 2",
+            "atmospheremc_legacy_pings_total 1",
+            "atmospheremc_ticks_total 0",
+            "atmospheremc_connections_rejected_total{reason=\"TooManyConnections\"} 0",
+            "atmospheremc_connections_rejected_total{reason=\"TooManyConnectionsFromAddress\"} 1",
+            "atmospheremc_connections_rejected_total{reason=\"Throttled\"} 0",
+            "atmospheremc_handshake_timeouts_total 1",
+            "# TYPE atmospheremc_tick_duration_seconds histogram",
+            "atmospheremc_tick_duration_seconds_bucket{le=\"0.001\"} 1",
+            "atmospheremc_tick_duration_seconds_bucket{le=\"0.01\"} 1",
+            "atmospheremc_tick_duration_seconds_bucket{le=\"0.025\"} 2",
+            "atmospheremc_tick_duration_seconds_bucket{le=\"0.25\"} 2",
+            "atmospheremc_tick_duration_seconds_bucket{le=\"+Inf\"} 3",
+            "atmospheremc_tick_duration_seconds_sum 1.0205",
+            "atmospheremc_tick_duration_seconds_count 3",
+        ] {
+            assert!(lines.contains(&expected), "missing `{expected}` in:\n{output}");
+        }
+        if cfg!(target_os = "linux") {
+            assert!(lines
+                .iter()
+                .any(|line| line.starts_with("atmospheremc_resident_memory_bytes ")));
+        }
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_respond_bounded() {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-metrics-respond-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let server: Server =
+            Server::new(Config::default(), AccessLists::load(&directory, false));
+        let listener: TcpListener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // Headers up to the limit without the empty line ending them: the
+        // request is answered without waiting for the rest of it
+        let client = thread::spawn(move || {
+            let mut stream: TcpStream = TcpStream::connect(address).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(2)))
+                .unwrap();
+            let mut request: Vec<u8> = b"GET /metrics HTTP/1.1\r\n".to_vec();
+            let length: usize = usize::try_from(MAX_REQUEST_LENGTH).unwrap();
+            request.resize(length - 2, b'a');
+            request.extend_from_slice(b"\r\n");
+            stream.write_all(&request).unwrap();
+            let mut response: String = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let (stream, _) = listener.accept().unwrap();
+        respond(&stream, &server).unwrap();
+        drop(stream);
+
+        let response: String = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,409 @@
+//! `mitm` subcommand: a proxy between a client and an upstream server, printing
+//! the decoded packets of both directions and flagging the frames that the codec
+//! would not encode the same way.
+//!
+//! The upstream server must be in offline mode: once encryption is enabled, the
+//! connection is relayed without being decoded.
+use std::{
+    borrow::Cow,
+    io::{self, BufReader, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    thread,
+    time::Instant,
+};
+
+use crate::{
+    capture::{tool::describe, Capture, Record},
+    protocol::{Codec, Direction},
+    state::ServerState,
+    types::{
+        var::{VarInt, VarString},
+        DataType, DataTypeDecodeError,
+    },
+};
+
+mod test;
+
+/// Usage of the subcommand.
+const USAGE: &str = "Usage:
+    atmospheremc-void mitm <listen address> <upstream address> [--record <directory>]";
+
+/// Maximum length of a frame, as in vanilla.
+const MAX_FRAME_LENGTH: usize = (1 << 21) - 1;
+
+/// Clientbound Encryption Request, in the login state.
+const ENCRYPTION_REQUEST: i32 = 0x01;
+
+/// Serverbound Encryption Response, in the login state.
+const ENCRYPTION_RESPONSE: i32 = 0x01;
+
+/// Clientbound Login Success, in the login state.
+const LOGIN_SUCCESS: i32 = 0x02;
+
+/// Clientbound Set Compression, in the login state.
+const SET_COMPRESSION: i32 = 0x03;
+
+/// Serverbound Login Acknowledged, in the login state.
+const LOGIN_ACKNOWLEDGED: i32 = 0x03;
+
+/// Clientbound Finish Configuration, in the configuration state.
+const FINISH_CONFIGURATION: i32 = 0x03;
+
+/// Serverbound Acknowledge Finish Configuration, in the configuration state.
+const ACKNOWLEDGE_FINISH_CONFIGURATION: i32 = 0x03;
+
+/// Clientbound Start Configuration, in the play state.
+const START_CONFIGURATION: i32 = 0x70;
+
+/// Serverbound Acknowledge Configuration, in the play state.
+const ACKNOWLEDGE_CONFIGURATION: i32 = 0x0E;
+
+/// Run the subcommand with its arguments, returning the exit code.
+pub fn main(args: &[String]) -> i32 {
+    let (listen, upstream, directory) = match args {
+        [listen, upstream] => (listen, upstream, None),
+        [listen, upstream, flag, directory] if flag == "--record" => {
+            (listen, upstream, Some(PathBuf::from(directory)))
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+
+    match run(listen, upstream, directory.as_deref()) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+/// Accept clients on `listen` and proxy each of them to `upstream`, recording
+/// their connections in `directory` if set.
+fn run(listen: &str, upstream: &str, directory: Option<&Path>) -> Result<(), io::Error> {
+    let listener: TcpListener = TcpListener::bind(listen)?;
+    println!("Proxying {listen} to {upstream}");
+
+    for (id, client) in (1..).zip(listener.incoming()) {
+        let client: TcpStream = match client {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+        let upstream: String = upstream.to_string();
+        let directory: Option<PathBuf> = directory.map(Path::to_path_buf);
+        thread::spawn(move || {
+            if let Err(e) = proxy(id, client, &upstream, directory.as_deref()) {
+                eprintln!("#{id} {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Proxy a client to `upstream` until either side closes the connection.
+fn proxy(
+    id: u64,
+    client: TcpStream,
+    upstream: &str,
+    directory: Option<&Path>,
+) -> Result<(), io::Error> {
+    let peer: SocketAddr = client.peer_addr()?;
+    let server: TcpStream = TcpStream::connect(upstream)?;
+    let capture: Option<Capture> = directory
+        .map(|directory| Capture::create(directory, id, peer))
+        .transpose()?;
+    println!("#{id} {peer} connected");
+
+    let session: Mutex<Session> = Mutex::new(Session::new());
+    let relay: Relay = Relay {
+        id,
+        opened_at: Instant::now(),
+        session: &session,
+        capture: capture.as_ref(),
+    };
+    let (client_reader, server_writer) = (client.try_clone()?, server.try_clone()?);
+    thread::scope(|scope| {
+        scope.spawn(|| relay.run(Direction::Serverbound, client_reader, server_writer));
+        relay.run(Direction::Clientbound, server, client);
+    });
+
+    println!("#{id} {peer} disconnected");
+    Ok(())
+}
+
+/// State of a proxied connection, followed from the packets of both directions.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Session {
+    /// State of the serverbound direction.
+    serverbound: ServerState,
+
+    /// State of the clientbound direction, ahead of the serverbound one while the
+    /// client has not acknowledged a change.
+    clientbound: ServerState,
+
+    /// Compression threshold, once set by the server.
+    compression_threshold: Option<usize>,
+
+    /// Whether the server asked for encryption.
+    encryption_requested: bool,
+
+    /// Whether the serverbound direction is encrypted.
+    serverbound_encrypted: bool,
+
+    /// Whether the clientbound direction is encrypted.
+    clientbound_encrypted: bool,
+}
+
+impl Session {
+    /// Session of a connection that just opened.
+    pub const fn new() -> Self {
+        Self {
+            serverbound: ServerState::Handshake,
+            clientbound: ServerState::Handshake,
+            compression_threshold: None,
+            encryption_requested: false,
+            serverbound_encrypted: false,
+            clientbound_encrypted: false,
+        }
+    }
+
+    /// State of a direction.
+    pub const fn state(&self, direction: Direction) -> ServerState {
+        match direction {
+            Direction::Serverbound => self.serverbound,
+            Direction::Clientbound => self.clientbound,
+        }
+    }
+
+    /// Compression threshold, if enabled.
+    pub const fn compression_threshold(&self) -> Option<usize> {
+        self.compression_threshold
+    }
+
+    /// Whether the frames of a direction are encrypted, and cannot be decoded.
+    pub const fn is_encrypted(&self, direction: Direction) -> bool {
+        match direction {
+            Direction::Serverbound => self.serverbound_encrypted,
+            Direction::Clientbound => self.clientbound_encrypted,
+        }
+    }
+
+    /// Follow the state changes made by a packet, the packet ID followed by its data.
+    pub fn observe(&mut self, direction: Direction, mut packet: &[u8]) {
+        let Ok(VarInt(id)) = VarInt::decode(&mut packet) else {
+            return;
+        };
+
+        match (direction, self.state(direction), id) {
+            (Direction::Serverbound, ServerState::Handshake, 0x00) => {
+                if let Some(state) = next_state(packet) {
+                    self.serverbound = state;
+                    self.clientbound = state;
+                }
+            }
+            (Direction::Clientbound, ServerState::Login, ENCRYPTION_REQUEST) => {
+                self.encryption_requested = true;
+                self.clientbound_encrypted = true;
+            }
+            (Direction::Serverbound, ServerState::Login, ENCRYPTION_RESPONSE) => {
+                self.serverbound_encrypted = self.encryption_requested;
+            }
+            (Direction::Clientbound, ServerState::Login, SET_COMPRESSION) => {
+                // A negative threshold disables compression
+                self.compression_threshold = VarInt::decode(&mut packet)
+                    .ok()
+                    .and_then(|VarInt(threshold)| usize::try_from(threshold).ok());
+            }
+            (Direction::Clientbound, ServerState::Login, LOGIN_SUCCESS)
+            | (Direction::Clientbound, ServerState::Play, START_CONFIGURATION) => {
+                self.clientbound = ServerState::Configuration;
+            }
+            (Direction::Serverbound, ServerState::Login, LOGIN_ACKNOWLEDGED)
+            | (Direction::Serverbound, ServerState::Play, ACKNOWLEDGE_CONFIGURATION) => {
+                self.serverbound = ServerState::Configuration;
+            }
+            (
+                Direction::Clientbound,
+                ServerState::Configuration,
+                FINISH_CONFIGURATION,
+            ) => {
+                self.clientbound = ServerState::Play;
+            }
+            (
+                Direction::Serverbound,
+                ServerState::Configuration,
+                ACKNOWLEDGE_FINISH_CONFIGURATION,
+            ) => {
+                self.serverbound = ServerState::Play;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// State requested by a handshake, after its packet ID.
+fn next_state(mut handshake: &[u8]) -> Option<ServerState> {
+    VarInt::decode(&mut handshake).ok()?;
+    VarString::decode_str(&mut handshake, VarString::MAX_LENGTH).ok()?;
+    u16::decode(&mut handshake).ok()?;
+    match VarInt::decode(&mut handshake).ok()? {
+        VarInt(1) => Some(ServerState::Status),
+        // Transfers are logins too
+        VarInt(2 | 3) => Some(ServerState::Login),
+        VarInt(_) => None,
+    }
+}
+
+/// How the re-encoding of a frame by the codec differs from the frame, without
+/// its length prefix, `None` if it does not or if the frame is undecodable.
+///
+/// Compressed data is only compared by length, zlib streams of the same data
+/// depending on the compressor.
+pub fn reencoding_mismatch(
+    frame: &[u8],
+    compression_threshold: Option<usize>,
+) -> Option<String> {
+    let mut codec: Codec = Codec::new();
+    codec.set_compression(compression_threshold);
+    let packet: Cow<[u8]> = codec.decode(frame).ok()?;
+    let encoded: Vec<u8> = codec.encode(&packet).ok()?;
+    let mut reencoded: &[u8] = &encoded;
+    VarInt::decode(&mut reencoded).ok()?;
+
+    if compression_threshold.is_none() {
+        return (reencoded != frame).then(|| "frame differs".to_string());
+    }
+    let mut original: &[u8] = frame;
+    let VarInt(original_length) = VarInt::decode(&mut original).ok()?;
+    let VarInt(reencoded_length) = VarInt::decode(&mut reencoded).ok()?;
+    match (original_length, reencoded_length) {
+        (0, 0) => (reencoded != original).then(|| "frame differs".to_string()),
+        (0, _) => Some("uncompressed above the threshold".to_string()),
+        (_, 0) => Some("compressed below the threshold".to_string()),
+        (original, reencoded) => (original != reencoded)
+            .then(|| format!("data length {original} instead of {reencoded}")),
+    }
+}
+
+/// Read a frame without its length prefix, `None` once the connection closed.
+fn read_frame(stream: &mut impl Read) -> Result<Option<Vec<u8>>, io::Error> {
+    let length: usize = match VarInt::read(stream) {
+        Err(DataTypeDecodeError::IOError(e))
+            if e.kind() == io::ErrorKind::UnexpectedEof =>
+        {
+            return Ok(None)
+        }
+        Err(DataTypeDecodeError::IOError(e)) => return Err(e),
+        Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        Ok(VarInt(length)) => usize::try_from(length)
+            .ok()
+            .filter(|length| *length <= MAX_FRAME_LENGTH)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("frame of {length} bytes"),
+                )
+            })?,
+    };
+    let mut frame: Vec<u8> = vec![0; length];
+    stream.read_exact(&mut frame)?;
+    Ok(Some(frame))
+}
+
+/// What both directions of a proxied connection share.
+struct Relay<'a> {
+    /// Number of the connection, in the order they were accepted.
+    id: u64,
+
+    /// When the connection opened.
+    opened_at: Instant,
+
+    /// State of the connection.
+    session: &'a Mutex<Session>,
+
+    /// Capture of the connection, if recording.
+    capture: Option<&'a Capture>,
+}
+
+impl Relay<'_> {
+    /// Relay the frames sent in `direction` from `from` to `to`, printing them,
+    /// until either stream closes. Both streams are then shut down, ending the
+    /// other direction too.
+    fn run(&self, direction: Direction, from: TcpStream, mut to: TcpStream) {
+        let mut from: BufReader<TcpStream> = BufReader::new(from);
+        if let Err(e) = self.relay(direction, &mut from, &mut to) {
+            eprintln!("#{} {e}", self.id);
+        }
+        let _ = from.get_ref().shutdown(Shutdown::Both);
+        let _ = to.shutdown(Shutdown::Both);
+    }
+
+    /// Relay frames, then raw bytes once encrypted.
+    fn relay(
+        &self,
+        direction: Direction,
+        from: &mut BufReader<TcpStream>,
+        to: &mut TcpStream,
+    ) -> Result<(), io::Error> {
+        while let Some(frame) = read_frame(from)? {
+            let mut prefixed: Vec<u8> = Vec::with_capacity(frame.len() + 3);
+            // Frames are at most `MAX_FRAME_LENGTH` long
+            VarInt(i32::try_from(frame.len()).unwrap_or(i32::MAX))
+                .encode(&mut prefixed)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+            prefixed.extend_from_slice(&frame);
+
+            // Inspected before being relayed, not to race with the answer
+            let encrypted: bool = self.inspect(direction, frame);
+            to.write_all(&prefixed)?;
+            if encrypted {
+                println!(
+                    "#{} {direction:?} encrypted, relaying without decoding",
+                    self.id
+                );
+                io::copy(from, to)?;
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Print and record a frame, and follow the state changes it makes. Returns
+    /// whether the direction is encrypted from now on.
+    fn inspect(&self, direction: Direction, frame: Vec<u8>) -> bool {
+        let mut session = self.session.lock().unwrap_or_else(PoisonError::into_inner);
+        let threshold: Option<usize> = session.compression_threshold();
+        let record: Record = Record {
+            elapsed: self.opened_at.elapsed(),
+            direction,
+            state: session.state(direction),
+            compressed: threshold.is_some(),
+            frame,
+        };
+
+        let mut line: String = format!("#{} {}", self.id, describe(&record));
+        if let Some(mismatch) = reencoding_mismatch(&record.frame, threshold) {
+            line.push_str(" [RE-ENCODING DIFFERS: ");
+            line.push_str(&mismatch);
+            line.push(']');
+        }
+        println!("{line}");
+        if let Some(capture) = self.capture {
+            capture.record(direction, record.state, record.compressed, &record.frame);
+        }
+
+        let mut codec: Codec = Codec::new();
+        codec.set_compression(threshold);
+        if let Ok(packet) = codec.decode(&record.frame) {
+            session.observe(direction, &packet);
+        }
+        session.is_encrypted(direction)
+    }
+}
@@ -0,0 +1,159 @@
+//! Test [`crate::mitm`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{self, File},
+        io::{BufReader, Read, Write},
+        net::{TcpListener, TcpStream},
+        path::PathBuf,
+        process, thread,
+    };
+
+    use crate::{
+        capture::{read_header, Record},
+        mitm::{proxy, reencoding_mismatch, Session},
+        protocol::{Codec, Direction},
+        state::ServerState,
+    };
+
+    /// Handshake of a 1.21.2 client to `localhost:25565`, for the login.
+    const HANDSHAKE: [u8; 16] = [
+        0x00, 0x80, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+        0x63, 0xdd, 0x02,
+    ];
+
+    #[test]
+    fn test_session() {
+        let mut session: Session = Session::new();
+        session.observe(Direction::Serverbound, &HANDSHAKE);
+        assert_eq!(session.state(Direction::Serverbound), ServerState::Login);
+        assert_eq!(session.state(Direction::Clientbound), ServerState::Login);
+
+        session.observe(Direction::Clientbound, &[0x03, 0x80, 0x02]);
+        assert_eq!(session.compression_threshold(), Some(256));
+
+        // The clientbound direction changes state before the client acknowledges it
+        session.observe(Direction::Clientbound, &[0x02]);
+        assert_eq!(
+            session.state(Direction::Clientbound),
+            ServerState::Configuration
+        );
+        assert_eq!(session.state(Direction::Serverbound), ServerState::Login);
+        session.observe(Direction::Serverbound, &[0x03]);
+        assert_eq!(
+            session.state(Direction::Serverbound),
+            ServerState::Configuration
+        );
+
+        session.observe(Direction::Clientbound, &[0x03]);
+        session.observe(Direction::Serverbound, &[0x03]);
+        assert_eq!(session.state(Direction::Clientbound), ServerState::Play);
+        assert_eq!(session.state(Direction::Serverbound), ServerState::Play);
+
+        session.observe(Direction::Clientbound, &[0x70]);
+        session.observe(Direction::Serverbound, &[0x0E]);
+        assert_eq!(
+            session.state(Direction::Clientbound),
+            ServerState::Configuration
+        );
+        assert_eq!(
+            session.state(Direction::Serverbound),
+            ServerState::Configuration
+        );
+        assert!(!session.is_encrypted(Direction::Serverbound));
+
+        let mut session: Session = Session::new();
+        session.observe(Direction::Serverbound, &HANDSHAKE);
+        // Encryption Response is sent in clear, before the serverbound encryption
+        session.observe(Direction::Clientbound, &[0x01]);
+        assert!(session.is_encrypted(Direction::Clientbound));
+        assert!(!session.is_encrypted(Direction::Serverbound));
+        session.observe(Direction::Serverbound, &[0x01]);
+        assert!(session.is_encrypted(Direction::Serverbound));
+    }
+
+    #[test]
+    fn test_reencoding_mismatch() {
+        assert_eq!(reencoding_mismatch(&HANDSHAKE, None), None);
+        let mut codec: Codec = Codec::new();
+        codec.set_compression(Some(4));
+        for packet in [&[0x00, 0x01][..], &[0x00; 64]] {
+            let frame: Vec<u8> = codec.encode(packet).unwrap();
+            assert_eq!(reencoding_mismatch(frame.get(1..).unwrap(), Some(4)), None);
+        }
+
+        // Vanilla would have compressed the packet
+        let mut uncompressed: Vec<u8> = vec![0x00];
+        uncompressed.extend_from_slice(&[0x00; 64]);
+        assert_eq!(
+            reencoding_mismatch(&uncompressed, Some(4)).as_deref(),
+            Some("uncompressed above the threshold")
+        );
+        let compressed: Vec<u8> = codec.encode(&[0x00; 64]).unwrap();
+        assert_eq!(
+            reencoding_mismatch(compressed.get(1..).unwrap(), Some(128)).as_deref(),
+            Some("compressed below the threshold")
+        );
+        assert_eq!(reencoding_mismatch(&[0x80], Some(4)), None);
+    }
+
+    #[test]
+    fn test_proxy() {
+        let directory: PathBuf =
+            std::env::temp_dir().join(format!("atmospheremc-mitm-{}", process::id()));
+        let upstream: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let upstream_address: String = upstream.local_addr().unwrap().to_string();
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client: TcpStream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (proxied, _) = listener.accept().unwrap();
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                proxy(1, proxied, &upstream_address, Some(&directory)).unwrap();
+            });
+
+            let mut frame: Vec<u8> = vec![16];
+            frame.extend_from_slice(&HANDSHAKE);
+            client.write_all(&frame).unwrap();
+            let (mut server, _) = upstream.accept().unwrap();
+            let mut received: [u8; 17] = [0; 17];
+            server.read_exact(&mut received).unwrap();
+            assert_eq!(received.as_slice(), frame.as_slice());
+
+            // Login Success, then the connection closes
+            server.write_all(&[0x01, 0x02]).unwrap();
+            let mut received: [u8; 2] = [0; 2];
+            client.read_exact(&mut received).unwrap();
+            assert_eq!(received, [0x01, 0x02]);
+            drop(server);
+            assert_eq!(client.read(&mut [0]).unwrap(), 0);
+        });
+
+        let path: PathBuf = fs::read_dir(&directory)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let mut reader: BufReader<File> = BufReader::new(File::open(&path).unwrap());
+        read_header(&mut reader).unwrap();
+        let handshake: Record = Record::read(&mut reader).unwrap().unwrap();
+        assert_eq!(
+            (handshake.direction, handshake.state, handshake.frame),
+            (
+                Direction::Serverbound,
+                ServerState::Handshake,
+                HANDSHAKE.to_vec()
+            )
+        );
+        let success: Record = Record::read(&mut reader).unwrap().unwrap();
+        assert_eq!(
+            (success.direction, success.state, success.frame),
+            (Direction::Clientbound, ServerState::Login, vec![0x02])
+        );
+        assert_eq!(Record::read(&mut reader).unwrap(), None);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
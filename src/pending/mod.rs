@@ -0,0 +1,93 @@
+//! Queries awaiting an answer from the client: login plugin requests, correlated by
+//! message ID, and cookie requests, correlated by key.
+use std::time::{Duration, Instant};
+
+mod test;
+
+/// What the answer to a query is correlated by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryKey {
+    /// Message ID of a Login Plugin Request.
+    LoginPlugin(i32),
+
+    /// Key of a Cookie Request.
+    Cookie(String),
+}
+
+/// Outcome of a query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Response {
+    /// The client answered. `None` if it does not understand the channel, or has no
+    /// cookie with this key.
+    Answered(Option<Vec<u8>>),
+
+    /// The client did not answer in time.
+    TimedOut,
+}
+
+/// A pending query.
+struct Entry<C> {
+    /// What the answer is correlated by.
+    key: QueryKey,
+
+    /// When the query times out.
+    deadline: Instant,
+
+    /// Called with the answer.
+    callback: C,
+}
+
+/// Queries awaiting an answer, each with a callback `C`.
+pub struct PendingQueries<C> {
+    /// Pending queries, oldest first.
+    entries: Vec<Entry<C>>,
+}
+
+impl<C> PendingQueries<C> {
+    /// No pending queries.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add a query, timing out after `timeout`.
+    pub fn insert(&mut self, key: QueryKey, timeout: Duration, callback: C) {
+        self.insert_at(key, Instant::now() + timeout, callback);
+    }
+
+    /// Add a query, timing out at `deadline`.
+    fn insert_at(&mut self, key: QueryKey, deadline: Instant, callback: C) {
+        self.entries.push(Entry {
+            key,
+            deadline,
+            callback,
+        });
+    }
+
+    /// Take the callback of the oldest query answered by `key`.
+    pub fn take(&mut self, key: &QueryKey) -> Option<C> {
+        let index: usize = self.entries.iter().position(|entry| entry.key == *key)?;
+        Some(self.entries.remove(index).callback)
+    }
+
+    /// When the next query times out.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.entries.iter().map(|entry| entry.deadline).min()
+    }
+
+    /// Take the callbacks of the queries that timed out.
+    pub fn take_expired(&mut self) -> Vec<C> {
+        self.take_expired_at(Instant::now())
+    }
+
+    /// Take the callbacks of the queries timed out at `now`.
+    fn take_expired_at(&mut self, now: Instant) -> Vec<C> {
+        let (expired, pending): (Vec<Entry<C>>, Vec<Entry<C>>) = self
+            .entries
+            .drain(..)
+            .partition(|entry| entry.deadline <= now);
+        self.entries = pending;
+        expired.into_iter().map(|entry| entry.callback).collect()
+    }
+}
@@ -0,0 +1,48 @@
+//! Test [`crate::pending`].
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::pending::{PendingQueries, QueryKey};
+
+    #[test]
+    fn test_pending_queries() {
+        let now: Instant = Instant::now();
+        let mut queries: PendingQueries<u32> = PendingQueries::new();
+        assert_eq!(queries.next_deadline(), None);
+
+        queries.insert_at(QueryKey::LoginPlugin(0), now + Duration::from_secs(5), 1);
+        queries.insert_at(
+            QueryKey::Cookie("lobby:session".to_string()),
+            now + Duration::from_secs(2),
+            2,
+        );
+        queries.insert_at(
+            QueryKey::Cookie("lobby:session".to_string()),
+            now + Duration::from_secs(3),
+            3,
+        );
+        queries.insert_at(QueryKey::LoginPlugin(1), now + Duration::from_secs(1), 4);
+        assert_eq!(queries.next_deadline(), Some(now + Duration::from_secs(1)));
+
+        // Answers are correlated by key, oldest query first
+        assert_eq!(queries.take(&QueryKey::LoginPlugin(0)), Some(1));
+        assert_eq!(queries.take(&QueryKey::LoginPlugin(0)), None);
+        assert_eq!(
+            queries.take(&QueryKey::Cookie("lobby:session".to_string())),
+            Some(2)
+        );
+
+        assert_eq!(queries.take_expired_at(now), Vec::<u32>::new());
+        assert_eq!(
+            queries.take_expired_at(now + Duration::from_secs(1)),
+            vec![4]
+        );
+        assert_eq!(queries.next_deadline(), Some(now + Duration::from_secs(3)));
+        assert_eq!(
+            queries.take_expired_at(now + Duration::from_secs(10)),
+            vec![3]
+        );
+        assert_eq!(queries.next_deadline(), None);
+    }
+}
@@ -0,0 +1,342 @@
+//! Connected players.
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    connection::ConnectionHandle,
+    profile::PlayerProfile,
+    protocol::play::CKeepAlive,
+    state::ServerState,
+    text::Text,
+    types::{uuid::Uuid, DataTypeEncodeError},
+};
+
+mod test;
+
+/// Interval between two keep alives.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Players not answering a keep alive within this delay are kicked.
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Position and rotation of a player.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+    /// X coordinate.
+    pub x: f64,
+
+    /// Y coordinate (feet).
+    pub y: f64,
+
+    /// Z coordinate.
+    pub z: f64,
+
+    /// Yaw in degrees.
+    pub yaw: f32,
+
+    /// Pitch in degrees.
+    pub pitch: f32,
+}
+
+/// Keep alive state of a player.
+struct KeepAlive {
+    /// ID and send time of the keep alive waiting for an answer.
+    pending: Option<(i64, Instant)>,
+
+    /// When the last keep alive was sent.
+    last_sent: Instant,
+
+    /// Round trip time of the last answered keep alive.
+    latency: Duration,
+}
+
+/// What to do after polling the [`KeepAlive`] state of a player.
+enum KeepAliveAction {
+    /// Nothing to do.
+    None,
+
+    /// Send a keep alive with this ID.
+    Send(i64),
+
+    /// The client did not answer in time.
+    TimedOut,
+}
+
+impl KeepAlive {
+    /// Check whether a keep alive must be sent or the client timed out.
+    fn poll(&mut self, now: Instant) -> KeepAliveAction {
+        match self.pending {
+            Some((_, sent)) if now.duration_since(sent) > KEEP_ALIVE_TIMEOUT => {
+                // Do not kick again while the connection closes
+                self.pending = None;
+                self.last_sent = now;
+                KeepAliveAction::TimedOut
+            }
+            None if now.duration_since(self.last_sent) >= KEEP_ALIVE_INTERVAL => {
+                // Vanilla uses the current time in milliseconds as the ID
+                let id: i64 = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| i64::try_from(d.as_millis()).unwrap_or(i64::MAX));
+                self.pending = Some((id, now));
+                self.last_sent = now;
+                KeepAliveAction::Send(id)
+            }
+            _ => KeepAliveAction::None,
+        }
+    }
+}
+
+/// A logged in player.
+pub struct Player {
+    /// Profile of the player.
+    profile: PlayerProfile,
+
+    /// Entity ID of the player.
+    entity_id: i32,
+
+    /// Connection of the player.
+    connection: Arc<ConnectionHandle>,
+
+    /// Last position reported by the client.
+    position: Mutex<Position>,
+
+    /// Keep alive state.
+    keep_alive: Mutex<KeepAlive>,
+}
+
+impl Player {
+    /// Create a player for a logged in connection.
+    pub fn new(
+        profile: PlayerProfile,
+        entity_id: i32,
+        connection: Arc<ConnectionHandle>,
+        position: Position,
+    ) -> Self {
+        Self {
+            profile,
+            entity_id,
+            connection,
+            position: Mutex::new(position),
+            keep_alive: Mutex::new(KeepAlive {
+                pending: None,
+                last_sent: Instant::now(),
+                latency: Duration::ZERO,
+            }),
+        }
+    }
+
+    /// Profile of the player.
+    pub const fn profile(&self) -> &PlayerProfile {
+        &self.profile
+    }
+
+    /// Username of the player.
+    pub fn name(&self) -> &str {
+        &self.profile.name
+    }
+
+    /// UUID of the player.
+    pub const fn uuid(&self) -> Uuid {
+        self.profile.uuid
+    }
+
+    /// Entity ID of the player.
+    pub const fn entity_id(&self) -> i32 {
+        self.entity_id
+    }
+
+    /// Connection of the player.
+    pub const fn connection(&self) -> &Arc<ConnectionHandle> {
+        &self.connection
+    }
+
+    /// Last position reported by the client.
+    pub fn position(&self) -> Position {
+        *self.position.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Update the position of the player.
+    pub fn update_position(&self, update: impl FnOnce(&mut Position)) {
+        update(&mut self.position.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+
+    /// Round trip time of the last answered keep alive.
+    #[allow(dead_code)] // No tab list yet
+    pub fn latency(&self) -> Duration {
+        self.keep_alive
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .latency
+    }
+
+    /// Update the player, once per tick: send keep alives and kick the player if it
+    /// stopped answering.
+    pub fn tick(&self) {
+        let action: KeepAliveAction = self
+            .keep_alive
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .poll(Instant::now());
+
+        match action {
+            KeepAliveAction::None => {}
+            KeepAliveAction::Send(id) => {
+                if let Err(e) = self.connection.send(&CKeepAlive::new(id)) {
+                    self.connection
+                        .in_context(|| log::error!("Failed to send keep alive: {e}"));
+                }
+            }
+            KeepAliveAction::TimedOut => {
+                self.disconnect(&Text::translate("disconnect.timeout", vec![]));
+            }
+        }
+    }
+
+    /// Handle a keep alive answer, returning the round trip time if it matches the
+    /// pending keep alive.
+    pub fn keep_alive_received(&self, id: i64) -> Option<Duration> {
+        let mut keep_alive = self
+            .keep_alive
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        match keep_alive.pending {
+            Some((pending, sent)) if pending == id => {
+                keep_alive.pending = None;
+                keep_alive.latency = sent.elapsed();
+                Some(keep_alive.latency)
+            }
+            _ => None,
+        }
+    }
+
+    /// Disconnect the player.
+    pub fn disconnect(&self, reason: &Text) {
+        self.connection.disconnect(reason);
+    }
+
+    /// Send the player to another server, storing `cookies` (key and payload) on
+    /// the client first.
+    #[allow(dead_code)] // API for lobbies, no transfer command yet
+    pub fn transfer(
+        &self,
+        host: &str,
+        port: u16,
+        cookies: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), DataTypeEncodeError> {
+        self.connection.transfer(host, port, cookies)
+    }
+}
+
+/// Players indexed by UUID and name.
+#[derive(Default)]
+struct Players {
+    /// Players by UUID.
+    by_uuid: HashMap<Uuid, Arc<Player>>,
+
+    /// UUIDs by lowercase name.
+    by_name: HashMap<String, Uuid>,
+}
+
+/// The connected players, shared by all connections.
+#[derive(Default)]
+pub struct PlayerRegistry {
+    /// The players.
+    players: RwLock<Players>,
+}
+
+impl PlayerRegistry {
+    /// Register a player, replacing the sessions with the same UUID or name, which
+    /// are returned.
+    pub fn insert(&self, player: Arc<Player>) -> Vec<Arc<Player>> {
+        let mut players = self.players.write().unwrap_or_else(PoisonError::into_inner);
+        let mut replaced: Vec<Arc<Player>> = Vec::new();
+
+        if let Some(uuid) = players.by_name.get(&player.name().to_lowercase()).copied() {
+            if let Some(old) = players.by_uuid.remove(&uuid) {
+                replaced.push(old);
+            }
+        }
+        if let Some(old) = players.by_uuid.remove(&player.uuid()) {
+            replaced.push(old);
+        }
+        for old in &replaced {
+            players.by_name.remove(&old.name().to_lowercase());
+        }
+
+        players
+            .by_name
+            .insert(player.name().to_lowercase(), player.uuid());
+        players.by_uuid.insert(player.uuid(), player);
+
+        replaced
+    }
+
+    /// Unregister a player, if this session is still the registered one.
+    ///
+    /// Returns whether the player was removed.
+    pub fn remove(&self, player: &Player) -> bool {
+        let mut players = self.players.write().unwrap_or_else(PoisonError::into_inner);
+
+        let registered: bool = players
+            .by_uuid
+            .get(&player.uuid())
+            .is_some_and(|p| p.connection().id() == player.connection().id());
+        if registered {
+            players.by_uuid.remove(&player.uuid());
+            players.by_name.remove(&player.name().to_lowercase());
+        }
+
+        registered
+    }
+
+    /// Find a player by UUID.
+    pub fn get(&self, uuid: Uuid) -> Option<Arc<Player>> {
+        let players = self.players.read().unwrap_or_else(PoisonError::into_inner);
+        players.by_uuid.get(&uuid).cloned()
+    }
+
+    /// Find a player by name, ignoring case.
+    pub fn by_name(&self, name: &str) -> Option<Arc<Player>> {
+        let players = self.players.read().unwrap_or_else(PoisonError::into_inner);
+        players
+            .by_name
+            .get(&name.to_lowercase())
+            .and_then(|uuid| players.by_uuid.get(uuid))
+            .cloned()
+    }
+
+    /// Number of connected players.
+    pub fn len(&self) -> usize {
+        self.players
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .by_uuid
+            .len()
+    }
+
+    /// Number of players in game, not counting those still in configuration.
+    pub fn in_game(&self) -> usize {
+        self.players
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .by_uuid
+            .values()
+            .filter(|player| player.connection().state() == ServerState::Play)
+            .count()
+    }
+
+    /// Snapshot of the connected players.
+    pub fn all(&self) -> Vec<Arc<Player>> {
+        self.players
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .by_uuid
+            .values()
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,179 @@
+//! Test [`crate::player`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        net::{SocketAddr, TcpListener, TcpStream},
+        path::PathBuf,
+        process,
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        access::AccessLists,
+        config::Config,
+        connection::Connection,
+        limiter::ConnectionPermit,
+        player::{KeepAlive, KeepAliveAction, Player, PlayerRegistry, Position},
+        profile::PlayerProfile,
+        server::Server,
+    };
+
+    /// Create a server with its access lists in a temporary directory.
+    fn new_server(name: &str) -> Arc<Server> {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-player-{name}-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        Arc::new(Server::new(
+            Config::default(),
+            AccessLists::load(&directory, false),
+        ))
+    }
+
+    /// A connection to `server`, with the client socket kept open.
+    fn connect(server: &Arc<Server>) -> (TcpStream, Connection) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client: TcpStream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, address): (TcpStream, SocketAddr) = listener.accept().unwrap();
+        let permit: ConnectionPermit =
+            server.limiter().try_acquire(address.ip()).unwrap();
+        let connection: Connection =
+            Connection::new(stream, address, Arc::clone(server), permit).unwrap();
+        (client, connection)
+    }
+
+    /// A player with `profile` on `connection`.
+    fn player(
+        server: &Server,
+        profile: PlayerProfile,
+        connection: &Connection,
+    ) -> Arc<Player> {
+        Arc::new(Player::new(
+            profile,
+            server.allocate_entity_id(),
+            Arc::clone(connection.handle()),
+            Position::default(),
+        ))
+    }
+
+    #[test]
+    fn test_duplicate_login() {
+        let server: Arc<Server> = new_server("duplicate");
+        let alice: PlayerProfile = PlayerProfile::offline("Alice".to_string());
+        let (_first_client, first_connection) = connect(&server);
+        let (_second_client, second_connection) = connect(&server);
+        let first: Arc<Player> = player(&server, alice.clone(), &first_connection);
+        let second: Arc<Player> = player(&server, alice.clone(), &second_connection);
+
+        // The older session is kicked, the new one replaces it
+        server.join(&first);
+        server.join(&second);
+        assert!(first.connection().is_closing());
+        assert!(!second.connection().is_closing());
+        assert_eq!(server.players().len(), 1);
+        // Not in game until its connection reaches the play state
+        assert_eq!(server.players().in_game(), 0);
+        let registered: Arc<Player> = server.players().get(alice.uuid).unwrap();
+        assert_eq!(registered.connection().id(), second.connection().id());
+
+        // The same name with another UUID is a duplicate too
+        let renamed: PlayerProfile = PlayerProfile {
+            name: "ALICE".to_string(),
+            ..PlayerProfile::offline("Bob".to_string())
+        };
+        let (_third_client, third_connection) = connect(&server);
+        let third: Arc<Player> = player(&server, renamed, &third_connection);
+        server.join(&third);
+        assert!(second.connection().is_closing());
+        assert!(server.players().get(alice.uuid).is_none());
+        assert_eq!(server.players().len(), 1);
+
+        // The kicked sessions leaving do not remove the new one
+        server.leave(&first);
+        server.leave(&second);
+        assert_eq!(server.players().len(), 1);
+    }
+
+    #[test]
+    fn test_remove() {
+        let server: Arc<Server> = new_server("remove");
+        let registry: PlayerRegistry = PlayerRegistry::default();
+        let (_alice_client, alice_connection) = connect(&server);
+        let (_bob_client, bob_connection) = connect(&server);
+        let alice: Arc<Player> = player(
+            &server,
+            PlayerProfile::offline("Alice".to_string()),
+            &alice_connection,
+        );
+        let bob: Arc<Player> = player(
+            &server,
+            PlayerProfile::offline("Bob".to_string()),
+            &bob_connection,
+        );
+        assert!(registry.insert(Arc::clone(&alice)).is_empty());
+        assert!(registry.insert(Arc::clone(&bob)).is_empty());
+
+        // Found by UUID and by name, ignoring case
+        assert_eq!(registry.get(alice.uuid()).unwrap().name(), "Alice");
+        assert_eq!(registry.by_name("aLiCe").unwrap().uuid(), alice.uuid());
+        assert_eq!(registry.len(), 2);
+
+        // Removed from both indexes
+        assert!(registry.remove(&alice));
+        assert!(!registry.remove(&alice));
+        assert!(registry.get(alice.uuid()).is_none());
+        assert!(registry.by_name("Alice").is_none());
+        assert_eq!(registry.by_name("bob").unwrap().uuid(), bob.uuid());
+        assert_eq!(registry.len(), 1);
+        assert!(registry.remove(&bob));
+        assert!(registry.all().is_empty());
+    }
+
+    #[test]
+    fn test_keep_alive() {
+        let start: Instant = Instant::now();
+        let mut keep_alive: KeepAlive = KeepAlive {
+            pending: None,
+            last_sent: start,
+            latency: Duration::ZERO,
+        };
+        let at = |seconds: u64| start + Duration::from_secs(seconds);
+
+        assert!(matches!(keep_alive.poll(at(1)), KeepAliveAction::None));
+        assert!(matches!(keep_alive.poll(at(15)), KeepAliveAction::Send(_)));
+        // Not sent again while waiting for the answer
+        assert!(matches!(keep_alive.poll(at(40)), KeepAliveAction::None));
+        assert!(matches!(keep_alive.poll(at(45)), KeepAliveAction::None));
+        assert!(matches!(keep_alive.poll(at(46)), KeepAliveAction::TimedOut));
+        // Kicked only once
+        assert!(matches!(keep_alive.poll(at(47)), KeepAliveAction::None));
+    }
+
+    #[test]
+    fn test_keep_alive_received() {
+        let server: Arc<Server> = new_server("keep-alive");
+        let (_client, connection) = connect(&server);
+        let alice: Arc<Player> = player(
+            &server,
+            PlayerProfile::offline("Alice".to_string()),
+            &connection,
+        );
+        assert!(alice.keep_alive_received(1).is_none());
+
+        let start: Instant = Instant::now().checked_sub(Duration::from_secs(40)).unwrap();
+        alice.keep_alive.lock().unwrap().last_sent = start;
+        alice.tick();
+        let (id, _) = alice.keep_alive.lock().unwrap().pending.unwrap();
+        assert!(alice.keep_alive_received(id + 1).is_none());
+        assert_eq!(alice.keep_alive_received(id), Some(alice.latency()));
+        assert!(alice.keep_alive_received(id).is_none());
+
+        // A player not answering in time is kicked
+        alice.keep_alive.lock().unwrap().pending = Some((id, start));
+        assert!(!alice.connection().is_closing());
+        alice.tick();
+        assert!(alice.connection().is_closing());
+    }
+}
@@ -0,0 +1,70 @@
+//! Player profiles.
+use std::io::Write;
+
+use crate::types::{
+    uuid::Uuid,
+    var::{VarInt, VarString},
+    DataType, DataTypeEncodeError,
+};
+
+/// A property of a [`PlayerProfile`], such as `textures`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileProperty {
+    /// Name of the property.
+    pub name: String,
+
+    /// Value of the property, usually base64 encoded JSON.
+    pub value: String,
+
+    /// Signature of the value by Mojang, if any.
+    pub signature: Option<String>,
+}
+
+/// The identity of a player, sent in Login Success and the player list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlayerProfile {
+    /// UUID of the player.
+    pub uuid: Uuid,
+
+    /// Username of the player.
+    pub name: String,
+
+    /// Properties of the profile (skin, cape...).
+    pub properties: Vec<ProfileProperty>,
+}
+
+impl PlayerProfile {
+    /// Create the profile of a player in offline mode, without properties.
+    pub fn offline(name: String) -> Self {
+        Self {
+            uuid: Uuid::offline(&name),
+            name,
+            properties: Vec::new(),
+        }
+    }
+
+    /// Encode the UUID, the name and the properties, as in Login Success.
+    pub fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.uuid.encode(to)?;
+        VarString::new(self.name.clone()).encode(to)?;
+        self.encode_properties(to)
+    }
+
+    /// Encode the properties as a prefixed array.
+    pub fn encode_properties(
+        &self,
+        to: &mut impl Write,
+    ) -> Result<(), DataTypeEncodeError> {
+        VarInt(i32::try_from(self.properties.len())?).encode(to)?;
+        for property in &self.properties {
+            VarString::new(property.name.clone()).encode(to)?;
+            VarString::new(property.value.clone()).encode(to)?;
+            property.signature.is_some().encode(to)?;
+            if let Some(signature) = &property.signature {
+                VarString::new(signature.clone()).encode(to)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+//! Packet framing: length prefix, compression and encryption.
+//!
+//! Encryption applies to the whole stream, length prefixes included: outbound
+//! frames are encrypted by [`Codec::encrypt`] once encoded, inbound bytes are
+//! decrypted by a [`CipherReader`] before they are framed.
+use std::{
+    borrow::Cow,
+    io::{self, Cursor, Read, Write},
+};
+
+use aes::{
+    cipher::{BlockCipherEncrypt, KeyInit},
+    Aes128, Block,
+};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+use crate::types::{var::VarInt, DataType, DataTypeDecodeError, DataTypeEncodeError};
+
+/// Maximum uncompressed size of a packet accepted by vanilla (2^23 bytes).
+const MAX_DATA_LENGTH: usize = 1 << 23;
+
+/// Size of the shared secret negotiated in the login, the AES key and IV.
+pub const SHARED_SECRET_SIZE: usize = 16;
+
+/// AES-128 in CFB8 mode, as used by vanilla with the shared secret as both the key
+/// and the IV.
+#[derive(Clone, Debug)]
+pub struct Cipher {
+    /// The block cipher.
+    aes: Aes128,
+
+    /// The last 16 bytes of ciphertext, the IV at first.
+    register: [u8; SHARED_SECRET_SIZE],
+}
+
+impl Cipher {
+    /// Create a cipher for one direction of a connection.
+    pub fn new(shared_secret: &[u8; SHARED_SECRET_SIZE]) -> Self {
+        Self::with_iv(shared_secret, shared_secret)
+    }
+
+    /// Create a cipher with an IV other than the key.
+    pub(super) fn with_iv(
+        key: &[u8; SHARED_SECRET_SIZE],
+        iv: &[u8; SHARED_SECRET_SIZE],
+    ) -> Self {
+        Self {
+            aes: Aes128::new(&(*key).into()),
+            register: *iv,
+        }
+    }
+
+    /// Encrypt `bytes` in place.
+    pub fn encrypt(&mut self, bytes: &mut [u8]) {
+        for byte in bytes {
+            *byte ^= self.key_byte();
+            self.shift(*byte);
+        }
+    }
+
+    /// Decrypt `bytes` in place.
+    pub fn decrypt(&mut self, bytes: &mut [u8]) {
+        for byte in bytes {
+            let encrypted: u8 = *byte;
+            *byte ^= self.key_byte();
+            self.shift(encrypted);
+        }
+    }
+
+    /// First byte of the encrypted register, combined with the next byte.
+    fn key_byte(&self) -> u8 {
+        let mut block: Block = self.register.into();
+        self.aes.encrypt_block(&mut block);
+        let [key, ..]: [u8; SHARED_SECRET_SIZE] = block.into();
+        key
+    }
+
+    /// Shift an encrypted byte into the register.
+    fn shift(&mut self, encrypted: u8) {
+        self.register.copy_within(1.., 0);
+        self.register[SHARED_SECRET_SIZE - 1] = encrypted;
+    }
+}
+
+/// Reader decrypting the bytes of `R` once encryption is enabled.
+#[derive(Debug)]
+pub struct CipherReader<R> {
+    /// The encrypted stream.
+    inner: R,
+
+    /// The cipher, if encryption is enabled.
+    cipher: Option<Cipher>,
+
+    /// Bytes read ahead before encryption was enabled, decrypted.
+    read_ahead: Cursor<Vec<u8>>,
+}
+
+impl<R: Read> CipherReader<R> {
+    /// Read `inner`, without encryption.
+    pub const fn new(inner: R) -> Self {
+        Self {
+            inner,
+            cipher: None,
+            read_ahead: Cursor::new(Vec::new()),
+        }
+    }
+
+    /// The encrypted stream.
+    pub const fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Decrypt the following bytes with `cipher`, starting with `read_ahead`: the
+    /// bytes already read from `inner` but not consumed yet. Encryption is enabled
+    /// once per connection.
+    pub fn set_encryption(&mut self, mut cipher: Cipher, mut read_ahead: Vec<u8>) {
+        cipher.decrypt(&mut read_ahead);
+        self.read_ahead = Cursor::new(read_ahead);
+        self.cipher = Some(cipher);
+    }
+}
+
+impl<R: Read> Read for CipherReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read: usize = self.read_ahead.read(buf)?;
+        if read > 0 {
+            return Ok(read);
+        }
+
+        let read: usize = self.inner.read(buf)?;
+        if let (Some(cipher), Some(bytes)) = (&mut self.cipher, buf.get_mut(..read)) {
+            cipher.decrypt(bytes);
+        }
+        Ok(read)
+    }
+}
+
+/// Codec state of one direction of a connection.
+///
+/// Packets handed to the codec are the packet ID followed by the packet data; frames
+/// are what is sent over the wire.
+#[derive(Clone, Debug, Default)]
+pub struct Codec {
+    /// Packets of at least this size are compressed, if set.
+    compression_threshold: Option<usize>,
+
+    /// The cipher of outbound frames, if encryption is enabled.
+    cipher: Option<Cipher>,
+}
+
+impl Codec {
+    /// Create a codec with compression and encryption disabled.
+    pub const fn new() -> Self {
+        Self {
+            compression_threshold: None,
+            cipher: None,
+        }
+    }
+
+    /// Enable or disable compression.
+    pub const fn set_compression(&mut self, threshold: Option<usize>) {
+        self.compression_threshold = threshold;
+    }
+
+    /// Whether compression is enabled.
+    pub const fn is_compressed(&self) -> bool {
+        self.compression_threshold.is_some()
+    }
+
+    /// Encrypt the frames passed to [`Codec::encrypt`] from now on.
+    pub fn set_encryption(&mut self, shared_secret: &[u8; SHARED_SECRET_SIZE]) {
+        self.cipher = Some(Cipher::new(shared_secret));
+    }
+
+    /// Encrypt encoded frames in place, in the order they are sent, if encryption
+    /// is enabled.
+    pub fn encrypt(&mut self, frames: &mut [u8]) {
+        if let Some(cipher) = &mut self.cipher {
+            cipher.encrypt(frames);
+        }
+    }
+
+    /// Decode the content of a frame (after the length prefix) into a packet.
+    ///
+    /// The packet borrows from the frame unless it is compressed.
+    pub fn decode<'a>(
+        &self,
+        mut frame: &'a [u8],
+    ) -> Result<Cow<'a, [u8]>, DataTypeDecodeError> {
+        if self.compression_threshold.is_none() {
+            return Ok(Cow::Borrowed(frame));
+        }
+
+        let data_length: usize = usize::try_from(VarInt::decode(&mut frame)?.0)?;
+        if data_length == 0 {
+            return Ok(Cow::Borrowed(frame));
+        }
+        if data_length > MAX_DATA_LENGTH {
+            return Err(DataTypeDecodeError::InvalidDataLength(data_length));
+        }
+
+        let mut packet: Vec<u8> = Vec::with_capacity(data_length);
+        ZlibDecoder::new(frame)
+            .take(data_length as u64 + 1)
+            .read_to_end(&mut packet)?;
+        if packet.len() != data_length {
+            return Err(DataTypeDecodeError::InvalidDataLength(packet.len()));
+        }
+
+        Ok(Cow::Owned(packet))
+    }
+
+    /// Encode a packet into a frame, with its length prefix.
+    pub fn encode(&self, packet: &[u8]) -> Result<Vec<u8>, DataTypeEncodeError> {
+        let mut body: Vec<u8> = Vec::new();
+        match self.compression_threshold {
+            None => body.extend_from_slice(packet),
+            Some(threshold) if packet.len() < threshold => {
+                VarInt(0).encode(&mut body)?;
+                body.extend_from_slice(packet);
+            }
+            Some(_) => {
+                VarInt(i32::try_from(packet.len())?).encode(&mut body)?;
+                let mut encoder = ZlibEncoder::new(body, Compression::default());
+                encoder.write_all(packet)?;
+                body = encoder.finish()?;
+            }
+        }
+
+        let length: VarInt = VarInt(i32::try_from(body.len())?);
+        let mut frame: Vec<u8> = Vec::with_capacity(length.size_hint() + body.len());
+        length.encode(&mut frame)?;
+        frame.append(&mut body);
+
+        Ok(frame)
+    }
+}
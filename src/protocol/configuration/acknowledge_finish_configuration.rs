@@ -0,0 +1,45 @@
+//! Acknowledge Finish Configuration packet (`0x03`).
+use std::{io::Write, sync::Arc};
+
+use crate::{
+    connection::Connection,
+    protocol::{
+        packet::{PacketParseError, ServerboundPacket},
+        play,
+    },
+    state::ServerState,
+    types::DataTypeEncodeError,
+};
+
+/// Acknowledge Finish Configuration, switching to the play state.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SAcknowledgeFinishConfiguration {}
+
+impl ServerboundPacket<'_> for SAcknowledgeFinishConfiguration {
+    const PACKET_ID: i32 = 0x03;
+
+    fn parse(_bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {})
+    }
+
+    fn encode(&self, _to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        Ok(())
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        let Some(player) = connection.player().map(Arc::clone) else {
+            log::warn!("Configuration finished without a player");
+            return Ok(ServerState::Closed);
+        };
+
+        play::join(connection, &player)?;
+
+        Ok(ServerState::Play)
+    }
+}
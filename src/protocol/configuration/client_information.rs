@@ -0,0 +1,56 @@
+//! Client Information (configuration) packet (`0x00`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{var::VarString, DataType, DataTypeEncodeError},
+};
+
+/// Maximum length of a locale.
+const MAX_LOCALE_LENGTH: usize = 16;
+
+/// Client Information, the settings of the client.
+///
+/// Only the fields the server uses are parsed.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SClientInformation<'a> {
+    /// Locale of the client, e.g. `en_us`.
+    locale: &'a str,
+
+    /// Client-side render distance, in chunks.
+    view_distance: u8,
+}
+
+impl<'a> ServerboundPacket<'a> for SClientInformation<'a> {
+    const PACKET_ID: i32 = 0x00;
+
+    fn parse(mut bytes: &'a [u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            locale: VarString::decode_str(&mut bytes, MAX_LOCALE_LENGTH)?,
+            view_distance: u8::decode(&mut bytes)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarString::encode_str(self.locale, to)?;
+        self.view_distance.encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        log::debug!(
+            "Client locale {0}, view distance {1}",
+            self.locale,
+            self.view_distance
+        );
+
+        Ok(connection.state())
+    }
+}
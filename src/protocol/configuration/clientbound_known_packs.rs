@@ -0,0 +1,64 @@
+//! Clientbound Known Packs packet (`0x0E`).
+use std::{borrow::Cow, io::Write};
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{
+        var::{VarInt, VarString},
+        DataType, DataTypeEncodeError,
+    },
+};
+
+/// A data pack, identified by namespace, ID and version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KnownPack<'a> {
+    /// Namespace of the pack.
+    pub namespace: Cow<'a, str>,
+
+    /// ID of the pack.
+    pub id: Cow<'a, str>,
+
+    /// Version of the pack.
+    pub version: Cow<'a, str>,
+}
+
+impl KnownPack<'_> {
+    /// The vanilla data pack the registry entries are taken from.
+    pub const fn core() -> Self {
+        Self {
+            namespace: Cow::Borrowed("minecraft"),
+            id: Cow::Borrowed("core"),
+            version: Cow::Borrowed("1.21.2"),
+        }
+    }
+}
+
+/// Clientbound Known Packs, the data packs the server uses.
+///
+/// Registry entries of packs known by both sides are sent without their data.
+pub struct CClientboundKnownPacks<'a> {
+    /// The packs.
+    packs: Vec<KnownPack<'a>>,
+}
+
+impl<'a> CClientboundKnownPacks<'a> {
+    /// Create a Clientbound Known Packs packet.
+    pub const fn new(packs: Vec<KnownPack<'a>>) -> Self {
+        Self { packs }
+    }
+}
+
+impl ClientboundPacket for CClientboundKnownPacks<'_> {
+    const PACKET_ID: i32 = 0x0E;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarInt(i32::try_from(self.packs.len())?).encode(to)?;
+        for pack in &self.packs {
+            VarString::encode_str(&pack.namespace, to)?;
+            VarString::encode_str(&pack.id, to)?;
+            VarString::encode_str(&pack.version, to)?;
+        }
+
+        Ok(())
+    }
+}
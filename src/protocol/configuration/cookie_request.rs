@@ -0,0 +1,30 @@
+//! Cookie Request (configuration) packet (`0x00`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{var::VarString, DataType, DataTypeEncodeError},
+};
+
+/// Cookie Request, asking the client for a cookie, answered by a Cookie Response.
+pub struct CConfigurationCookieRequest {
+    /// Key of the cookie.
+    key: VarString,
+}
+
+impl CConfigurationCookieRequest {
+    /// Request the cookie with a key.
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: VarString::new(key.to_string()),
+        }
+    }
+}
+
+impl ClientboundPacket for CConfigurationCookieRequest {
+    const PACKET_ID: i32 = 0x00;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.key.encode(to)
+    }
+}
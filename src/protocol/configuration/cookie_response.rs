@@ -0,0 +1,38 @@
+//! Cookie Response (configuration) packet (`0x01`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::{
+        cookie::CookieResponse,
+        packet::{PacketParseError, ServerboundPacket},
+    },
+    state::ServerState,
+    types::DataTypeEncodeError,
+};
+
+/// Cookie Response, the answer to a Cookie Request.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SConfigurationCookieResponse<'a>(CookieResponse<'a>);
+
+impl<'a> ServerboundPacket<'a> for SConfigurationCookieResponse<'a> {
+    const PACKET_ID: i32 = 0x01;
+
+    fn parse(mut bytes: &'a [u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self(CookieResponse::decode(&mut bytes)?))
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.0.encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        self.0.handle(connection)
+    }
+}
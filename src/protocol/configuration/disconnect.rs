@@ -0,0 +1,27 @@
+//! Disconnect (configuration) packet (`0x02`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket, text::Text, types::DataTypeEncodeError,
+};
+
+/// Disconnect (configuration), kicking the client during configuration.
+pub struct CConfigurationDisconnect {
+    /// The reason, shown to the player.
+    reason: Text,
+}
+
+impl CConfigurationDisconnect {
+    /// Create a Disconnect (configuration) packet.
+    pub const fn new(reason: Text) -> Self {
+        Self { reason }
+    }
+}
+
+impl ClientboundPacket for CConfigurationDisconnect {
+    const PACKET_ID: i32 = 0x02;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.reason.encode(to)
+    }
+}
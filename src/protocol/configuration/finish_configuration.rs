@@ -0,0 +1,15 @@
+//! Finish Configuration packet (`0x03`).
+use std::io::Write;
+
+use crate::{protocol::packet::ClientboundPacket, types::DataTypeEncodeError};
+
+/// Finish Configuration, asking the client to switch to the play state.
+pub struct CFinishConfiguration {}
+
+impl ClientboundPacket for CFinishConfiguration {
+    const PACKET_ID: i32 = 0x03;
+
+    fn encode(&self, _to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+//! Configuration state.
+
+use acknowledge_finish_configuration::SAcknowledgeFinishConfiguration;
+use client_information::SClientInformation;
+use cookie_response::SConfigurationCookieResponse;
+use plugin_message::SPluginMessage;
+use serverbound_known_packs::SServerboundKnownPacks;
+
+use super::packet::{ServerboundPacket, StateEnum};
+use crate::{
+    connection::Connection,
+    protocol::PacketParseError,
+    state::ServerState,
+    types::{self, DataType, DataTypeEncodeError},
+};
+
+/// Acknowledge Finish Configuration packet.
+mod acknowledge_finish_configuration;
+/// Client Information (configuration) packet.
+mod client_information;
+/// Clientbound Known Packs packet.
+mod clientbound_known_packs;
+/// Cookie Request (configuration) packet.
+mod cookie_request;
+/// Cookie Response (configuration) packet.
+mod cookie_response;
+/// Disconnect (configuration) packet.
+mod disconnect;
+/// Finish Configuration packet.
+mod finish_configuration;
+/// Serverbound Plugin Message (configuration) packet.
+mod plugin_message;
+/// Registry Data packet.
+mod registry_data;
+/// Serverbound Known Packs packet.
+mod serverbound_known_packs;
+/// Store Cookie (configuration) packet.
+mod store_cookie;
+/// Transfer (configuration) packet.
+mod transfer;
+
+pub use clientbound_known_packs::{CClientboundKnownPacks, KnownPack};
+pub use cookie_request::CConfigurationCookieRequest;
+pub use disconnect::CConfigurationDisconnect;
+pub use store_cookie::CConfigurationStoreCookie;
+pub use transfer::CConfigurationTransfer;
+
+/// Serverbound packets of the [`ServerState::Configuration`] state.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigurationServerBoundPacket<'a> {
+    /// Client Information (`0x00`).
+    ClientInformation(SClientInformation<'a>),
+
+    /// Cookie Response (`0x01`).
+    CookieResponse(SConfigurationCookieResponse<'a>),
+
+    /// Serverbound Plugin Message (`0x02`).
+    PluginMessage(SPluginMessage<'a>),
+
+    /// Acknowledge Finish Configuration (`0x03`).
+    AcknowledgeFinishConfiguration(SAcknowledgeFinishConfiguration),
+
+    /// Serverbound Known Packs (`0x07`).
+    KnownPacks(SServerboundKnownPacks<'a>),
+}
+
+impl<'a> StateEnum<'a> for ConfigurationServerBoundPacket<'a> {
+    fn parse(
+        packet_id: i32,
+        bytes: &'a [u8],
+    ) -> Result<Self, super::packet::PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(match packet_id {
+            SClientInformation::PACKET_ID => {
+                Self::ClientInformation(SClientInformation::parse(bytes)?)
+            }
+            SConfigurationCookieResponse::PACKET_ID => {
+                Self::CookieResponse(SConfigurationCookieResponse::parse(bytes)?)
+            }
+            SPluginMessage::PACKET_ID => {
+                Self::PluginMessage(SPluginMessage::parse(bytes)?)
+            }
+            SAcknowledgeFinishConfiguration::PACKET_ID => {
+                Self::AcknowledgeFinishConfiguration(
+                    SAcknowledgeFinishConfiguration::parse(bytes)?,
+                )
+            }
+            SServerboundKnownPacks::PACKET_ID => {
+                Self::KnownPacks(SServerboundKnownPacks::parse(bytes)?)
+            }
+            other => {
+                return Err(super::packet::PacketParseError::UnknownPacket(
+                    other,
+                    ServerState::Configuration,
+                ))
+            }
+        })
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        match self {
+            Self::ClientInformation(p) => p.handle(connection),
+            Self::CookieResponse(p) => p.handle(connection),
+            Self::PluginMessage(p) => p.handle(connection),
+            Self::AcknowledgeFinishConfiguration(p) => p.handle(connection),
+            Self::KnownPacks(p) => p.handle(connection),
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, DataTypeEncodeError> {
+        match self {
+            Self::ClientInformation(p) => p.to_bytes(),
+            Self::CookieResponse(p) => p.to_bytes(),
+            Self::PluginMessage(p) => p.to_bytes(),
+            Self::AcknowledgeFinishConfiguration(p) => p.to_bytes(),
+            Self::KnownPacks(p) => p.to_bytes(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for ConfigurationServerBoundPacket<'a> {
+    type Error = PacketParseError;
+
+    fn try_from(mut value: &'a [u8]) -> Result<Self, Self::Error> {
+        let packet_id: i32 = types::var::VarInt::decode(&mut value)?.0;
+
+        Self::parse(packet_id, value)
+    }
+}
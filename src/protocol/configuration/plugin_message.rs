@@ -0,0 +1,56 @@
+//! Serverbound Plugin Message (configuration) packet (`0x02`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{var::VarString, DataTypeEncodeError},
+};
+
+/// Serverbound Plugin Message, a message on a custom channel.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SPluginMessage<'a> {
+    /// Channel identifier, e.g. `minecraft:brand`.
+    channel: &'a str,
+
+    /// Content of the message.
+    data: &'a [u8],
+}
+
+impl<'a> ServerboundPacket<'a> for SPluginMessage<'a> {
+    const PACKET_ID: i32 = 0x02;
+
+    fn parse(mut bytes: &'a [u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            channel: VarString::decode_str(&mut bytes, VarString::MAX_LENGTH)?,
+            data: bytes,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarString::encode_str(self.channel, to)?;
+        to.write_all(self.data)?;
+
+        Ok(())
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if self.channel == "minecraft:brand" {
+            let mut data: &[u8] = self.data;
+            if let Ok(brand) = VarString::decode_str(&mut data, VarString::MAX_LENGTH) {
+                log::info!("Client brand: {brand}");
+            }
+        } else {
+            log::debug!("Plugin message on {0}: {1:X?}", self.channel, self.data);
+        }
+
+        Ok(connection.state())
+    }
+}
@@ -0,0 +1,42 @@
+//! Registry Data packet (`0x07`).
+use std::io::Write;
+
+use crate::{
+    data::registries::Registry,
+    protocol::packet::ClientboundPacket,
+    types::{
+        var::{VarInt, VarString},
+        DataType, DataTypeEncodeError,
+    },
+};
+
+/// Registry Data, the entries of a synchronized registry.
+///
+/// Entries are sent without data: the client takes it from the known packs.
+pub struct CRegistryData {
+    /// The registry.
+    registry: &'static Registry,
+}
+
+impl CRegistryData {
+    /// Create a Registry Data packet for a registry.
+    pub const fn new(registry: &'static Registry) -> Self {
+        Self { registry }
+    }
+}
+
+impl ClientboundPacket for CRegistryData {
+    const PACKET_ID: i32 = 0x07;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarString::new(self.registry.name.to_string()).encode(to)?;
+
+        VarInt(i32::try_from(self.registry.entries().len())?).encode(to)?;
+        for entry in self.registry.entries() {
+            VarString::new((*entry).to_string()).encode(to)?;
+            false.encode(to)?; // Has data
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+//! Serverbound Known Packs packet (`0x07`).
+use std::io::Write;
+
+use super::{
+    clientbound_known_packs::{CClientboundKnownPacks, KnownPack},
+    finish_configuration::CFinishConfiguration,
+    registry_data::CRegistryData,
+};
+use crate::{
+    connection::Connection,
+    data::registries::Registry,
+    protocol::packet::{ClientboundPacket, PacketParseError, ServerboundPacket},
+    state::ServerState,
+    text::Text,
+    types::{
+        var::{VarInt, VarString},
+        DataType, DataTypeEncodeError,
+    },
+};
+
+/// Serverbound Known Packs, the packs of the server also known by the client.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SServerboundKnownPacks<'a> {
+    /// The packs.
+    packs: Vec<KnownPack<'a>>,
+}
+
+impl<'a> ServerboundPacket<'a> for SServerboundKnownPacks<'a> {
+    const PACKET_ID: i32 = 0x07;
+
+    fn parse(mut bytes: &'a [u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        let count: usize = usize::try_from(VarInt::decode(&mut bytes)?.0)
+            .map_err(crate::types::DataTypeDecodeError::from)?;
+        let mut packs: Vec<KnownPack> = Vec::new();
+        for _ in 0..count {
+            packs.push(KnownPack {
+                namespace: VarString::decode_str(&mut bytes, VarString::MAX_LENGTH)?
+                    .into(),
+                id: VarString::decode_str(&mut bytes, VarString::MAX_LENGTH)?.into(),
+                version: VarString::decode_str(&mut bytes, VarString::MAX_LENGTH)?.into(),
+            });
+        }
+
+        Ok(Self { packs })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        // Same format as the clientbound packet
+        CClientboundKnownPacks::new(self.packs.clone()).encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if !self.packs.contains(&KnownPack::core()) {
+            // Registry entries are sent without data, the client must have them
+            connection.disconnect(&Text::plain(
+                "This server requires the vanilla 1.21.2 data pack",
+            ));
+            return Ok(ServerState::Closed);
+        }
+
+        for registry in Registry::synchronized() {
+            CRegistryData::new(registry).send(connection)?;
+        }
+
+        CFinishConfiguration {}.send(connection)?;
+
+        Ok(connection.state())
+    }
+}
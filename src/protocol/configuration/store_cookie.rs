@@ -0,0 +1,35 @@
+//! Store Cookie (configuration) packet (`0x0A`).
+use std::io::Write;
+
+use crate::{
+    protocol::{cookie, packet::ClientboundPacket},
+    types::{var::VarString, DataType, DataTypeEncodeError},
+};
+
+/// Store Cookie, storing a cookie on the client, kept across transfers.
+pub struct CConfigurationStoreCookie {
+    /// Key of the cookie.
+    key: VarString,
+
+    /// Payload of the cookie, at most [`cookie::MAX_PAYLOAD_SIZE`] bytes.
+    payload: Vec<u8>,
+}
+
+impl CConfigurationStoreCookie {
+    /// Store a cookie.
+    pub fn new(key: &str, payload: Vec<u8>) -> Self {
+        Self {
+            key: VarString::new(key.to_string()),
+            payload,
+        }
+    }
+}
+
+impl ClientboundPacket for CConfigurationStoreCookie {
+    const PACKET_ID: i32 = 0x0A;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.key.encode(to)?;
+        cookie::encode_payload(&self.payload, to)
+    }
+}
@@ -0,0 +1,38 @@
+//! Transfer (configuration) packet (`0x0B`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{
+        var::{VarInt, VarString},
+        DataType, DataTypeEncodeError,
+    },
+};
+
+/// Transfer, telling the client to connect to another server.
+pub struct CConfigurationTransfer {
+    /// Host of the server.
+    host: VarString,
+
+    /// Port of the server.
+    port: VarInt,
+}
+
+impl CConfigurationTransfer {
+    /// Transfer the client to a server.
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            host: VarString::new(host.to_string()),
+            port: VarInt(i32::from(port)),
+        }
+    }
+}
+
+impl ClientboundPacket for CConfigurationTransfer {
+    const PACKET_ID: i32 = 0x0B;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.host.encode(to)?;
+        self.port.encode(to)
+    }
+}
@@ -0,0 +1,87 @@
+//! Cookies, stored by the client across transfers and requested by the server in the
+//! login, configuration and play states.
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    pending::{QueryKey, Response},
+    state::ServerState,
+    types::{
+        var::{VarInt, VarString},
+        DataType, DataTypeDecodeError, DataTypeEncodeError, ReadBytes,
+    },
+};
+
+/// Maximum size of a cookie payload.
+pub const MAX_PAYLOAD_SIZE: usize = 5120;
+
+/// Content of the Cookie Response packets.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CookieResponse<'a> {
+    /// Key of the cookie.
+    key: &'a str,
+
+    /// Payload of the cookie, `None` if the client has no cookie with this key.
+    payload: Option<&'a [u8]>,
+}
+
+impl<'a> CookieResponse<'a> {
+    /// Decode a Cookie Response.
+    pub fn decode(bytes: &mut &'a [u8]) -> Result<Self, DataTypeDecodeError> {
+        let key: &str = VarString::decode_str(&mut *bytes, VarString::MAX_LENGTH)?;
+        let payload: Option<&[u8]> = if bool::decode(&mut *bytes)? {
+            let length: usize = usize::try_from(VarInt::decode(&mut *bytes)?.0)?;
+            if length > MAX_PAYLOAD_SIZE {
+                return Err(DataTypeDecodeError::ByteArrayTooLong {
+                    length,
+                    max_length: MAX_PAYLOAD_SIZE,
+                });
+            }
+            Some(bytes.read_bytes(length)?)
+        } else {
+            None
+        };
+
+        Ok(Self { key, payload })
+    }
+
+    /// Encode a Cookie Response.
+    #[allow(dead_code)] // Only used by the tests
+    pub fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarString::encode_str(self.key, to)?;
+        self.payload.is_some().encode(to)?;
+        if let Some(payload) = self.payload {
+            encode_payload(payload, to)?;
+        }
+
+        Ok(())
+    }
+
+    /// Answer the pending request for the cookie.
+    pub fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        connection.answer_query(
+            &QueryKey::Cookie(self.key.to_string()),
+            Response::Answered(self.payload.map(<[u8]>::to_vec)),
+        )
+    }
+}
+
+/// Encode the payload of a Store Cookie packet.
+pub fn encode_payload(
+    payload: &[u8],
+    to: &mut impl Write,
+) -> Result<(), DataTypeEncodeError> {
+    if payload.len() > MAX_PAYLOAD_SIZE {
+        return Err(DataTypeEncodeError::ByteArrayTooLong {
+            length: payload.len(),
+            max_length: MAX_PAYLOAD_SIZE,
+        });
+    }
+    VarInt(i32::try_from(payload.len())?).encode(to)?;
+    to.write_all(payload)?;
+
+    Ok(())
+}
@@ -1,15 +1,27 @@
-use std::{collections::VecDeque, net::TcpStream};
+//! Handshake packet (`0x00`).
+use std::io::Write;
 
 use crate::{
+    connection::Connection,
+    forwarding::{self, ForwardingMode},
     protocol::packet::{PacketParseError, ServerboundPacket},
     state::ServerState,
     types::{self, DataType, DataTypeEncodeError},
 };
 
-#[derive(Debug)]
+/// Maximum length of the server address, without the data appended by proxies.
+const MAX_ADDRESS_LENGTH: usize = 255;
+
+/// Next state requested by the client.
+#[derive(Debug, PartialEq, Eq)]
 enum State {
+    /// Server list ping.
     Status,
+
+    /// Login.
     Login,
+
+    /// Login after a transfer from another server.
     Transfer,
 }
 
@@ -30,55 +42,126 @@ impl TryFrom<types::var::VarInt> for State {
         })
     }
 }
-#[derive(Debug)]
+
+impl From<&State> for types::var::VarInt {
+    fn from(value: &State) -> Self {
+        Self(match value {
+            State::Status => 1,
+            State::Login => 2,
+            State::Transfer => 3,
+        })
+    }
+}
+
+/// Handshake, the first packet sent by the client.
+#[derive(Debug, PartialEq, Eq)]
 pub struct SHandshake {
+    /// Protocol version of the client.
     protocol_version: types::var::VarInt,
-    server_address: types::var::VarString,
+
+    /// Address used by the client to connect.
+    server_address: String,
+
+    /// Data appended to the address after a `\0`, such as legacy forwarding.
+    forwarded: Option<String>,
+
+    /// Port used by the client to connect.
     server_port: u16,
+
+    /// State to switch to.
     next_state: State,
 }
 
-impl ServerboundPacket for SHandshake {
+impl ServerboundPacket<'_> for SHandshake {
     const PACKET_ID: i32 = 0;
 
-    fn parse(mut bytes: VecDeque<u8>) -> Result<Self, PacketParseError>
+    fn parse(mut bytes: &[u8]) -> Result<Self, PacketParseError>
     where
         Self: Sized,
     {
+        let protocol_version: types::var::VarInt =
+            types::var::VarInt::decode(&mut bytes)?;
+
+        // Legacy forwarding appends the player information to the address
+        let address: &str = types::var::VarString::decode_str(
+            &mut bytes,
+            types::var::VarString::MAX_LENGTH,
+        )?;
+        let (host, forwarded) = forwarding::split_address(address);
+        let length: usize = host.encode_utf16().count();
+        if length > MAX_ADDRESS_LENGTH {
+            return Err(types::DataTypeDecodeError::StringTooLong {
+                length,
+                max_length: MAX_ADDRESS_LENGTH,
+            }
+            .into());
+        }
+
         Ok(Self {
-            protocol_version: types::var::VarInt::decode(&mut bytes)?,
-            server_address: types::var::VarString::decode(&mut bytes)?,
+            protocol_version,
+            server_address: host.to_string(),
+            forwarded: forwarded.map(ToString::to_string),
             server_port: u16::decode(&mut bytes)?,
             next_state: State::try_from(types::var::VarInt::decode(&mut bytes)?)?,
         })
     }
 
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.protocol_version.encode(to)?;
+        let address: String = self.forwarded.as_ref().map_or_else(
+            || self.server_address.clone(),
+            |forwarded| format!("{}\0{forwarded}", self.server_address),
+        );
+        types::var::VarString::new(address).encode(to)?;
+        self.server_port.encode(to)?;
+        types::var::VarInt::from(&self.next_state).encode(to)
+    }
+
     fn handle(
         &self,
-        _server_state: ServerState,
-        addr: &str,
-        _stream: &mut TcpStream,
+        connection: &mut Connection,
     ) -> Result<ServerState, DataTypeEncodeError> {
+        connection.set_protocol_version(self.protocol_version);
+        connection
+            .server()
+            .metrics()
+            .record_protocol_version(self.protocol_version.0);
+
         if self.protocol_version != types::var::VarInt(768) {
             log::warn!(
-                target: addr,
                 "Received protocol version `{0}` instead of 1.21.2 `768` ; continuing",
                 self.protocol_version
             );
         }
 
         log::info!(
-            target: addr,
-            "Connected to {0:?}:{1} - Switching to {2:?} state",
+            "Connected to {0}:{1} - Switching to {2:?} state",
             self.server_address,
             self.server_port,
             self.next_state
         );
 
+        if matches!(self.next_state, State::Transfer) {
+            // Transfers are logins, refused at Login Start if not accepted
+            connection.set_transferred();
+        }
+
+        if matches!(self.next_state, State::Login | State::Transfer)
+            && connection.server().config().forwarding_mode == ForwardingMode::Legacy
+        {
+            // Checked at login, where the client can be told what went wrong
+            match self.forwarded.as_deref().map(forwarding::parse_legacy) {
+                Some(Ok(forwarded)) => connection.set_forwarded(forwarded),
+                Some(Err(e)) => {
+                    log::warn!("Invalid legacy forwarding: {e}");
+                }
+                None => {}
+            }
+        }
+
         Ok(match self.next_state {
             State::Status => ServerState::Status,
-            State::Login => ServerState::Login,
-            State::Transfer => ServerState::Play,
+            State::Login | State::Transfer => ServerState::Login,
         })
     }
 }
@@ -1,24 +1,29 @@
-use std::{collections::VecDeque, net::TcpStream};
+//! Handshake state.
 
 use super::packet::{ServerboundPacket, StateEnum};
 use crate::{
+    connection::Connection,
     protocol::PacketParseError,
     state::ServerState,
     types::{self, DataType, DataTypeEncodeError},
 };
 use handshake::SHandshake;
 
+/// Handshake packet.
 #[allow(clippy::module_inception)] // Handshake packet while in handshake state
 mod handshake;
 
+/// Serverbound packets of the [`ServerState::Handshake`] state.
+#[derive(Debug, PartialEq, Eq)]
 pub enum HandshakeServerBoundPacket {
+    /// Handshake (`0x00`).
     Handshake(SHandshake),
 }
 
-impl StateEnum for HandshakeServerBoundPacket {
+impl StateEnum<'_> for HandshakeServerBoundPacket {
     fn parse(
         packet_id: i32,
-        bytes: VecDeque<u8>,
+        bytes: &[u8],
     ) -> Result<Self, super::packet::PacketParseError>
     where
         Self: Sized,
@@ -36,20 +41,24 @@ impl StateEnum for HandshakeServerBoundPacket {
 
     fn handle(
         &self,
-        server_state: ServerState,
-        addr: &str,
-        stream: &mut TcpStream,
+        connection: &mut Connection,
     ) -> Result<ServerState, DataTypeEncodeError> {
         match self {
-            Self::Handshake(p) => p.handle(server_state, addr, stream),
+            Self::Handshake(p) => p.handle(connection),
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, DataTypeEncodeError> {
+        match self {
+            Self::Handshake(p) => p.to_bytes(),
         }
     }
 }
 
-impl TryFrom<VecDeque<u8>> for HandshakeServerBoundPacket {
+impl TryFrom<&[u8]> for HandshakeServerBoundPacket {
     type Error = PacketParseError;
 
-    fn try_from(mut value: VecDeque<u8>) -> Result<Self, Self::Error> {
+    fn try_from(mut value: &[u8]) -> Result<Self, Self::Error> {
         let packet_id: i32 = types::var::VarInt::decode(&mut value)?.0;
 
         Self::parse(packet_id, value)
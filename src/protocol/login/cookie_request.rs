@@ -0,0 +1,30 @@
+//! Cookie Request (login) packet (`0x05`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{var::VarString, DataType, DataTypeEncodeError},
+};
+
+/// Cookie Request, asking the client for a cookie, answered by a Cookie Response.
+pub struct CLoginCookieRequest {
+    /// Key of the cookie.
+    key: VarString,
+}
+
+impl CLoginCookieRequest {
+    /// Request the cookie with a key.
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: VarString::new(key.to_string()),
+        }
+    }
+}
+
+impl ClientboundPacket for CLoginCookieRequest {
+    const PACKET_ID: i32 = 0x05;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.key.encode(to)
+    }
+}
@@ -0,0 +1,31 @@
+//! Disconnect (login) packet (`0x00`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    text::Text,
+    types::{var::VarString, DataType, DataTypeEncodeError},
+};
+
+/// Disconnect (login), kicking the client during login.
+pub struct CLoginDisconnect {
+    /// The reason, shown to the player.
+    reason: Text,
+}
+
+impl CLoginDisconnect {
+    /// Create a Disconnect (login) packet.
+    pub const fn new(reason: Text) -> Self {
+        Self { reason }
+    }
+}
+
+impl ClientboundPacket for CLoginDisconnect {
+    const PACKET_ID: i32 = 0x00;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        // JSON text in this state
+        VarString::new(self.reason.to_json().to_string()).encode(to)?;
+        Ok(())
+    }
+}
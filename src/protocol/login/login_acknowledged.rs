@@ -0,0 +1,47 @@
+//! Login Acknowledged packet (`0x03`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::{
+        configuration::{CClientboundKnownPacks, KnownPack},
+        packet::{ClientboundPacket, PacketParseError, ServerboundPacket},
+    },
+    state::ServerState,
+    types::DataTypeEncodeError,
+};
+
+/// Login Acknowledged, switching to the configuration state.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SLoginAcknowledged {}
+
+impl ServerboundPacket<'_> for SLoginAcknowledged {
+    const PACKET_ID: i32 = 0x03;
+
+    fn parse(_bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {})
+    }
+
+    fn encode(&self, _to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        Ok(())
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if connection.profile().is_none() {
+            log::warn!("Login acknowledged before login start");
+            return Ok(ServerState::Closed);
+        }
+
+        log::debug!("Login acknowledged");
+
+        CClientboundKnownPacks::new(vec![KnownPack::core()]).send(connection)?;
+
+        Ok(ServerState::Configuration)
+    }
+}
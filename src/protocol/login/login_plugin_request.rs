@@ -0,0 +1,46 @@
+//! Login Plugin Request packet (`0x04`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{
+        var::{VarInt, VarString},
+        DataType, DataTypeEncodeError,
+    },
+};
+
+/// Login Plugin Request, a query on a custom channel answered by a Login Plugin
+/// Response with the same message ID.
+pub struct CLoginPluginRequest {
+    /// ID of the query, chosen by the server.
+    message_id: VarInt,
+
+    /// Channel identifier, e.g. `velocity:player_info`.
+    channel: VarString,
+
+    /// Content of the query.
+    data: Vec<u8>,
+}
+
+impl CLoginPluginRequest {
+    /// Create a query on a channel.
+    pub fn new(message_id: i32, channel: &str, data: Vec<u8>) -> Self {
+        Self {
+            message_id: VarInt(message_id),
+            channel: VarString::new(channel.to_string()),
+            data,
+        }
+    }
+}
+
+impl ClientboundPacket for CLoginPluginRequest {
+    const PACKET_ID: i32 = 0x04;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.message_id.encode(to)?;
+        self.channel.encode(to)?;
+        to.write_all(&self.data)?;
+
+        Ok(())
+    }
+}
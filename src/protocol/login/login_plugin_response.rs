@@ -0,0 +1,57 @@
+//! Login Plugin Response packet (`0x02`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    pending::{QueryKey, Response},
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{var::VarInt, DataType, DataTypeEncodeError},
+};
+
+/// Login Plugin Response, the answer to a Login Plugin Request.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SLoginPluginResponse {
+    /// ID of the query.
+    message_id: VarInt,
+
+    /// Content of the answer, or `None` if the client does not know the channel.
+    data: Option<Vec<u8>>,
+}
+
+impl ServerboundPacket<'_> for SLoginPluginResponse {
+    const PACKET_ID: i32 = 0x02;
+
+    fn parse(mut bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        let message_id: VarInt = VarInt::decode(&mut bytes)?;
+        let successful: bool = bool::decode(&mut bytes)?;
+
+        Ok(Self {
+            message_id,
+            data: successful.then(|| bytes.into()),
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.message_id.encode(to)?;
+        self.data.is_some().encode(to)?;
+        if let Some(data) = &self.data {
+            to.write_all(data)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        connection.answer_query(
+            &QueryKey::LoginPlugin(self.message_id.0),
+            Response::Answered(self.data.clone()),
+        )
+    }
+}
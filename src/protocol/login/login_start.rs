@@ -0,0 +1,189 @@
+//! Login Start packet (`0x00`).
+use std::{io::Write, net::IpAddr, sync::Arc, time::Duration};
+
+use crate::{
+    connection::Connection,
+    forwarding::{self, ForwardedPlayer, ForwardingMode},
+    pending::Response,
+    player::Player,
+    profile::PlayerProfile,
+    protocol::{
+        login::{login_success::CLoginSuccess, set_compression::CSetCompression},
+        packet::{ClientboundPacket, PacketParseError, ServerboundPacket},
+        play::SPAWN,
+    },
+    server::Server,
+    state::ServerState,
+    text::Text,
+    types::{uuid::Uuid, var::VarString, DataType, DataTypeEncodeError},
+};
+
+/// Maximum length of a username.
+const MAX_NAME_LENGTH: usize = 16;
+
+/// Delay for Velocity to answer the forwarding query.
+const FORWARDING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Login Start, sent by the client to start logging in.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SLoginStart<'a> {
+    /// Username of the player.
+    name: &'a str,
+
+    /// UUID of the player, as known by the client.
+    uuid: Uuid,
+}
+
+impl<'a> ServerboundPacket<'a> for SLoginStart<'a> {
+    const PACKET_ID: i32 = 0x00;
+
+    fn parse(mut bytes: &'a [u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            name: VarString::decode_str(&mut bytes, MAX_NAME_LENGTH)?,
+            uuid: Uuid::decode(&mut bytes)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarString::encode_str(self.name, to)?;
+        self.uuid.encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        // Accepted once, as in vanilla: a second one would query Velocity again
+        if !connection.start_login() {
+            connection.disconnect(&Text::plain("Unexpected Login Start"));
+            return Ok(ServerState::Closed);
+        }
+
+        if connection.is_transferred() && !connection.server().config().accepts_transfers
+        {
+            connection.disconnect(&Text::translate(
+                "multiplayer.disconnect.transfers_disabled",
+                vec![],
+            ));
+            return Ok(ServerState::Closed);
+        }
+
+        let profile: PlayerProfile = match connection.server().config().forwarding_mode {
+            // Offline mode: the UUID sent by the client is ignored
+            ForwardingMode::None => PlayerProfile::offline(self.name.to_string()),
+            ForwardingMode::Legacy => {
+                let Some(forwarded) = connection.take_forwarded() else {
+                    connection.disconnect(&Text::plain(
+                        "If you wish to use IP forwarding, please enable it in your BungeeCord config as well!",
+                    ));
+                    return Ok(ServerState::Closed);
+                };
+                forwarded.into_profile(self.name.to_string())
+            }
+            ForwardingMode::Modern => {
+                // The login continues when the proxy answers
+                connection.send_login_query(
+                    forwarding::VELOCITY_CHANNEL,
+                    forwarding::modern_request(),
+                    FORWARDING_TIMEOUT,
+                    modern_forwarding_login,
+                )?;
+                return Ok(connection.state());
+            }
+        };
+
+        log::info!(
+            "{0} logging in with UUID {1} (client sent {2})",
+            profile.name,
+            profile.uuid,
+            self.uuid
+        );
+
+        complete_login(connection, profile)
+    }
+}
+
+/// Log a player in with the profile forwarded by Velocity.
+fn modern_forwarding_login(
+    connection: &mut Connection,
+    response: Response,
+) -> Result<ServerState, DataTypeEncodeError> {
+    let Response::Answered(Some(data)) = response else {
+        connection.disconnect(&Text::plain(
+            "This server requires you to connect with Velocity.",
+        ));
+        return Ok(ServerState::Closed);
+    };
+
+    let secret: &[u8] = connection.server().config().forwarding_secret.as_bytes();
+    let forwarded: ForwardedPlayer = match forwarding::parse_modern(&data, secret) {
+        Ok(forwarded) => forwarded,
+        Err(e) => {
+            log::warn!("Invalid modern forwarding: {e}");
+            connection.disconnect(&Text::plain("Unable to verify player details."));
+            return Ok(ServerState::Closed);
+        }
+    };
+
+    connection.set_forwarded_ip(forwarded.address);
+    let profile: PlayerProfile = forwarded.into_profile(String::new());
+    log::info!(
+        "{0} logging in with UUID {1} through Velocity",
+        profile.name,
+        profile.uuid
+    );
+
+    complete_login(connection, profile)
+}
+
+/// Log a player in once its profile is known: enable compression, send Login
+/// Success and register the player.
+pub fn complete_login(
+    connection: &mut Connection,
+    profile: PlayerProfile,
+) -> Result<ServerState, DataTypeEncodeError> {
+    let server: Arc<Server> = Arc::clone(connection.server());
+
+    let ip: IpAddr = connection.handle().address().ip();
+    if let Some(reason) = server.access().login_refusal(profile.uuid, ip) {
+        connection.disconnect(&reason);
+        return Ok(ServerState::Closed);
+    }
+
+    let max_players: usize = usize::try_from(server.config().max_players).unwrap_or(0);
+    if server.players().len() >= max_players
+        && server.players().get(profile.uuid).is_none()
+        && !server
+            .access()
+            .op(profile.uuid)
+            .is_some_and(|op| op.bypasses_player_limit)
+    {
+        connection.disconnect(&Text::translate(
+            "multiplayer.disconnect.server_full",
+            vec![],
+        ));
+        return Ok(ServerState::Closed);
+    }
+
+    if let Some(threshold) = server.config().compression_threshold {
+        CSetCompression::new(threshold).send(connection)?;
+        connection.set_compression(Some(threshold));
+    }
+
+    CLoginSuccess::new(profile.clone()).send(connection)?;
+
+    let player: Arc<Player> = Arc::new(Player::new(
+        profile.clone(),
+        server.allocate_entity_id(),
+        Arc::clone(connection.handle()),
+        SPAWN,
+    ));
+    server.join(&player);
+    connection.set_player(player);
+    connection.set_profile(profile);
+
+    Ok(connection.state())
+}
@@ -1,33 +1,28 @@
+//! Login Success packet (`0x02`).
+use std::io::Write;
+
 use crate::{
-    protocol::packet::ServerboundPacket,
-    types::{Uuid, VarString},
+    profile::PlayerProfile, protocol::packet::ClientboundPacket,
+    types::DataTypeEncodeError,
 };
 
-struct Property {}
+/// Login Success, ending the login.
+pub struct CLoginSuccess {
+    /// Profile of the player.
+    profile: PlayerProfile,
+}
 
-pub struct SLoginSuccess {
-    uuid: Uuid,
-    username: VarString,
+impl CLoginSuccess {
+    /// Create a Login Success for a player.
+    pub const fn new(profile: PlayerProfile) -> Self {
+        Self { profile }
+    }
 }
 
-impl ServerboundPacket for SLoginSuccess {
+impl ClientboundPacket for CLoginSuccess {
     const PACKET_ID: i32 = 0x02;
 
-    fn parse(
-        mut bytes: std::collections::VecDeque<u8>,
-    ) -> Result<Self, crate::protocol::PacketParseError>
-    where
-        Self: Sized,
-    {
-        let uuid: Uuid = Uuid::try_from(&mut bytes)?;
-    }
-
-    fn handle(
-        &self,
-        server_state: crate::state::ServerState,
-        _addr: &str,
-        _stream: &mut std::net::TcpStream,
-    ) -> crate::state::ServerState {
-        server_state
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.profile.encode(to)
     }
 }
@@ -1,50 +1,112 @@
-use std::collections::VecDeque;
+//! Login state.
 
-use login_success::SLoginSuccess;
+use cookie_response::SLoginCookieResponse;
+use login_acknowledged::SLoginAcknowledged;
+use login_plugin_response::SLoginPluginResponse;
+use login_start::SLoginStart;
 
-use crate::{state::ServerState, types::VarInt};
-
-use super::{packet::ServerboundPacket, PacketParseError, StateEnum};
+use super::packet::{ServerboundPacket, StateEnum};
+use crate::{
+    connection::Connection,
+    protocol::PacketParseError,
+    state::ServerState,
+    types::{self, DataType, DataTypeEncodeError},
+};
 
+/// Cookie Request (login) packet.
+mod cookie_request;
+/// Cookie Response (login) packet.
+mod cookie_response;
+/// Disconnect (login) packet.
+mod disconnect;
+/// Login Acknowledged packet.
+mod login_acknowledged;
+/// Login Plugin Request packet.
+mod login_plugin_request;
+/// Login Plugin Response packet.
+mod login_plugin_response;
+/// Login Start packet.
+mod login_start;
+/// Login Success packet.
 mod login_success;
+/// Set Compression packet.
+mod set_compression;
+
+pub use cookie_request::CLoginCookieRequest;
+pub use disconnect::CLoginDisconnect;
+pub use login_plugin_request::CLoginPluginRequest;
+
+/// Serverbound packets of the [`ServerState::Login`] state.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoginServerBoundPacket<'a> {
+    /// Login Start (`0x00`).
+    LoginStart(SLoginStart<'a>),
 
-pub enum LoginServerBoundPacket {
-    LoginSuccess(SLoginSuccess),
+    /// Login Plugin Response (`0x02`).
+    LoginPluginResponse(SLoginPluginResponse),
+
+    /// Login Acknowledged (`0x03`).
+    LoginAcknowledged(SLoginAcknowledged),
+
+    /// Cookie Response (`0x04`).
+    CookieResponse(SLoginCookieResponse<'a>),
 }
 
-impl StateEnum for LoginServerBoundPacket {
+impl<'a> StateEnum<'a> for LoginServerBoundPacket<'a> {
     fn parse(
         packet_id: i32,
-        bytes: std::collections::VecDeque<u8>,
-    ) -> Result<Self, super::PacketParseError>
+        bytes: &'a [u8],
+    ) -> Result<Self, super::packet::PacketParseError>
     where
         Self: Sized,
     {
         Ok(match packet_id {
-            SLoginSuccess::PACKET_ID => Self::LoginSuccess(SLoginSuccess::parse(bytes)?),
+            SLoginStart::PACKET_ID => Self::LoginStart(SLoginStart::parse(bytes)?),
+            SLoginPluginResponse::PACKET_ID => {
+                Self::LoginPluginResponse(SLoginPluginResponse::parse(bytes)?)
+            }
+            SLoginAcknowledged::PACKET_ID => {
+                Self::LoginAcknowledged(SLoginAcknowledged::parse(bytes)?)
+            }
+            SLoginCookieResponse::PACKET_ID => {
+                Self::CookieResponse(SLoginCookieResponse::parse(bytes)?)
+            }
             other => {
-                return Err(PacketParseError::UnknownPacket(other, ServerState::Login))
+                return Err(super::packet::PacketParseError::UnknownPacket(
+                    other,
+                    ServerState::Login,
+                ))
             }
         })
     }
 
     fn handle(
         &self,
-        server_state: crate::state::ServerState,
-        addr: &str,
-        stream: &mut std::net::TcpStream,
-    ) -> crate::state::ServerState {
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        match self {
+            Self::LoginStart(p) => p.handle(connection),
+            Self::LoginPluginResponse(p) => p.handle(connection),
+            Self::LoginAcknowledged(p) => p.handle(connection),
+            Self::CookieResponse(p) => p.handle(connection),
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, DataTypeEncodeError> {
         match self {
-            Self::LoginSuccess(p) => p.handle(server_state, addr, stream),
+            Self::LoginStart(p) => p.to_bytes(),
+            Self::LoginPluginResponse(p) => p.to_bytes(),
+            Self::LoginAcknowledged(p) => p.to_bytes(),
+            Self::CookieResponse(p) => p.to_bytes(),
         }
     }
 }
 
-impl TryFrom<VecDeque<u8>> for LoginServerBoundPacket {
+impl<'a> TryFrom<&'a [u8]> for LoginServerBoundPacket<'a> {
     type Error = PacketParseError;
 
-    fn try_from(mut value: VecDeque<u8>) -> Result<Self, Self::Error> {
-        let packet_id: i32 = VarInt::try_from(&mut value)?.0;
+    fn try_from(mut value: &'a [u8]) -> Result<Self, Self::Error> {
+        let packet_id: i32 = types::var::VarInt::decode(&mut value)?.0;
 
         Self::parse(packet_id, value)
     }
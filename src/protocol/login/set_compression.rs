@@ -0,0 +1,29 @@
+//! Set Compression packet (`0x03`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{var::VarInt, DataType, DataTypeEncodeError},
+};
+
+/// Set Compression, enabling compression for the following packets.
+pub struct CSetCompression {
+    /// Packets of at least this size are compressed.
+    threshold: usize,
+}
+
+impl CSetCompression {
+    /// Create a Set Compression packet.
+    pub const fn new(threshold: usize) -> Self {
+        Self { threshold }
+    }
+}
+
+impl ClientboundPacket for CSetCompression {
+    const PACKET_ID: i32 = 0x03;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarInt(i32::try_from(self.threshold)?).encode(to)?;
+        Ok(())
+    }
+}
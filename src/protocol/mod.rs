@@ -1,9 +1,26 @@
+//! Protocol packets, by state.
+mod codec;
+pub use codec::{Cipher, CipherReader, Codec, SHARED_SECRET_SIZE};
+mod cookie;
 mod packet;
-pub use packet::{PacketParseError, StateEnum};
+pub use packet::{ClientboundPacket, Direction, PacketParseError, StateEnum};
+mod test;
 
 mod handshake;
 pub use handshake::HandshakeServerBoundPacket;
 mod status;
 pub use status::StatusServerBoundPacket;
-// mod login;
-// pub use login::LoginServerBoundPacket;
+mod login;
+pub use login::{
+    CLoginCookieRequest, CLoginDisconnect, CLoginPluginRequest, LoginServerBoundPacket,
+};
+mod configuration;
+pub use configuration::{
+    CConfigurationCookieRequest, CConfigurationDisconnect, CConfigurationStoreCookie,
+    CConfigurationTransfer, ConfigurationServerBoundPacket,
+};
+pub mod play;
+pub use play::{
+    CPlayCookieRequest, CPlayDisconnect, CPlayStoreCookie, CPlayTransfer,
+    CSystemChatMessage, PlayServerBoundPacket,
+};
@@ -1,89 +1,126 @@
-use std::collections::VecDeque;
+//! Packet traits.
 use std::io::Write;
-use std::net::TcpStream;
 
 use thiserror::Error;
 
+use crate::connection::Connection;
 use crate::state::ServerState;
-use crate::types::{self, var::VarInt};
-use crate::types::{DataType, DataTypeEncodeError};
+use crate::text::Text;
+use crate::types::{self, var::VarInt, DataType, DataTypeEncodeError};
 
+/// Error when parsing a packet.
 #[derive(Error, Debug)]
 pub enum PacketParseError {
+    /// A field of the packet could not be decoded.
     #[error(transparent)]
     DataTypeDecodeError(#[from] types::DataTypeDecodeError),
 
+    /// The packet ID is not known in the current state.
     #[error("Unknown packet: {0:X?} in {1:?}")]
     UnknownPacket(i32, ServerState),
+
+    /// The client sent too many packets in the current state.
+    #[error("Packet rate limit exceeded in {0:?}")]
+    RateLimitExceeded(ServerState),
+}
+
+impl PacketParseError {
+    /// The reason to kick the client with, if the error breaks a limit.
+    pub fn kick_reason(&self) -> Option<Text> {
+        match self {
+            Self::RateLimitExceeded(_) => {
+                Some(Text::translate("disconnect.exceeded_packet_rate", vec![]))
+            }
+            Self::DataTypeDecodeError(
+                types::DataTypeDecodeError::StringTooLong { .. }
+                | types::DataTypeDecodeError::PacketTooLarge(_)
+                | types::DataTypeDecodeError::ByteArrayTooLong { .. },
+            ) => Some(Text::plain(self.to_string())),
+            _ => None,
+        }
+    }
 }
 
-pub trait ServerboundPacket {
+/// Direction of a packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Sent by the client.
+    Serverbound,
+
+    /// Sent by the server.
+    Clientbound,
+}
+
+/// A packet sent by the client, borrowing its strings and byte arrays from the
+/// packet data it is parsed from.
+pub trait ServerboundPacket<'a> {
+    /// ID of the packet in its state.
     const PACKET_ID: i32;
 
-    fn parse(bytes: VecDeque<u8>) -> Result<Self, PacketParseError>
+    /// Parse the packet data (after the packet ID).
+    fn parse(bytes: &'a [u8]) -> Result<Self, PacketParseError>
     where
         Self: Sized;
 
+    /// Encode the packet data (after the packet ID), as far as it is parsed.
+    #[allow(dead_code)] // Only used by the tests
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError>;
+
+    /// Encode the packet ID followed by the packet data.
+    #[allow(dead_code)] // Only used by the tests
+    fn to_bytes(&self) -> Result<Vec<u8>, DataTypeEncodeError> {
+        let mut bytes: Vec<u8> = Vec::new();
+        VarInt(Self::PACKET_ID).encode(&mut bytes)?;
+        self.encode(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Handle the packet, returning the next state of the connection.
     fn handle(
         &self,
-        server_state: ServerState,
-        addr: &str,
-        stream: &mut TcpStream,
+        connection: &mut Connection,
     ) -> Result<ServerState, DataTypeEncodeError>;
 }
 
-pub trait StateEnum: TryFrom<VecDeque<u8>> {
-    fn parse(packet_id: i32, bytes: VecDeque<u8>) -> Result<Self, PacketParseError>
+/// All the [`ServerboundPacket`]s of a state.
+pub trait StateEnum<'a>: TryFrom<&'a [u8], Error = PacketParseError> {
+    /// Parse a packet from its ID and data.
+    fn parse(packet_id: i32, bytes: &'a [u8]) -> Result<Self, PacketParseError>
     where
         Self: Sized;
 
+    /// Handle the packet, returning the next state of the connection.
     fn handle(
         &self,
-        server_state: ServerState,
-        addr: &str,
-        stream: &mut TcpStream,
+        connection: &mut Connection,
     ) -> Result<ServerState, DataTypeEncodeError>;
+
+    /// Encode the packet ID followed by the packet data, as far as it is parsed.
+    #[allow(dead_code)] // Only used by the tests
+    fn to_bytes(&self) -> Result<Vec<u8>, DataTypeEncodeError>;
 }
 
+/// A packet sent by the server.
 pub trait ClientboundPacket {
+    /// ID of the packet in its state.
     const PACKET_ID: i32;
 
+    /// Encode the packet data (after the packet ID).
     fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError>;
 
-    fn send(
-        &self,
-        addr: &str,
-        stream: &mut TcpStream,
-    ) -> Result<(), DataTypeEncodeError> {
+    /// Encode the packet ID followed by the packet data.
+    fn to_bytes(&self) -> Result<Vec<u8>, DataTypeEncodeError> {
         let mut bytes: Vec<u8> = Vec::new();
-
         VarInt(Self::PACKET_ID).encode(&mut bytes)?;
-
         self.encode(&mut bytes)?;
+        Ok(bytes)
+    }
 
-        let length: VarInt = VarInt(match bytes.len().try_into() {
-            Ok(v) => v,
-            Err(e) => {
-                log::error!(target: addr, "Failed to encode packet {0} ({1:X?}) length {2}: {e}", Self::PACKET_ID, bytes, bytes.len());
-                return Ok(());
-            }
-        });
-
-        let mut payload: Vec<u8> = Vec::new();
-        length.encode(&mut payload)?;
-
-        bytes.flush()?;
-        payload.append(&mut bytes);
-
-        log::trace!(target: addr, "Sending packet {0} ({1:X?})", Self::PACKET_ID, payload);
-
-        payload.flush()?;
-        if let Err(e) = stream.write_all(&payload) {
-            log::warn!(target: addr, "Failed to send packet {0} ({1:X?}): {e}", Self::PACKET_ID, payload);
-        }
-
-        log::trace!(target: addr, "Packet {0} sent", Self::PACKET_ID);
-
-        Ok(())
+    /// Queue the packet on the outbound queue of a connection.
+    fn send(&self, connection: &mut Connection) -> Result<(), DataTypeEncodeError>
+    where
+        Self: Sized,
+    {
+        connection.send(self)
     }
 }
@@ -0,0 +1,47 @@
+//! Chat Command packet (`0x05`).
+use std::{io::Write, sync::Arc};
+
+use crate::{
+    command::CommandSource,
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{var::VarString, DataTypeEncodeError},
+};
+
+/// Chat Command, a command typed by the player, without the leading slash.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SChatCommand<'a> {
+    /// The command.
+    command: &'a str,
+}
+
+impl<'a> ServerboundPacket<'a> for SChatCommand<'a> {
+    const PACKET_ID: i32 = 0x05;
+
+    fn parse(mut bytes: &'a [u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            command: VarString::decode_str(&mut bytes, VarString::MAX_LENGTH)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarString::encode_str(self.command, to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if let Some(player) = connection.player() {
+            let source: CommandSource = CommandSource::Player(Arc::clone(player));
+            // Errors are sent back to the player by the dispatcher
+            let _ = connection.server().run_command(&source, self.command);
+        }
+
+        Ok(connection.state())
+    }
+}
@@ -0,0 +1,183 @@
+//! Chunk Data and Update Light packet (`0x28`).
+use std::{collections::BTreeMap, io::Write};
+
+use crate::{
+    data::{blocks::BlockState, registries::Registry},
+    protocol::packet::ClientboundPacket,
+    types::{nbt::Nbt, var::VarInt, DataType, DataTypeEncodeError},
+};
+
+/// Number of sections in a chunk of the overworld, from -64 to 320.
+const SECTIONS: usize = 24;
+
+/// Lowest block Y coordinate of the overworld.
+const MIN_Y: i32 = -64;
+
+/// Number of blocks in a section.
+const SECTION_BLOCKS: usize = 16 * 16 * 16;
+
+/// Largest palette sent instead of global state IDs, with 8 bits per entry.
+const MAX_PALETTE: usize = 1 << 8;
+
+/// Chunk Data and Update Light, a chunk filled with air but for a few blocks.
+pub struct CChunkDataAndUpdateLight {
+    /// Chunk X coordinate.
+    x: i32,
+
+    /// Chunk Z coordinate.
+    z: i32,
+
+    /// Blocks other than air, by section and index in the section.
+    blocks: BTreeMap<(usize, usize), BlockState>,
+}
+
+impl CChunkDataAndUpdateLight {
+    /// Create an empty chunk.
+    pub const fn new(x: i32, z: i32) -> Self {
+        Self {
+            x,
+            z,
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    /// Place a block at world coordinates, ignored outside of the chunk.
+    pub fn with_block(mut self, x: i32, y: i32, z: i32, state: BlockState) -> Self {
+        let section: Option<usize> = usize::try_from((y - MIN_Y) >> 4)
+            .ok()
+            .filter(|section| *section < SECTIONS);
+        let Some(section) = section.filter(|_| x >> 4 == self.x && z >> 4 == self.z)
+        else {
+            return self;
+        };
+        // YZX order, `& 15` being within 0..16
+        let index: usize = usize::try_from(((y & 15) * 16 + (z & 15)) * 16 + (x & 15))
+            .unwrap_or_default();
+
+        if state == BlockState::AIR {
+            self.blocks.remove(&(section, index));
+        } else {
+            self.blocks.insert((section, index), state);
+        }
+        self
+    }
+}
+
+/// Number of bits needed to write values below `count`.
+const fn bits_for(count: usize) -> u32 {
+    usize::BITS - count.saturating_sub(1).leading_zeros()
+}
+
+/// Encode a section holding `blocks`, `(index, state)` pairs of blocks other than
+/// air, as the block count and the paletted container of the states.
+fn section(
+    blocks: &[(usize, BlockState)],
+    to: &mut impl Write,
+) -> Result<(), DataTypeEncodeError> {
+    let mut palette: Vec<BlockState> = vec![BlockState::AIR];
+    for (_, state) in blocks {
+        if !palette.contains(state) {
+            palette.push(*state);
+        }
+    }
+    // Indirect palettes use at least 4 bits, larger ones give way to state IDs
+    let direct: bool = palette.len() > MAX_PALETTE;
+    let bits: u32 = if direct {
+        bits_for(usize::try_from(BlockState::COUNT)?)
+    } else {
+        bits_for(palette.len()).max(4)
+    };
+
+    let mut values: Vec<u32> = vec![0; SECTION_BLOCKS];
+    for (index, state) in blocks {
+        let value: u32 = if direct {
+            state.id()
+        } else {
+            let position: usize = palette.iter().position(|p| p == state).unwrap_or(0);
+            u32::try_from(position)?
+        };
+        if let Some(slot) = values.get_mut(*index) {
+            *slot = value;
+        }
+    }
+    // Entries do not span longs, the first one in the lowest bits
+    let per_long: usize = usize::try_from(u64::BITS / bits)?;
+    let longs: Vec<u64> = values
+        .chunks(per_long)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .zip((0..).step_by(usize::try_from(bits).unwrap_or(64)))
+                .fold(0u64, |long, (value, shift)| {
+                    long | u64::from(*value) << shift
+                })
+        })
+        .collect();
+
+    i16::try_from(blocks.len())?.encode(to)?; // Non-air block count
+    u8::try_from(bits)?.encode(to)?;
+    if !direct {
+        VarInt(i32::try_from(palette.len())?).encode(to)?;
+        for state in palette {
+            VarInt::from(state).encode(to)?;
+        }
+    }
+    VarInt(i32::try_from(longs.len())?).encode(to)?;
+    for long in longs {
+        long.cast_signed().encode(to)?;
+    }
+
+    Ok(())
+}
+
+/// Encode a paletted container holding a single value.
+fn single_valued(value: i32, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+    0u8.encode(to)?; // Bits per entry
+    VarInt(value).encode(to)?; // Value
+    VarInt(0).encode(to)?; // Data array length
+
+    Ok(())
+}
+
+impl ClientboundPacket for CChunkDataAndUpdateLight {
+    const PACKET_ID: i32 = 0x28;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        let biome: i32 = Registry::by_name("minecraft:worldgen/biome")
+            .and_then(|r| r.id_of("minecraft:the_void"))
+            .unwrap_or(0);
+        let air: i32 = i32::try_from(BlockState::AIR.id())?;
+
+        let mut data: Vec<u8> = Vec::new();
+        for index in 0..SECTIONS {
+            let blocks: Vec<(usize, BlockState)> = self
+                .blocks
+                .range((index, 0)..(index + 1, 0))
+                .map(|(&(_, index), state)| (index, *state))
+                .collect();
+            if blocks.is_empty() {
+                0i16.encode(&mut data)?; // Non-air block count
+                single_valued(air, &mut data)?;
+            } else {
+                section(&blocks, &mut data)?;
+            }
+            single_valued(biome, &mut data)?;
+        }
+
+        self.x.encode(to)?;
+        self.z.encode(to)?;
+        Nbt::Compound(Vec::new()).encode(to)?; // Heightmaps
+        VarInt(i32::try_from(data.len())?).encode(to)?;
+        to.write_all(&data)?;
+        VarInt(0).encode(to)?; // Block entities
+
+        // Light: all masks empty, no light arrays
+        for _ in 0..4 {
+            VarInt(0).encode(to)?;
+        }
+        VarInt(0).encode(to)?; // Sky light arrays
+        VarInt(0).encode(to)?; // Block light arrays
+
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+//! Client Tick End packet (`0x0B`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::DataTypeEncodeError,
+};
+
+/// Client Tick End, sent by the client at the end of each tick.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SClientTickEnd {}
+
+impl ServerboundPacket<'_> for SClientTickEnd {
+    const PACKET_ID: i32 = 0x0B;
+
+    fn parse(_bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {})
+    }
+
+    fn encode(&self, _to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        Ok(())
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        Ok(connection.state())
+    }
+}
@@ -0,0 +1,28 @@
+//! Clientbound Keep Alive (play) packet (`0x27`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{DataType, DataTypeEncodeError},
+};
+
+/// Clientbound Keep Alive, echoed by the client to prove it is still connected.
+pub struct CKeepAlive {
+    /// ID to echo.
+    id: i64,
+}
+
+impl CKeepAlive {
+    /// Create a Keep Alive packet.
+    pub const fn new(id: i64) -> Self {
+        Self { id }
+    }
+}
+
+impl ClientboundPacket for CKeepAlive {
+    const PACKET_ID: i32 = 0x27;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.id.encode(to)
+    }
+}
@@ -0,0 +1,77 @@
+//! Command Suggestions Request packet (`0x0D`).
+use std::{io::Write, sync::Arc};
+
+use crate::{
+    command::CommandSource,
+    connection::Connection,
+    protocol::{
+        packet::{PacketParseError, ServerboundPacket},
+        play::CCommandSuggestionsResponse,
+    },
+    state::ServerState,
+    types::{var::VarInt, var::VarString, DataType, DataTypeEncodeError},
+};
+
+/// Maximum length of the text to complete.
+const MAX_TEXT_LENGTH: usize = 32500;
+
+/// Command Suggestions Request, sent while the player types a command with an
+/// argument completed by the server.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SCommandSuggestionsRequest<'a> {
+    /// ID of the request, sent back in the response.
+    id: VarInt,
+
+    /// The command typed so far, with the leading slash.
+    text: &'a str,
+}
+
+impl<'a> ServerboundPacket<'a> for SCommandSuggestionsRequest<'a> {
+    const PACKET_ID: i32 = 0x0D;
+
+    fn parse(mut bytes: &'a [u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            id: VarInt::decode(&mut bytes)?,
+            text: VarString::decode_str(&mut bytes, MAX_TEXT_LENGTH)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.id.encode(to)?;
+        VarString::encode_str(self.text, to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        let Some(player) = connection.player() else {
+            return Ok(connection.state());
+        };
+        let text: &str = self.text;
+        let (slash, line): (&str, &str) = text
+            .strip_prefix('/')
+            .map_or(("", text), |line| ("/", line));
+
+        let source: CommandSource = CommandSource::Player(Arc::clone(player));
+        let (start, matches) = connection.server().commands().suggestions(
+            connection.server(),
+            &source,
+            line,
+        );
+        // The client counts in UTF-16 code units
+        let prefix: &str = line.get(..start).unwrap_or_default();
+        let word: &str = line.get(start..).unwrap_or_default();
+        connection.send(&CCommandSuggestionsResponse::new(
+            self.id.0,
+            slash.len() + prefix.encode_utf16().count(),
+            word.encode_utf16().count(),
+            matches,
+        ))?;
+
+        Ok(connection.state())
+    }
+}
@@ -0,0 +1,53 @@
+//! Command Suggestions Response packet (`0x10`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{var::VarInt, var::VarString, DataType, DataTypeEncodeError},
+};
+
+/// Command Suggestions Response, the completions of a Command Suggestions Request.
+pub struct CCommandSuggestionsResponse {
+    /// ID of the request.
+    id: i32,
+
+    /// Index of the first character replaced by the suggestions, in the text of the
+    /// request.
+    start: usize,
+
+    /// Number of characters replaced by the suggestions.
+    length: usize,
+
+    /// The suggestions.
+    matches: Vec<String>,
+}
+
+impl CCommandSuggestionsResponse {
+    /// Answer a request, replacing `length` characters from `start` with one of
+    /// `matches`.
+    pub const fn new(id: i32, start: usize, length: usize, matches: Vec<String>) -> Self {
+        Self {
+            id,
+            start,
+            length,
+            matches,
+        }
+    }
+}
+
+impl ClientboundPacket for CCommandSuggestionsResponse {
+    const PACKET_ID: i32 = 0x10;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarInt(self.id).encode(to)?;
+        VarInt(i32::try_from(self.start)?).encode(to)?;
+        VarInt(i32::try_from(self.length)?).encode(to)?;
+        VarInt(i32::try_from(self.matches.len())?).encode(to)?;
+        for suggestion in &self.matches {
+            VarString::new(suggestion.clone()).encode(to)?;
+            false.encode(to)?; // Has tooltip
+        }
+
+        Ok(())
+    }
+}
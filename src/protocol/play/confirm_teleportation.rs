@@ -0,0 +1,42 @@
+//! Confirm Teleportation packet (`0x00`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{var::VarInt, DataType, DataTypeEncodeError},
+};
+
+/// Confirm Teleportation, acknowledging a Synchronize Player Position.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SConfirmTeleportation {
+    /// ID of the teleport.
+    teleport_id: VarInt,
+}
+
+impl ServerboundPacket<'_> for SConfirmTeleportation {
+    const PACKET_ID: i32 = 0x00;
+
+    fn parse(mut bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            teleport_id: VarInt::decode(&mut bytes)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.teleport_id.encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        log::trace!("Teleport {} confirmed", self.teleport_id);
+
+        Ok(connection.state())
+    }
+}
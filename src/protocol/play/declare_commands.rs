@@ -0,0 +1,102 @@
+//! Commands packet (`0x11`).
+use std::io::Write;
+
+use crate::{
+    command::{tree::NodeKind, DeclaredNode},
+    protocol::packet::ClientboundPacket,
+    types::{var::VarInt, var::VarString, DataType, DataTypeEncodeError},
+};
+
+/// Node type of the root, in the flags.
+const TYPE_ROOT: u8 = 0x00;
+
+/// Node type of a literal, in the flags.
+const TYPE_LITERAL: u8 = 0x01;
+
+/// Node type of an argument, in the flags.
+const TYPE_ARGUMENT: u8 = 0x02;
+
+/// Flag of the nodes the input may end at.
+const FLAG_EXECUTABLE: u8 = 0x04;
+
+/// Flag of the nodes with a redirect.
+const FLAG_REDIRECT: u8 = 0x08;
+
+/// Flag of the arguments with a suggestions type.
+const FLAG_SUGGESTIONS: u8 = 0x10;
+
+/// Suggestions type of the arguments completed by the server.
+const ASK_SERVER: &str = "minecraft:ask_server";
+
+/// Commands, the command graph used by the client for completion and syntax
+/// highlighting.
+pub struct CDeclareCommands {
+    /// The nodes, the root first.
+    nodes: Vec<DeclaredNode>,
+}
+
+impl CDeclareCommands {
+    /// Create a Commands packet from the nodes a player may use, the root first.
+    pub const fn new(nodes: Vec<DeclaredNode>) -> Self {
+        Self { nodes }
+    }
+}
+
+/// Encode an index into the nodes.
+fn encode_index(index: usize, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+    VarInt(i32::try_from(index)?).encode(to)
+}
+
+impl ClientboundPacket for CDeclareCommands {
+    const PACKET_ID: i32 = 0x11;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        encode_index(self.nodes.len(), to)?;
+        for node in &self.nodes {
+            let mut flags: u8 = match &node.kind {
+                NodeKind::Root => TYPE_ROOT,
+                NodeKind::Literal(_) => TYPE_LITERAL,
+                NodeKind::Argument { parser, .. } => {
+                    TYPE_ARGUMENT
+                        | if parser.asks_server() {
+                            FLAG_SUGGESTIONS
+                        } else {
+                            0
+                        }
+                }
+            };
+            if node.executable {
+                flags |= FLAG_EXECUTABLE;
+            }
+            if node.redirect.is_some() {
+                flags |= FLAG_REDIRECT;
+            }
+            flags.encode(to)?;
+
+            encode_index(node.children.len(), to)?;
+            for child in &node.children {
+                encode_index(*child, to)?;
+            }
+            if let Some(redirect) = node.redirect {
+                encode_index(redirect, to)?;
+            }
+
+            match &node.kind {
+                NodeKind::Root => {}
+                NodeKind::Literal(name) => {
+                    VarString::new((*name).to_string()).encode(to)?;
+                }
+                NodeKind::Argument { name, parser } => {
+                    VarString::new((*name).to_string()).encode(to)?;
+                    parser.encode(to)?;
+                    if parser.asks_server() {
+                        VarString::new(ASK_SERVER.to_string()).encode(to)?;
+                    }
+                }
+            }
+        }
+        VarInt(0).encode(to)?; // Root index
+
+        Ok(())
+    }
+}
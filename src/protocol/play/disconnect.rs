@@ -0,0 +1,27 @@
+//! Disconnect (play) packet (`0x1D`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket, text::Text, types::DataTypeEncodeError,
+};
+
+/// Disconnect (play), kicking the player.
+pub struct CPlayDisconnect {
+    /// The reason, shown to the player.
+    reason: Text,
+}
+
+impl CPlayDisconnect {
+    /// Create a Disconnect (play) packet.
+    pub const fn new(reason: Text) -> Self {
+        Self { reason }
+    }
+}
+
+impl ClientboundPacket for CPlayDisconnect {
+    const PACKET_ID: i32 = 0x1D;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.reason.encode(to)
+    }
+}
@@ -0,0 +1,37 @@
+//! Game Event packet (`0x23`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{DataType, DataTypeEncodeError},
+};
+
+/// Game Event, a change of the game state.
+pub struct CGameEvent {
+    /// The event.
+    event: u8,
+
+    /// Value of the event, depending on the event.
+    value: f32,
+}
+
+impl CGameEvent {
+    /// Tell the client chunks are coming, so it can leave the loading screen.
+    pub const START_WAITING_FOR_CHUNKS: u8 = 13;
+
+    /// Create a Game Event packet.
+    pub const fn new(event: u8, value: f32) -> Self {
+        Self { event, value }
+    }
+}
+
+impl ClientboundPacket for CGameEvent {
+    const PACKET_ID: i32 = 0x23;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.event.encode(to)?;
+        self.value.encode(to)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+//! Login (play) packet (`0x2C`).
+use std::io::Write;
+
+use crate::{
+    config::Config,
+    data::registries::Registry,
+    protocol::packet::ClientboundPacket,
+    types::{
+        var::{VarInt, VarString},
+        DataType, DataTypeEncodeError,
+    },
+};
+
+/// The only dimension of the server.
+const DIMENSION: &str = "minecraft:overworld";
+
+/// Login (play), the first packet of the play state.
+pub struct CLogin {
+    /// Entity ID of the player.
+    entity_id: i32,
+
+    /// Maximum number of players, unused by the client.
+    max_players: i32,
+
+    /// View distance in chunks.
+    view_distance: i32,
+
+    /// Simulation distance in chunks.
+    simulation_distance: i32,
+
+    /// Game mode of the player.
+    game_mode: u8,
+}
+
+impl CLogin {
+    /// Create a Login packet for a player.
+    pub fn new(entity_id: i32, config: &Config) -> Self {
+        Self {
+            entity_id,
+            max_players: i32::try_from(config.max_players).unwrap_or(i32::MAX),
+            view_distance: i32::from(config.view_distance),
+            simulation_distance: i32::from(config.simulation_distance),
+            game_mode: config.game_mode.id(),
+        }
+    }
+}
+
+impl ClientboundPacket for CLogin {
+    const PACKET_ID: i32 = 0x2C;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        let dimension_type: i32 = Registry::by_name("minecraft:dimension_type")
+            .and_then(|r| r.id_of(DIMENSION))
+            .unwrap_or(0);
+
+        self.entity_id.encode(to)?;
+        false.encode(to)?; // Hardcore
+        VarInt(1).encode(to)?; // Dimension names
+        VarString::new(DIMENSION.to_string()).encode(to)?;
+        VarInt(self.max_players).encode(to)?;
+        VarInt(self.view_distance).encode(to)?;
+        VarInt(self.simulation_distance).encode(to)?;
+        false.encode(to)?; // Reduced debug info
+        true.encode(to)?; // Enable respawn screen
+        false.encode(to)?; // Do limited crafting
+        VarInt(dimension_type).encode(to)?;
+        VarString::new(DIMENSION.to_string()).encode(to)?;
+        0i64.encode(to)?; // Hashed seed
+        self.game_mode.encode(to)?;
+        (-1i8).encode(to)?; // Previous game mode
+        false.encode(to)?; // Is debug
+        true.encode(to)?; // Is flat
+        false.encode(to)?; // Has death location
+        VarInt(0).encode(to)?; // Portal cooldown
+        VarInt(63).encode(to)?; // Sea level
+        false.encode(to)?; // Enforces secure chat
+
+        Ok(())
+    }
+}
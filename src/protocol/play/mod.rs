@@ -0,0 +1,277 @@
+//! Play state.
+
+use chat_command::SChatCommand;
+use client_tick_end::SClientTickEnd;
+use command_suggestions_request::SCommandSuggestionsRequest;
+use confirm_teleportation::SConfirmTeleportation;
+use cookie_response::SPlayCookieResponse;
+use serverbound_keep_alive::SKeepAlive;
+use set_player_movement_flags::SSetPlayerMovementFlags;
+use set_player_position::SSetPlayerPosition;
+use set_player_position_and_rotation::SSetPlayerPositionAndRotation;
+use set_player_rotation::SSetPlayerRotation;
+use signed_chat_command::SSignedChatCommand;
+
+use super::packet::{ServerboundPacket, StateEnum};
+use crate::{
+    connection::Connection,
+    player::{Player, Position},
+    protocol::PacketParseError,
+    state::ServerState,
+    text::Text,
+    types::{self, DataType, DataTypeEncodeError},
+};
+
+/// Chat Command packet.
+mod chat_command;
+/// Chunk Data and Update Light packet.
+mod chunk_data;
+/// Client Tick End packet.
+mod client_tick_end;
+/// Clientbound Keep Alive (play) packet.
+mod clientbound_keep_alive;
+/// Command Suggestions Request packet.
+mod command_suggestions_request;
+/// Command Suggestions Response packet.
+mod command_suggestions_response;
+/// Confirm Teleportation packet.
+mod confirm_teleportation;
+/// Cookie Request (play) packet.
+mod cookie_request;
+/// Cookie Response (play) packet.
+mod cookie_response;
+/// Commands packet.
+mod declare_commands;
+/// Disconnect (play) packet.
+mod disconnect;
+/// Game Event packet.
+mod game_event;
+/// Login (play) packet.
+mod login;
+/// Serverbound Keep Alive (play) packet.
+mod serverbound_keep_alive;
+/// Set Center Chunk packet.
+mod set_center_chunk;
+/// Set Player Movement Flags packet.
+mod set_player_movement_flags;
+/// Set Player Position packet.
+mod set_player_position;
+/// Set Player Position and Rotation packet.
+mod set_player_position_and_rotation;
+/// Set Player Rotation packet.
+mod set_player_rotation;
+/// Signed Chat Command packet.
+mod signed_chat_command;
+/// Store Cookie (play) packet.
+mod store_cookie;
+/// Synchronize Player Position packet.
+mod synchronize_player_position;
+/// System Chat Message packet.
+mod system_chat_message;
+/// Transfer (play) packet.
+mod transfer;
+/// Update Time packet.
+mod update_time;
+
+pub use chunk_data::CChunkDataAndUpdateLight;
+pub use clientbound_keep_alive::CKeepAlive;
+pub use command_suggestions_response::CCommandSuggestionsResponse;
+pub use cookie_request::CPlayCookieRequest;
+pub use declare_commands::CDeclareCommands;
+pub use disconnect::CPlayDisconnect;
+pub use game_event::CGameEvent;
+pub use login::CLogin;
+pub use set_center_chunk::CSetCenterChunk;
+pub use store_cookie::CPlayStoreCookie;
+pub use synchronize_player_position::CSynchronizePlayerPosition;
+pub use system_chat_message::CSystemChatMessage;
+pub use transfer::CPlayTransfer;
+pub use update_time::CUpdateTime;
+
+/// Where players spawn, above the void.
+pub const SPAWN: Position = Position {
+    x: 0.5,
+    y: 100.0,
+    z: 0.5,
+    yaw: 0.0,
+    pitch: 0.0,
+};
+
+/// Block under [`SPAWN`] where the configured spawn platform is placed.
+const SPAWN_PLATFORM: [i32; 3] = [0, 99, 0];
+
+/// Serverbound packets of the [`ServerState::Play`] state.
+#[derive(Debug, PartialEq)]
+pub enum PlayServerBoundPacket<'a> {
+    /// Confirm Teleportation (`0x00`).
+    ConfirmTeleportation(SConfirmTeleportation),
+
+    /// Chat Command (`0x05`).
+    ChatCommand(SChatCommand<'a>),
+
+    /// Signed Chat Command (`0x06`).
+    SignedChatCommand(SSignedChatCommand<'a>),
+
+    /// Client Tick End (`0x0B`).
+    ClientTickEnd(SClientTickEnd),
+
+    /// Command Suggestions Request (`0x0D`).
+    CommandSuggestionsRequest(SCommandSuggestionsRequest<'a>),
+
+    /// Cookie Response (`0x13`).
+    CookieResponse(SPlayCookieResponse<'a>),
+
+    /// Serverbound Keep Alive (`0x1A`).
+    KeepAlive(SKeepAlive),
+
+    /// Set Player Position (`0x1C`).
+    SetPlayerPosition(SSetPlayerPosition),
+
+    /// Set Player Position and Rotation (`0x1D`).
+    SetPlayerPositionAndRotation(SSetPlayerPositionAndRotation),
+
+    /// Set Player Rotation (`0x1E`).
+    SetPlayerRotation(SSetPlayerRotation),
+
+    /// Set Player Movement Flags (`0x1F`).
+    SetPlayerMovementFlags(SSetPlayerMovementFlags),
+}
+
+impl<'a> StateEnum<'a> for PlayServerBoundPacket<'a> {
+    fn parse(
+        packet_id: i32,
+        bytes: &'a [u8],
+    ) -> Result<Self, super::packet::PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(match packet_id {
+            SConfirmTeleportation::PACKET_ID => {
+                Self::ConfirmTeleportation(SConfirmTeleportation::parse(bytes)?)
+            }
+            SChatCommand::PACKET_ID => Self::ChatCommand(SChatCommand::parse(bytes)?),
+            SSignedChatCommand::PACKET_ID => {
+                Self::SignedChatCommand(SSignedChatCommand::parse(bytes)?)
+            }
+            SClientTickEnd::PACKET_ID => {
+                Self::ClientTickEnd(SClientTickEnd::parse(bytes)?)
+            }
+            SCommandSuggestionsRequest::PACKET_ID => {
+                Self::CommandSuggestionsRequest(SCommandSuggestionsRequest::parse(bytes)?)
+            }
+            SPlayCookieResponse::PACKET_ID => {
+                Self::CookieResponse(SPlayCookieResponse::parse(bytes)?)
+            }
+            SKeepAlive::PACKET_ID => Self::KeepAlive(SKeepAlive::parse(bytes)?),
+            SSetPlayerPosition::PACKET_ID => {
+                Self::SetPlayerPosition(SSetPlayerPosition::parse(bytes)?)
+            }
+            SSetPlayerPositionAndRotation::PACKET_ID => {
+                Self::SetPlayerPositionAndRotation(SSetPlayerPositionAndRotation::parse(
+                    bytes,
+                )?)
+            }
+            SSetPlayerRotation::PACKET_ID => {
+                Self::SetPlayerRotation(SSetPlayerRotation::parse(bytes)?)
+            }
+            SSetPlayerMovementFlags::PACKET_ID => {
+                Self::SetPlayerMovementFlags(SSetPlayerMovementFlags::parse(bytes)?)
+            }
+            other => {
+                return Err(super::packet::PacketParseError::UnknownPacket(
+                    other,
+                    ServerState::Play,
+                ))
+            }
+        })
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        match self {
+            Self::ConfirmTeleportation(p) => p.handle(connection),
+            Self::ChatCommand(p) => p.handle(connection),
+            Self::SignedChatCommand(p) => p.handle(connection),
+            Self::ClientTickEnd(p) => p.handle(connection),
+            Self::CommandSuggestionsRequest(p) => p.handle(connection),
+            Self::CookieResponse(p) => p.handle(connection),
+            Self::KeepAlive(p) => p.handle(connection),
+            Self::SetPlayerPosition(p) => p.handle(connection),
+            Self::SetPlayerPositionAndRotation(p) => p.handle(connection),
+            Self::SetPlayerRotation(p) => p.handle(connection),
+            Self::SetPlayerMovementFlags(p) => p.handle(connection),
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, DataTypeEncodeError> {
+        match self {
+            Self::ConfirmTeleportation(p) => p.to_bytes(),
+            Self::ChatCommand(p) => p.to_bytes(),
+            Self::SignedChatCommand(p) => p.to_bytes(),
+            Self::ClientTickEnd(p) => p.to_bytes(),
+            Self::CommandSuggestionsRequest(p) => p.to_bytes(),
+            Self::CookieResponse(p) => p.to_bytes(),
+            Self::KeepAlive(p) => p.to_bytes(),
+            Self::SetPlayerPosition(p) => p.to_bytes(),
+            Self::SetPlayerPositionAndRotation(p) => p.to_bytes(),
+            Self::SetPlayerRotation(p) => p.to_bytes(),
+            Self::SetPlayerMovementFlags(p) => p.to_bytes(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PlayServerBoundPacket<'a> {
+    type Error = PacketParseError;
+
+    fn try_from(mut value: &'a [u8]) -> Result<Self, Self::Error> {
+        let packet_id: i32 = types::var::VarInt::decode(&mut value)?.0;
+
+        Self::parse(packet_id, value)
+    }
+}
+
+/// Send the packets spawning a player in the world, then announce it.
+pub fn join(connection: &Connection, player: &Player) -> Result<(), DataTypeEncodeError> {
+    let server = connection.server();
+    let view_distance: i32 = i32::from(server.config().view_distance);
+
+    log::info!(
+        "{0} joined with entity ID {1}",
+        player.name(),
+        player.entity_id()
+    );
+
+    connection.send(&CLogin::new(player.entity_id(), server.config()))?;
+    connection.send(&CGameEvent::new(CGameEvent::START_WAITING_FOR_CHUNKS, 0.0))?;
+    connection.send(&CSetCenterChunk::new(0, 0))?;
+    for x in -view_distance..=view_distance {
+        for z in -view_distance..=view_distance {
+            let mut chunk: CChunkDataAndUpdateLight = CChunkDataAndUpdateLight::new(x, z);
+            if let Some(platform) = server.config().spawn_platform {
+                let [x, y, z] = SPAWN_PLATFORM;
+                chunk = chunk.with_block(x, y, z, platform);
+            }
+            connection.send(&chunk)?;
+        }
+    }
+    connection.send(&CSynchronizePlayerPosition::new(0, player.position()))?;
+    server.send_commands(player)?;
+    connection.send(&CUpdateTime::new(
+        server.time().world_age(),
+        server.time().time_of_day(),
+    ))?;
+
+    // The connection switches to the play state once this returns: the broadcast
+    // does not reach the joining player yet
+    let message: Text = Text::translate(
+        "multiplayer.player.joined",
+        vec![Text::plain(player.name())],
+    )
+    .color("yellow");
+    server.broadcast_message(&message);
+    connection.send(&CSystemChatMessage::new(message, false))?;
+
+    Ok(())
+}
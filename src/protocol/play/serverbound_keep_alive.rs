@@ -0,0 +1,48 @@
+//! Serverbound Keep Alive (play) packet (`0x1A`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{DataType, DataTypeEncodeError},
+};
+
+/// Serverbound Keep Alive, the answer to a clientbound Keep Alive.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SKeepAlive {
+    /// ID of the clientbound Keep Alive.
+    id: i64,
+}
+
+impl ServerboundPacket<'_> for SKeepAlive {
+    const PACKET_ID: i32 = 0x1A;
+
+    fn parse(mut bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            id: i64::decode(&mut bytes)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.id.encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if let Some(player) = connection.player() {
+            if let Some(latency) = player.keep_alive_received(self.id) {
+                log::trace!("Latency: {latency:?}");
+            } else {
+                log::debug!("Unexpected keep alive {}", self.id);
+            }
+        }
+
+        Ok(connection.state())
+    }
+}
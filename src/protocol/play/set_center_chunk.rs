@@ -0,0 +1,34 @@
+//! Set Center Chunk packet (`0x58`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{var::VarInt, DataType, DataTypeEncodeError},
+};
+
+/// Set Center Chunk, the chunk the loaded area is centered on.
+pub struct CSetCenterChunk {
+    /// Chunk X coordinate.
+    x: i32,
+
+    /// Chunk Z coordinate.
+    z: i32,
+}
+
+impl CSetCenterChunk {
+    /// Create a Set Center Chunk packet.
+    pub const fn new(x: i32, z: i32) -> Self {
+        Self { x, z }
+    }
+}
+
+impl ClientboundPacket for CSetCenterChunk {
+    const PACKET_ID: i32 = 0x58;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarInt(self.x).encode(to)?;
+        VarInt(self.z).encode(to)?;
+
+        Ok(())
+    }
+}
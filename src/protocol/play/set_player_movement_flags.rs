@@ -0,0 +1,42 @@
+//! Set Player Movement Flags packet (`0x1F`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{DataType, DataTypeEncodeError},
+};
+
+/// Set Player Movement Flags, sent when the player stands still.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SSetPlayerMovementFlags {
+    /// Movement flags (on ground, pushing against a wall).
+    flags: u8,
+}
+
+impl ServerboundPacket<'_> for SSetPlayerMovementFlags {
+    const PACKET_ID: i32 = 0x1F;
+
+    fn parse(mut bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            flags: u8::decode(&mut bytes)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.flags.encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        log::trace!("Movement flags {:#04b}", self.flags);
+
+        Ok(connection.state())
+    }
+}
@@ -0,0 +1,70 @@
+//! Set Player Position packet (`0x1C`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{DataType, DataTypeEncodeError},
+};
+
+/// Set Player Position, sent when the player moves.
+#[derive(Debug, PartialEq)]
+pub struct SSetPlayerPosition {
+    /// X coordinate.
+    x: f64,
+
+    /// Y coordinate (feet).
+    y: f64,
+
+    /// Z coordinate.
+    z: f64,
+
+    /// Movement flags (on ground, pushing against a wall).
+    flags: u8,
+}
+
+impl ServerboundPacket<'_> for SSetPlayerPosition {
+    const PACKET_ID: i32 = 0x1C;
+
+    fn parse(mut bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            x: f64::decode(&mut bytes)?,
+            y: f64::decode(&mut bytes)?,
+            z: f64::decode(&mut bytes)?,
+            flags: u8::decode(&mut bytes)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.x.encode(to)?;
+        self.y.encode(to)?;
+        self.z.encode(to)?;
+        self.flags.encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if let Some(player) = connection.player() {
+            player.update_position(|position| {
+                position.x = self.x;
+                position.y = self.y;
+                position.z = self.z;
+            });
+        }
+        log::trace!(
+            "Moved to {0} {1} {2} (flags {3:#04b})",
+            self.x,
+            self.y,
+            self.z,
+            self.flags
+        );
+
+        Ok(connection.state())
+    }
+}
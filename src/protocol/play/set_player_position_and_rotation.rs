@@ -0,0 +1,61 @@
+//! Set Player Position and Rotation packet (`0x1D`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    player::Position,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{DataType, DataTypeEncodeError},
+};
+
+/// Set Player Position and Rotation, sent when the player moves and looks around.
+#[derive(Debug, PartialEq)]
+pub struct SSetPlayerPositionAndRotation {
+    /// New position and rotation.
+    position: Position,
+
+    /// Movement flags (on ground, pushing against a wall).
+    flags: u8,
+}
+
+impl ServerboundPacket<'_> for SSetPlayerPositionAndRotation {
+    const PACKET_ID: i32 = 0x1D;
+
+    fn parse(mut bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            position: Position {
+                x: f64::decode(&mut bytes)?,
+                y: f64::decode(&mut bytes)?,
+                z: f64::decode(&mut bytes)?,
+                yaw: f32::decode(&mut bytes)?,
+                pitch: f32::decode(&mut bytes)?,
+            },
+            flags: u8::decode(&mut bytes)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.position.x.encode(to)?;
+        self.position.y.encode(to)?;
+        self.position.z.encode(to)?;
+        self.position.yaw.encode(to)?;
+        self.position.pitch.encode(to)?;
+        self.flags.encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if let Some(player) = connection.player() {
+            player.update_position(|position| *position = self.position);
+        }
+        log::trace!("Moved to {0:?} (flags {1:#04b})", self.position, self.flags);
+
+        Ok(connection.state())
+    }
+}
@@ -0,0 +1,63 @@
+//! Set Player Rotation packet (`0x1E`).
+use std::io::Write;
+
+use crate::{
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{DataType, DataTypeEncodeError},
+};
+
+/// Set Player Rotation, sent when the player looks around.
+#[derive(Debug, PartialEq)]
+pub struct SSetPlayerRotation {
+    /// Yaw in degrees.
+    yaw: f32,
+
+    /// Pitch in degrees.
+    pitch: f32,
+
+    /// Movement flags (on ground, pushing against a wall).
+    flags: u8,
+}
+
+impl ServerboundPacket<'_> for SSetPlayerRotation {
+    const PACKET_ID: i32 = 0x1E;
+
+    fn parse(mut bytes: &[u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            yaw: f32::decode(&mut bytes)?,
+            pitch: f32::decode(&mut bytes)?,
+            flags: u8::decode(&mut bytes)?,
+        })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.yaw.encode(to)?;
+        self.pitch.encode(to)?;
+        self.flags.encode(to)
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if let Some(player) = connection.player() {
+            player.update_position(|position| {
+                position.yaw = self.yaw;
+                position.pitch = self.pitch;
+            });
+        }
+        log::trace!(
+            "Rotated to {0} {1} (flags {2:#04b})",
+            self.yaw,
+            self.pitch,
+            self.flags
+        );
+
+        Ok(connection.state())
+    }
+}
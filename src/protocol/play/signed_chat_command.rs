@@ -0,0 +1,91 @@
+//! Signed Chat Command packet (`0x06`).
+use std::{io::Write, sync::Arc};
+
+use crate::{
+    command::CommandSource,
+    connection::Connection,
+    protocol::packet::{PacketParseError, ServerboundPacket},
+    state::ServerState,
+    types::{
+        var::{VarInt, VarString},
+        DataType, DataTypeDecodeError, DataTypeEncodeError, ReadBytes,
+    },
+};
+
+/// Maximum number of signed arguments.
+const MAX_ARGUMENT_SIGNATURES: usize = 8;
+
+/// Maximum length of the name of a signed argument.
+const MAX_ARGUMENT_NAME_LENGTH: usize = 16;
+
+/// Length of a message signature.
+const SIGNATURE_LENGTH: usize = 256;
+
+/// Length of the acknowledged messages bit set (20 bits).
+const ACKNOWLEDGED_LENGTH: usize = 3;
+
+/// Signed Chat Command, a command with message arguments signed by the client.
+///
+/// Secure chat is not enforced: the signatures are read and ignored.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SSignedChatCommand<'a> {
+    /// The command, without the leading slash.
+    command: &'a str,
+}
+
+impl<'a> ServerboundPacket<'a> for SSignedChatCommand<'a> {
+    const PACKET_ID: i32 = 0x06;
+
+    fn parse(mut bytes: &'a [u8]) -> Result<Self, PacketParseError>
+    where
+        Self: Sized,
+    {
+        let command: &str = VarString::decode_str(&mut bytes, VarString::MAX_LENGTH)?;
+        i64::decode(&mut bytes)?; // Timestamp
+        i64::decode(&mut bytes)?; // Salt
+
+        let count: usize = usize::try_from(VarInt::decode(&mut bytes)?.0)
+            .map_err(DataTypeDecodeError::from)?;
+        if count > MAX_ARGUMENT_SIGNATURES {
+            return Err(DataTypeDecodeError::ByteArrayTooLong {
+                length: count,
+                max_length: MAX_ARGUMENT_SIGNATURES,
+            }
+            .into());
+        }
+        for _ in 0..count {
+            VarString::decode_str(&mut bytes, MAX_ARGUMENT_NAME_LENGTH)?;
+            bytes.read_bytes(SIGNATURE_LENGTH)?;
+        }
+
+        VarInt::decode(&mut bytes)?; // Message count
+        bytes.read_bytes(ACKNOWLEDGED_LENGTH)?;
+
+        Ok(Self { command })
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        // Unsigned: no timestamp, salt, signatures nor acknowledged messages
+        VarString::encode_str(self.command, to)?;
+        0_i64.encode(to)?;
+        0_i64.encode(to)?;
+        VarInt(0).encode(to)?;
+        VarInt(0).encode(to)?;
+        to.write_all(&[0; ACKNOWLEDGED_LENGTH])?;
+
+        Ok(())
+    }
+
+    fn handle(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<ServerState, DataTypeEncodeError> {
+        if let Some(player) = connection.player() {
+            let source: CommandSource = CommandSource::Player(Arc::clone(player));
+            // Errors are sent back to the player by the dispatcher
+            let _ = connection.server().run_command(&source, self.command);
+        }
+
+        Ok(connection.state())
+    }
+}
@@ -0,0 +1,46 @@
+//! Synchronize Player Position packet (`0x42`).
+use std::io::Write;
+
+use crate::{
+    player::Position,
+    protocol::packet::ClientboundPacket,
+    types::{var::VarInt, DataType, DataTypeEncodeError},
+};
+
+/// Synchronize Player Position, teleporting the player.
+pub struct CSynchronizePlayerPosition {
+    /// ID of the teleport, confirmed by the client.
+    teleport_id: i32,
+
+    /// Absolute position and rotation.
+    position: Position,
+}
+
+impl CSynchronizePlayerPosition {
+    /// Create a Synchronize Player Position packet.
+    pub const fn new(teleport_id: i32, position: Position) -> Self {
+        Self {
+            teleport_id,
+            position,
+        }
+    }
+}
+
+impl ClientboundPacket for CSynchronizePlayerPosition {
+    const PACKET_ID: i32 = 0x42;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        VarInt(self.teleport_id).encode(to)?;
+        self.position.x.encode(to)?;
+        self.position.y.encode(to)?;
+        self.position.z.encode(to)?;
+        for _ in 0..3 {
+            0f64.encode(to)?; // Velocity
+        }
+        self.position.yaw.encode(to)?;
+        self.position.pitch.encode(to)?;
+        0i32.encode(to)?; // Flags: all absolute
+
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+//! System Chat Message packet (`0x73`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    text::Text,
+    types::{DataType, DataTypeEncodeError},
+};
+
+/// System Chat Message, a message from the server.
+pub struct CSystemChatMessage {
+    /// The message.
+    content: Text,
+
+    /// Whether to show the message above the hotbar instead of in the chat.
+    overlay: bool,
+}
+
+impl CSystemChatMessage {
+    /// Create a System Chat Message packet.
+    pub const fn new(content: Text, overlay: bool) -> Self {
+        Self { content, overlay }
+    }
+}
+
+impl ClientboundPacket for CSystemChatMessage {
+    const PACKET_ID: i32 = 0x73;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.content.encode(to)?;
+        self.overlay.encode(to)?;
+
+        Ok(())
+    }
+}
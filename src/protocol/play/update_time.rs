@@ -0,0 +1,38 @@
+//! Update Time packet (`0x6B`).
+use std::io::Write;
+
+use crate::{
+    protocol::packet::ClientboundPacket,
+    types::{DataType, DataTypeEncodeError},
+};
+
+/// Update Time, the age of the world and the time of day.
+pub struct CUpdateTime {
+    /// Age of the world, in ticks.
+    world_age: i64,
+
+    /// Time of day, in ticks.
+    time_of_day: i64,
+}
+
+impl CUpdateTime {
+    /// Create an Update Time packet.
+    pub const fn new(world_age: i64, time_of_day: i64) -> Self {
+        Self {
+            world_age,
+            time_of_day,
+        }
+    }
+}
+
+impl ClientboundPacket for CUpdateTime {
+    const PACKET_ID: i32 = 0x6B;
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.world_age.encode(to)?;
+        self.time_of_day.encode(to)?;
+        true.encode(to)?; // Time of day increasing
+
+        Ok(())
+    }
+}
@@ -1,29 +1,39 @@
-use std::{collections::VecDeque, net::TcpStream};
+//! Status state.
 
 use ping_request::SPingRequest;
 use status_request::SStatusRequest;
 
 use super::packet::{ServerboundPacket, StateEnum};
 use crate::{
+    connection::Connection,
     protocol::PacketParseError,
     state::ServerState,
     types::{self, DataType, DataTypeEncodeError},
 };
 
+/// Ping Request packet.
 mod ping_request;
+/// Ping Response packet.
 mod ping_response;
+/// Status Request packet.
 mod status_request;
+/// Status Response packet.
 mod status_response;
 
+/// Serverbound packets of the [`ServerState::Status`] state.
+#[derive(Debug, PartialEq, Eq)]
 pub enum StatusServerBoundPacket {
+    /// Status Request (`0x00`).
     StatusRequest(SStatusRequest),
+
+    /// Ping Request (`0x01`).
     PingRequest(SPingRequest),
 }
 
-impl StateEnum for StatusServerBoundPacket {
+impl StateEnum<'_> for StatusServerBoundPacket {
     fn parse(
         packet_id: i32,
-        bytes: VecDeque<u8>,
+        bytes: &[u8],
     ) -> Result<Self, super::packet::PacketParseError>
     where
         Self: Sized,
@@ -44,21 +54,26 @@ impl StateEnum for StatusServerBoundPacket {
 
     fn handle(
         &self,
-        server_state: ServerState,
-        addr: &str,
-        stream: &mut TcpStream,
+        connection: &mut Connection,
     ) -> Result<ServerState, DataTypeEncodeError> {
         match self {
-            Self::StatusRequest(p) => p.handle(server_state, addr, stream),
-            Self::PingRequest(p) => p.handle(server_state, addr, stream),
+            Self::StatusRequest(p) => p.handle(connection),
+            Self::PingRequest(p) => p.handle(connection),
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, DataTypeEncodeError> {
+        match self {
+            Self::StatusRequest(p) => p.to_bytes(),
+            Self::PingRequest(p) => p.to_bytes(),
         }
     }
 }
 
-impl TryFrom<VecDeque<u8>> for StatusServerBoundPacket {
+impl TryFrom<&[u8]> for StatusServerBoundPacket {
     type Error = PacketParseError;
 
-    fn try_from(mut value: VecDeque<u8>) -> Result<Self, Self::Error> {
+    fn try_from(mut value: &[u8]) -> Result<Self, Self::Error> {
         let packet_id: i32 = types::var::VarInt::decode(&mut value)?.0;
 
         Self::parse(packet_id, value)
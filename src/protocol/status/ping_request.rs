@@ -1,6 +1,8 @@
-use std::{collections::VecDeque, net::TcpStream};
+//! Ping Request packet (`0x01`).
+use std::io::Write;
 
 use crate::{
+    connection::Connection,
     protocol::{
         packet::{ClientboundPacket, PacketParseError, ServerboundPacket},
         status::ping_response::CPingResponse,
@@ -9,15 +11,17 @@ use crate::{
     types::{DataType, DataTypeEncodeError},
 };
 
-#[derive(Debug)]
+/// Ping Request, answered with a [`CPingResponse`].
+#[derive(Debug, PartialEq, Eq)]
 pub struct SPingRequest {
+    /// Payload to send back, usually a timestamp.
     time: i64,
 }
 
-impl ServerboundPacket for SPingRequest {
+impl ServerboundPacket<'_> for SPingRequest {
     const PACKET_ID: i32 = 1;
 
-    fn parse(mut bytes: VecDeque<u8>) -> Result<Self, PacketParseError>
+    fn parse(mut bytes: &[u8]) -> Result<Self, PacketParseError>
     where
         Self: Sized,
     {
@@ -26,17 +30,19 @@ impl ServerboundPacket for SPingRequest {
         })
     }
 
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.time.encode(to)
+    }
+
     fn handle(
         &self,
-        _server_state: ServerState,
-        addr: &str,
-        stream: &mut TcpStream,
+        connection: &mut Connection,
     ) -> Result<ServerState, DataTypeEncodeError> {
-        log::debug!(target: addr, "Received ping request at {}", self.time);
+        log::debug!("Received ping request at {}", self.time);
 
         let packet: CPingResponse = CPingResponse::new(self.time);
 
-        packet.send(addr, stream)?;
+        packet.send(connection)?;
 
         Ok(ServerState::Closed)
     }
@@ -1,3 +1,4 @@
+//! Ping Response packet (`0x01`).
 use std::io::Write;
 
 use crate::{
@@ -5,11 +6,14 @@ use crate::{
     types::{DataType, DataTypeEncodeError},
 };
 
+/// Ping Response, echoing the payload of the Ping Request.
 pub struct CPingResponse {
+    /// Payload of the Ping Request.
     time: i64,
 }
 
 impl CPingResponse {
+    /// Create a Ping Response for the payload of a Ping Request.
     pub const fn new(time: i64) -> Self {
         Self { time }
     }
@@ -1,41 +1,74 @@
-use std::{collections::VecDeque, net::TcpStream};
+//! Status Request packet (`0x00`).
+use std::io::Write;
+
+use serde_json::{json, Value};
 
 use crate::{
+    connection::Connection,
     protocol::{
         packet::{ClientboundPacket, PacketParseError, ServerboundPacket},
         status::status_response::CStatusResponse,
     },
+    server::{Server, ServerStatus, PROTOCOL_VERSION, VERSION_NAME},
     state::ServerState,
+    text::Text,
     types::DataTypeEncodeError,
 };
 
-const RESPONSE: &str = "{\"version\":{\"name\":\"1.21.2\",\"protocol\":768},\"players\":{\"max\":100,\"online\":5,\"sample\":[{\"name\":\"thinkofdeath\",\"id\":\"4566e69f-c907-48ee-8d71-d7ba5aa00d20\"}]},\"description\":{\"text\":\"Hello, world!\"},\"favicon\":\"data:image/png;base64,<data>\",\"enforcesSecureChat\":false}";
+/// Maximum number of players listed in the server list sample, as in vanilla.
+const SAMPLE_SIZE: usize = 12;
 
-#[derive(Debug)]
+/// Status Request, answered with a [`CStatusResponse`].
+#[derive(Debug, PartialEq, Eq)]
 pub struct SStatusRequest {}
 
-impl ServerboundPacket for SStatusRequest {
+impl ServerboundPacket<'_> for SStatusRequest {
     const PACKET_ID: i32 = 0;
 
-    fn parse(mut _bytes: VecDeque<u8>) -> Result<Self, PacketParseError>
+    fn parse(_bytes: &[u8]) -> Result<Self, PacketParseError>
     where
         Self: Sized,
     {
         Ok(Self {})
     }
 
+    fn encode(&self, _to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        Ok(())
+    }
+
     fn handle(
         &self,
-        server_state: ServerState,
-        addr: &str,
-        stream: &mut TcpStream,
+        connection: &mut Connection,
     ) -> Result<ServerState, DataTypeEncodeError> {
-        log::debug!(target: addr, "Received status request");
+        log::debug!("Received status request");
 
-        let packet: CStatusResponse = CStatusResponse::new(RESPONSE.to_string());
+        let packet: CStatusResponse = CStatusResponse::new(status(connection.server()));
 
-        packet.send(addr, stream)?;
+        packet.send(connection)?;
 
-        Ok(server_state)
+        Ok(connection.state())
     }
 }
+
+/// Build the status of the server, as JSON.
+fn status(server: &Server) -> String {
+    let status: ServerStatus = server.status();
+    let sample: Vec<Value> = status
+        .players
+        .iter()
+        .take(SAMPLE_SIZE)
+        .map(|p| json!({ "name": p.name(), "id": p.uuid().to_string() }))
+        .collect();
+
+    json!({
+        "version": { "name": VERSION_NAME, "protocol": PROTOCOL_VERSION },
+        "players": {
+            "max": status.max_players,
+            "online": status.players.len(),
+            "sample": sample,
+        },
+        "description": Text::plain(status.motd).to_json(),
+        "enforcesSecureChat": false,
+    })
+    .to_string()
+}
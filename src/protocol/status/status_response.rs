@@ -1,3 +1,4 @@
+//! Status Response packet (`0x00`).
 use std::io::Write;
 
 use crate::{
@@ -5,11 +6,14 @@ use crate::{
     types::{var::VarString, DataType, DataTypeEncodeError},
 };
 
+/// Status Response, the server list entry.
 pub struct CStatusResponse {
+    /// The status, as JSON.
     json_response: VarString,
 }
 
 impl CStatusResponse {
+    /// Create a Status Response from a JSON status.
     pub fn new(response: String) -> Self {
         Self {
             json_response: VarString::new(response),
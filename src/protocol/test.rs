@@ -0,0 +1,561 @@
+//! Test [`crate::protocol`]: serverbound packets round trip through their state
+//! enum, clientbound packets decode to what they were created with.
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use proptest::{collection::vec, option, prelude::*};
+
+    use crate::{
+        data::blocks::BlockState,
+        player::Position,
+        protocol::{
+            configuration::{CClientboundKnownPacks, KnownPack},
+            packet::{ClientboundPacket, StateEnum},
+            play::{
+                CChunkDataAndUpdateLight, CCommandSuggestionsResponse, CGameEvent,
+                CKeepAlive, CSetCenterChunk, CSynchronizePlayerPosition, CUpdateTime,
+            },
+            CConfigurationCookieRequest, CConfigurationStoreCookie,
+            CConfigurationTransfer, CLoginCookieRequest, CLoginPluginRequest,
+            CPlayCookieRequest, CPlayStoreCookie, CPlayTransfer, Cipher, CipherReader,
+            Codec, ConfigurationServerBoundPacket, HandshakeServerBoundPacket,
+            LoginServerBoundPacket, PacketParseError, PlayServerBoundPacket,
+            StatusServerBoundPacket,
+        },
+        types::{
+            uuid::Uuid,
+            var::{VarInt, VarString},
+            DataType, DataTypeDecodeError,
+        },
+    };
+
+    /// Bytes of a packet, written field by field.
+    struct Packet(Vec<u8>);
+
+    impl Packet {
+        /// Start a packet with its ID.
+        fn new(id: i32) -> Self {
+            Self(Vec::new()).field(&VarInt(id))
+        }
+
+        /// Append a field.
+        fn field<I, T: DataType<I>>(mut self, value: &T) -> Self {
+            value.encode(&mut self.0).unwrap();
+            self
+        }
+
+        /// Append a string field.
+        fn string(self, value: &str) -> Self {
+            self.field(&VarString::new(value.to_string()))
+        }
+
+        /// Append bytes as they are.
+        fn raw(mut self, bytes: &[u8]) -> Self {
+            self.0.extend_from_slice(bytes);
+            self
+        }
+    }
+
+    /// Parse `packet` with the state enum `$state`, encode it back to the same
+    /// bytes and parse these into the same packet. A macro rather than a function,
+    /// as each parsed packet borrows from the bytes it is parsed from.
+    macro_rules! round_trip {
+        ($state:ident, $packet:expr $(,)?) => {{
+            let bytes: Vec<u8> = $packet.0;
+            let parsed: $state = $state::try_from(bytes.as_slice())
+                .map_err(|e| TestCaseError::fail(format!("{e} parsing {bytes:02x?}")))?;
+            let encoded: Vec<u8> = parsed.to_bytes().unwrap();
+            prop_assert_eq!(&encoded, &bytes);
+            prop_assert_eq!($state::try_from(encoded.as_slice()).unwrap(), parsed);
+        }};
+    }
+
+    /// Data of a clientbound packet, after checking its ID.
+    fn data<P: ClientboundPacket>(packet: &P) -> Vec<u8> {
+        let bytes: Vec<u8> = packet.to_bytes().unwrap();
+        let mut data: &[u8] = &bytes;
+        assert_eq!(VarInt::decode(&mut data).unwrap(), VarInt(P::PACKET_ID));
+        data.to_vec()
+    }
+
+    /// Decode a string of a clientbound packet.
+    fn string(bytes: &mut &[u8]) -> String {
+        VarString::decode(bytes).unwrap().get()
+    }
+
+    /// Finite coordinates, as sent by a client.
+    fn coordinate() -> impl Strategy<Value = f64> {
+        -3.0e7..3.0e7
+    }
+
+    /// Finite angles, in degrees.
+    fn angle() -> impl Strategy<Value = f32> {
+        -360.0_f32..360.0
+    }
+
+    /// Cookie Response data, after the packet ID.
+    fn cookie_response() -> impl Strategy<Value = (String, Option<Vec<u8>>)> {
+        (
+            "[a-z_]{1,16}:[a-z_/]{1,32}",
+            option::of(vec(any::<u8>(), 0..64)),
+        )
+    }
+
+    /// Append a Cookie Response to `packet`.
+    fn with_cookie(packet: Packet, (key, payload): &(String, Option<Vec<u8>>)) -> Packet {
+        let packet: Packet = packet.string(key).field(&payload.is_some());
+        match payload {
+            Some(payload) => packet
+                .field(&VarInt(i32::try_from(payload.len()).unwrap()))
+                .raw(payload),
+            None => packet,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_handshake(
+            version: i32,
+            host in "[a-z0-9.-]{0,255}",
+            forwarded in option::of("\\PC{0,64}"),
+            port: u16,
+            next_state in 1..=3,
+        ) {
+            let address: String = forwarded
+                .as_ref()
+                .map_or_else(|| host.clone(), |forwarded| format!("{host}\0{forwarded}"));
+            round_trip!(
+                HandshakeServerBoundPacket,
+                Packet::new(0x00)
+                    .field(&VarInt(version))
+                    .string(&address)
+                    .field(&port)
+                    .field(&VarInt(next_state)),
+            );
+        }
+
+        #[test]
+        fn test_status(time: i64) {
+            round_trip!(StatusServerBoundPacket, Packet::new(0x00));
+            round_trip!(StatusServerBoundPacket, Packet::new(0x01).field(&time));
+        }
+
+        #[test]
+        fn test_login(
+            name in "[A-Za-z0-9_]{1,16}",
+            uuid: u128,
+            message_id: i32,
+            data in option::of(vec(any::<u8>(), 0..64)),
+            cookie in cookie_response(),
+        ) {
+            round_trip!(
+                LoginServerBoundPacket,
+                Packet::new(0x00).string(&name).field(&Uuid(uuid)),
+            );
+            let response: Packet = Packet::new(0x02)
+                .field(&VarInt(message_id))
+                .field(&data.is_some());
+            round_trip!(LoginServerBoundPacket, match &data {
+                Some(data) => response.raw(data),
+                None => response,
+            });
+            round_trip!(LoginServerBoundPacket, Packet::new(0x03));
+            round_trip!(LoginServerBoundPacket, with_cookie(Packet::new(0x04), &cookie));
+        }
+
+        #[test]
+        fn test_configuration(
+            locale in "[a-z]{2}_[a-z]{2}",
+            view_distance: u8,
+            channel in "[a-z]{1,16}:[a-z/]{1,32}",
+            data in vec(any::<u8>(), 0..64),
+            packs in vec(("[a-z]{1,16}", "[a-z_]{1,16}", "[0-9.]{1,8}"), 0..4),
+            cookie in cookie_response(),
+        ) {
+            round_trip!(
+                ConfigurationServerBoundPacket,
+                Packet::new(0x00).string(&locale).field(&view_distance),
+            );
+            round_trip!(
+                ConfigurationServerBoundPacket,
+                with_cookie(Packet::new(0x01), &cookie),
+            );
+            round_trip!(
+                ConfigurationServerBoundPacket,
+                Packet::new(0x02).string(&channel).raw(&data),
+            );
+            round_trip!(ConfigurationServerBoundPacket, Packet::new(0x03));
+
+            let mut known: Packet =
+                Packet::new(0x07).field(&VarInt(i32::try_from(packs.len()).unwrap()));
+            for (namespace, id, version) in &packs {
+                known = known.string(namespace).string(id).string(version);
+            }
+            round_trip!(ConfigurationServerBoundPacket, known);
+        }
+
+        #[test]
+        fn test_play(
+            teleport_id: i32,
+            command in "\\PC{0,256}",
+            suggestions_id: i32,
+            keep_alive: i64,
+            (x, y, z) in (coordinate(), coordinate(), coordinate()),
+            (yaw, pitch) in (angle(), angle()),
+            flags in 0_u8..4,
+            cookie in cookie_response(),
+        ) {
+            round_trip!(PlayServerBoundPacket, Packet::new(0x00).field(&VarInt(teleport_id)));
+            round_trip!(PlayServerBoundPacket, Packet::new(0x05).string(&command));
+            // Unsigned: signed arguments are read and dropped
+            round_trip!(
+                PlayServerBoundPacket,
+                Packet::new(0x06)
+                    .string(&command)
+                    .field(&0_i64)
+                    .field(&0_i64)
+                    .field(&VarInt(0))
+                    .field(&VarInt(0))
+                    .raw(&[0; 3]),
+            );
+            round_trip!(PlayServerBoundPacket, Packet::new(0x0B));
+            round_trip!(
+                PlayServerBoundPacket,
+                Packet::new(0x0D).field(&VarInt(suggestions_id)).string(&format!("/{command}")),
+            );
+            round_trip!(PlayServerBoundPacket, with_cookie(Packet::new(0x13), &cookie));
+            round_trip!(PlayServerBoundPacket, Packet::new(0x1A).field(&keep_alive));
+            round_trip!(
+                PlayServerBoundPacket,
+                Packet::new(0x1C).field(&x).field(&y).field(&z).field(&flags),
+            );
+            round_trip!(
+                PlayServerBoundPacket,
+                Packet::new(0x1D)
+                    .field(&x)
+                    .field(&y)
+                    .field(&z)
+                    .field(&yaw)
+                    .field(&pitch)
+                    .field(&flags),
+            );
+            round_trip!(
+                PlayServerBoundPacket,
+                Packet::new(0x1E).field(&yaw).field(&pitch).field(&flags),
+            );
+            round_trip!(PlayServerBoundPacket, Packet::new(0x1F).field(&flags));
+        }
+
+        #[test]
+        fn test_clientbound(
+            (a, b) in any::<(i32, i32)>(),
+            (c, d) in any::<(i64, i64)>(),
+            event: u8,
+            value: f32,
+            key in "[a-z_]{1,16}:[a-z_/]{1,32}",
+            payload in vec(any::<u8>(), 0..64),
+            host in "[a-z0-9.-]{1,64}",
+            port: u16,
+            matches in vec("\\PC{0,16}", 0..4),
+            (start, length) in (0_usize..1024, 0_usize..1024),
+        ) {
+            let packet: Vec<u8> = data(&CKeepAlive::new(c));
+            let mut bytes: &[u8] = &packet;
+            prop_assert_eq!(i64::decode(&mut bytes).unwrap(), c);
+
+            let packet: Vec<u8> = data(&CGameEvent::new(event, value));
+            let mut bytes: &[u8] = &packet;
+            prop_assert_eq!(u8::decode(&mut bytes).unwrap(), event);
+            prop_assert_eq!(f32::decode(&mut bytes).unwrap().to_bits(), value.to_bits());
+
+            let packet: Vec<u8> = data(&CSetCenterChunk::new(a, b));
+            let mut bytes: &[u8] = &packet;
+            prop_assert_eq!(VarInt::decode(&mut bytes).unwrap(), VarInt(a));
+            prop_assert_eq!(VarInt::decode(&mut bytes).unwrap(), VarInt(b));
+
+            let packet: Vec<u8> = data(&CUpdateTime::new(c, d));
+            let mut bytes: &[u8] = &packet;
+            prop_assert_eq!(i64::decode(&mut bytes).unwrap(), c);
+            prop_assert_eq!(i64::decode(&mut bytes).unwrap(), d);
+            prop_assert!(bool::decode(&mut bytes).unwrap());
+            prop_assert!(bytes.is_empty());
+
+            let position: Position = Position { x: 1.5, y: -2.0, z: 3.25, yaw: 90.0, pitch: -45.0 };
+            let packet: Vec<u8> = data(&CSynchronizePlayerPosition::new(a, position));
+            let mut bytes: &[u8] = &packet;
+            prop_assert_eq!(VarInt::decode(&mut bytes).unwrap(), VarInt(a));
+            let mut decoded: [f64; 6] = [0.0; 6];
+            for coordinate in &mut decoded {
+                *coordinate = f64::decode(&mut bytes).unwrap();
+            }
+            prop_assert_eq!(
+                decoded.map(f64::to_bits),
+                [1.5, -2.0, 3.25, 0.0, 0.0, 0.0].map(f64::to_bits)
+            );
+            prop_assert_eq!(f32::decode(&mut bytes).unwrap().to_bits(), 90.0_f32.to_bits());
+            prop_assert_eq!(f32::decode(&mut bytes).unwrap().to_bits(), (-45.0_f32).to_bits());
+            prop_assert_eq!(i32::decode(&mut bytes).unwrap(), 0);
+            prop_assert!(bytes.is_empty());
+
+            for packet in [
+                data(&CLoginCookieRequest::new(&key)),
+                data(&CConfigurationCookieRequest::new(&key)),
+                data(&CPlayCookieRequest::new(&key)),
+            ] {
+                let mut bytes: &[u8] = &packet;
+                prop_assert_eq!(&string(&mut bytes), &key);
+                prop_assert!(bytes.is_empty());
+            }
+
+            for packet in [
+                data(&CConfigurationStoreCookie::new(&key, payload.clone())),
+                data(&CPlayStoreCookie::new(&key, payload.clone())),
+            ] {
+                let mut bytes: &[u8] = &packet;
+                prop_assert_eq!(&string(&mut bytes), &key);
+                let length: VarInt = VarInt::decode(&mut bytes).unwrap();
+                prop_assert_eq!(usize::try_from(length.0).unwrap(), payload.len());
+                prop_assert_eq!(bytes, payload.as_slice());
+            }
+
+            for packet in [
+                data(&CConfigurationTransfer::new(&host, port)),
+                data(&CPlayTransfer::new(&host, port)),
+            ] {
+                let mut bytes: &[u8] = &packet;
+                prop_assert_eq!(&string(&mut bytes), &host);
+                prop_assert_eq!(VarInt::decode(&mut bytes).unwrap(), VarInt(i32::from(port)));
+                prop_assert!(bytes.is_empty());
+            }
+
+            let packet: Vec<u8> =
+                data(&CLoginPluginRequest::new(a, &key, payload.clone()));
+            let mut bytes: &[u8] = &packet;
+            prop_assert_eq!(VarInt::decode(&mut bytes).unwrap(), VarInt(a));
+            prop_assert_eq!(&string(&mut bytes), &key);
+            prop_assert_eq!(bytes, payload.as_slice());
+
+            let packs: Vec<KnownPack> = matches
+                .iter()
+                .map(|id| KnownPack {
+                    namespace: key.clone().into(),
+                    id: id.clone().into(),
+                    version: host.clone().into(),
+                })
+                .collect();
+            let packet: Vec<u8> = data(&CClientboundKnownPacks::new(packs.clone()));
+            let mut bytes: &[u8] = &packet;
+            prop_assert_eq!(usize::try_from(VarInt::decode(&mut bytes).unwrap().0).unwrap(), packs.len());
+            for pack in &packs {
+                let decoded: KnownPack = KnownPack {
+                    namespace: string(&mut bytes).into(),
+                    id: string(&mut bytes).into(),
+                    version: string(&mut bytes).into(),
+                };
+                prop_assert_eq!(&decoded, pack);
+            }
+            prop_assert!(bytes.is_empty());
+
+            let packet: Vec<u8> =
+                data(&CCommandSuggestionsResponse::new(a, start, length, matches.clone()));
+            let mut bytes: &[u8] = &packet;
+            prop_assert_eq!(VarInt::decode(&mut bytes).unwrap(), VarInt(a));
+            prop_assert_eq!(usize::try_from(VarInt::decode(&mut bytes).unwrap().0).unwrap(), start);
+            prop_assert_eq!(usize::try_from(VarInt::decode(&mut bytes).unwrap().0).unwrap(), length);
+            prop_assert_eq!(usize::try_from(VarInt::decode(&mut bytes).unwrap().0).unwrap(), matches.len());
+            for suggestion in &matches {
+                prop_assert_eq!(&string(&mut bytes), suggestion);
+                prop_assert!(!bool::decode(&mut bytes).unwrap()); // No tooltip
+            }
+            prop_assert!(bytes.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_malformed() {
+        fn parse(bytes: &[u8]) -> Result<PlayServerBoundPacket<'_>, PacketParseError> {
+            PlayServerBoundPacket::try_from(bytes)
+        }
+
+        // Truncated Set Player Position
+        assert!(matches!(
+            parse(&[0x1C, 0x00, 0x00]),
+            Err(PacketParseError::DataTypeDecodeError(
+                DataTypeDecodeError::PrematureEnd
+            ))
+        ));
+        // Overlong packet ID
+        assert!(matches!(
+            parse(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x00]),
+            Err(PacketParseError::DataTypeDecodeError(
+                DataTypeDecodeError::VarNumberTooBig
+            ))
+        ));
+        // Invalid UTF-8 command
+        assert!(matches!(
+            parse(&[0x05, 0x01, 0xff]),
+            Err(PacketParseError::DataTypeDecodeError(
+                DataTypeDecodeError::Utf8Error(_)
+            ))
+        ));
+        // Too many signed arguments
+        let mut signed: Vec<u8> = vec![0x06, 0x00];
+        signed.extend_from_slice(&[0; 16]);
+        signed.push(0x7F);
+        assert!(matches!(
+            parse(&signed),
+            Err(PacketParseError::DataTypeDecodeError(
+                DataTypeDecodeError::ByteArrayTooLong { length: 127, .. }
+            ))
+        ));
+
+        // Cookie payload over the limit, rejected from its length
+        let cookie: Packet = Packet::new(0x04)
+            .string("a:b")
+            .field(&true)
+            .field(&VarInt(5121));
+        assert!(matches!(
+            LoginServerBoundPacket::try_from(cookie.0.as_slice()),
+            Err(PacketParseError::DataTypeDecodeError(
+                DataTypeDecodeError::ByteArrayTooLong { length: 5121, .. }
+            ))
+        ));
+        // Unknown next state
+        let handshake: Packet = Packet::new(0x00)
+            .field(&VarInt(768))
+            .string("localhost")
+            .field(&25565_u16)
+            .field(&VarInt(4));
+        assert!(matches!(
+            HandshakeServerBoundPacket::try_from(handshake.0.as_slice()),
+            Err(PacketParseError::DataTypeDecodeError(
+                DataTypeDecodeError::InvalidVarIntEnumVariant { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_cipher() {
+        // AES-128 CFB8 vector of NIST SP 800-38A, F.3.7, with the IV as the key
+        let key: [u8; 16] = [
+            0x2B, 0x7E, 0x15, 0x16, 0x28, 0xAE, 0xD2, 0xA6, 0xAB, 0xF7, 0x15, 0x88, 0x09,
+            0xCF, 0x4F, 0x3C,
+        ];
+        let iv: [u8; 16] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C,
+            0x0D, 0x0E, 0x0F,
+        ];
+        let plain: [u8; 18] = [
+            0x6B, 0xC1, 0xBE, 0xE2, 0x2E, 0x40, 0x9F, 0x96, 0xE9, 0x3D, 0x7E, 0x11, 0x73,
+            0x93, 0x17, 0x2A, 0xAE, 0x2D,
+        ];
+        let encrypted: [u8; 18] = [
+            0x3B, 0x79, 0x42, 0x4C, 0x9C, 0x0D, 0xD4, 0x36, 0xBA, 0xCE, 0x9E, 0x0E, 0xD4,
+            0x58, 0x6A, 0x4F, 0x32, 0xB9,
+        ];
+        let mut cipher: Cipher = Cipher::with_iv(&key, &iv);
+        let mut bytes: [u8; 18] = plain;
+        cipher.encrypt(&mut bytes[..7]);
+        cipher.encrypt(&mut bytes[7..]);
+        assert_eq!(bytes, encrypted);
+
+        let mut cipher: Cipher = Cipher::with_iv(&key, &iv);
+        cipher.decrypt(&mut bytes);
+        assert_eq!(bytes, plain);
+    }
+
+    #[test]
+    fn test_encrypted_frames() {
+        let secret: [u8; 16] = *b"0123456789abcdef";
+        let mut codec: Codec = Codec::new();
+        let mut stream: Vec<u8> = codec.encode(&[0x01, 0x2A]).unwrap();
+        codec.set_encryption(&secret);
+        let mut encrypted: Vec<u8> = codec.encode(&[0x02, 0x2A]).unwrap();
+        codec.encrypt(&mut encrypted);
+        let mut next: Vec<u8> = codec.encode(&[0x03; 300]).unwrap();
+        codec.encrypt(&mut next);
+        encrypted.append(&mut next);
+        assert!(!encrypted.starts_with(&[0x02, 0x02, 0x2A]));
+        stream.extend_from_slice(&encrypted);
+
+        // The reader read ahead past the last clear frame
+        let (read_ahead, unread): (&[u8], &[u8]) = stream.split_at(5);
+        let (clear, read_ahead): (&[u8], &[u8]) = read_ahead.split_at(3);
+        assert_eq!(
+            codec.decode(clear.get(1..).unwrap()).unwrap(),
+            [0x01, 0x2A].as_slice()
+        );
+        let mut reader: CipherReader<&[u8]> = CipherReader::new(unread);
+        reader.set_encryption(Cipher::new(&secret), read_ahead.to_vec());
+        let mut decrypted: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut decrypted).unwrap();
+        let mut frames: &[u8] = &decrypted;
+        for packet in [vec![0x02, 0x2A], vec![0x03; 300]] {
+            let length: usize =
+                usize::try_from(VarInt::decode(&mut frames).unwrap().0).unwrap();
+            let (frame, rest) = frames.split_at(length);
+            assert_eq!(codec.decode(frame).unwrap(), packet.as_slice());
+            frames = rest;
+        }
+        assert!(frames.is_empty());
+    }
+
+    #[test]
+    fn test_chunk_blocks() {
+        let stone: BlockState = "minecraft:stone".parse().unwrap();
+        let grass: BlockState = "minecraft:grass_block".parse().unwrap();
+        let chunk: CChunkDataAndUpdateLight = CChunkDataAndUpdateLight::new(0, -1)
+            .with_block(1, 99, -16, stone)
+            .with_block(0, 99, -15, grass)
+            .with_block(0, 100, -15, grass)
+            .with_block(0, 100, -15, BlockState::AIR)
+            // Outside of the chunk
+            .with_block(16, 99, -16, stone)
+            .with_block(0, 320, -16, stone);
+        let packet: Vec<u8> = data(&chunk);
+        let mut bytes: &[u8] = &packet;
+        assert_eq!(i32::decode(&mut bytes).unwrap(), 0);
+        assert_eq!(i32::decode(&mut bytes).unwrap(), -1);
+        let (heightmaps, mut bytes) = bytes.split_at(2);
+        assert_eq!(heightmaps, [10, 0]);
+        let length: usize =
+            usize::try_from(VarInt::decode(&mut bytes).unwrap().0).unwrap();
+        let (mut sections, _) = bytes.split_at(length);
+
+        for section in 0..24 {
+            let count: i16 = i16::decode(&mut sections).unwrap();
+            let bits: u8 = u8::decode(&mut sections).unwrap();
+            if section == 10 {
+                // Y 99 is the fourth layer of the section from 96 to 112
+                assert_eq!((count, bits), (2, 4));
+                let palette: Vec<VarInt> = (0..VarInt::decode(&mut sections).unwrap().0)
+                    .map(|_| VarInt::decode(&mut sections).unwrap())
+                    .collect();
+                assert_eq!(palette, [BlockState::AIR, stone, grass].map(VarInt::from));
+                assert_eq!(VarInt::decode(&mut sections).unwrap(), VarInt(256));
+                let longs: Vec<i64> = (0..256)
+                    .map(|_| i64::decode(&mut sections).unwrap())
+                    .collect();
+                // 16 entries per long, 16 longs per layer, 1 long per row along X
+                for (i, long) in longs.iter().enumerate() {
+                    let expected: i64 = match i {
+                        48 => 1 << 4,
+                        49 => 2,
+                        _ => 0,
+                    };
+                    assert_eq!(*long, expected, "long {i}");
+                }
+            } else {
+                assert_eq!((count, bits), (0, 0));
+                assert_eq!(VarInt::decode(&mut sections).unwrap(), VarInt(0)); // Air
+                assert_eq!(VarInt::decode(&mut sections).unwrap(), VarInt(0));
+            }
+            // Biomes
+            assert_eq!(u8::decode(&mut sections).unwrap(), 0);
+            VarInt::decode(&mut sections).unwrap();
+            assert_eq!(VarInt::decode(&mut sections).unwrap(), VarInt(0));
+        }
+        assert!(sections.is_empty());
+    }
+}
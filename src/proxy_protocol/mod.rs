@@ -0,0 +1,203 @@
+//! `HAProxy` PROXY protocol v1 and v2 headers, sent by load balancers before the
+//! first Minecraft frame to pass on the real address of the client.
+//!
+//! See <https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt>.
+use std::{
+    fmt::Display,
+    io::{self, Read},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+mod test;
+
+/// Signature starting a v2 header.
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Maximum length of a v1 header, including the final CRLF.
+const V1_MAX_LENGTH: usize = 107;
+
+/// Error when reading a PROXY protocol header.
+#[derive(Error, Debug)]
+pub enum ProxyProtocolError {
+    /// The header could not be read.
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+
+    /// The connection does not start with a PROXY protocol header.
+    #[error("Missing PROXY protocol header")]
+    MissingHeader,
+
+    /// The header is malformed.
+    #[error("Invalid PROXY protocol header: {0}")]
+    InvalidHeader(&'static str),
+}
+
+/// A network of trusted addresses, such as `10.0.0.0/8`, or a single address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpNetwork {
+    /// First address of the network.
+    address: IpAddr,
+
+    /// Number of leading bits shared by the addresses of the network.
+    prefix: u8,
+}
+
+impl IpNetwork {
+    /// Whether the network contains an address. IPv4-mapped IPv6 addresses match
+    /// IPv4 networks.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        let address: IpAddr = address.to_canonical();
+        match (self.address, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask: u32 = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask: u128 = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) =
+            s.split_once('/').map_or((s, None), |(a, p)| (a, Some(p)));
+        let address: IpAddr = address.trim().parse::<IpAddr>().map_err(|_| ())?;
+        let max: u8 = if address.is_ipv4() { 32 } else { 128 };
+        let prefix: u8 = match prefix {
+            Some(prefix) => prefix.trim().parse().map_err(|_| ())?,
+            None => max,
+        };
+        if prefix > max {
+            return Err(());
+        }
+
+        Ok(Self { address, prefix })
+    }
+}
+
+impl Display for IpNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+/// Read a PROXY protocol v1 or v2 header, and nothing past it.
+///
+/// Returns the address of the client, or `None` if the proxy does not relay one,
+/// as for its own health checks.
+pub fn read_header(
+    from: &mut impl Read,
+) -> Result<Option<SocketAddr>, ProxyProtocolError> {
+    let mut first: [u8; 1] = [0];
+    from.read_exact(&mut first)?;
+
+    match first {
+        [b'P'] => read_v1(from),
+        [b'\r'] => read_v2(from),
+        _ => Err(ProxyProtocolError::MissingHeader),
+    }
+}
+
+/// Read the rest of a v1 header, after its first byte.
+fn read_v1(from: &mut impl Read) -> Result<Option<SocketAddr>, ProxyProtocolError> {
+    let mut line: Vec<u8> = vec![b'P'];
+    while !line.ends_with(b"\r\n") {
+        if line.len() == V1_MAX_LENGTH {
+            return Err(ProxyProtocolError::InvalidHeader("v1 header too long"));
+        }
+        let mut byte: [u8; 1] = [0];
+        from.read_exact(&mut byte)?;
+        line.extend(byte);
+    }
+
+    let line: &str = std::str::from_utf8(&line)
+        .map_err(|_| ProxyProtocolError::InvalidHeader("v1 header is not ASCII"))?;
+    let fields: Vec<&str> = line.trim_end_matches("\r\n").split(' ').collect();
+
+    match fields.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => Ok(None),
+        ["PROXY", protocol @ ("TCP4" | "TCP6"), source, _, port, _] => {
+            let ip: IpAddr = source.parse().map_err(|_| {
+                ProxyProtocolError::InvalidHeader("invalid v1 source address")
+            })?;
+            if ip.is_ipv4() != (*protocol == "TCP4") {
+                return Err(ProxyProtocolError::InvalidHeader(
+                    "v1 address does not match the protocol",
+                ));
+            }
+            let port: u16 = port.parse().map_err(|_| {
+                ProxyProtocolError::InvalidHeader("invalid v1 source port")
+            })?;
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        ["PROXY", ..] => Err(ProxyProtocolError::InvalidHeader("malformed v1 header")),
+        _ => Err(ProxyProtocolError::MissingHeader),
+    }
+}
+
+/// Read the rest of a v2 header, after its first byte.
+fn read_v2(from: &mut impl Read) -> Result<Option<SocketAddr>, ProxyProtocolError> {
+    let mut signature: [u8; 12] = [b'\r'; 12];
+    if let Some(rest) = signature.get_mut(1..) {
+        from.read_exact(rest)?;
+    }
+    if signature != V2_SIGNATURE {
+        return Err(ProxyProtocolError::MissingHeader);
+    }
+
+    let mut header: [u8; 4] = [0; 4];
+    from.read_exact(&mut header)?;
+    let [version_command, family, length @ ..] = header;
+    let mut addresses: Vec<u8> = vec![0; usize::from(u16::from_be_bytes(length))];
+    from.read_exact(&mut addresses)?;
+
+    if version_command >> 4 != 2 {
+        return Err(ProxyProtocolError::InvalidHeader("unsupported version"));
+    }
+    match version_command & 0x0F {
+        // LOCAL, sent by the proxy itself
+        0x0 => return Ok(None),
+        // PROXY
+        0x1 => {}
+        _ => return Err(ProxyProtocolError::InvalidHeader("unsupported command")),
+    }
+
+    // The addresses may be followed by TLVs, which are ignored
+    match (family >> 4, addresses.as_slice()) {
+        // AF_INET
+        (0x1, [a, b, c, d, _, _, _, _, p1, p2, ..]) => Ok(Some(SocketAddr::new(
+            IpAddr::V4(Ipv4Addr::new(*a, *b, *c, *d)),
+            u16::from_be_bytes([*p1, *p2]),
+        ))),
+        // AF_INET6
+        (0x2, addresses) => {
+            let ip: Option<[u8; 16]> =
+                addresses.get(..16).and_then(|s| s.try_into().ok());
+            let port: Option<[u8; 2]> =
+                addresses.get(32..34).and_then(|s| s.try_into().ok());
+            match (ip, port) {
+                (Some(ip), Some(port)) => Ok(Some(SocketAddr::new(
+                    IpAddr::V6(Ipv6Addr::from(ip)),
+                    u16::from_be_bytes(port),
+                ))),
+                _ => Err(ProxyProtocolError::InvalidHeader("address block too short")),
+            }
+        }
+        (0x1, _) => Err(ProxyProtocolError::InvalidHeader("address block too short")),
+        // AF_UNSPEC and AF_UNIX
+        _ => Ok(None),
+    }
+}
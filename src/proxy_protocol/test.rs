@@ -0,0 +1,119 @@
+//! Test [`crate::proxy_protocol`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Read,
+        net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    };
+
+    use crate::proxy_protocol::{read_header, IpNetwork, ProxyProtocolError};
+
+    #[test]
+    fn test_v1() {
+        let mut stream: &[u8] =
+            b"PROXY TCP4 203.0.113.7 192.0.2.1 51234 25565\r\n\x10\x00";
+        assert_eq!(
+            read_header(&mut stream).unwrap(),
+            Some(SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)),
+                51234
+            ))
+        );
+        // The first Minecraft frame is left in the stream
+        assert_eq!(stream, b"\x10\x00");
+
+        let mut stream: &[u8] = b"PROXY TCP6 2001:db8::1 2001:db8::2 4000 25565\r\n";
+        assert_eq!(
+            read_header(&mut stream).unwrap(),
+            Some("[2001:db8::1]:4000".parse().unwrap())
+        );
+
+        let mut stream: &[u8] = b"PROXY UNKNOWN\r\n";
+        assert_eq!(read_header(&mut stream).unwrap(), None);
+
+        let mut stream: &[u8] = b"PROXY TCP4 2001:db8::1 192.0.2.1 1 2\r\n";
+        assert!(matches!(
+            read_header(&mut stream),
+            Err(ProxyProtocolError::InvalidHeader(_))
+        ));
+
+        let mut stream: &[u8] = &[b'P'; 200];
+        assert!(matches!(
+            read_header(&mut stream),
+            Err(ProxyProtocolError::InvalidHeader(_))
+        ));
+
+        // A handshake instead of a header
+        let mut stream: &[u8] = b"\x10\x00\xfa\x05";
+        assert!(matches!(
+            read_header(&mut stream),
+            Err(ProxyProtocolError::MissingHeader)
+        ));
+    }
+
+    #[test]
+    fn test_v2() {
+        let mut header: Vec<u8> = b"\r\n\r\n\0\r\nQUIT\n".to_vec();
+        header.extend([0x21, 0x11, 0x00, 0x0F]); // PROXY, TCP over IPv4, length 15
+        header.extend([203, 0, 113, 7, 192, 0, 2, 1]);
+        header.extend(51234_u16.to_be_bytes());
+        header.extend(25565_u16.to_be_bytes());
+        header.extend([0x04, 0x00, 0x00]); // Empty NOOP TLV
+        header.extend(b"\x10\x00");
+
+        let mut stream: &[u8] = &header;
+        assert_eq!(
+            read_header(&mut stream).unwrap(),
+            Some(SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)),
+                51234
+            ))
+        );
+        let mut rest: Vec<u8> = Vec::new();
+        stream.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"\x10\x00");
+
+        let mut header: Vec<u8> = b"\r\n\r\n\0\r\nQUIT\n".to_vec();
+        header.extend([0x21, 0x21, 0x00, 0x24]); // PROXY, TCP over IPv6, length 36
+        header.extend("2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+        header.extend([0; 16]);
+        header.extend(4000_u16.to_be_bytes());
+        header.extend(25565_u16.to_be_bytes());
+        assert_eq!(
+            read_header(&mut header.as_slice()).unwrap(),
+            Some("[2001:db8::1]:4000".parse().unwrap())
+        );
+
+        // LOCAL, for health checks
+        let mut header: Vec<u8> = b"\r\n\r\n\0\r\nQUIT\n".to_vec();
+        header.extend([0x20, 0x00, 0x00, 0x00]);
+        assert_eq!(read_header(&mut header.as_slice()).unwrap(), None);
+
+        let mut header: Vec<u8> = b"\r\n\r\n\0\r\nQUIT\n".to_vec();
+        header.extend([0x21, 0x11, 0x00, 0x04, 1, 2, 3, 4]);
+        assert!(matches!(
+            read_header(&mut header.as_slice()),
+            Err(ProxyProtocolError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_ip_network() {
+        let network: IpNetwork = "10.0.0.0/8".parse().unwrap();
+        assert!(network.contains("10.1.2.3".parse().unwrap()));
+        assert!(network.contains("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!network.contains("11.0.0.1".parse().unwrap()));
+        assert!(!network.contains("::1".parse().unwrap()));
+
+        let single: IpNetwork = "::1".parse().unwrap();
+        assert_eq!(single.to_string(), "::1/128");
+        assert!(single.contains("::1".parse().unwrap()));
+        assert!(!single.contains("::2".parse().unwrap()));
+
+        let any: IpNetwork = "0.0.0.0/0".parse().unwrap();
+        assert!(any.contains("192.0.2.1".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<IpNetwork>().is_err());
+        assert!("localhost".parse::<IpNetwork>().is_err());
+    }
+}
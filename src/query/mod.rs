@@ -0,0 +1,274 @@
+//! Query listener: server lists and monitoring tools get the status and the
+//! players of the server over UDP, with the `GameSpy4` protocol.
+//!
+//! See <https://wiki.vg/Query>.
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hasher},
+    net::{SocketAddr, UdpSocket},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::server::{Server, ServerStatus, VERSION_NAME};
+
+mod test;
+
+/// First bytes of the requests.
+const MAGIC: [u8; 2] = [0xfe, 0xfd];
+
+/// Type of the requests for a challenge token, and of their responses.
+const HANDSHAKE: u8 = 9;
+
+/// Type of the stat requests, and of their responses.
+const STAT: u8 = 0;
+
+/// Challenge tokens are only accepted for this long after being issued.
+const TOKEN_LIFETIME: Duration = Duration::from_secs(30);
+
+/// Size of the receive buffer, longer datagrams are truncated and ignored.
+const MAX_REQUEST_LENGTH: usize = 1460;
+
+/// Padding between the header and the key-value section of a full stat.
+const FULL_STAT_PADDING: &[u8] = b"splitnum\0\x80\0";
+
+/// Padding between the key-value section and the players of a full stat.
+const PLAYERS_PADDING: &[u8] = b"\x01player_\0\0";
+
+/// A request from a query client.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Request {
+    /// Ask for a challenge token.
+    Handshake {
+        /// Session ID chosen by the client, sent back in the response.
+        session: i32,
+    },
+
+    /// Ask for the MOTD and the player counts.
+    BasicStat {
+        /// Session ID chosen by the client, sent back in the response.
+        session: i32,
+
+        /// Challenge token given by the handshake.
+        token: i32,
+    },
+
+    /// Ask for the basic stat, the version, the plugins and the player names.
+    FullStat {
+        /// Session ID chosen by the client, sent back in the response.
+        session: i32,
+
+        /// Challenge token given by the handshake.
+        token: i32,
+    },
+}
+
+impl Request {
+    /// Parse a datagram, `None` if it is not a query request.
+    pub fn parse(datagram: &[u8]) -> Option<Self> {
+        let (magic, rest) = datagram.split_first_chunk::<2>()?;
+        if *magic != MAGIC {
+            return None;
+        }
+        let (kind, rest) = rest.split_first()?;
+        let (session, payload) = rest.split_first_chunk::<4>()?;
+        let session: i32 = i32::from_be_bytes(*session);
+
+        match (*kind, payload.len()) {
+            (HANDSHAKE, 0) => Some(Self::Handshake { session }),
+            (STAT, 4 | 8) => {
+                let token: i32 = i32::from_be_bytes(*payload.first_chunk::<4>()?);
+                // The full stat is requested by padding the token
+                Some(if payload.len() == 4 {
+                    Self::BasicStat { session, token }
+                } else {
+                    Self::FullStat { session, token }
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Challenge tokens issued to clients, expiring after [`TOKEN_LIFETIME`] so that
+/// they keep changing.
+#[derive(Default)]
+pub struct Challenges {
+    /// Token of each client address, with the time it was issued.
+    tokens: HashMap<SocketAddr, (i32, Instant)>,
+}
+
+impl Challenges {
+    /// Issue a new token to `client`, replacing its previous one, and forget the
+    /// expired tokens.
+    pub fn issue(&mut self, client: SocketAddr, now: Instant) -> i32 {
+        self.tokens
+            .retain(|_, (_, issued)| now.duration_since(*issued) < TOKEN_LIFETIME);
+
+        let random: u64 = RandomState::new().build_hasher().finish();
+        // Positive 24-bit tokens, as in vanilla
+        let token: i32 = i32::try_from(random & 0xff_ffff).unwrap_or_default();
+        self.tokens.insert(client, (token, now));
+        token
+    }
+
+    /// Whether `token` is the unexpired token issued to `client`.
+    pub fn verify(&self, client: SocketAddr, token: i32, now: Instant) -> bool {
+        self.tokens
+            .get(&client)
+            .is_some_and(|(issued_token, issued)| {
+                *issued_token == token && now.duration_since(*issued) < TOKEN_LIFETIME
+            })
+    }
+}
+
+/// A response being built.
+struct Response(Vec<u8>);
+
+impl Response {
+    /// Start a response of type `kind` to `session`.
+    fn new(kind: u8, session: i32) -> Self {
+        let mut bytes: Vec<u8> = vec![kind];
+        bytes.extend_from_slice(&session.to_be_bytes());
+        Self(bytes)
+    }
+
+    /// Append a null-terminated string.
+    fn string(&mut self, value: &str) -> &mut Self {
+        // Null bytes would end the string early
+        self.0.extend(value.bytes().filter(|b| *b != 0).chain([0]));
+        self
+    }
+
+    /// Append raw bytes.
+    fn bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.0.extend_from_slice(value);
+        self
+    }
+}
+
+/// Response to a handshake, giving the challenge token.
+pub fn handshake(session: i32, token: i32) -> Vec<u8> {
+    let mut response: Response = Response::new(HANDSHAKE, session);
+    response.string(&token.to_string());
+    response.0
+}
+
+/// Response to a basic stat request, `address` being the game address.
+pub fn basic_stat(session: i32, status: &ServerStatus, address: SocketAddr) -> Vec<u8> {
+    let mut response: Response = Response::new(STAT, session);
+    response
+        .string(&status.motd)
+        .string("SMP")
+        .string("world")
+        .string(&status.players.len().to_string())
+        .string(&status.max_players.to_string())
+        .bytes(&address.port().to_le_bytes())
+        .string(&address.ip().to_string());
+    response.0
+}
+
+/// Response to a full stat request, `address` being the game address.
+pub fn full_stat(session: i32, status: &ServerStatus, address: SocketAddr) -> Vec<u8> {
+    let mut response: Response = Response::new(STAT, session);
+    response.bytes(FULL_STAT_PADDING);
+    for (key, value) in [
+        ("hostname", status.motd.clone()),
+        ("gametype", "SMP".to_string()),
+        ("game_id", "MINECRAFT".to_string()),
+        ("version", VERSION_NAME.to_string()),
+        // No plugins, only the server software
+        ("plugins", "AtmosphereMC - Void".to_string()),
+        ("map", "world".to_string()),
+        ("numplayers", status.players.len().to_string()),
+        ("maxplayers", status.max_players.to_string()),
+        ("hostport", address.port().to_string()),
+        ("hostip", address.ip().to_string()),
+    ] {
+        response.string(key).string(&value);
+    }
+    response.string("").bytes(PLAYERS_PADDING);
+    for player in &status.players {
+        response.string(player.name());
+    }
+    response.string("");
+    response.0
+}
+
+/// Start the query listener if enabled, on a dedicated thread.
+pub fn spawn(server: &Arc<Server>) {
+    let config = server.config();
+    if !config.enable_query {
+        return;
+    }
+
+    let address: SocketAddr = SocketAddr::new(config.address.ip(), config.query_port);
+    let socket: UdpSocket = match UdpSocket::bind(address) {
+        Ok(socket) => socket,
+        Err(e) => {
+            log::error!(target: "Query", "Failed to bind {address}: {e}");
+            return;
+        }
+    };
+    log::info!(target: "Query", "Query running on {address}");
+
+    let server: Arc<Server> = Arc::clone(server);
+    // The listener thread stays blocked in `recv_from`, it ends with the process
+    let spawned = thread::Builder::new()
+        .name("Query Listener".to_string())
+        .spawn(move || listen(&socket, &server));
+    if let Err(e) = spawned {
+        log::error!(target: "Query", "Failed to spawn the listener thread: {e}");
+    }
+}
+
+/// Answer query requests until the server stops.
+fn listen(socket: &UdpSocket, server: &Server) {
+    let mut challenges: Challenges = Challenges::default();
+    let mut buf: [u8; MAX_REQUEST_LENGTH] = [0; MAX_REQUEST_LENGTH];
+    while server.is_running() {
+        let (length, client) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e) => {
+                log::error!(target: "Query", "{e}");
+                continue;
+            }
+        };
+
+        let Some(response) = buf
+            .get(..length)
+            .and_then(Request::parse)
+            .and_then(|request| answer(&request, client, &mut challenges, server))
+        else {
+            log::debug!(target: "Query", "Ignored invalid request from {client}");
+            continue;
+        };
+
+        if let Err(e) = socket.send_to(&response, client) {
+            log::debug!(target: "Query", "Failed to answer {client}: {e}");
+        }
+    }
+}
+
+/// Answer a request from `client`, `None` if its challenge token is invalid.
+pub fn answer(
+    request: &Request,
+    client: SocketAddr,
+    challenges: &mut Challenges,
+    server: &Server,
+) -> Option<Vec<u8>> {
+    let now: Instant = Instant::now();
+    let address: SocketAddr = server.config().address;
+    match *request {
+        Request::Handshake { session } => {
+            Some(handshake(session, challenges.issue(client, now)))
+        }
+        Request::BasicStat { session, token } => challenges
+            .verify(client, token, now)
+            .then(|| basic_stat(session, &server.status(), address)),
+        Request::FullStat { session, token } => challenges
+            .verify(client, token, now)
+            .then(|| full_stat(session, &server.status(), address)),
+    }
+}
@@ -0,0 +1,99 @@
+//! Test [`crate::query`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        query::{basic_stat, full_stat, handshake, Challenges, Request},
+        server::ServerStatus,
+    };
+
+    /// A query client.
+    const ALICE: SocketAddr =
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), 4000);
+    /// Another query client.
+    const BOB: SocketAddr =
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2)), 4000);
+    /// The game address.
+    const GAME: SocketAddr =
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 10)), 25565);
+
+    #[test]
+    fn test_requests() {
+        assert_eq!(
+            Request::parse(&[0xfe, 0xfd, 9, 0, 0, 0, 1]),
+            Some(Request::Handshake { session: 1 })
+        );
+        assert_eq!(
+            Request::parse(&[0xfe, 0xfd, 0, 0, 0, 0, 1, 0, 0x91, 0x29, 0x5b]),
+            Some(Request::BasicStat {
+                session: 1,
+                token: 9_513_307
+            })
+        );
+        assert_eq!(
+            Request::parse(&[0xfe, 0xfd, 0, 0, 0, 0, 1, 0, 0x91, 0x29, 0x5b, 0, 0, 0, 0]),
+            Some(Request::FullStat {
+                session: 1,
+                token: 9_513_307
+            })
+        );
+        assert_eq!(Request::parse(&[0xfe, 0xfc, 9, 0, 0, 0, 1]), None);
+        assert_eq!(Request::parse(&[0xfe, 0xfd, 9, 0, 0]), None);
+        assert_eq!(Request::parse(&[0xfe, 0xfd, 0, 0, 0, 0, 1, 0, 0]), None);
+        assert_eq!(Request::parse(&[0xfe, 0xfd, 1, 0, 0, 0, 1]), None);
+    }
+
+    #[test]
+    fn test_challenges() {
+        let mut challenges: Challenges = Challenges::default();
+        let start: Instant = Instant::now();
+
+        let token: i32 = challenges.issue(ALICE, start);
+        assert!((0..=0xff_ffff).contains(&token));
+        assert!(challenges.verify(ALICE, token, start + Duration::from_secs(29)));
+        assert!(!challenges.verify(ALICE, token.wrapping_add(1), start));
+        assert!(!challenges.verify(BOB, token, start));
+        assert!(!challenges.verify(ALICE, token, start + Duration::from_secs(30)));
+
+        // A new handshake replaces the token, and forgets the expired ones
+        let later: Instant = start + Duration::from_secs(31);
+        let bob: i32 = challenges.issue(BOB, start + Duration::from_secs(20));
+        let renewed: i32 = challenges.issue(ALICE, later);
+        assert!(challenges.verify(ALICE, renewed, later));
+        assert!(challenges.verify(BOB, bob, later));
+        assert_eq!(challenges.tokens.len(), 2);
+        challenges.issue(ALICE, start + Duration::from_secs(60));
+        assert_eq!(challenges.tokens.len(), 1);
+    }
+
+    #[test]
+    fn test_responses() {
+        assert_eq!(handshake(1, 9_513_307), b"\x09\0\0\0\x019513307\0".to_vec());
+
+        let status: ServerStatus = ServerStatus {
+            motd: "A Minecraft Server".to_string(),
+            max_players: 20,
+            players: Vec::new(),
+        };
+        assert_eq!(
+            basic_stat(1, &status, GAME),
+            b"\0\0\0\0\x01A Minecraft Server\0SMP\0world\x000\x0020\0\xdd\x63192.0.2.10\0"
+                .to_vec()
+        );
+        assert_eq!(
+            full_stat(1, &status, GAME),
+            [
+                b"\0\0\0\0\x01splitnum\0\x80\0".as_slice(),
+                b"hostname\0A Minecraft Server\0gametype\0SMP\0game_id\0MINECRAFT\0",
+                b"version\x001.21.2\0plugins\0AtmosphereMC - Void\0map\0world\0",
+                b"numplayers\x000\0maxplayers\x0020\0hostport\x0025565\0hostip\x00192.0.2.10\0",
+                b"\0\x01player_\0\0\0",
+            ]
+            .concat()
+        );
+    }
+}
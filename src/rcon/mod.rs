@@ -0,0 +1,324 @@
+//! RCON server: remote administration tools authenticate with a password and run
+//! commands through the [`crate::command::CommandDispatcher`], getting their
+//! output back.
+//!
+//! See <https://developer.valvesoftware.com/wiki/Source_RCON_Protocol>.
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+use thiserror::Error;
+
+use crate::{command::CommandSource, listener::ListenerThread, server::Server};
+
+mod test;
+
+/// Type of the packets authenticating a client.
+const SERVERDATA_AUTH: i32 = 3;
+
+/// Type of the answers to [`SERVERDATA_AUTH`] packets.
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+
+/// Type of the packets running a command.
+const SERVERDATA_EXECCOMMAND: i32 = 2;
+
+/// Type of the packets with the output of a command.
+const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+
+/// Request ID of the [`SERVERDATA_AUTH_RESPONSE`] when the password is wrong.
+const AUTH_FAILED: i32 = -1;
+
+/// Length of the ID and the type, counted in the length of a packet.
+const HEADER_LENGTH: usize = 8;
+
+/// Maximum length of a request, as accepted by vanilla.
+const MAX_REQUEST_LENGTH: usize = 1460;
+
+/// Maximum length of the body of a response, longer output is split in several
+/// packets.
+const MAX_RESPONSE_BODY: usize = 4096;
+
+/// Clients not sending anything for this long are disconnected.
+const READ_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Maximum number of clients connected at the same time.
+const MAX_CLIENTS: usize = 8;
+
+/// Clients sending this many wrong passwords are disconnected.
+const MAX_AUTH_FAILURES: u32 = 3;
+
+/// Error on an RCON connection.
+#[derive(Error, Debug)]
+pub enum RconError {
+    /// The connection failed.
+    #[error(transparent)]
+    IOError(#[from] io::Error),
+
+    /// A packet has an invalid length.
+    #[error("Invalid RCON packet length: {0}")]
+    InvalidLength(i32),
+
+    /// The client sent too many wrong passwords.
+    #[error("Too many wrong passwords")]
+    TooManyAuthFailures,
+}
+
+/// An RCON packet.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Packet {
+    /// Request ID chosen by the client, sent back in the responses.
+    pub id: i32,
+
+    /// Type of the packet.
+    pub kind: i32,
+
+    /// Body of the packet, a password, a command or its output.
+    pub body: String,
+}
+
+impl Packet {
+    /// Create a packet.
+    pub fn new(id: i32, kind: i32, body: impl Into<String>) -> Self {
+        Self {
+            id,
+            kind,
+            body: body.into(),
+        }
+    }
+
+    /// Read a packet, failing with [`ErrorKind::UnexpectedEof`] if the client
+    /// disconnected.
+    pub fn read(from: &mut impl Read) -> Result<Self, RconError> {
+        let mut buf: [u8; 4] = [0; 4];
+        from.read_exact(&mut buf)?;
+        let length: i32 = i32::from_le_bytes(buf);
+        let size: usize = usize::try_from(length)
+            .ok()
+            // ID, type and the two null terminators
+            .filter(|size| (HEADER_LENGTH + 2..=MAX_REQUEST_LENGTH).contains(size))
+            .ok_or(RconError::InvalidLength(length))?;
+
+        let mut data: Vec<u8> = vec![0; size];
+        from.read_exact(&mut data)?;
+        let (header, body) = data.split_at(HEADER_LENGTH);
+        let (id, kind) = header.split_at(4);
+        let body: &[u8] = body.split(|b| *b == 0).next().unwrap_or_default();
+
+        Ok(Self {
+            id: i32::from_le_bytes(id.try_into().unwrap_or_default()),
+            kind: i32::from_le_bytes(kind.try_into().unwrap_or_default()),
+            body: String::from_utf8_lossy(body).into_owned(),
+        })
+    }
+
+    /// Write the packet.
+    pub fn write(&self, to: &mut impl Write) -> Result<(), RconError> {
+        let length: usize = HEADER_LENGTH + self.body.len() + 2;
+        let mut data: Vec<u8> = Vec::with_capacity(4 + length);
+        data.extend_from_slice(
+            &i32::try_from(length)
+                .map_err(|_| RconError::InvalidLength(i32::MAX))?
+                .to_le_bytes(),
+        );
+        data.extend_from_slice(&self.id.to_le_bytes());
+        data.extend_from_slice(&self.kind.to_le_bytes());
+        data.extend_from_slice(self.body.as_bytes());
+        data.extend_from_slice(&[0, 0]);
+
+        to.write_all(&data)?;
+        Ok(())
+    }
+}
+
+/// Split the output of a command into response packets of at most
+/// [`MAX_RESPONSE_BODY`] bytes.
+pub fn responses(id: i32, output: &str) -> Vec<Packet> {
+    let mut packets: Vec<Packet> = Vec::new();
+    let mut rest: &str = output;
+    loop {
+        let mut end: usize = rest.len().min(MAX_RESPONSE_BODY);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (body, after) = rest.split_at(end);
+        packets.push(Packet::new(id, SERVERDATA_RESPONSE_VALUE, body));
+        rest = after;
+        if rest.is_empty() {
+            return packets;
+        }
+    }
+}
+
+/// Whether the password sent by a client is the configured one, comparing in
+/// constant time.
+fn password_matches(sent: &str, expected: &str) -> bool {
+    sent.len() == expected.len()
+        && sent
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// A slot among the [`MAX_CLIENTS`] connected clients, freed when dropped.
+struct ClientSlot(Arc<AtomicUsize>);
+
+impl ClientSlot {
+    /// Take a slot, `None` if all are taken.
+    fn take(clients: &Arc<AtomicUsize>) -> Option<Self> {
+        clients
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < MAX_CLIENTS).then_some(count + 1)
+            })
+            .ok()?;
+        Some(Self(Arc::clone(clients)))
+    }
+}
+
+impl Drop for ClientSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Start the RCON listener if enabled, accepting clients on dedicated threads.
+pub fn spawn(server: &Arc<Server>) -> Option<ListenerThread> {
+    let config = server.config();
+    if !config.enable_rcon {
+        return None;
+    }
+    if config.rcon_password.is_empty() {
+        log::warn!(target: "RCON", "No rcon.password set in server.properties, RCON disabled");
+        return None;
+    }
+
+    let listener: TcpListener = match TcpListener::bind(config.rcon_address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!(target: "RCON", "Failed to bind {}: {e}", config.rcon_address);
+            return None;
+        }
+    };
+    log::info!(target: "RCON", "RCON running on {}", config.rcon_address);
+
+    let server: Arc<Server> = Arc::clone(server);
+    ListenerThread::spawn("RCON Listener", listener, move |listener| {
+        accept(listener, &server);
+    })
+    .inspect_err(
+        |e| log::error!(target: "RCON", "Failed to spawn the listener thread: {e}"),
+    )
+    .ok()
+}
+
+/// Accept RCON clients until the server stops.
+fn accept(listener: &TcpListener, server: &Arc<Server>) {
+    let clients: Arc<AtomicUsize> = Arc::default();
+    for stream in listener.incoming() {
+        if !server.is_running() {
+            break;
+        }
+
+        let (stream, peer) = match stream.and_then(|s| s.peer_addr().map(|a| (s, a))) {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!(target: "RCON", "{e}");
+                continue;
+            }
+        };
+
+        let Some(slot) = ClientSlot::take(&clients) else {
+            log::warn!(target: "RCON", "Rejected RCON client {peer}: too many clients");
+            let _ = stream.shutdown(Shutdown::Both);
+            continue;
+        };
+
+        let server: Arc<Server> = Arc::clone(server);
+        thread::spawn(move || {
+            let _slot: ClientSlot = slot;
+            log::info!(target: "RCON", "RCON client {peer} connected");
+            match handle(stream, peer, &server) {
+                Ok(()) => log::info!(target: "RCON", "RCON client {peer} disconnected"),
+                Err(e) => log::info!(target: "RCON", "RCON client {peer} closed: {e}"),
+            }
+        });
+    }
+}
+
+/// Answer the packets of a client until it disconnects, stays idle for
+/// [`READ_TIMEOUT`] or sends [`MAX_AUTH_FAILURES`] wrong passwords.
+fn handle(
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    server: &Server,
+) -> Result<(), RconError> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut authenticated: bool = false;
+    let mut auth_failures: u32 = 0;
+    loop {
+        let packet: Packet = match Packet::read(&mut stream) {
+            Ok(packet) => packet,
+            Err(RconError::IOError(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        match packet.kind {
+            SERVERDATA_AUTH => {
+                authenticated =
+                    password_matches(&packet.body, &server.config().rcon_password);
+                let id: i32 = if authenticated {
+                    packet.id
+                } else {
+                    log::warn!(target: "RCON", "RCON client {peer} sent a wrong password");
+                    auth_failures += 1;
+                    AUTH_FAILED
+                };
+                Packet::new(id, SERVERDATA_AUTH_RESPONSE, "").write(&mut stream)?;
+                if auth_failures >= MAX_AUTH_FAILURES {
+                    return Err(RconError::TooManyAuthFailures);
+                }
+            }
+            SERVERDATA_EXECCOMMAND if authenticated => {
+                log::info!(target: "RCON", "RCON client {peer} issued server command: /{}", packet.body);
+                let output: Arc<Mutex<String>> = Arc::default();
+                // Errors are part of the output
+                let _ = server
+                    .run_command(&CommandSource::Rcon(Arc::clone(&output)), &packet.body);
+                let output: String = output
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .clone();
+                for response in responses(packet.id, &output) {
+                    response.write(&mut stream)?;
+                }
+            }
+            SERVERDATA_EXECCOMMAND => {
+                Packet::new(AUTH_FAILED, SERVERDATA_AUTH_RESPONSE, "")
+                    .write(&mut stream)?;
+            }
+            // Sent after a command to find the end of a split response: mirrored once
+            // the output was sent
+            SERVERDATA_RESPONSE_VALUE => {
+                Packet::new(packet.id, SERVERDATA_RESPONSE_VALUE, "")
+                    .write(&mut stream)?;
+            }
+            kind => {
+                Packet::new(
+                    packet.id,
+                    SERVERDATA_RESPONSE_VALUE,
+                    format!("Unknown request {kind:x}"),
+                )
+                .write(&mut stream)?;
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Test [`crate::rcon`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::Read,
+        net::{TcpListener, TcpStream},
+        path::PathBuf,
+        process,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+    };
+
+    use crate::{
+        access::AccessLists,
+        config::Config,
+        rcon::{
+            handle, password_matches, responses, ClientSlot, Packet, RconError,
+            AUTH_FAILED, MAX_AUTH_FAILURES, MAX_CLIENTS, MAX_RESPONSE_BODY,
+            SERVERDATA_AUTH, SERVERDATA_AUTH_RESPONSE, SERVERDATA_EXECCOMMAND,
+            SERVERDATA_RESPONSE_VALUE,
+        },
+        server::Server,
+    };
+
+    #[test]
+    fn test_packets() {
+        let packet: Packet = Packet::new(7, SERVERDATA_EXECCOMMAND, "list");
+        let mut bytes: Vec<u8> = Vec::new();
+        packet.write(&mut bytes).unwrap();
+        assert_eq!(
+            bytes,
+            [
+                14, 0, 0, 0, // Length
+                7, 0, 0, 0, // ID
+                2, 0, 0, 0, // Type
+                b'l', b'i', b's', b't', 0, 0,
+            ]
+        );
+        assert_eq!(Packet::read(&mut bytes.as_slice()).unwrap(), packet);
+
+        let mut too_long: &[u8] = &[0xff, 0xff, 0, 0];
+        assert!(matches!(
+            Packet::read(&mut too_long),
+            Err(RconError::InvalidLength(0xffff))
+        ));
+        let mut too_short: &[u8] = &[4, 0, 0, 0, 0, 0, 0, 0];
+        assert!(matches!(
+            Packet::read(&mut too_short),
+            Err(RconError::InvalidLength(4))
+        ));
+
+        let output: String = "é".repeat(MAX_RESPONSE_BODY);
+        let packets: Vec<Packet> = responses(3, &output);
+        assert_eq!(packets.len(), 2);
+        assert!(packets
+            .iter()
+            .all(|p| p.id == 3 && p.body.len() <= MAX_RESPONSE_BODY));
+        assert_eq!(
+            packets.iter().map(|p| p.body.as_str()).collect::<String>(),
+            output
+        );
+        assert_eq!(
+            responses(4, ""),
+            [Packet::new(4, SERVERDATA_RESPONSE_VALUE, "")]
+        );
+
+        assert!(password_matches("secret", "secret"));
+        assert!(!password_matches("secreT", "secret"));
+        assert!(!password_matches("secret2", "secret"));
+    }
+
+    #[test]
+    fn test_session() {
+        let directory: PathBuf =
+            std::env::temp_dir().join(format!("atmospheremc-rcon-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let config: Config = Config {
+            rcon_password: "secret".to_string(),
+            ..Config::default()
+        };
+        let server: Server = Server::new(config, AccessLists::load(&directory, false));
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client: TcpStream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, peer) = listener.accept().unwrap();
+
+        thread::scope(|scope| {
+            let handler = scope.spawn(|| handle(stream, peer, &server));
+
+            let mut exchange = |packet: Packet| {
+                packet.write(&mut client).unwrap();
+                Packet::read(&mut client).unwrap()
+            };
+            assert_eq!(
+                exchange(Packet::new(1, SERVERDATA_EXECCOMMAND, "list")),
+                Packet::new(AUTH_FAILED, SERVERDATA_AUTH_RESPONSE, "")
+            );
+            assert_eq!(
+                exchange(Packet::new(2, SERVERDATA_AUTH, "wrong")),
+                Packet::new(AUTH_FAILED, SERVERDATA_AUTH_RESPONSE, "")
+            );
+            assert_eq!(
+                exchange(Packet::new(3, SERVERDATA_AUTH, "secret")),
+                Packet::new(3, SERVERDATA_AUTH_RESPONSE, "")
+            );
+            assert_eq!(
+                exchange(Packet::new(4, SERVERDATA_EXECCOMMAND, "list")),
+                Packet::new(
+                    4,
+                    SERVERDATA_RESPONSE_VALUE,
+                    "There are 0 of a max of 100 players online: \n"
+                )
+            );
+            assert_eq!(
+                exchange(Packet::new(5, SERVERDATA_EXECCOMMAND, "fly")),
+                Packet::new(
+                    5,
+                    SERVERDATA_RESPONSE_VALUE,
+                    "Unknown or incomplete command, see below for error\n"
+                )
+            );
+            assert_eq!(
+                exchange(Packet::new(6, SERVERDATA_RESPONSE_VALUE, "")),
+                Packet::new(6, SERVERDATA_RESPONSE_VALUE, "")
+            );
+
+            drop(client);
+            assert!(handler.join().unwrap().is_ok());
+        });
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_auth_failures() {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-rcon-auth-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let config: Config = Config {
+            rcon_password: "secret".to_string(),
+            ..Config::default()
+        };
+        let server: Server = Server::new(config, AccessLists::load(&directory, false));
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client: TcpStream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, peer) = listener.accept().unwrap();
+
+        thread::scope(|scope| {
+            let handler = scope.spawn(|| handle(stream, peer, &server));
+
+            for id in 0..MAX_AUTH_FAILURES {
+                Packet::new(i32::try_from(id).unwrap(), SERVERDATA_AUTH, "guess")
+                    .write(&mut client)
+                    .unwrap();
+                assert_eq!(
+                    Packet::read(&mut client).unwrap(),
+                    Packet::new(AUTH_FAILED, SERVERDATA_AUTH_RESPONSE, "")
+                );
+            }
+
+            // Disconnected after the last failure, even with the right password
+            assert!(matches!(
+                handler.join().unwrap(),
+                Err(RconError::TooManyAuthFailures)
+            ));
+            let _ = Packet::new(9, SERVERDATA_AUTH, "secret").write(&mut client);
+            assert_eq!(client.read(&mut [0; 1]).unwrap_or_default(), 0);
+        });
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_client_slots() {
+        let clients: Arc<AtomicUsize> = Arc::default();
+        let mut slots: Vec<ClientSlot> = (0..MAX_CLIENTS)
+            .map(|_| ClientSlot::take(&clients).unwrap())
+            .collect();
+        assert!(ClientSlot::take(&clients).is_none());
+
+        // Freed when the client disconnects
+        slots.pop();
+        slots.push(ClientSlot::take(&clients).unwrap());
+        assert!(ClientSlot::take(&clients).is_none());
+        assert_eq!(clients.load(Ordering::Relaxed), slots.len());
+        slots.clear();
+        assert!(ClientSlot::take(&clients).is_some());
+    }
+}
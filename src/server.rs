@@ -0,0 +1,444 @@
+//! Server context, shared by all connections.
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering},
+        Arc, Condvar, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use crate::{
+    access::AccessLists,
+    command::{CommandDispatcher, CommandError, CommandSource},
+    config::Config,
+    connection::ConnectionHandle,
+    limiter::ConnectionLimiter,
+    metrics::Metrics,
+    player::{Player, PlayerRegistry},
+    protocol::{play::CDeclareCommands, CSystemChatMessage, ClientboundPacket},
+    state::ServerState,
+    text::Text,
+    tick::{Scheduler, TickStats, WorldTime},
+    types::DataTypeEncodeError,
+};
+
+mod test;
+
+/// Name of the supported Minecraft version.
+pub const VERSION_NAME: &str = "1.21.2";
+
+/// Protocol version of [`VERSION_NAME`].
+pub const PROTOCOL_VERSION: i32 = 768;
+
+/// Public state of the server, shown in the server list and to query clients.
+pub struct ServerStatus {
+    /// Message of the day.
+    pub motd: String,
+
+    /// Maximum number of players.
+    pub max_players: u32,
+
+    /// The connected players.
+    pub players: Vec<Arc<Player>>,
+}
+
+/// Open connections, and whether new ones are accepted.
+struct Connections {
+    /// Whether the server accepts connections, until [`Server::shutdown`].
+    running: bool,
+
+    /// Open connections by ID.
+    open: HashMap<u64, Arc<ConnectionHandle>>,
+}
+
+/// The server context, shared by all connections.
+pub struct Server {
+    /// The configuration.
+    config: Config,
+
+    /// The connected players.
+    players: PlayerRegistry,
+
+    /// The whitelist, ban lists and operators.
+    access: AccessLists,
+
+    /// Age of the world and time of day.
+    time: WorldTime,
+
+    /// Tasks run on the tick thread.
+    scheduler: Scheduler,
+
+    /// Statistics about the last ticks.
+    ticks: TickStats,
+
+    /// Open connections.
+    connections: Mutex<Connections>,
+
+    /// Limits on open and new connections.
+    limiter: Arc<ConnectionLimiter>,
+
+    /// Counters exposed on the metrics endpoint.
+    metrics: Arc<Metrics>,
+
+    /// Notified when a connection is unregistered.
+    connection_closed: Condvar,
+
+    /// Whether stopping the server was requested, by a signal or the `stop`
+    /// command.
+    stop_requested: Mutex<bool>,
+
+    /// Notified when stopping the server is requested.
+    stop_condvar: Condvar,
+
+    /// Whether a stop signal was received.
+    signaled: AtomicBool,
+
+    /// The commands.
+    commands: CommandDispatcher,
+
+    /// Next entity ID to allocate.
+    next_entity_id: AtomicI32,
+
+    /// Next connection ID to allocate.
+    next_connection_id: AtomicU64,
+}
+
+impl Server {
+    /// Create the server context.
+    pub fn new(config: Config, access: AccessLists) -> Self {
+        Self {
+            limiter: Arc::new(ConnectionLimiter::new(config.connection_limits)),
+            config,
+            players: PlayerRegistry::default(),
+            access,
+            time: WorldTime::new(),
+            scheduler: Scheduler::new(),
+            ticks: TickStats::new(),
+            connections: Mutex::new(Connections {
+                running: true,
+                open: HashMap::new(),
+            }),
+            metrics: Arc::default(),
+            connection_closed: Condvar::new(),
+            stop_requested: Mutex::new(false),
+            stop_condvar: Condvar::new(),
+            signaled: AtomicBool::new(false),
+            commands: CommandDispatcher::new(),
+            next_entity_id: AtomicI32::new(1),
+            next_connection_id: AtomicU64::new(1),
+        }
+    }
+
+    /// The configuration.
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The connected players.
+    pub const fn players(&self) -> &PlayerRegistry {
+        &self.players
+    }
+
+    /// Snapshot of the public state of the server.
+    pub fn status(&self) -> ServerStatus {
+        ServerStatus {
+            motd: self.config.motd.clone(),
+            max_players: self.config.max_players,
+            players: self.players.all(),
+        }
+    }
+
+    /// The whitelist, ban lists and operators.
+    pub const fn access(&self) -> &AccessLists {
+        &self.access
+    }
+
+    /// Age of the world and time of day.
+    pub const fn time(&self) -> &WorldTime {
+        &self.time
+    }
+
+    /// Tasks run on the tick thread.
+    pub const fn scheduler(&self) -> &Scheduler {
+        &self.scheduler
+    }
+
+    /// Statistics about the last ticks, for MSPT and TPS.
+    pub const fn ticks(&self) -> &TickStats {
+        &self.ticks
+    }
+
+    /// Allocate a unique entity ID.
+    pub fn allocate_entity_id(&self) -> i32 {
+        self.next_entity_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Allocate a unique connection ID.
+    pub fn allocate_connection_id(&self) -> u64 {
+        self.next_connection_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Limits on open and new connections.
+    pub const fn limiter(&self) -> &Arc<ConnectionLimiter> {
+        &self.limiter
+    }
+
+    /// Counters exposed on the metrics endpoint.
+    pub const fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
+    /// Close connections that did not reach the play state in time.
+    pub fn close_stale_logins(&self) {
+        let timeout: Duration = self.config.handshake_timeout;
+        let stale: Vec<Arc<ConnectionHandle>> = self
+            .connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .open
+            .values()
+            .filter(|c| {
+                !matches!(c.state(), ServerState::Play | ServerState::Closed)
+                    && !c.is_closing()
+            })
+            .filter(|c| c.opened_at().elapsed() > timeout)
+            .cloned()
+            .collect();
+
+        for connection in stale {
+            self.limiter.record_handshake_timeout();
+            let timeouts: u64 = self.limiter.counters().handshake_timeouts();
+            connection.in_context(|| {
+                log::info!(
+                    "Login timed out in {:?} ({timeouts} so far)",
+                    connection.state()
+                );
+            });
+            connection.disconnect(&Text::translate(
+                "multiplayer.disconnect.slow_login",
+                vec![],
+            ));
+        }
+    }
+
+    /// Ask the main thread to stop the server.
+    pub fn request_stop(&self) {
+        *self
+            .stop_requested
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = true;
+        self.stop_condvar.notify_all();
+    }
+
+    /// Handle a stop signal (SIGINT or SIGTERM): ask to stop the server the first
+    /// time, and return `true` on the next ones, when the process must exit
+    /// without waiting for the shutdown.
+    pub fn signal_stop(&self) -> bool {
+        if self.signaled.swap(true, Ordering::Relaxed) {
+            return true;
+        }
+        self.request_stop();
+        false
+    }
+
+    /// Block until stopping the server is requested.
+    pub fn wait_for_stop_request(&self) {
+        let _requested = self
+            .stop_condvar
+            .wait_while(
+                self.stop_requested
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner),
+                |requested| !*requested,
+            )
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+
+    /// Whether stopping the server was requested.
+    pub fn is_stop_requested(&self) -> bool {
+        *self
+            .stop_requested
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The commands.
+    pub const fn commands(&self) -> &CommandDispatcher {
+        &self.commands
+    }
+
+    /// Send the commands a player may use with its operator level, for completion
+    /// and syntax highlighting.
+    pub fn send_commands(&self, player: &Player) -> Result<(), DataTypeEncodeError> {
+        let level: u8 = self.access.permission_level(player.uuid());
+        player
+            .connection()
+            .send(&CDeclareCommands::new(self.commands.declared_nodes(level)))
+    }
+
+    /// Run a command line from `source`, sending the result to it.
+    pub fn run_command(
+        &self,
+        source: &CommandSource,
+        line: &str,
+    ) -> Result<(), CommandError> {
+        self.commands.dispatch(self, source, line)
+    }
+
+    /// Whether the server accepts connections, until [`Server::shutdown`].
+    pub fn is_running(&self) -> bool {
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .running
+    }
+
+    /// Register an open connection, so it is disconnected when the server stops.
+    ///
+    /// Returns `false` if the server is stopping, in which case the connection must
+    /// be closed.
+    pub fn register_connection(&self, connection: &Arc<ConnectionHandle>) -> bool {
+        let mut connections = self
+            .connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if connections.running {
+            connections
+                .open
+                .insert(connection.id(), Arc::clone(connection));
+        }
+        connections.running
+    }
+
+    /// State of each open connection.
+    pub fn connection_states(&self) -> Vec<ServerState> {
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .open
+            .values()
+            .map(|connection| connection.state())
+            .collect()
+    }
+
+    /// Unregister a closed connection.
+    pub fn unregister_connection(&self, id: u64) {
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .open
+            .remove(&id);
+        self.connection_closed.notify_all();
+    }
+
+    /// Stop accepting connections and disconnect every client with `reason`, then
+    /// wait up to `timeout` for their outbound queues to be flushed.
+    ///
+    /// Returns the number of connections still open after the timeout.
+    pub fn shutdown(&self, reason: &Text, timeout: Duration) -> usize {
+        let open: Vec<Arc<ConnectionHandle>> = {
+            let mut connections = self
+                .connections
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            connections.running = false;
+            connections.open.values().cloned().collect()
+        };
+
+        for connection in open {
+            connection.disconnect(reason);
+        }
+
+        // Connections unregister once their writer thread has flushed the queue
+        let (connections, _) = self
+            .connection_closed
+            .wait_timeout_while(
+                self.connections
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner),
+                timeout,
+                |connections| !connections.open.is_empty(),
+            )
+            .unwrap_or_else(PoisonError::into_inner);
+        connections.open.len()
+    }
+
+    /// Register a player that logged in. Previous sessions of the same player are
+    /// kicked.
+    pub fn join(&self, player: &Arc<Player>) {
+        for old in self.players.insert(Arc::clone(player)) {
+            log::info!(
+                "{0} logged in from another location, kicking the previous session",
+                player.name()
+            );
+            old.disconnect(&Text::translate(
+                "multiplayer.disconnect.duplicate_login",
+                vec![],
+            ));
+        }
+    }
+
+    /// Unregister a player whose connection closed, announcing it if the player was
+    /// in game.
+    pub fn leave(&self, player: &Player) {
+        if self.players.remove(player)
+            && player.connection().state() == ServerState::Play
+            && self.is_running()
+        {
+            self.broadcast_message(
+                &Text::translate(
+                    "multiplayer.player.left",
+                    vec![Text::plain(player.name())],
+                )
+                .color("yellow"),
+            );
+        }
+    }
+
+    /// Kick the players who were banned, or who are no longer whitelisted if
+    /// `enforce-whitelist` is set, and send the commands again to the others, as
+    /// their operator level may have changed.
+    pub fn enforce_access_lists(&self) {
+        for player in self.players.all() {
+            let ip: IpAddr = player.connection().address().ip();
+            if let Some(reason) =
+                self.access
+                    .kick_reason(player.uuid(), ip, self.config.enforce_whitelist)
+            {
+                player.disconnect(&reason);
+            } else if player.connection().state() == ServerState::Play {
+                if let Err(e) = self.send_commands(&player) {
+                    player
+                        .connection()
+                        .in_context(|| log::error!("Failed to send the commands: {e}"));
+                }
+            }
+        }
+    }
+
+    /// Send a packet to all players in game.
+    pub fn broadcast<P: ClientboundPacket>(
+        &self,
+        packet: &P,
+    ) -> Result<(), DataTypeEncodeError> {
+        let bytes: Vec<u8> = packet.to_bytes()?;
+        for player in self.players.all() {
+            if player.connection().state() == ServerState::Play {
+                player.connection().send_bytes(bytes.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send a system message to all players in game.
+    pub fn broadcast_message(&self, message: &Text) {
+        log::info!(target: "Chat", "{message}");
+
+        if let Err(e) = self.broadcast(&CSystemChatMessage::new(message.clone(), false)) {
+            log::error!(target: "Chat", "Failed to broadcast message: {e}");
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Test [`crate::server`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        net::{SocketAddr, TcpListener, TcpStream},
+        path::PathBuf,
+        process,
+        sync::Arc,
+        thread::{self, JoinHandle},
+        time::Duration,
+    };
+
+    use crate::{
+        access::AccessLists,
+        config::Config,
+        connection::{Connection, ConnectionHandle},
+        limiter::ConnectionPermit,
+        server::Server,
+        text::Text,
+    };
+
+    /// Create a server with its access lists in a temporary directory.
+    fn new_server(name: &str) -> Arc<Server> {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-server-{name}-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        Arc::new(Server::new(
+            Config::default(),
+            AccessLists::load(&directory, false),
+        ))
+    }
+
+    /// Open a connection to `server` through `listener`, returning the client
+    /// socket, the handle of the connection and its running thread.
+    fn connect(
+        server: &Arc<Server>,
+        listener: &TcpListener,
+    ) -> (TcpStream, Arc<ConnectionHandle>, JoinHandle<()>) {
+        let client: TcpStream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, address): (TcpStream, SocketAddr) = listener.accept().unwrap();
+        let permit: ConnectionPermit =
+            server.limiter().try_acquire(address.ip()).unwrap();
+        let connection: Connection =
+            Connection::new(stream, address, Arc::clone(server), permit).unwrap();
+        let handle: Arc<ConnectionHandle> = Arc::clone(connection.handle());
+        (client, handle, thread::spawn(|| connection.run()))
+    }
+
+    #[test]
+    fn test_signal_stop() {
+        let server: Arc<Server> = new_server("signal");
+        assert!(!server.is_stop_requested());
+
+        // The first signal asks for a graceful stop, the next ones exit
+        assert!(!server.signal_stop());
+        assert!(server.is_stop_requested());
+        server.wait_for_stop_request();
+        assert!(server.signal_stop());
+        assert!(server.signal_stop());
+
+        // The `stop` command does not count as a signal
+        let server: Arc<Server> = new_server("stop-command");
+        server.request_stop();
+        assert!(!server.signal_stop());
+        assert!(server.signal_stop());
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let server: Arc<Server> = new_server("shutdown");
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let (_client, _, connection) = connect(&server, &listener);
+
+        // The connection flushes its queue and closes in time
+        assert_eq!(
+            server.shutdown(&Text::plain("Bye"), Duration::from_secs(5)),
+            0
+        );
+        assert!(!server.is_running());
+        connection.join().unwrap();
+
+        // No connection is accepted once stopping
+        let _client: TcpStream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, address): (TcpStream, SocketAddr) = listener.accept().unwrap();
+        let permit: ConnectionPermit =
+            server.limiter().try_acquire(address.ip()).unwrap();
+        assert!(Connection::new(stream, address, Arc::clone(&server), permit).is_err());
+    }
+
+    #[test]
+    fn test_shutdown_timeout() {
+        let server: Arc<Server> = new_server("shutdown-timeout");
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let (client, handle, connection) = connect(&server, &listener);
+
+        // The client does not read: the writer thread blocks on the full socket
+        handle.send_bytes(vec![0; 64 << 20]);
+        let timeout: Duration = Duration::from_millis(200);
+        assert_eq!(server.shutdown(&Text::plain("Bye"), timeout), 1);
+
+        // The connection unregisters once the client is gone
+        drop(client);
+        connection.join().unwrap();
+        assert_eq!(server.shutdown(&Text::plain("Bye"), timeout), 0);
+    }
+}
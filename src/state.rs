@@ -1,7 +1,7 @@
 //! Server state.
 
 /// An enum representing the current server state.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ServerState {
     /// Handshake state.
     ///
@@ -20,9 +20,15 @@ pub enum ServerState {
     /// [`ServerState::Play`] state.
     Login,
 
+    /// Configuration state.
+    ///
+    /// After [`ServerState::Login`]. Registries and settings are synchronized before
+    /// [`ServerState::Play`] state.
+    Configuration,
+
     /// Play state.
     ///
-    /// Active state of the server, after login [`ServerState::Login`].
+    /// Active state of the server, after [`ServerState::Configuration`].
     Play,
 
     /// Connection closed.
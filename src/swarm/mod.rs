@@ -0,0 +1,384 @@
+//! `swarm` subcommand: load test a server with simulated players, which log in,
+//! join the void world, answer keep alives and move in circles around the spawn.
+//!
+//! Every [`REPORT_INTERVAL`], the players in game and the throughput and memory
+//! of the server, read from its metrics endpoint, are printed. The connection
+//! success rate and the join latency percentiles are printed at the end.
+//!
+//! The connection limits of the server (`max-connections-per-ip`,
+//! `connection-throttle-rate`, `connection-throttle-burst`) and `max-players`
+//! must allow the players, which all connect from the same address.
+use std::{
+    f64::consts::TAU,
+    fmt::Write as _,
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    client::{Client, ClientError, Packet},
+    state::ServerState,
+    types::{var::VarInt, DataType},
+};
+
+mod test;
+
+/// Usage of the subcommand.
+const USAGE: &str = "Usage:
+    atmospheremc-void swarm <address> <players> [--rate <players per second>]
+        [--duration <seconds>] [--metrics <metrics address>]";
+
+/// Interval between two reports.
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Minimum interval between two moves of a player. Players move after receiving
+/// a packet, at least once per second with Update Time.
+const MOVE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Radius of the circles walked by the players, in blocks.
+const MOVE_RADIUS: f64 = 2.0;
+
+/// Options of the subcommand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    /// Address of the server.
+    pub address: SocketAddr,
+
+    /// Number of players.
+    pub players: usize,
+
+    /// Players connecting per second.
+    pub rate: f64,
+
+    /// How long the players stay connected, from the start of the test.
+    pub duration: Duration,
+
+    /// Address of the metrics endpoint of the server, if enabled.
+    pub metrics: Option<SocketAddr>,
+}
+
+impl Options {
+    /// Parse the arguments of the subcommand.
+    pub fn parse(args: &[String]) -> Option<Self> {
+        let (address, rest) = args.split_first()?;
+        let (players, mut rest) = rest.split_first()?;
+        let mut options: Self = Self {
+            address: address.to_socket_addrs().ok()?.next()?,
+            players: players.parse().ok()?,
+            rate: 10.0,
+            duration: Duration::from_secs(60),
+            metrics: None,
+        };
+
+        while let [flag, value, remaining @ ..] = rest {
+            match flag.as_str() {
+                "--rate" => {
+                    options.rate = value.parse().ok().filter(|rate: &f64| *rate > 0.0)?;
+                }
+                "--duration" => {
+                    options.duration = Duration::from_secs(value.parse().ok()?);
+                }
+                "--metrics" => options.metrics = value.to_socket_addrs().ok()?.next(),
+                _ => return None,
+            }
+            rest = remaining;
+        }
+        rest.is_empty().then_some(options)
+    }
+}
+
+/// Outcomes of the players, shared by their threads.
+#[derive(Default)]
+pub struct Stats {
+    /// Players in game.
+    in_game: AtomicUsize,
+
+    /// Players that joined the world.
+    joined: AtomicUsize,
+
+    /// Players that failed to join, or were disconnected before the end.
+    failed: AtomicUsize,
+
+    /// Packets received by the players in game.
+    packets: AtomicU64,
+
+    /// Time from connecting to joining the world, of each player that joined.
+    join_latencies: Mutex<Vec<Duration>>,
+}
+
+/// Values read from the metrics endpoint of the server.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ServerSample {
+    /// Bytes received by the server.
+    pub bytes_in: u64,
+
+    /// Bytes sent by the server.
+    pub bytes_out: u64,
+
+    /// Packets received and sent by the server.
+    pub packets: u64,
+
+    /// Resident memory of the server, if known.
+    pub memory: Option<u64>,
+}
+
+/// Run the subcommand with its arguments, returning the exit code.
+pub fn main(args: &[String]) -> i32 {
+    let Some(options) = Options::parse(args) else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+
+    let stats: Stats = Stats::default();
+    let deadline: Instant = Instant::now() + options.duration;
+    println!(
+        "Connecting {0} players to {1} for {2:?}",
+        options.players, options.address, options.duration
+    );
+
+    thread::scope(|scope| {
+        let stats: &Stats = &stats;
+        scope.spawn(move || {
+            let interval: Duration = Duration::from_secs_f64(1.0 / options.rate);
+            for id in 0..options.players {
+                if Instant::now() >= deadline {
+                    break;
+                }
+                let spawned = thread::Builder::new()
+                    .name(format!("Bot{id}"))
+                    .spawn_scoped(scope, move || {
+                        bot(id, options.address, stats, deadline);
+                    });
+                if let Err(e) = spawned {
+                    eprintln!("Failed to spawn player {id}: {e}");
+                    stats.failed.fetch_add(1, Ordering::Relaxed);
+                }
+                thread::sleep(interval);
+            }
+        });
+        report(&options, stats, deadline);
+    });
+
+    summarize(options.players, &stats);
+    0
+}
+
+/// Connect a player and keep it in game until `deadline`.
+fn bot(id: usize, address: SocketAddr, stats: &Stats, deadline: Instant) {
+    let connected_at: Instant = Instant::now();
+    let (mut client, spawn) = match join(id, address) {
+        Ok(joined) => joined,
+        Err(e) => {
+            eprintln!("Bot{id} failed to join: {e}");
+            stats.failed.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    };
+    stats
+        .join_latencies
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(connected_at.elapsed());
+    stats.joined.fetch_add(1, Ordering::Relaxed);
+    stats.in_game.fetch_add(1, Ordering::Relaxed);
+
+    if let Err(e) = play(&mut client, spawn, stats, deadline) {
+        eprintln!("Bot{id} disconnected: {e}");
+        stats.failed.fetch_add(1, Ordering::Relaxed);
+    }
+    stats.in_game.fetch_sub(1, Ordering::Relaxed);
+}
+
+/// Log a player in and join the world, returning the client and the spawn.
+fn join(id: usize, address: SocketAddr) -> Result<(Client, [f64; 3]), ClientError> {
+    let mut client: Client = Client::connect(address)?;
+    client.handshake(ServerState::Login)?;
+    client.login(&format!("Bot{id}"))?;
+    client.configure()?;
+    let packets: Vec<Packet> = client.join()?;
+
+    // Synchronize Player Position: teleport ID, then the coordinates
+    let mut teleport: &[u8] = packets
+        .last()
+        .map(|packet| packet.data.as_slice())
+        .unwrap_or_default();
+    VarInt::decode(&mut teleport)?;
+    let spawn: [f64; 3] = [
+        f64::decode(&mut teleport)?,
+        f64::decode(&mut teleport)?,
+        f64::decode(&mut teleport)?,
+    ];
+    Ok((client, spawn))
+}
+
+/// Receive packets and walk in circles around `spawn` until `deadline`.
+fn play(
+    client: &mut Client,
+    [x, y, z]: [f64; 3],
+    stats: &Stats,
+    deadline: Instant,
+) -> Result<(), ClientError> {
+    let start: Instant = Instant::now();
+    let mut moved_at: Instant = start;
+    while Instant::now() < deadline {
+        client.receive()?;
+        stats.packets.fetch_add(1, Ordering::Relaxed);
+
+        if moved_at.elapsed() >= MOVE_INTERVAL {
+            moved_at = Instant::now();
+            // One turn every ten seconds
+            let angle: f64 = start.elapsed().as_secs_f64() / 10.0 * TAU;
+            client.move_to(
+                MOVE_RADIUS.mul_add(angle.cos(), x),
+                y,
+                MOVE_RADIUS.mul_add(angle.sin(), z),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Print a report every [`REPORT_INTERVAL`] until `deadline`.
+fn report(options: &Options, stats: &Stats, deadline: Instant) {
+    let start: Instant = Instant::now();
+    let mut previous: Option<(Instant, ServerSample)> = None;
+    let mut packets: (Instant, u64) = (start, 0);
+    while Instant::now() < deadline {
+        thread::sleep(
+            REPORT_INTERVAL.min(deadline.saturating_duration_since(Instant::now())),
+        );
+
+        let received: u64 = stats.packets.load(Ordering::Relaxed);
+        let elapsed: f64 = packets.0.elapsed().as_secs_f64().max(f64::EPSILON);
+        // Precision does not matter for a rate
+        #[allow(clippy::cast_precision_loss)]
+        let rate: f64 = received.saturating_sub(packets.1) as f64 / elapsed;
+        let mut line: String = format!(
+            "{:>6.1}s {} in game, {} failed, {rate:.0} packets/s received by the players",
+            start.elapsed().as_secs_f64(),
+            stats.in_game.load(Ordering::Relaxed),
+            stats.failed.load(Ordering::Relaxed),
+        );
+        packets = (Instant::now(), received);
+
+        if let Some(metrics) = options.metrics {
+            match scrape(metrics) {
+                Ok(sample) => {
+                    let now: Instant = Instant::now();
+                    if let Some((at, last)) = previous {
+                        line.push_str(&throughput(
+                            &last,
+                            &sample,
+                            now.duration_since(at),
+                        ));
+                    }
+                    if let Some(memory) = sample.memory {
+                        let _ = write!(line, ", {} MiB resident", memory >> 20);
+                    }
+                    previous = Some((now, sample));
+                }
+                Err(e) => {
+                    let _ = write!(line, ", metrics unavailable: {e}");
+                }
+            }
+        }
+        println!("{line}");
+    }
+}
+
+/// Throughput of the server between two samples taken `elapsed` apart.
+pub fn throughput(
+    before: &ServerSample,
+    after: &ServerSample,
+    elapsed: Duration,
+) -> String {
+    let seconds: f64 = elapsed.as_secs_f64().max(f64::EPSILON);
+    // Precision does not matter for a rate
+    #[allow(clippy::cast_precision_loss)]
+    let rate = |before: u64, after: u64| after.saturating_sub(before) as f64 / seconds;
+    format!(
+        ", server {:.0} packets/s, {:.1} KiB/s in, {:.1} KiB/s out",
+        rate(before.packets, after.packets),
+        rate(before.bytes_in, after.bytes_in) / 1024.0,
+        rate(before.bytes_out, after.bytes_out) / 1024.0,
+    )
+}
+
+/// Print the connection success rate and the join latency percentiles.
+fn summarize(players: usize, stats: &Stats) {
+    let joined: usize = stats.joined.load(Ordering::Relaxed);
+    // Precision does not matter for a percentage
+    #[allow(clippy::cast_precision_loss)]
+    let success: f64 = joined as f64 * 100.0 / players.max(1) as f64;
+    println!(
+        "{joined}/{players} players joined ({success:.1}%), {} failed or disconnected early",
+        stats.failed.load(Ordering::Relaxed)
+    );
+
+    let mut latencies: Vec<Duration> = stats
+        .join_latencies
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    latencies.sort_unstable();
+    if latencies.is_empty() {
+        return;
+    }
+    println!(
+        "Join latency: p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+        percentile(&latencies, 50),
+        percentile(&latencies, 90),
+        percentile(&latencies, 99),
+        percentile(&latencies, 100),
+    );
+}
+
+/// The `percent`th percentile of sorted durations, by the nearest-rank method.
+pub fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let rank: usize = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted
+        .get(rank.min(sorted.len()).saturating_sub(1))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Read the metrics of the server.
+fn scrape(address: SocketAddr) -> Result<ServerSample, io::Error> {
+    let mut stream: TcpStream = TcpStream::connect_timeout(&address, REPORT_INTERVAL)?;
+    stream.set_read_timeout(Some(REPORT_INTERVAL))?;
+    stream.write_all(
+        b"GET /metrics HTTP/1.1\r\nHost: swarm\r\nConnection: close\r\n\r\n",
+    )?;
+    let mut response: String = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(parse_metrics(&response))
+}
+
+/// Extract the throughput and memory counters from a metrics page.
+pub fn parse_metrics(page: &str) -> ServerSample {
+    let mut sample: ServerSample = ServerSample::default();
+    for line in page.lines().filter(|line| !line.starts_with('#')) {
+        let Some((name, value)) = line.rsplit_once(' ') else {
+            continue;
+        };
+        let Ok(value) = value.parse::<u64>() else {
+            continue;
+        };
+        match name {
+            "atmospheremc_bytes_total{direction=\"in\"}" => sample.bytes_in = value,
+            "atmospheremc_bytes_total{direction=\"out\"}" => sample.bytes_out = value,
+            "atmospheremc_resident_memory_bytes" => sample.memory = Some(value),
+            _ if name.starts_with("atmospheremc_packets_total{") => {
+                sample.packets += value;
+            }
+            _ => {}
+        }
+    }
+    sample
+}
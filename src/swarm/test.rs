@@ -0,0 +1,87 @@
+//! Test [`crate::swarm`].
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::swarm::{parse_metrics, percentile, throughput, Options, ServerSample};
+
+    #[test]
+    fn test_options() {
+        let args = |args: &[&str]| -> Vec<String> {
+            args.iter().map(ToString::to_string).collect()
+        };
+
+        let options: Options = Options::parse(&args(&["127.0.0.1:25565", "50"])).unwrap();
+        assert_eq!(options.address, "127.0.0.1:25565".parse().unwrap());
+        assert_eq!(options.players, 50);
+        assert_eq!(options.duration, Duration::from_secs(60));
+        assert_eq!(options.metrics, None);
+
+        let options: Options = Options::parse(&args(&[
+            "127.0.0.1:25565",
+            "5",
+            "--rate",
+            "2.5",
+            "--duration",
+            "30",
+            "--metrics",
+            "127.0.0.1:9225",
+        ]))
+        .unwrap();
+        assert!((options.rate - 2.5).abs() < f64::EPSILON);
+        assert_eq!(options.duration, Duration::from_secs(30));
+        assert_eq!(options.metrics, Some("127.0.0.1:9225".parse().unwrap()));
+
+        for invalid in [
+            &["127.0.0.1:25565"][..],
+            &["127.0.0.1:25565", "many"],
+            &["127.0.0.1:25565", "5", "--rate", "0"],
+            &["127.0.0.1:25565", "5", "--duration"],
+            &["127.0.0.1:25565", "5", "--verbose", "1"],
+        ] {
+            assert_eq!(Options::parse(&args(invalid)), None, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<Duration> = (1..=200).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&latencies, 50), Duration::from_millis(100));
+        assert_eq!(percentile(&latencies, 99), Duration::from_millis(198));
+        assert_eq!(percentile(&latencies, 100), Duration::from_millis(200));
+        assert_eq!(percentile(&latencies, 0), Duration::from_millis(1));
+        assert_eq!(
+            percentile(&[Duration::from_secs(1)], 90),
+            Duration::from_secs(1)
+        );
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let page: &str = "\
+# HELP atmospheremc_bytes_total Bytes by direction.
+# TYPE atmospheremc_bytes_total counter
+atmospheremc_bytes_total{direction=\"in\"} 2048
+atmospheremc_bytes_total{direction=\"out\"} 10240
+atmospheremc_resident_memory_bytes 52428800
+atmospheremc_packets_total{direction=\"in\",state=\"Play\",id=\"0x1A\"} 3
+atmospheremc_packets_total{direction=\"out\",state=\"Play\",id=\"0x27\"} 4
+atmospheremc_tick_duration_seconds_sum 1.5
+";
+        let after: ServerSample = parse_metrics(page);
+        assert_eq!(
+            after,
+            ServerSample {
+                bytes_in: 2048,
+                bytes_out: 10240,
+                packets: 7,
+                memory: Some(52_428_800),
+            }
+        );
+        assert_eq!(
+            throughput(&ServerSample::default(), &after, Duration::from_secs(2)),
+            ", server 4 packets/s, 1.0 KiB/s in, 5.0 KiB/s out"
+        );
+    }
+}
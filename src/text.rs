@@ -0,0 +1,353 @@
+//! Text components, for chat messages and disconnect reasons.
+use std::{fmt::Display, io::Write};
+
+use serde_json::{json, Map, Value};
+
+use crate::types::{nbt::Nbt, DataTypeEncodeError};
+
+/// Content of a [`Text`] component.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Content {
+    /// Literal text.
+    Text(String),
+
+    /// A translation key, with its arguments.
+    Translate {
+        /// The translation key.
+        key: String,
+
+        /// Arguments of the translation.
+        with: Vec<Text>,
+    },
+}
+
+/// A text component.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Text {
+    /// Content of the component.
+    content: Content,
+
+    /// Color name (e.g. `yellow`) or `#RRGGBB`.
+    color: Option<String>,
+
+    /// Children, inheriting the style of this component.
+    extra: Vec<Self>,
+}
+
+impl Text {
+    /// Create a literal text component.
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            content: Content::Text(text.into()),
+            color: None,
+            extra: Vec::new(),
+        }
+    }
+
+    /// Create a translated text component, resolved by the client.
+    pub fn translate(key: impl Into<String>, with: Vec<Self>) -> Self {
+        Self {
+            content: Content::Translate {
+                key: key.into(),
+                with,
+            },
+            color: None,
+            extra: Vec::new(),
+        }
+    }
+
+    /// Set the color of the component.
+    #[must_use]
+    pub fn color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// Add a child component, inheriting the style of this one.
+    #[must_use]
+    pub fn append(mut self, child: Self) -> Self {
+        self.extra.push(child);
+        self
+    }
+
+    /// Convert to JSON, as used in the login state and the server list.
+    pub fn to_json(&self) -> Value {
+        let mut object: Map<String, Value> = Map::new();
+        match &self.content {
+            Content::Text(text) => {
+                object.insert("text".to_string(), json!(text));
+            }
+            Content::Translate { key, with } => {
+                object.insert("translate".to_string(), json!(key));
+                if !with.is_empty() {
+                    object.insert(
+                        "with".to_string(),
+                        Value::Array(with.iter().map(Self::to_json).collect()),
+                    );
+                }
+            }
+        }
+        if let Some(color) = &self.color {
+            object.insert("color".to_string(), json!(color));
+        }
+        if !self.extra.is_empty() {
+            object.insert(
+                "extra".to_string(),
+                Value::Array(self.extra.iter().map(Self::to_json).collect()),
+            );
+        }
+        Value::Object(object)
+    }
+
+    /// Convert to NBT, as used in the configuration and play states.
+    pub fn to_nbt(&self) -> Nbt {
+        let mut entries: Vec<(String, Nbt)> = Vec::new();
+        match &self.content {
+            Content::Text(text) => {
+                entries.push(("text".to_string(), Nbt::String(text.clone())));
+            }
+            Content::Translate { key, with } => {
+                entries.push(("translate".to_string(), Nbt::String(key.clone())));
+                if !with.is_empty() {
+                    entries.push((
+                        "with".to_string(),
+                        Nbt::List(with.iter().map(Self::to_nbt).collect()),
+                    ));
+                }
+            }
+        }
+        if let Some(color) = &self.color {
+            entries.push(("color".to_string(), Nbt::String(color.clone())));
+        }
+        if !self.extra.is_empty() {
+            entries.push((
+                "extra".to_string(),
+                Nbt::List(self.extra.iter().map(Self::to_nbt).collect()),
+            ));
+        }
+        Nbt::Compound(entries)
+    }
+
+    /// Encode as a network NBT text component.
+    pub fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.to_nbt().encode(to)
+    }
+}
+
+impl Display for Text {
+    /// Plain text rendering, for logs and the console. Translations are shown in
+    /// English if known, as their key otherwise.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.content {
+            Content::Text(text) => write!(f, "{text}")?,
+            Content::Translate { key, with } => {
+                let args: Vec<String> = with.iter().map(ToString::to_string).collect();
+                if let Some(format) = english(key) {
+                    write!(f, "{}", substitute(format, &args))?;
+                } else {
+                    write!(f, "{key}")?;
+                    if !args.is_empty() {
+                        write!(f, "[{}]", args.join(", "))?;
+                    }
+                }
+            }
+        }
+        for child in &self.extra {
+            write!(f, "{child}")?;
+        }
+        Ok(())
+    }
+}
+
+/// English translations of the keys sent by the server, from vanilla's `en_us.json`.
+const ENGLISH: [(&str, &str); 55] = [
+    ("argument.entity.invalid", "Invalid name or UUID"),
+    ("argument.entity.notfound.player", "No player was found"),
+    (
+        "argument.entity.selector.unknown",
+        "Unknown selector type '%s'",
+    ),
+    (
+        "argument.entity.toomany",
+        "Only one entity is allowed, but the provided selector allows more than one",
+    ),
+    (
+        "argument.integer.big",
+        "Integer must not be more than %s, found %s",
+    ),
+    (
+        "argument.integer.low",
+        "Integer must not be less than %s, found %s",
+    ),
+    (
+        "argument.player.entities",
+        "Only players may be affected by this command, but the provided selector includes entities",
+    ),
+    (
+        "argument.player.toomany",
+        "Only one player is allowed, but the provided selector allows more than one",
+    ),
+    (
+        "argument.pos.incomplete",
+        "Incomplete (expected 3 coordinates)",
+    ),
+    ("argument.pos.missing.int", "Expected a block position"),
+    ("chat.type.announcement", "[%s] %s"),
+    (
+        "command.expected.separator",
+        "Expected whitespace to end one argument, but found trailing data",
+    ),
+    (
+        "command.unknown.command",
+        "Unknown or incomplete command, see below for error",
+    ),
+    (
+        "commands.ban.failed",
+        "Nothing changed. The player is already banned",
+    ),
+    ("commands.ban.success", "Banned %s: %s"),
+    ("commands.kick.success", "Kicked %s: %s"),
+    (
+        "commands.list.players",
+        "There are %s of a max of %s players online: %s",
+    ),
+    (
+        "commands.pardon.failed",
+        "Nothing changed. The player isn't banned",
+    ),
+    ("commands.pardon.success", "Unbanned %s"),
+    ("commands.reload.failure", "Reload failed; keeping old data"),
+    ("commands.reload.success", "Reloading!"),
+    ("commands.stop.stopping", "Stopping the server"),
+    (
+        "commands.whitelist.add.failed",
+        "Player is already whitelisted",
+    ),
+    (
+        "commands.whitelist.add.success",
+        "Added %s to the whitelist",
+    ),
+    (
+        "commands.whitelist.alreadyOff",
+        "Whitelist is already turned off",
+    ),
+    (
+        "commands.whitelist.alreadyOn",
+        "Whitelist is already turned on",
+    ),
+    ("commands.whitelist.disabled", "Whitelist is now turned off"),
+    ("commands.whitelist.enabled", "Whitelist is now turned on"),
+    (
+        "commands.whitelist.list",
+        "There are %s whitelisted player(s): %s",
+    ),
+    (
+        "commands.whitelist.none",
+        "There are no whitelisted players",
+    ),
+    ("commands.whitelist.reloaded", "Reloaded the whitelist"),
+    (
+        "commands.whitelist.remove.failed",
+        "Player is not whitelisted",
+    ),
+    (
+        "commands.whitelist.remove.success",
+        "Removed %s from the whitelist",
+    ),
+    (
+        "disconnect.exceeded_packet_rate",
+        "Kicked for exceeding packet rate limit",
+    ),
+    ("disconnect.timeout", "Timed out"),
+    (
+        "multiplayer.disconnect.banned",
+        "You are banned from this server",
+    ),
+    (
+        "multiplayer.disconnect.banned.expiration",
+        "\nYour ban will be removed on %s",
+    ),
+    (
+        "multiplayer.disconnect.banned.reason",
+        "You are banned from this server.\nReason: %s",
+    ),
+    (
+        "multiplayer.disconnect.banned_ip.expiration",
+        "\nYour ban will be removed on %s",
+    ),
+    (
+        "multiplayer.disconnect.banned_ip.reason",
+        "Your IP address is banned from this server.\nReason: %s",
+    ),
+    (
+        "multiplayer.disconnect.duplicate_login",
+        "You logged in from another location",
+    ),
+    (
+        "multiplayer.disconnect.ip_banned",
+        "You have been IP banned from this server",
+    ),
+    ("multiplayer.disconnect.kicked", "Kicked by an operator"),
+    (
+        "multiplayer.disconnect.not_whitelisted",
+        "You are not white-listed on this server!",
+    ),
+    ("multiplayer.disconnect.server_full", "The server is full!"),
+    ("multiplayer.disconnect.slow_login", "Took too long to log in"),
+    (
+        "multiplayer.disconnect.transfers_disabled",
+        "The server does not accept transfers",
+    ),
+    ("multiplayer.player.joined", "%s joined the game"),
+    ("multiplayer.player.left", "%s left the game"),
+    ("parsing.bool.expected", "Expected boolean"),
+    (
+        "parsing.bool.invalid",
+        "Invalid boolean, expected 'true' or 'false' but found '%s'",
+    ),
+    ("parsing.int.expected", "Expected integer"),
+    ("parsing.int.invalid", "Invalid integer '%s'"),
+    (
+        "parsing.quote.escape",
+        "Invalid escape sequence '\\%s' in quoted string",
+    ),
+    ("parsing.quote.expected.end", "Unclosed quoted string"),
+];
+
+/// English translation of a key, if known.
+fn english(key: &str) -> Option<&'static str> {
+    ENGLISH
+        .iter()
+        .find(|(known, _)| *known == key)
+        .map(|(_, format)| *format)
+}
+
+/// Replace the `%s` and `%1$s` placeholders of a translation with its arguments.
+fn substitute(format: &str, args: &[String]) -> String {
+    let mut result: String = String::new();
+    let mut next: usize = 0;
+    let mut rest: &str = format;
+    while let Some(start) = rest.find('%') {
+        result.push_str(rest.get(..start).unwrap_or_default());
+        rest = rest.get(start + 1..).unwrap_or_default();
+
+        if let Some(after) = rest.strip_prefix('%') {
+            result.push('%');
+            rest = after;
+            continue;
+        }
+        let index: Option<usize> = rest.split_once("$s").and_then(|(position, after)| {
+            let position: usize = position.parse().ok()?;
+            rest = after;
+            position.checked_sub(1)
+        });
+        let index: usize = index.unwrap_or_else(|| {
+            rest = rest.strip_prefix('s').unwrap_or(rest);
+            next += 1;
+            next - 1
+        });
+        result.push_str(args.get(index).map_or("", String::as_str));
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,366 @@
+//! Fixed-rate server ticks: world time, scheduled tasks, player updates and tick
+//! statistics.
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{protocol::play::CUpdateTime, server::Server, state::ServerState};
+
+mod test;
+
+/// Number of ticks per second.
+pub const TPS: u32 = 20;
+
+/// Duration of a tick.
+pub const TICK_DURATION: Duration = Duration::from_millis(1000 / TPS as u64);
+
+/// Number of ticks in a Minecraft day.
+const DAY_LENGTH: i64 = 24000;
+
+/// Interval between two Update Time packets, in ticks.
+const TIME_UPDATE_INTERVAL: u64 = TPS as u64;
+
+/// Delay after which "can't keep up" is logged.
+const MAX_LATENESS: Duration = Duration::from_secs(2);
+
+/// Number of ticks used to compute the statistics.
+const STATS_WINDOW: usize = 100;
+
+/// Age of the world and time of day.
+pub struct WorldTime {
+    /// Ticks since the world was created.
+    world_age: AtomicI64,
+
+    /// Time of day in ticks, 0 being sunrise.
+    time_of_day: AtomicI64,
+}
+
+impl WorldTime {
+    /// Create a clock at noon of the first day.
+    pub const fn new() -> Self {
+        Self {
+            world_age: AtomicI64::new(0),
+            time_of_day: AtomicI64::new(6000),
+        }
+    }
+
+    /// Ticks since the world was created.
+    pub fn world_age(&self) -> i64 {
+        self.world_age.load(Ordering::Relaxed)
+    }
+
+    /// Time of day in ticks, between 0 and 24000.
+    pub fn time_of_day(&self) -> i64 {
+        self.time_of_day.load(Ordering::Relaxed)
+    }
+
+    /// Set the time of day.
+    #[allow(dead_code)] // No time command yet
+    pub fn set_time_of_day(&self, time: i64) {
+        self.time_of_day
+            .store(time.rem_euclid(DAY_LENGTH), Ordering::Relaxed);
+    }
+
+    /// Advance the clock by one tick.
+    fn advance(&self) {
+        self.world_age.fetch_add(1, Ordering::Relaxed);
+        // Not a load and a store, which would undo a concurrent set
+        let _ =
+            self.time_of_day
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |time| {
+                    Some((time + 1).rem_euclid(DAY_LENGTH))
+                });
+    }
+}
+
+/// A task run by the [`Scheduler`] on the tick thread.
+type TaskFn = Box<dyn FnMut(&Server) + Send>;
+
+/// A scheduled task.
+struct Task {
+    /// ID of the task, to cancel it.
+    id: u64,
+
+    /// Tick at which the task runs next.
+    next_run: u64,
+
+    /// Interval between runs in ticks, for repeating tasks.
+    period: Option<u64>,
+
+    /// The task.
+    run: TaskFn,
+}
+
+/// Tasks run on the tick thread, after a delay in ticks.
+pub struct Scheduler {
+    /// Pending tasks.
+    tasks: Mutex<Vec<Task>>,
+
+    /// IDs of the tasks being run, removed when they are cancelled.
+    running: Mutex<Vec<u64>>,
+
+    /// Next task ID to allocate.
+    next_id: AtomicU64,
+
+    /// Current tick, as seen by the scheduler.
+    current_tick: AtomicU64,
+}
+
+impl Scheduler {
+    /// Create an empty scheduler.
+    pub const fn new() -> Self {
+        Self {
+            tasks: Mutex::new(Vec::new()),
+            running: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            current_tick: AtomicU64::new(0),
+        }
+    }
+
+    /// Run a task once, `delay` ticks from now. Returns the ID of the task.
+    #[allow(dead_code)] // No delayed task yet
+    pub fn schedule(
+        &self,
+        delay: u64,
+        task: impl FnMut(&Server) + Send + 'static,
+    ) -> u64 {
+        self.add(delay, None, Box::new(task))
+    }
+
+    /// Run a task every `period` ticks, starting `delay` ticks from now. Returns the
+    /// ID of the task.
+    pub fn schedule_repeating(
+        &self,
+        delay: u64,
+        period: u64,
+        task: impl FnMut(&Server) + Send + 'static,
+    ) -> u64 {
+        self.add(delay, Some(period.max(1)), Box::new(task))
+    }
+
+    /// Cancel a task, even while it runs. Returns whether the task was pending or
+    /// running.
+    pub fn cancel(&self, id: u64) -> bool {
+        let mut tasks = self.tasks.lock().unwrap_or_else(PoisonError::into_inner);
+        let count: usize = tasks.len();
+        tasks.retain(|task| task.id != id);
+        tasks.len() != count || remove_id(&self.running, id)
+    }
+
+    /// Add a task.
+    fn add(&self, delay: u64, period: Option<u64>, run: TaskFn) -> u64 {
+        let id: u64 = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let next_run: u64 = self.current_tick.load(Ordering::Relaxed) + delay;
+        self.tasks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Task {
+                id,
+                next_run,
+                period,
+                run,
+            });
+        id
+    }
+
+    /// Run the tasks due at `tick`.
+    fn run(&self, server: &Server, tick: u64) {
+        self.current_tick.store(tick, Ordering::Relaxed);
+
+        // Tasks are taken out of the lock while running, so they can schedule others
+        let due: Vec<Task> = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(PoisonError::into_inner);
+            let (due, pending) = tasks.drain(..).partition(|task| task.next_run <= tick);
+            *tasks = pending;
+            self.running
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .extend(due.iter().map(|task| task.id));
+            drop(tasks);
+            due
+        };
+
+        for mut task in due {
+            let cancelled: bool = !self
+                .running
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .contains(&task.id);
+            // Cancelled by a task run before it
+            if cancelled {
+                continue;
+            }
+
+            (task.run)(server);
+            // Locked in the order of `cancel`, not to miss a cancellation
+            let mut tasks = self.tasks.lock().unwrap_or_else(PoisonError::into_inner);
+            let cancelled: bool = !remove_id(&self.running, task.id);
+            if let (Some(period), false) = (task.period, cancelled) {
+                task.next_run = tick + period;
+                tasks.push(task);
+            }
+        }
+    }
+}
+
+/// Remove `id` from `ids`. Returns whether it was there.
+fn remove_id(ids: &Mutex<Vec<u64>>, id: u64) -> bool {
+    let mut ids = ids.lock().unwrap_or_else(PoisonError::into_inner);
+    ids.iter()
+        .position(|&other| other == id)
+        .map(|index| ids.swap_remove(index))
+        .is_some()
+}
+
+/// Timing of a tick.
+#[derive(Clone, Copy)]
+struct Sample {
+    /// When the tick started.
+    start: Instant,
+
+    /// Time spent in the tick.
+    duration: Duration,
+}
+
+/// Statistics about the last ticks.
+pub struct TickStats {
+    /// Number of ticks since the server started.
+    tick: AtomicU64,
+
+    /// Timing of the last [`STATS_WINDOW`] ticks.
+    samples: Mutex<VecDeque<Sample>>,
+}
+
+impl TickStats {
+    /// Create empty statistics.
+    pub const fn new() -> Self {
+        Self {
+            tick: AtomicU64::new(0),
+            samples: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Number of ticks since the server started.
+    pub fn tick(&self) -> u64 {
+        self.tick.load(Ordering::Relaxed)
+    }
+
+    /// Average milliseconds per tick.
+    pub fn mspt(&self) -> f64 {
+        let samples = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+        if samples.is_empty() {
+            return 0.0;
+        }
+
+        let total: Duration = samples.iter().map(|s| s.duration).sum();
+        #[allow(clippy::cast_precision_loss)]
+        let average: f64 = total.as_secs_f64() * 1000.0 / samples.len() as f64;
+        average
+    }
+
+    /// Ticks per second, at most [`TPS`].
+    pub fn tps(&self) -> f64 {
+        let (count, elapsed): (usize, Duration) = {
+            let samples = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+            match (samples.front(), samples.back()) {
+                (Some(first), Some(last)) => {
+                    (samples.len() - 1, last.start.duration_since(first.start))
+                }
+                _ => return f64::from(TPS),
+            }
+        };
+
+        if elapsed.is_zero() {
+            return f64::from(TPS);
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let tps: f64 = count as f64 / elapsed.as_secs_f64();
+        tps.min(f64::from(TPS))
+    }
+
+    /// Record a tick.
+    fn record(&self, sample: Sample) {
+        self.tick.fetch_add(1, Ordering::Relaxed);
+        let mut samples = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+        if samples.len() == STATS_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+}
+
+/// Run the tick loop on a dedicated thread.
+pub fn spawn(server: std::sync::Arc<Server>) -> thread::JoinHandle<()> {
+    thread::Builder::new()
+        .name("Tick".to_string())
+        .spawn(move || run(&server))
+        .expect("Failed to spawn the tick thread")
+}
+
+/// Tick the server [`TPS`] times per second, until it stops.
+fn run(server: &Server) {
+    let mut next_tick: Instant = Instant::now();
+    let mut tick: u64 = 0;
+
+    while server.is_running() {
+        let start: Instant = Instant::now();
+        tick_once(server, tick);
+        let duration: Duration = start.elapsed();
+        server.ticks().record(Sample { start, duration });
+        server.metrics().record_tick(duration);
+
+        tick += 1;
+        next_tick += TICK_DURATION;
+
+        let now: Instant = Instant::now();
+        if let Some(wait) = next_tick.checked_duration_since(now) {
+            thread::sleep(wait);
+        } else {
+            let late: Duration = now.duration_since(next_tick);
+            if late > MAX_LATENESS {
+                let skipped: u128 = late.as_millis() / TICK_DURATION.as_millis();
+                log::warn!(
+                    target: "Tick",
+                    "Can't keep up! Is the server overloaded? Running {0}ms or {skipped} ticks behind",
+                    late.as_millis()
+                );
+                // Skip the missed ticks instead of running them in a burst
+                next_tick = now;
+            }
+        }
+    }
+}
+
+/// Run a single tick.
+fn tick_once(server: &Server, tick: u64) {
+    server.time().advance();
+
+    server.scheduler().run(server, tick);
+
+    let players = server.players().all();
+    for player in &players {
+        if player.connection().state() == ServerState::Play {
+            player.tick();
+        }
+    }
+
+    if tick.is_multiple_of(TIME_UPDATE_INTERVAL) {
+        broadcast_time(server);
+    }
+}
+
+/// Send the world age and the time of day to the players.
+fn broadcast_time(server: &Server) {
+    let time: &WorldTime = server.time();
+    if let Err(e) =
+        server.broadcast(&CUpdateTime::new(time.world_age(), time.time_of_day()))
+    {
+        log::error!(target: "Tick", "Failed to broadcast time: {e}");
+    }
+}
@@ -0,0 +1,190 @@
+//! Test [`crate::tick`].
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::PathBuf,
+        process,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        access::AccessLists,
+        config::Config,
+        server::Server,
+        tick::{Sample, Scheduler, TickStats, WorldTime, DAY_LENGTH, STATS_WINDOW, TPS},
+    };
+
+    /// Create a server with its access lists in a temporary directory.
+    fn new_server(name: &str) -> Server {
+        let directory: PathBuf = std::env::temp_dir()
+            .join(format!("atmospheremc-tick-{name}-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        Server::new(Config::default(), AccessLists::load(&directory, false))
+    }
+
+    /// A task counting its runs in `runs`.
+    fn counter(runs: &Arc<AtomicU64>) -> impl FnMut(&Server) + Send + 'static {
+        let runs: Arc<AtomicU64> = Arc::clone(runs);
+        move |_| {
+            runs.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_scheduler() {
+        let server: Server = new_server("scheduler");
+        let scheduler: Scheduler = Scheduler::new();
+        let once: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let repeating: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let cancelled: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+
+        scheduler.schedule(2, counter(&once));
+        let every_three: u64 = scheduler.schedule_repeating(1, 3, counter(&repeating));
+        let never: u64 = scheduler.schedule(1, counter(&cancelled));
+        assert!(scheduler.cancel(never));
+        assert!(!scheduler.cancel(never));
+
+        // Runs at ticks 1, 4, 7 and 10 for the repeating task
+        let mut runs: Vec<(u64, u64)> = Vec::new();
+        for tick in 0..=10 {
+            scheduler.run(&server, tick);
+            runs.push((
+                once.load(Ordering::Relaxed),
+                repeating.load(Ordering::Relaxed),
+            ));
+        }
+        assert_eq!(
+            runs,
+            [
+                (0, 0),
+                (0, 1),
+                (1, 1),
+                (1, 1),
+                (1, 2),
+                (1, 2),
+                (1, 2),
+                (1, 3),
+                (1, 3),
+                (1, 3),
+                (1, 4)
+            ]
+        );
+        assert_eq!(cancelled.load(Ordering::Relaxed), 0);
+
+        // Delays count from the last tick run
+        assert!(scheduler.cancel(every_three));
+        scheduler.schedule(5, counter(&once));
+        scheduler.run(&server, 14);
+        assert_eq!(once.load(Ordering::Relaxed), 1);
+        scheduler.run(&server, 15);
+        assert_eq!(once.load(Ordering::Relaxed), 2);
+        assert_eq!(repeating.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn test_scheduler_nested() {
+        let server: Server = new_server("scheduler-nested");
+        let runs: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+
+        // A task may schedule another one, run on a later tick
+        let nested: Arc<AtomicU64> = Arc::clone(&runs);
+        server.scheduler().schedule(0, move |server| {
+            server.scheduler().schedule(0, counter(&nested));
+        });
+        server.scheduler().run(&server, 0);
+        assert_eq!(runs.load(Ordering::Relaxed), 0);
+        server.scheduler().run(&server, 1);
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_scheduler_cancel_running() {
+        let server: Server = new_server("scheduler-cancel");
+        let runs: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let id: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+
+        // A repeating task cancelling itself is not scheduled again
+        let mut count = counter(&runs);
+        let own_id: Arc<AtomicU64> = Arc::clone(&id);
+        let task: u64 = server.scheduler().schedule_repeating(0, 1, move |server| {
+            count(server);
+            assert!(server.scheduler().cancel(own_id.load(Ordering::Relaxed)));
+        });
+        id.store(task, Ordering::Relaxed);
+        for tick in 0..3 {
+            server.scheduler().run(&server, tick);
+        }
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+        assert!(!server.scheduler().cancel(task));
+
+        // Nor is a due task cancelled by the one run before it
+        let skipped: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let next: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let next_id: Arc<AtomicU64> = Arc::clone(&next);
+        server.scheduler().schedule(0, move |server| {
+            assert!(server.scheduler().cancel(next_id.load(Ordering::Relaxed)));
+        });
+        next.store(
+            server.scheduler().schedule(0, counter(&skipped)),
+            Ordering::Relaxed,
+        );
+        server.scheduler().run(&server, 3);
+        assert_eq!(skipped.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_tick_stats() {
+        let stats: TickStats = TickStats::new();
+        assert_eq!(stats.tick(), 0);
+        assert!((stats.mspt() - 0.0).abs() < f64::EPSILON);
+        assert!((stats.tps() - f64::from(TPS)).abs() < f64::EPSILON);
+
+        // Ticks of 10ms, 100ms apart: 10 TPS
+        let start: Instant = Instant::now();
+        for i in 0..10 {
+            stats.record(Sample {
+                start: start + Duration::from_millis(100) * i,
+                duration: Duration::from_millis(10),
+            });
+        }
+        assert_eq!(stats.tick(), 10);
+        assert!((stats.mspt() - 10.0).abs() < 1e-9);
+        assert!((stats.tps() - 10.0).abs() < 1e-9);
+
+        // Only the last ticks are averaged, and the rate is capped
+        let mut next: Instant = start + Duration::from_secs(1);
+        for _ in 0..STATS_WINDOW {
+            stats.record(Sample {
+                start: next,
+                duration: Duration::from_millis(30),
+            });
+            next += Duration::from_millis(10);
+        }
+        assert_eq!(stats.tick(), 10 + u64::try_from(STATS_WINDOW).unwrap());
+        assert!((stats.mspt() - 30.0).abs() < 1e-9);
+        assert!((stats.tps() - f64::from(TPS)).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_world_time() {
+        let time: WorldTime = WorldTime::new();
+        assert_eq!(time.world_age(), 0);
+        assert_eq!(time.time_of_day(), 6000);
+
+        time.set_time_of_day(DAY_LENGTH - 1);
+        time.advance();
+        assert_eq!(time.world_age(), 1);
+        assert_eq!(time.time_of_day(), 0);
+
+        time.set_time_of_day(-1000);
+        assert_eq!(time.time_of_day(), DAY_LENGTH - 1000);
+        time.set_time_of_day(DAY_LENGTH * 3 + 500);
+        assert_eq!(time.time_of_day(), 500);
+        assert_eq!(time.world_age(), 1);
+    }
+}
@@ -1,10 +1,7 @@
 //! Macros for implementing [`DataType`].
-use std::{
-    io::{Read, Write},
-    mem::size_of,
-};
+use std::{io::Write, mem::size_of};
 
-use super::{DataType, DataTypeDecodeError, DataTypeEncodeError};
+use super::{DataType, DataTypeDecodeError, DataTypeEncodeError, ReadBytes};
 
 /// Add an implementation of [`DataType`] for primitives.
 macro_rules! add_impl {
@@ -22,24 +19,50 @@ macro_rules! add_impl {
                 self
             }
 
-            fn decode(from: &mut impl Read) -> Result<Self, DataTypeDecodeError> {
-                let mut buf: [u8; size_of::<$t>()] = [0; size_of::<$t>()];
-                from.read_exact(&mut buf)?;
-
-                Ok(<$t>::from_be_bytes(
-                    buf,
-                ))
+            fn decode(from: &mut &[u8]) -> Result<Self, DataTypeDecodeError> {
+                Ok(<$t>::from_be_bytes(from.read_array()?))
             }
 
             fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
                 to.write_all(&self.to_be_bytes())?;
                 Ok(())
             }
+
+            fn size_hint(&self) -> usize {
+                size_of::<$t>()
+            }
         }
     )*};
 }
 
-add_impl!(u8 u16 i64);
+add_impl!(i8 u8 i16 u16 i32 i64 u128 f32 f64);
+
+impl DataType<Self> for bool {
+    fn new(value: Self) -> Self {
+        value
+    }
+
+    fn get(&self) -> Self {
+        *self
+    }
+
+    fn get_ref(&self) -> &Self {
+        self
+    }
+
+    fn decode(from: &mut &[u8]) -> Result<Self, DataTypeDecodeError> {
+        Ok(from.read_byte()? != 0)
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        to.write_all(&[u8::from(*self)])?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> usize {
+        1
+    }
+}
 
 /// Add an implementation of [`DataType::new`], [`DataType::get`] and
 /// [`DataType::get_ref`] for tuple structs.
@@ -1,40 +1,24 @@
 //! Types used in the protocol.
 use std::{
-    cmp,
-    collections::VecDeque,
-    io::{self, Read, Write},
+    io::{self, Write},
     num::TryFromIntError,
-    string::FromUtf8Error,
+    str::Utf8Error,
 };
 
 use thiserror::Error;
 
 pub mod macros;
+pub mod nbt;
 mod test;
+pub mod uuid;
 pub mod var;
 
-/// Chunk size to read in [`ReadBytes::read_bytes`].
-const CHUNK_SIZE: usize = 256;
-
-// fn drain<R>(bytes: &impl Read, range: R) -> Result<Vec<u8>, DataTypeDecodeError>
-// where
-//     R: RangeBounds<usize> + Iterator,
-// {
-//     let mut output: Vec<u8> = Vec::new();
-//     for _ in range {
-//         output.push(bytes.rea().ok_or_else(|| {
-//             DataTypeDecodeError::PrematureEndOfVarNumber(bytes.clone())
-//         })?);
-//     }
-//     Ok(output)
-// }
-
 /// Error when decoding a [`DataType`] using [`DataType::decode`].
 #[derive(Error, Debug)]
 pub enum DataTypeDecodeError {
     /// Stream ended prematurely while reading variable length data ([`var`]).
-    #[error("VarNumber ended prematurely: {0:X?}")]
-    PrematureEndOfVarNumber(VecDeque<u8>),
+    #[error("VarNumber ended prematurely")]
+    PrematureEndOfVarNumber,
 
     /// Variable length data ([`var`]) exceeds maximum value.
     #[error("VarNumber too big")]
@@ -44,12 +28,12 @@ pub enum DataTypeDecodeError {
     #[error(transparent)]
     TryFromIntError(#[from] TryFromIntError),
 
-    /// Error when converting a type from UTF-8. See [`FromUtf8Error`].
+    /// Error when converting a type from UTF-8. See [`Utf8Error`].
     #[error(transparent)]
-    FromUtf8Error(#[from] FromUtf8Error),
+    Utf8Error(#[from] Utf8Error),
 
-    /// Stream ended prematurely while reading fixed length data.
-    #[error("Premature end of stream while reading a fixed length data type")]
+    /// Data ended prematurely while reading a data type.
+    #[error("Premature end of data while reading a data type")]
     PrematureEnd,
 
     /// When reading an enum, an invalid variant is received.
@@ -62,22 +46,72 @@ pub enum DataTypeDecodeError {
         enumeration: String,
     },
 
+    /// A string is longer than allowed.
+    #[error("String too long: {length} > {max_length}")]
+    StringTooLong {
+        /// Length of the string, in bytes or UTF-16 code units.
+        length: usize,
+
+        /// Maximum length of the string, in UTF-16 code units.
+        max_length: usize,
+    },
+
+    /// A packet is larger than the maximum packet size.
+    #[error("Packet too large: {0} bytes")]
+    PacketTooLarge(usize),
+
+    /// A byte array is longer than allowed.
+    #[error("Byte array too long: {length} > {max_length}")]
+    ByteArrayTooLong {
+        /// Length of the array.
+        length: usize,
+
+        /// Maximum length of the array.
+        max_length: usize,
+    },
+
+    /// The uncompressed length of a compressed packet is invalid.
+    #[error("Invalid uncompressed data length: {0}")]
+    InvalidDataLength(usize),
+
     /// An [`io::Error`].
     #[error(transparent)]
     IOError(#[from] io::Error),
 }
 
+/// Error when encoding a [`DataType`] using [`DataType::encode`].
 #[derive(Error, Debug)]
 pub enum DataTypeEncodeError {
     /// Error when converting a type from an integer. See [`TryFromIntError`].
     #[error(transparent)]
     TryFromIntError(#[from] TryFromIntError),
 
+    /// A byte array is longer than allowed.
+    #[error("Byte array too long: {length} > {max_length}")]
+    ByteArrayTooLong {
+        /// Length of the array.
+        length: usize,
+
+        /// Maximum length of the array.
+        max_length: usize,
+    },
+
     /// An [`io::Error`].
     #[error(transparent)]
     IOError(#[from] io::Error),
+
+    /// An entry to send by ID is missing from its registry.
+    #[error("Unknown entry {entry} of registry {registry}")]
+    UnknownRegistryEntry {
+        /// Name of the registry.
+        registry: &'static str,
+
+        /// Name of the entry.
+        entry: &'static str,
+    },
 }
 
+/// A type of the protocol, wrapping an `Inner` Rust value.
 pub trait DataType<Inner>: Clone {
     /// Create a new instance of the data type from a value.
     fn new(value: Inner) -> Self;
@@ -90,47 +124,58 @@ pub trait DataType<Inner>: Clone {
     #[allow(dead_code)]
     fn get_ref(&self) -> &Inner;
 
-    /// Decode the value from a [`Read`] stream.
-    fn decode(from: &mut impl Read) -> Result<Self, DataTypeDecodeError>;
+    /// Decode the value from the start of a byte slice, advancing it past the
+    /// value.
+    fn decode(from: &mut &[u8]) -> Result<Self, DataTypeDecodeError>;
 
     /// Encode the value to a [`Write`] stream.
     fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError>;
+
+    /// Number of bytes written by [`DataType::encode`].
+    fn size_hint(&self) -> usize;
 }
 
-/// A helper trait for reading bytes easily:
+/// A helper trait for reading bytes easily from a byte slice, advancing it past
+/// what is read. Bytes are borrowed from the slice, not copied:
 ///
-/// ```
-/// # let buf: Vec<u8> = [0x01, 0x23, 0x45, 0x67, 0x89];
+/// ```ignore
+/// # let mut buf: &[u8] = &[0x01, 0x23, 0x45, 0x67, 0x89];
 /// println!("Reading a single byte: {:X?}", buf.read_byte());
 /// println!("Reading 4 bytes: {:X?}", buf.read_bytes(4));
 /// ```
-pub trait ReadBytes: Read {
+pub trait ReadBytes<'a> {
     /// Read a single byte.
-    fn read_byte(&mut self) -> Result<u8, io::Error> {
-        let mut buf: [u8; 1] = [0; 1];
-        self.read_exact(&mut buf)?;
-        Ok(buf[0])
-    }
+    fn read_byte(&mut self) -> Result<u8, DataTypeDecodeError>;
 
     /// Read `length` bytes.
-    fn read_bytes<L: Into<usize> + Copy>(
-        &mut self,
-        length: L,
-    ) -> Result<Vec<u8>, io::Error> {
-        let length = length.into();
-        let mut buf: Vec<u8> = Vec::reser(length);
-        let mut bytes_read: usize = 0;
-        while bytes_read < length {
-            let bytes_to_read: usize = cmp::min(length - bytes_read, CHUNK_SIZE);
-
-            let mut tmp_buf: Vec<u8> = vec![0; bytes_to_read];
-            self.read_exact(&mut tmp_buf)?;
-            buf.append(&mut tmp_buf);
-
-            bytes_read += bytes_to_read;
-        }
-        Ok(buf)
-    }
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], DataTypeDecodeError>;
+
+    /// Read `N` bytes into an array.
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DataTypeDecodeError>;
 }
 
-impl<T: Read> ReadBytes for T {}
+impl<'a> ReadBytes<'a> for &'a [u8] {
+    fn read_byte(&mut self) -> Result<u8, DataTypeDecodeError> {
+        let (byte, rest) = self
+            .split_first()
+            .ok_or(DataTypeDecodeError::PrematureEnd)?;
+        *self = rest;
+        Ok(*byte)
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], DataTypeDecodeError> {
+        let (bytes, rest) = self
+            .split_at_checked(length)
+            .ok_or(DataTypeDecodeError::PrematureEnd)?;
+        *self = rest;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DataTypeDecodeError> {
+        let (array, rest) = self
+            .split_first_chunk::<N>()
+            .ok_or(DataTypeDecodeError::PrematureEnd)?;
+        *self = rest;
+        Ok(*array)
+    }
+}
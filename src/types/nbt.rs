@@ -0,0 +1,88 @@
+//! Named Binary Tag, as used by text components and chunk data.
+use std::io::Write;
+
+use super::DataTypeEncodeError;
+
+/// An NBT tag.
+///
+/// Only encoding is supported, in the network format: the root tag has no name.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Nbt {
+    /// `TAG_String`.
+    String(String),
+
+    /// `TAG_List`, all elements must have the same type.
+    List(Vec<Self>),
+
+    /// `TAG_Compound`.
+    Compound(Vec<(String, Self)>),
+}
+
+impl Nbt {
+    /// Type ID of the tag.
+    const fn id(&self) -> u8 {
+        match self {
+            Self::String(_) => 8,
+            Self::List(_) => 9,
+            Self::Compound(_) => 10,
+        }
+    }
+
+    /// Encode the tag as a nameless root tag.
+    pub fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        to.write_all(&[self.id()])?;
+        self.encode_payload(to)
+    }
+
+    /// Encode the payload of the tag, without its type ID.
+    fn encode_payload(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        match self {
+            Self::String(v) => encode_string(v, to)?,
+            Self::List(elements) => {
+                to.write_all(&[elements.first().map_or(0, Self::id)])?;
+                to.write_all(&i32::try_from(elements.len())?.to_be_bytes())?;
+                for element in elements {
+                    element.encode_payload(to)?;
+                }
+            }
+            Self::Compound(entries) => {
+                for (name, value) in entries {
+                    to.write_all(&[value.id()])?;
+                    encode_string(name, to)?;
+                    value.encode_payload(to)?;
+                }
+                to.write_all(&[0])?; // TAG_End
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Encode a string in Java's modified UTF-8, prefixed by its length.
+fn encode_string(value: &str, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\0' => bytes.extend_from_slice(&[0xC0, 0x80]),
+            c if c.len_utf8() == 4 => {
+                // Supplementary characters are encoded as two 3 bytes surrogates
+                let mut units: [u16; 2] = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    let unit: u32 = u32::from(*unit);
+                    #[allow(clippy::cast_possible_truncation)]
+                    bytes.extend_from_slice(&[
+                        (0xE0 | (unit >> 12)) as u8,
+                        (0x80 | ((unit >> 6) & 0x3F)) as u8,
+                        (0x80 | (unit & 0x3F)) as u8,
+                    ]);
+                }
+            }
+            c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    to.write_all(&u16::try_from(bytes.len())?.to_be_bytes())?;
+    to.write_all(&bytes)?;
+    Ok(())
+}
@@ -1,13 +1,144 @@
 //! Test [`crate::types`].
 #[cfg(test)]
 mod tests {
-    use std::collections::VecDeque;
+    use std::io;
+
+    use proptest::prelude::*;
 
     use crate::types::{
-        var::{VarInt, VarLong},
+        nbt::Nbt,
+        uuid::Uuid,
+        var::{VarInt, VarLong, VarString},
         DataType, DataTypeDecodeError,
     };
 
+    /// Encode `value`, checking the length against [`DataType::size_hint`], and
+    /// decode it back, checking every byte is read.
+    fn round_trip<I, T: DataType<I>>(value: &T) -> T {
+        let mut buf: Vec<u8> = Vec::new();
+        value.encode(&mut buf).unwrap();
+        assert_eq!(buf.len(), value.size_hint(), "size hint of {buf:02x?}");
+
+        let mut bytes: &[u8] = &buf;
+        let decoded: T = T::decode(&mut bytes).unwrap();
+        assert!(bytes.is_empty(), "{} bytes left", bytes.len());
+        decoded
+    }
+
+    /// Decode `bytes` as a `T`, which may fail but not panic.
+    fn decode<I, T: DataType<I>>(bytes: &[u8]) -> Result<T, DataTypeDecodeError> {
+        T::decode(&mut &*bytes)
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip_integers(
+            a: i8, b: u8, c: i16, d: u16, e: i32, f: i64, g: u128, h: bool
+        ) {
+            prop_assert_eq!(round_trip(&a), a);
+            prop_assert_eq!(round_trip(&b), b);
+            prop_assert_eq!(round_trip(&c), c);
+            prop_assert_eq!(round_trip(&d), d);
+            prop_assert_eq!(round_trip(&e), e);
+            prop_assert_eq!(round_trip(&f), f);
+            prop_assert_eq!(round_trip(&g), g);
+            prop_assert_eq!(round_trip(&h), h);
+        }
+
+        #[test]
+        fn test_round_trip_floats(a in any::<u32>(), b in any::<u64>()) {
+            // From the bits, NaNs included
+            let (a, b): (f32, f64) = (f32::from_bits(a), f64::from_bits(b));
+            prop_assert_eq!(round_trip(&a).to_bits(), a.to_bits());
+            prop_assert_eq!(round_trip(&b).to_bits(), b.to_bits());
+        }
+
+        #[test]
+        fn test_round_trip_var_numbers(a: i32, b: i64) {
+            prop_assert_eq!(round_trip(&VarInt(a)), VarInt(a));
+            prop_assert_eq!(round_trip(&VarLong::new(b)), VarLong::new(b));
+        }
+
+        #[test]
+        fn test_round_trip_uuid(a: u128) {
+            prop_assert_eq!(round_trip(&Uuid(a)), Uuid(a));
+        }
+
+        #[test]
+        fn test_round_trip_string(a in "\\PC{0,300}") {
+            prop_assert_eq!(round_trip(&VarString::new(a.clone())).get(), a);
+        }
+
+        #[test]
+        fn test_decode_arbitrary(bytes: Vec<u8>) {
+            // Decoding garbage fails or succeeds, but never panics
+            let _ = decode::<bool, bool>(&bytes);
+            let _ = decode::<i64, i64>(&bytes);
+            let _ = decode::<u128, u128>(&bytes);
+            let _ = decode::<f64, f64>(&bytes);
+            let _ = decode::<i32, VarInt>(&bytes);
+            let _ = decode::<i64, VarLong>(&bytes);
+            let _ = decode::<String, VarString>(&bytes);
+            let _ = decode::<u128, Uuid>(&bytes);
+        }
+    }
+
+    #[test]
+    fn test_size_hint() {
+        for (value, size) in [
+            (0, 1),
+            (127, 1),
+            (128, 2),
+            (25565, 3),
+            (i32::MAX, 5),
+            (-1, 5),
+        ] {
+            assert_eq!(VarInt(value).size_hint(), size, "{value}");
+        }
+        assert_eq!(VarLong::new(-1).size_hint(), 10);
+        assert_eq!(VarString::new("\u{1F600}".to_string()).size_hint(), 5);
+    }
+
+    #[test]
+    fn test_malformed() {
+        // Overlong: a continue bit on the last byte
+        assert!(matches!(
+            decode::<i32, VarInt>(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x01]),
+            Err(DataTypeDecodeError::VarNumberTooBig)
+        ));
+        assert!(matches!(
+            decode::<i64, VarLong>(&[0xff; 11]),
+            Err(DataTypeDecodeError::VarNumberTooBig)
+        ));
+
+        // Truncated
+        let truncated = |result: Result<(), DataTypeDecodeError>| {
+            matches!(result, Err(DataTypeDecodeError::PrematureEnd))
+        };
+        assert!(truncated(decode::<i32, VarInt>(&[0xff, 0xff]).map(drop)));
+        assert!(truncated(decode::<i64, i64>(&[0x00; 7]).map(drop)));
+        assert!(truncated(decode::<u128, Uuid>(&[]).map(drop)));
+        assert!(truncated(
+            decode::<String, VarString>(&[0x05, b'a', b'b']).map(drop)
+        ));
+
+        // Invalid UTF-8: a lone continuation byte, then an unpaired surrogate
+        assert!(matches!(
+            decode::<String, VarString>(&[0x02, b'a', 0x80]),
+            Err(DataTypeDecodeError::Utf8Error(_))
+        ));
+        assert!(matches!(
+            decode::<String, VarString>(&[0x03, 0xed, 0xa0, 0xbd]),
+            Err(DataTypeDecodeError::Utf8Error(_))
+        ));
+
+        // Negative string length
+        assert!(matches!(
+            decode::<String, VarString>(&[0xff, 0xff, 0xff, 0xff, 0x0f]),
+            Err(DataTypeDecodeError::TryFromIntError(_))
+        ));
+    }
+
     #[test]
     fn test_fixed_long() {
         let tests: [(Vec<u8>, i64); 1] = [(
@@ -16,7 +147,7 @@ mod tests {
         )];
         for (bytes, value) in tests {
             let parsed: Result<i64, DataTypeDecodeError> =
-                i64::decode(&mut VecDeque::from(bytes.clone()));
+                i64::decode(&mut bytes.as_slice());
             assert_eq!(
                 *parsed.as_ref().unwrap(),
                 value,
@@ -50,10 +181,21 @@ mod tests {
 
             // Bytes -> Value
             assert_eq!(
-                VarInt::decode(&mut VecDeque::from(bytes)).unwrap(),
+                VarInt::decode(&mut bytes.as_slice()).unwrap(),
                 VarInt(value)
             );
+            assert_eq!(VarInt::read(&mut bytes.as_slice()).unwrap(), VarInt(value));
         }
+
+        // The end of a stream, before and within a VarInt
+        assert!(matches!(
+            VarInt::read(&mut [].as_slice()),
+            Err(DataTypeDecodeError::IOError(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+        assert!(matches!(
+            VarInt::read(&mut [0x80, 0x80].as_slice()),
+            Err(DataTypeDecodeError::PrematureEndOfVarNumber)
+        ));
     }
 
     #[test]
@@ -93,9 +235,94 @@ mod tests {
 
             // Bytes -> Value
             assert_eq!(
-                VarLong::decode(&mut VecDeque::from(bytes)).unwrap(),
+                VarLong::decode(&mut bytes.as_slice()).unwrap(),
                 VarLong::new(value)
             );
         }
     }
+
+    #[test]
+    fn test_nbt() {
+        let mut buf: Vec<u8> = Vec::new();
+        Nbt::Compound(Vec::new()).encode(&mut buf).unwrap();
+        assert_eq!(buf, [0x0a, 0x00]);
+
+        let mut buf: Vec<u8> = Vec::new();
+        Nbt::Compound(vec![
+            ("text".to_string(), Nbt::String("\0\u{1F600}".to_string())),
+            ("extra".to_string(), Nbt::List(Vec::new())),
+        ])
+        .encode(&mut buf)
+        .unwrap();
+        assert_eq!(
+            buf,
+            [
+                0x0a, // Compound
+                0x08, 0x00, 0x04, b't', b'e', b'x', b't', // String "text"
+                0x00, 0x08, 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb8,
+                0x80, // Modified UTF-8
+                0x09, 0x00, 0x05, b'e', b'x', b't', b'r', b'a', // List "extra"
+                0x00, 0x00, 0x00, 0x00, 0x00, // Empty list
+                0x00, // End
+            ]
+        );
+    }
+
+    #[test]
+    fn test_string_limits() {
+        let encode = |value: &str| {
+            let mut buf: Vec<u8> = Vec::new();
+            VarString::new(value.to_string()).encode(&mut buf).unwrap();
+            buf
+        };
+
+        let address: String = "a".repeat(255);
+        assert_eq!(
+            VarString::decode_bounded(&mut encode(&address).as_slice(), 255).unwrap(),
+            VarString::new(address)
+        );
+        assert!(matches!(
+            VarString::decode_bounded(&mut encode(&"a".repeat(256)).as_slice(), 255),
+            Err(DataTypeDecodeError::StringTooLong {
+                length: 256,
+                max_length: 255
+            })
+        ));
+
+        // Counted in UTF-16 code units, not bytes
+        let accented: String = "\u{e9}".repeat(255);
+        assert_eq!(
+            VarString::decode_bounded(&mut encode(&accented).as_slice(), 255).unwrap(),
+            VarString::new(accented)
+        );
+        assert!(matches!(
+            VarString::decode_bounded(
+                &mut encode(&"\u{1F600}".repeat(128)).as_slice(),
+                255
+            ),
+            Err(DataTypeDecodeError::StringTooLong {
+                length: 256,
+                max_length: 255
+            })
+        ));
+
+        // Borrowed from the decoded bytes
+        let bytes: Vec<u8> = encode("minecraft:overworld");
+        let mut cursor: &[u8] = &bytes;
+        let borrowed: &str = VarString::decode_str(&mut cursor, 255).unwrap();
+        assert_eq!(borrowed, "minecraft:overworld");
+        assert!(bytes.as_ptr_range().contains(&borrowed.as_ptr()));
+        assert!(cursor.is_empty());
+
+        // Rejected from the length prefix, before reading the content
+        let mut bytes: Vec<u8> = Vec::new();
+        VarInt::new(1_000_000).encode(&mut bytes).unwrap();
+        assert!(matches!(
+            VarString::decode(&mut bytes.as_slice()),
+            Err(DataTypeDecodeError::StringTooLong {
+                length: 1_000_000,
+                max_length: VarString::MAX_LENGTH
+            })
+        ));
+    }
 }
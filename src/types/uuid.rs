@@ -0,0 +1,84 @@
+//! Universally unique identifiers.
+use std::{fmt::Display, io::Write, str::FromStr};
+
+use md5::{Digest, Md5};
+use thiserror::Error;
+
+use crate::add_tuple_impl;
+
+use super::{DataType, DataTypeDecodeError, DataTypeEncodeError};
+
+/// Error when parsing a [`Uuid`] from a string.
+#[derive(Error, Debug)]
+#[error("Invalid UUID: {0}")]
+pub struct UuidParseError(String);
+
+/// A UUID, encoded as an unsigned 128-bit integer (most significant bits first).
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, PartialOrd, Ord)]
+pub struct Uuid(pub u128);
+
+impl Uuid {
+    /// Get the UUID vanilla assigns to a player in offline mode:
+    /// `UUID.nameUUIDFromBytes("OfflinePlayer:" + name)`.
+    pub fn offline(name: &str) -> Self {
+        let hash: [u8; 16] = Md5::digest(format!("OfflinePlayer:{name}")).into();
+        let mut value: u128 = u128::from_be_bytes(hash);
+
+        // Version 3 (name based, MD5)
+        value &= !(0xF << 76);
+        value |= 0x3 << 76;
+
+        // IETF variant
+        value &= !(0x3 << 62);
+        value |= 0x2 << 62;
+
+        Self(value)
+    }
+}
+
+impl DataType<u128> for Uuid {
+    add_tuple_impl!(Uuid u128);
+
+    fn decode(from: &mut &[u8]) -> Result<Self, DataTypeDecodeError> {
+        Ok(Self(u128::decode(from)?))
+    }
+
+    fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
+        self.0.encode(to)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.0.size_hint()
+    }
+}
+
+impl Display for Uuid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hex: String = format!("{:032x}", self.0);
+        write!(
+            f,
+            "{}-{}-{}-{}-{}",
+            hex.get(0..8).unwrap_or_default(),
+            hex.get(8..12).unwrap_or_default(),
+            hex.get(12..16).unwrap_or_default(),
+            hex.get(16..20).unwrap_or_default(),
+            hex.get(20..32).unwrap_or_default()
+        )
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = UuidParseError;
+
+    /// Parse a UUID, with or without hyphens.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex: String = s.chars().filter(|c| *c != '-').collect();
+        if hex.len() != 32 {
+            return Err(UuidParseError(s.to_string()));
+        }
+
+        u128::from_str_radix(&hex, 16)
+            .map(Self)
+            .map_err(|_| UuidParseError(s.to_string()))
+    }
+}
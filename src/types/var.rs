@@ -1,9 +1,7 @@
 //! Variable length types (Var...).
 use std::{
-    collections::VecDeque,
     fmt::Display,
-    io::{Read, Write},
-    net::TcpStream,
+    io::{self, Read, Write},
 };
 
 use crate::add_tuple_impl;
@@ -15,6 +13,16 @@ const SEGMENT_BITS: i32 = 0x7F;
 /// Continue bit of a [`VarInt`] or [`VarLong`].
 const CONTINUE_BIT: i32 = 0x80;
 
+/// Number of bytes of a variable length number, 7 bits per byte.
+const fn var_length(value: u64) -> usize {
+    let bits: u32 = u64::BITS - value.leading_zeros();
+    if bits == 0 {
+        1
+    } else {
+        bits.div_ceil(7) as usize
+    }
+}
+
 /// A variable length [`i32`].
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub struct VarInt(pub i32);
@@ -50,7 +58,12 @@ impl DataType<i32> for VarInt {
         Ok(())
     }
 
-    fn decode(from: &mut impl Read) -> Result<Self, DataTypeDecodeError> {
+    fn size_hint(&self) -> usize {
+        #[allow(clippy::cast_sign_loss)]
+        var_length(u64::from(self.0 as u32))
+    }
+
+    fn decode(from: &mut &[u8]) -> Result<Self, DataTypeDecodeError> {
         let mut value: i32 = 0;
         let mut position: u8 = 0;
         let mut current_byte: u8;
@@ -71,20 +84,25 @@ impl DataType<i32> for VarInt {
         }
     }
 }
-impl TryFrom<&mut TcpStream> for VarInt {
-    type Error = DataTypeDecodeError;
 
-    fn try_from(bytes: &mut TcpStream) -> Result<Self, Self::Error> {
+impl VarInt {
+    /// Read a [`VarInt`] from a [`Read`] stream, such as the length prefix of a frame.
+    ///
+    /// The end of the stream is an [`io::ErrorKind::UnexpectedEof`] error before the
+    /// first byte, and [`DataTypeDecodeError::PrematureEndOfVarNumber`] after it.
+    pub fn read(from: &mut impl Read) -> Result<Self, DataTypeDecodeError> {
         let mut value: i32 = 0;
         let mut position: u8 = 0;
-        let mut current_byte: u8;
 
         loop {
             let mut buf: [u8; 1] = [0];
-            bytes.read(&mut buf).map_err(|_| {
-                DataTypeDecodeError::PrematureEndOfVarNumber(VecDeque::new())
-            })?;
-            current_byte = buf[0];
+            match from.read_exact(&mut buf) {
+                Err(e) if position > 0 && e.kind() == io::ErrorKind::UnexpectedEof => {
+                    break Err(DataTypeDecodeError::PrematureEndOfVarNumber);
+                }
+                result => result?,
+            }
+            let [current_byte] = buf;
             value |= (i32::from(current_byte) & SEGMENT_BITS) << position;
 
             if (i32::from(current_byte) & CONTINUE_BIT) == 0 {
@@ -104,6 +122,59 @@ impl TryFrom<&mut TcpStream> for VarInt {
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct VarString(String);
 
+impl VarString {
+    /// Default maximum length of a string, in UTF-16 code units.
+    pub const MAX_LENGTH: usize = 32767;
+
+    /// Decode a string of at most `max_length` UTF-16 code units, as counted by
+    /// vanilla.
+    pub fn decode_bounded(
+        from: &mut &[u8],
+        max_length: usize,
+    ) -> Result<Self, DataTypeDecodeError> {
+        Ok(Self(Self::decode_str(from, max_length)?.to_string()))
+    }
+
+    /// Decode a string of at most `max_length` UTF-16 code units, borrowing it
+    /// from the decoded bytes.
+    pub fn decode_str<'a>(
+        from: &mut &'a [u8],
+        max_length: usize,
+    ) -> Result<&'a str, DataTypeDecodeError> {
+        let byte_size: usize = usize::try_from(VarInt::decode(from)?.0)?;
+        // A code unit takes up to 3 bytes in UTF-8
+        if byte_size > max_length * 3 {
+            return Err(DataTypeDecodeError::StringTooLong {
+                length: byte_size,
+                max_length,
+            });
+        }
+
+        let value: &str = str::from_utf8(from.read_bytes(byte_size)?)?;
+        // Each byte is at most a code unit
+        if byte_size > max_length {
+            let length: usize = value.encode_utf16().count();
+            if length > max_length {
+                return Err(DataTypeDecodeError::StringTooLong { length, max_length });
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Encode a string without copying it into a [`VarString`].
+    pub fn encode_str(
+        value: &str,
+        to: &mut impl Write,
+    ) -> Result<(), DataTypeEncodeError> {
+        VarInt::new(i32::try_from(value.len())?).encode(to)?;
+
+        to.write_all(value.as_bytes())?;
+
+        Ok(())
+    }
+}
+
 impl DataType<String> for VarString {
     fn new(value: String) -> Self {
         Self(value)
@@ -117,18 +188,16 @@ impl DataType<String> for VarString {
         &self.0
     }
 
-    fn decode(from: &mut impl Read) -> Result<Self, DataTypeDecodeError> {
-        let byte_size: usize = usize::try_from(VarInt::decode(&mut *from)?.0)?;
-
-        Ok(Self(String::from_utf8(from.read_bytes(byte_size)?)?))
+    fn decode(from: &mut &[u8]) -> Result<Self, DataTypeDecodeError> {
+        Self::decode_bounded(from, Self::MAX_LENGTH)
     }
 
     fn encode(&self, to: &mut impl Write) -> Result<(), DataTypeEncodeError> {
-        VarInt::new(i32::try_from(self.0.len())?).encode(to)?;
-
-        to.write_all(self.0.as_bytes())?;
+        Self::encode_str(&self.0, to)
+    }
 
-        Ok(())
+    fn size_hint(&self) -> usize {
+        var_length(self.0.len() as u64) + self.0.len()
     }
 }
 
@@ -139,7 +208,7 @@ pub struct VarLong(i64);
 impl DataType<i64> for VarLong {
     add_tuple_impl!(VarLong i64);
 
-    fn decode(from: &mut impl Read) -> Result<Self, DataTypeDecodeError> {
+    fn decode(from: &mut &[u8]) -> Result<Self, DataTypeDecodeError> {
         let mut value: i64 = 0;
         let mut position: u8 = 0;
         let mut current_byte: u8;
@@ -180,4 +249,9 @@ impl DataType<i64> for VarLong {
 
         Ok(())
     }
+
+    fn size_hint(&self) -> usize {
+        #[allow(clippy::cast_sign_loss)]
+        var_length(self.0 as u64)
+    }
 }